@@ -0,0 +1,67 @@
+#import bevy_pbr::{
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::{alpha_discard, apply_pbr_lighting, main_pass_post_lighting_processing},
+    forward_io::{VertexOutput, FragmentOutput},
+    mesh_view_bindings::globals,
+}
+
+struct WaterSettings {
+    shallow: vec4<f32>,
+    deep: vec4<f32>,
+    foam: vec4<f32>,
+    // Fully deep depth, foam width, terrain height scale, wave speed.
+    levels: vec4<f32>,
+    // Grid width and height in cells, cell size.
+    grid: vec4<f32>,
+}
+
+@group(2) @binding(100) var<uniform> water: WaterSettings;
+@group(2) @binding(101) var heights_texture: texture_2d<f32>;
+@group(2) @binding(102) var heights_sampler: sampler;
+
+// Slope of one layer of waves travelling in a direction.
+fn waves(p: vec2<f32>, direction: vec2<f32>, frequency: f32, speed: f32, t: f32) -> vec2<f32> {
+    let phase = dot(p, direction) * frequency + t * speed;
+    return direction * cos(phase) * frequency;
+}
+
+fn ground_height(world: vec2<f32>) -> f32 {
+    let size = water.grid.xy;
+    let vertex = world / water.grid.z + size * 0.5;
+    let uv = (vertex + vec2<f32>(0.5)) / (size + vec2<f32>(1.0));
+    let normalized = textureSample(heights_texture, heights_sampler, uv).r;
+    return (normalized * 2.0 - 1.0) * water.levels.z;
+}
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+    let world = in.world_position.xz;
+    let t = globals.time * water.levels.w;
+    let depth = in.world_position.y - ground_height(world);
+
+    var color = mix(water.shallow, water.deep, smoothstep(0.0, water.levels.x, depth));
+
+    let slope = waves(world, normalize(vec2<f32>(1.0, 0.3)), 2.1, 1.3, t) * 0.04
+        + waves(world, normalize(vec2<f32>(-0.4, 1.0)), 3.7, 1.9, t) * 0.025;
+    let normal = normalize(vec3<f32>(-slope.x, 1.0, -slope.y));
+
+    let ripple = 0.5 + 0.5 * sin(length(world) * 6.0 - t * 2.0);
+    let foam = (1.0 - smoothstep(0.0, water.levels.y * (0.75 + 0.5 * ripple), depth));
+    color = mix(color, water.foam, foam * water.foam.a);
+
+    pbr_input.material.base_color = color;
+    pbr_input.N = normal;
+    pbr_input.world_normal = normal;
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+    var out: FragmentOutput;
+    out.color = apply_pbr_lighting(pbr_input);
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+
+    return out;
+}
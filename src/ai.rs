@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    building::{
+        catalog::Gun, Cannon, ConstructionEvent, ConstructionSet, Pieces, Structure,
+        StructureLayers, TurnEnded, Wall,
+    },
+    firing::TargetPicked,
+    model::{AiSettings, AppState, Health, Phase, Rules, Settings, SquareGrid},
+    network::following,
+    terrain::{Props, Terrain},
+    units::Ship,
+};
+
+mod opponent;
+mod planning;
+mod targeting;
+#[cfg(test)]
+mod tests;
+
+pub use opponent::choosing_opponent;
+
+/// Plays one side against a person, how well depending on `AiSettings`.
+pub struct AiPlugin;
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(opponent::OpponentPlugin).add_systems(
+            Update,
+            ((fortify, arm).in_set(ConstructionSet::Input), target)
+                .run_if(in_state(AppState::Game))
+                .run_if(against_computer)
+                .run_if(not(following)),
+        );
+    }
+}
+
+fn against_computer(settings: Res<Settings>) -> bool {
+    settings.ai().is_some()
+}
+
+/// Waits out the computer's reaction time, starting over every phase. Returns
+/// true each time it's time to act.
+fn react(ai: &AiSettings, phase: &Res<State<Phase>>, waiting: &mut Timer, time: &Time) -> bool {
+    if phase.is_changed() {
+        *waiting = Timer::from_seconds(ai.reaction, TimerMode::Repeating);
+    }
+
+    waiting.tick(time.delta()).just_finished()
+}
+
+/// Territory the computer means to hold, which is what it had the last time
+/// its walls were sealed and grows once they are again.
+#[derive(Default)]
+struct Intended(Option<SquareGrid<bool>>);
+
+/// Closes the largest gap in the walls around the territory it means to hold,
+/// or starts on a larger ring of walls once it's sealed. Every so often, and
+/// whenever there's no plan, it places a wall beside the ones it has instead.
+#[allow(clippy::too_many_arguments)]
+fn fortify(
+    mut construction: EventWriter<ConstructionEvent>,
+    mut turns: EventWriter<TurnEnded>,
+    mut waiting: Local<Timer>,
+    mut intended: Local<Intended>,
+    mut pieces: ResMut<Pieces>,
+    settings: Res<Settings>,
+    phase: Res<State<Phase>>,
+    structures: Res<StructureLayers>,
+    props: Res<Props>,
+    terrain: Query<&Terrain>,
+    time: Res<Time>,
+) {
+    // A new game, with new castles.
+    if structures.is_added() {
+        intended.0 = None;
+    }
+
+    let Some(ai) = settings.ai() else {
+        return;
+    };
+
+    if !react(ai, &phase, &mut waiting, &time) {
+        return;
+    }
+
+    if *phase.get() != Phase::Fortify(ai.player) {
+        return;
+    }
+
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    let buildable = |p: IVec2| {
+        terrain
+            .survey_grid(p)
+            .is_some_and(|survey| survey.can_build())
+            && !props.blocked(p)
+            && !structures.occupied(p)
+    };
+
+    let walls = structures.wall_grid(&ai.player);
+    let territory = structures.territory(&ai.player);
+
+    // Anything newly enclosed is worth holding onto.
+    let held = match intended.0.take() {
+        Some(held) => held
+            .apply(|p, inside| *inside || territory.get(p.as_ivec2()).copied().unwrap_or_default()),
+        None => territory,
+    };
+
+    // Once sealed, start on a larger ring around what's held.
+    let (held, planned) = match planning::next_piece(&held, &walls, buildable) {
+        Some(piece) => (held, Some(piece)),
+        None => {
+            let grown = planning::grow(&held);
+            match planning::next_piece(&grown, &walls, buildable) {
+                Some(piece) => {
+                    debug!(player = ?ai.player, "ai: expanding");
+                    (grown, Some(piece))
+                }
+                None => (held, None),
+            }
+        }
+    };
+    intended.0 = Some(held);
+
+    let mut rng = rand::thread_rng();
+    let chosen = match planned {
+        Some(piece) if rng.gen::<f32>() >= ai.gap_chance => Some(piece),
+        _ => {
+            let mut candidates: Vec<IVec2> = walls
+                .layout()
+                .into_iter()
+                .filter(|(_, _, wall)| **wall)
+                .flat_map(|(p, _, _)| {
+                    [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y].map(|n| p + n)
+                })
+                .filter(|p| buildable(*p))
+                .collect();
+            candidates.sort_by_key(|p| (p.x, p.y));
+            candidates.dedup();
+            candidates.choose(&mut rng).copied()
+        }
+    };
+
+    let Some(grid) = chosen else {
+        info!(player = ?ai.player, "ai: nowhere to build");
+        return;
+    };
+
+    let Some(left) = pieces.take() else {
+        return;
+    };
+
+    info!(player = ?ai.player, %grid, planned = planned == Some(grid), left, "ai: building");
+
+    construction.send(ConstructionEvent::new(
+        grid.into(),
+        Structure::Wall(Wall::new(ai.player)),
+    ));
+    if left == 0 {
+        turns.send(TurnEnded::new(ai.player));
+    }
+}
+
+/// Puts a gun inside its walls as close to the other player as it can, a
+/// mortar once it has cannons enough.
+#[allow(clippy::too_many_arguments)]
+fn arm(
+    mut construction: EventWriter<ConstructionEvent>,
+    mut turns: EventWriter<TurnEnded>,
+    mut waiting: Local<Timer>,
+    mut pieces: ResMut<Pieces>,
+    settings: Res<Settings>,
+    phase: Res<State<Phase>>,
+    structures: Res<StructureLayers>,
+    props: Res<Props>,
+    terrain: Query<&Terrain>,
+    time: Res<Time>,
+) {
+    let Some(ai) = settings.ai() else {
+        return;
+    };
+
+    if !react(ai, &phase, &mut waiting, &time) {
+        return;
+    }
+
+    if *phase.get() != Phase::Arm(ai.player) {
+        return;
+    }
+
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    let free = |p: IVec2| {
+        terrain
+            .survey_grid(p)
+            .is_some_and(|survey| survey.can_build())
+            && !props.blocked(p)
+            && !structures.occupied(p)
+    };
+
+    // Toward the middle of the other player's walls, or of the map without.
+    let enemy = structures.walls(&ai.player.next());
+    let toward = match enemy.len() as i32 {
+        0 => terrain.size().as_ivec2() / 2,
+        n => enemy.iter().map(|(grid, _)| *grid).sum::<IVec2>() / n,
+    };
+
+    let territory = structures.territory(&ai.player);
+    let Some(grid) = planning::gun_site(&territory, toward, free) else {
+        info!(player = ?ai.player, "ai: nowhere to arm");
+        return;
+    };
+
+    let guns: Vec<Gun> = structures
+        .guns(&ai.player)
+        .into_iter()
+        .map(|(_, gun)| gun)
+        .collect();
+    let gun = planning::next_gun(&guns);
+
+    let Some(left) = pieces.take() else {
+        return;
+    };
+
+    info!(player = ?ai.player, %grid, ?gun, left, "ai: arming");
+
+    construction.send(ConstructionEvent::new(
+        grid.into(),
+        Structure::Cannon(Cannon::armed(ai.player, gun)),
+    ));
+    if left == 0 {
+        turns.send(TurnEnded::new(ai.player));
+    }
+}
+
+/// Fires at whatever of the other player's is worth the most and in range of
+/// its cannons, missing by as much as its aim allows.
+#[allow(clippy::too_many_arguments)]
+fn target(
+    mut picks: EventWriter<TargetPicked>,
+    mut waiting: Local<Timer>,
+    settings: Res<Settings>,
+    phase: Res<State<Phase>>,
+    structures: Res<StructureLayers>,
+    health: Query<&Health>,
+    ships: Query<&Transform, With<Ship>>,
+    terrain: Query<&Terrain>,
+    rules: Res<Rules>,
+    time: Res<Time>,
+) {
+    let Some(ai) = settings.ai() else {
+        return;
+    };
+
+    if !react(ai, &phase, &mut waiting, &time) {
+        return;
+    }
+
+    if *phase.get() != Phase::Target(ai.player) {
+        return;
+    }
+
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    let world = |grid: IVec2| terrain.survey_grid(grid).map(|survey| survey.world());
+
+    // Mortars don't reach nearly as far as cannons, but the computer fires
+    // on anything either can.
+    let cannons: Vec<(Vec3, Gun)> = structures
+        .guns(&ai.player)
+        .into_iter()
+        .filter_map(|(grid, gun)| world(grid).map(|cannon| (cannon, gun)))
+        .collect();
+    let in_range = |target: Vec3| {
+        cannons
+            .iter()
+            .any(|(cannon, gun)| gun.entry().ordnance.in_range(*cannon, target))
+    };
+    let reachable = |grid: IVec2| world(grid).is_some_and(in_range);
+    let hp = |entity: Entity| health.get(entity).map(|h| h.hp()).unwrap_or_default();
+
+    // Playing together the only thing to shoot at is the ships, whichever
+    // comes closest to one of its cannons.
+    if rules.coop {
+        let closest = |ship: &Vec3| {
+            cannons
+                .iter()
+                .map(|(cannon, _)| cannon.distance(*ship))
+                .fold(f32::MAX, f32::min)
+        };
+        let Some(aim) = ships
+            .iter()
+            .map(|ship| ship.translation)
+            .filter(|ship| in_range(*ship))
+            .min_by(|a, b| closest(a).total_cmp(&closest(b)))
+        else {
+            info!(player = ?ai.player, "ai: no ships in range");
+            return;
+        };
+
+        let target = aim + aim_error(ai);
+
+        info!(player = ?ai.player, %target, "ai: firing on ship");
+
+        picks.send(TargetPicked::new(ai.player, target, false));
+        return;
+    }
+
+    let enemy = ai.player.next();
+    let breaches: HashMap<IVec2, usize> = structures.breaches(&enemy).into_iter().collect();
+    let walls =
+        structures
+            .walls(&enemy)
+            .into_iter()
+            .map(|(grid, entity)| targeting::Candidate::Wall {
+                grid,
+                hp: hp(entity),
+                breach: breaches.get(&grid).copied().unwrap_or_default(),
+            });
+    let cannons = structures
+        .cannons(&enemy)
+        .into_iter()
+        .map(|(grid, entity)| targeting::Candidate::Cannon {
+            grid,
+            hp: hp(entity),
+        });
+
+    let Some(best) = targeting::best(walls.chain(cannons).filter(|c| reachable(c.grid()))) else {
+        info!(player = ?ai.player, "ai: nothing in range");
+        return;
+    };
+    let Some(aim) = world(best.grid()) else {
+        return;
+    };
+
+    let target = aim + aim_error(ai);
+
+    info!(player = ?ai.player, ?best, %target, "ai: firing");
+
+    picks.send(TargetPicked::new(ai.player, target, false));
+}
+
+/// How far off a shot goes, up to the computer's aim error in any direction.
+fn aim_error(ai: &AiSettings) -> Vec3 {
+    let mut rng = rand::thread_rng();
+    let error = Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+    let error = error.clamp_length_max(1.0) * ai.aim_error;
+
+    Vec3::new(error.x, 0., error.y)
+}
@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+
+use crate::{
+    challenge::Challenges,
+    model::{Activity, AiSettings, AppState, Difficulty, Player, Settings},
+    network,
+    replay::Playback,
+};
+
+/// Whoever the computer plays as, chosen from the menu.
+const COMPUTER: Player = Player::Two;
+
+const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.2);
+const HOVERED_COLOR: Color = Color::rgb(0.25, 0.25, 0.35);
+
+/// Asks who the second player is before a game, another person or the
+/// computer at one of its difficulties, unless `--ai` already said.
+pub struct OpponentPlugin;
+
+impl Plugin for OpponentPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::Menu),
+            spawn_select
+                .run_if(not(resource_exists::<Challenges>))
+                .run_if(not(network::networked))
+                .run_if(choosing_opponent),
+        )
+        .add_systems(OnExit(AppState::Menu), despawn_select)
+        .add_systems(Update, select.run_if(in_state(AppState::Menu)));
+    }
+}
+
+/// Nobody's said who the opponent is, and there's no replay deciding it.
+pub fn choosing_opponent(settings: Res<Settings>, playback: Option<Res<Playback>>) -> bool {
+    settings.ai().is_none() && playback.is_none()
+}
+
+#[derive(Component)]
+struct OpponentSelect;
+
+#[derive(Component, Clone, Copy, Debug)]
+enum OpponentButton {
+    Person,
+    Computer(Difficulty),
+}
+
+impl OpponentButton {
+    fn label(&self) -> String {
+        match self {
+            OpponentButton::Person => "Two players".to_owned(),
+            OpponentButton::Computer(difficulty) => format!("Computer: {:?}", difficulty),
+        }
+    }
+
+    fn ai(&self) -> Option<AiSettings> {
+        match self {
+            OpponentButton::Person => None,
+            OpponentButton::Computer(difficulty) => Some(AiSettings::new(COMPUTER, *difficulty)),
+        }
+    }
+}
+
+fn spawn_select(mut commands: Commands) {
+    let text = |value: &str, font_size: f32| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+    };
+
+    commands
+        .spawn((
+            Name::new("Opponents"),
+            OpponentSelect,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(12.),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("New game", 32.));
+
+            for button in [
+                OpponentButton::Person,
+                OpponentButton::Computer(Difficulty::Easy),
+                OpponentButton::Computer(Difficulty::Normal),
+                OpponentButton::Computer(Difficulty::Hard),
+            ] {
+                parent
+                    .spawn((
+                        button,
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(320.),
+                                padding: UiRect::all(Val::Px(8.)),
+                                justify_content: JustifyContent::Center,
+                                ..default()
+                            },
+                            background_color: BUTTON_COLOR.into(),
+                            ..default()
+                        },
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(text(&button.label(), 22.));
+                    });
+            }
+        });
+}
+
+fn despawn_select(mut commands: Commands, screens: Query<Entity, With<OpponentSelect>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn select(
+    mut buttons: Query<(&Interaction, &OpponentButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut settings: ResMut<Settings>,
+    mut app_state: ResMut<NextState<AppState>>,
+    mut activity: ResMut<NextState<Activity>>,
+) {
+    for (interaction, button, mut color) in &mut buttons {
+        match interaction {
+            Interaction::Pressed => {
+                info!(?button, "opponent");
+
+                settings.ai = button.ai();
+
+                app_state.set(AppState::Generating);
+                activity.set(Activity::Observing);
+            }
+            Interaction::Hovered => *color = HOVERED_COLOR.into(),
+            Interaction::None => *color = BUTTON_COLOR.into(),
+        }
+    }
+}
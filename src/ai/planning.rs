@@ -0,0 +1,105 @@
+use std::collections::HashSet;
+
+use bevy::math::IVec2;
+
+use crate::{
+    building::catalog::Gun,
+    model::{Connectivity, SquareGrid},
+};
+
+const AROUND: [IVec2; 8] = [
+    IVec2::new(-1, -1),
+    IVec2::new(0, -1),
+    IVec2::new(1, -1),
+    IVec2::new(-1, 0),
+    IVec2::new(1, 0),
+    IVec2::new(-1, 1),
+    IVec2::new(0, 1),
+    IVec2::new(1, 1),
+];
+
+fn inside(grid: &SquareGrid<bool>, p: IVec2) -> bool {
+    grid.get(p).copied().unwrap_or_default()
+}
+
+/// Mortars to lob over walls once there are two cannons for each, cannons
+/// otherwise.
+pub fn next_gun(guns: &[Gun]) -> Gun {
+    let mortars = guns.iter().filter(|gun| **gun == Gun::Mortar).count();
+    if guns.len() - mortars >= 2 * (mortars + 1) {
+        Gun::Mortar
+    } else {
+        Gun::Cannon
+    }
+}
+
+/// Where inside the territory a gun goes, as close to `toward` as it can be.
+pub fn gun_site(
+    territory: &SquareGrid<bool>,
+    toward: IVec2,
+    free: impl Fn(IVec2) -> bool,
+) -> Option<IVec2> {
+    territory
+        .layout()
+        .into_iter()
+        .filter(|(p, _, inside)| **inside && free(*p))
+        .map(|(p, _, _)| p)
+        .min_by_key(|p| ((*p - toward).length_squared(), p.x, p.y))
+}
+
+/// Cells just outside of the territory, where walls go to hold it.
+pub fn ring(territory: &SquareGrid<bool>) -> Vec<IVec2> {
+    territory
+        .layout()
+        .into_iter()
+        .filter(|(p, _, inside)| {
+            !**inside
+                && AROUND
+                    .iter()
+                    .any(|n| territory.get(*p + *n).copied().unwrap_or_default())
+        })
+        .map(|(p, _, _)| p)
+        .collect()
+}
+
+/// The territory grown by a cell in every direction.
+pub fn grow(territory: &SquareGrid<bool>) -> SquareGrid<bool> {
+    let ring: HashSet<IVec2> = ring(territory).into_iter().collect();
+
+    territory.apply(|p, inside| *inside || ring.contains(&p.as_ivec2()))
+}
+
+/// Runs of the ring that aren't walled, largest first.
+pub fn gaps(territory: &SquareGrid<bool>, walls: &SquareGrid<bool>) -> Vec<Vec<IVec2>> {
+    let mut open: SquareGrid<bool> = SquareGrid::new_flat(territory.size());
+    for p in ring(territory) {
+        open[p] = !inside(walls, p);
+    }
+
+    let regions = open.regions(Connectivity::Corners, |open| *open);
+    let mut gaps: Vec<Vec<IVec2>> = (0..regions.len())
+        .map(|label| {
+            let mut gap = regions.cells(label);
+            gap.sort_by_key(|p| (p.x, p.y));
+            gap
+        })
+        .collect();
+
+    gaps.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    gaps
+}
+
+/// Where in the largest gap that can be closed to build next, working in from
+/// the walls on either side.
+pub fn next_piece(
+    territory: &SquareGrid<bool>,
+    walls: &SquareGrid<bool>,
+    buildable: impl Fn(IVec2) -> bool,
+) -> Option<IVec2> {
+    let touching = |p: &IVec2| AROUND.iter().filter(|n| inside(walls, *p + **n)).count();
+
+    gaps(territory, walls)
+        .into_iter()
+        .filter(|gap| gap.iter().all(|p| buildable(*p)))
+        .find_map(|gap| gap.into_iter().max_by_key(touching))
+}
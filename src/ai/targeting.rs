@@ -0,0 +1,55 @@
+use bevy::prelude::*;
+
+/// Worth of every enclosed cell a wall would open up if destroyed.
+const BREACH_VALUE_PER_CELL: f32 = 4.0;
+
+/// Worth of destroying a cannon, about as much as a small breach.
+const CANNON_VALUE: f32 = 40.0;
+
+/// Worth of a wall that doesn't open anything up by itself.
+const WALL_VALUE: f32 = 1.0;
+
+/// Something of the other player's that could be shot at.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Candidate {
+    /// A wall, with the territory lost when it comes down.
+    Wall {
+        grid: IVec2,
+        hp: f32,
+        breach: usize,
+    },
+    Cannon {
+        grid: IVec2,
+        hp: f32,
+    },
+}
+
+impl Candidate {
+    pub fn grid(&self) -> IVec2 {
+        match self {
+            Candidate::Wall { grid, .. } | Candidate::Cannon { grid, .. } => *grid,
+        }
+    }
+
+    /// What destroying it is worth for each point of damage it'll take, so
+    /// anything already damaged is worth more than the same thing untouched.
+    pub fn value(&self) -> f32 {
+        let (worth, hp) = match self {
+            Candidate::Wall { hp, breach: 0, .. } => (WALL_VALUE, *hp),
+            Candidate::Wall { hp, breach, .. } => (BREACH_VALUE_PER_CELL * *breach as f32, *hp),
+            Candidate::Cannon { hp, .. } => (CANNON_VALUE, *hp),
+        };
+
+        worth / hp.max(1.0)
+    }
+}
+
+/// The most valuable of the candidates, ties going to the first.
+pub fn best(candidates: impl IntoIterator<Item = Candidate>) -> Option<Candidate> {
+    candidates
+        .into_iter()
+        .fold(None, |best, candidate| match best {
+            Some(best) if best.value() >= candidate.value() => Some(best),
+            _ => Some(candidate),
+        })
+}
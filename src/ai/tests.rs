@@ -0,0 +1,101 @@
+use bevy::math::{IVec2, UVec2};
+
+use crate::{building::catalog::Gun, model::SquareGrid};
+
+use super::{
+    planning,
+    targeting::{best, Candidate},
+};
+
+#[test]
+fn test_breach_beats_plain_wall() {
+    let plain = Candidate::Wall {
+        grid: IVec2::new(0, 0),
+        hp: 100.0,
+        breach: 0,
+    };
+    let breach = Candidate::Wall {
+        grid: IVec2::new(1, 0),
+        hp: 100.0,
+        breach: 9,
+    };
+
+    assert_eq!(best([plain, breach.clone()]), Some(breach));
+}
+
+#[test]
+fn test_damaged_worth_finishing() {
+    let fresh = Candidate::Cannon {
+        grid: IVec2::new(0, 0),
+        hp: 150.0,
+    };
+    let damaged = Candidate::Cannon {
+        grid: IVec2::new(1, 0),
+        hp: 20.0,
+    };
+
+    assert_eq!(best([fresh, damaged.clone()]), Some(damaged));
+    assert_eq!(best([]), None);
+}
+
+fn outline(size: UVec2, p0: IVec2, p1: IVec2) -> SquareGrid<bool> {
+    let mut walls = SquareGrid::new_flat(size);
+    walls.outline(p0, p1, true);
+    walls
+}
+
+#[test]
+fn test_planner_closes_largest_gap_first() {
+    let size = UVec2::new(10, 10);
+    let held = outline(size, IVec2::new(2, 2), IVec2::new(6, 6))
+        .apply(|p, _| (3..=5).contains(&p.x) && (3..=5).contains(&p.y));
+    let mut walls = outline(size, IVec2::new(2, 2), IVec2::new(6, 6));
+    walls.set(IVec2::new(2, 4), false);
+    walls.set(IVec2::new(6, 3), false);
+    walls.set(IVec2::new(6, 4), false);
+
+    let gaps = planning::gaps(&held, &walls);
+    assert_eq!(gaps.len(), 2);
+    assert_eq!(gaps[0].len(), 2);
+
+    let piece = planning::next_piece(&held, &walls, |_| true);
+    assert!(matches!(piece, Some(p) if p.x == 6));
+}
+
+#[test]
+fn test_planner_expands_once_sealed() {
+    let size = UVec2::new(10, 10);
+    let walls = outline(size, IVec2::new(2, 2), IVec2::new(6, 6));
+    let held = walls.apply(|p, _| (3..=5).contains(&p.x) && (3..=5).contains(&p.y));
+
+    assert_eq!(planning::next_piece(&held, &walls, |_| true), None);
+
+    let grown = planning::grow(&held);
+    assert_eq!(planning::ring(&grown).len(), 24);
+    assert!(planning::next_piece(&grown, &walls, |_| true).is_some());
+}
+
+#[test]
+fn test_mortars_once_there_are_cannons_enough() {
+    assert_eq!(planning::next_gun(&[]), Gun::Cannon);
+    assert_eq!(planning::next_gun(&[Gun::Cannon]), Gun::Cannon);
+    assert_eq!(planning::next_gun(&[Gun::Cannon, Gun::Cannon]), Gun::Mortar);
+    assert_eq!(
+        planning::next_gun(&[Gun::Cannon, Gun::Cannon, Gun::Mortar]),
+        Gun::Cannon
+    );
+}
+
+#[test]
+fn test_guns_go_inside_closest_to_the_enemy() {
+    let size = UVec2::new(10, 10);
+    let held = outline(size, IVec2::new(2, 2), IVec2::new(6, 6))
+        .apply(|p, _| (3..=5).contains(&p.x) && (3..=5).contains(&p.y));
+
+    let site = planning::gun_site(&held, IVec2::new(9, 4), |_| true);
+    assert_eq!(site, Some(IVec2::new(5, 4)));
+
+    let site = planning::gun_site(&held, IVec2::new(9, 4), |p| p.x < 5);
+    assert_eq!(site, Some(IVec2::new(4, 4)));
+    assert_eq!(planning::gun_site(&held, IVec2::ZERO, |_| false), None);
+}
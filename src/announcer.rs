@@ -0,0 +1,236 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    building::StructureLayers,
+    display::DisplaySettings,
+    helpers::{Expires, GamePlayLifetime, PlayerColor},
+    model::{AppState, Phase, Player},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Seconds each banner is up for.
+const BANNER_SECONDS: f32 = 2.0;
+
+/// Seconds spent sliding in, and fading out at the end.
+const BANNER_EASE_SECONDS: f32 = 0.3;
+
+/// How far banners slide down as they come in, in percent of the screen.
+const BANNER_SLIDE: f32 = 4.0;
+
+const BANNER_TOP: f32 = 18.0;
+
+const TONE_SECONDS: f32 = 0.25;
+
+/// Calls out the big moments of a game in a banner across the screen, along
+/// with a tone, wherever they come from.
+pub struct AnnouncerPlugin;
+
+impl Plugin for AnnouncerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Announcement>()
+            .init_resource::<Held>()
+            .add_systems(OnEnter(AppState::Game), reset_held)
+            .add_systems(
+                Update,
+                (
+                    announce_phases,
+                    watch_territory,
+                    show_banners,
+                    slide_banners,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+/// Something worth calling out.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Announcement {
+    Phase(Phase),
+    /// Walls broken, and territory lost.
+    Breach(Player),
+    /// Nothing left inside their walls at all.
+    CastleLost(Player),
+    /// Ships sent for the round.
+    Wave(u32),
+}
+
+impl Event for Announcement {}
+
+impl Announcement {
+    pub fn text(&self) -> String {
+        match self {
+            Announcement::Phase(Phase::Choose(_)) => "Choose your castle!".to_owned(),
+            Announcement::Phase(Phase::Fortify(_)) => "Fortify!".to_owned(),
+            Announcement::Phase(Phase::Arm(_)) => "Arm!".to_owned(),
+            Announcement::Phase(Phase::Target(_)) => "Fire!".to_owned(),
+            Announcement::Breach(_) => "Breach!".to_owned(),
+            Announcement::CastleLost(_) => "Castle Lost!".to_owned(),
+            Announcement::Wave(round) => format!("Ships sighted! Wave {}", round),
+        }
+    }
+
+    /// Whose moment it is, if anyone's in particular.
+    pub fn player(&self) -> Option<Player> {
+        match self {
+            Announcement::Phase(phase) => Some(phase.player()),
+            Announcement::Breach(player) | Announcement::CastleLost(player) => Some(*player),
+            Announcement::Wave(_) => None,
+        }
+    }
+
+    /// Pitch of the tone played along with it, lower for worse news.
+    fn tone(&self) -> f32 {
+        match self {
+            Announcement::Phase(_) => 520.0,
+            Announcement::Wave(_) => 390.0,
+            Announcement::Breach(_) => 260.0,
+            Announcement::CastleLost(_) => 130.0,
+        }
+    }
+
+    /// What a change in the territory a player holds amounts to, if anything.
+    pub fn territory(player: Player, before: usize, after: usize) -> Option<Self> {
+        match (before, after) {
+            (before, 0) if before > 0 => Some(Announcement::CastleLost(player)),
+            (before, after) if after < before => Some(Announcement::Breach(player)),
+            _ => None,
+        }
+    }
+}
+
+/// Territory each player held when last looked at.
+#[derive(Debug, Default, Resource)]
+struct Held([usize; 2]);
+
+#[derive(Component)]
+struct Banner {
+    age: f32,
+}
+
+fn reset_held(mut held: ResMut<Held>) {
+    held.0 = [0, 0];
+}
+
+fn announce_phases(phase: Res<State<Phase>>, mut announcements: EventWriter<Announcement>) {
+    if phase.is_changed() {
+        announcements.send(Announcement::Phase(phase.get().clone()));
+    }
+}
+
+fn watch_territory(
+    structures: Res<StructureLayers>,
+    mut held: ResMut<Held>,
+    mut announcements: EventWriter<Announcement>,
+) {
+    if !structures.is_changed() {
+        return;
+    }
+
+    for (index, player) in [Player::One, Player::Two].into_iter().enumerate() {
+        let now = structures
+            .territory(&player)
+            .iter()
+            .filter(|cell| **cell)
+            .count();
+        if let Some(announcement) = Announcement::territory(player, held.0[index], now) {
+            announcements.send(announcement);
+        }
+        held.0[index] = now;
+    }
+}
+
+/// Puts up the latest announcement, replacing whatever banner was up.
+fn show_banners(
+    mut commands: Commands,
+    mut announcements: EventReader<Announcement>,
+    mut pitches: ResMut<Assets<Pitch>>,
+    banners: Query<Entity, With<Banner>>,
+) {
+    let Some(announcement) = announcements.read().last() else {
+        return;
+    };
+
+    info!(?announcement, "announced");
+
+    for entity in &banners {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    commands.spawn(PitchBundle {
+        source: pitches.add(Pitch::new(
+            announcement.tone(),
+            Duration::from_secs_f32(TONE_SECONDS),
+        )),
+        settings: PlaybackSettings::DESPAWN,
+    });
+
+    let color = announcement
+        .player()
+        .map(|player| player.color())
+        .unwrap_or(Color::WHITE);
+
+    commands
+        .spawn((
+            Name::new("Banner"),
+            Banner { age: 0. },
+            Expires::after(BANNER_SECONDS),
+            GamePlayLifetime,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    top: Val::Percent(BANNER_TOP),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                announcement.text(),
+                TextStyle {
+                    font_size: 48.,
+                    color: color.with_a(0.),
+                    ..default()
+                },
+            ));
+        });
+}
+
+/// Slides banners in and fades them back out.
+fn slide_banners(
+    mut banners: Query<(&mut Banner, &mut Style, &Children)>,
+    mut texts: Query<&mut Text>,
+    display: Res<DisplaySettings>,
+    timer: Res<Time>,
+) {
+    for (mut banner, mut style, children) in &mut banners {
+        banner.age += timer.delta_seconds();
+
+        // Fading without sliding, when keeping things still.
+        let slide = if display.reduce_motion {
+            0.
+        } else {
+            BANNER_SLIDE
+        };
+        let coming = (banner.age / BANNER_EASE_SECONDS).min(1.0);
+        let going = ((BANNER_SECONDS - banner.age) / BANNER_EASE_SECONDS).clamp(0.0, 1.0);
+
+        style.top = Val::Percent(BANNER_TOP - slide * (1.0 - coming));
+
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                for section in text.sections.iter_mut() {
+                    section.style.color.set_a(coming.min(going));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use crate::model::{Phase, Player};
+
+use super::Announcement;
+
+#[test]
+fn test_losing_territory_is_a_breach_until_there_is_none_left() {
+    assert_eq!(
+        Announcement::territory(Player::One, 20, 12),
+        Some(Announcement::Breach(Player::One))
+    );
+    assert_eq!(
+        Announcement::territory(Player::Two, 20, 0),
+        Some(Announcement::CastleLost(Player::Two))
+    );
+    assert_eq!(Announcement::territory(Player::One, 12, 20), None);
+    assert_eq!(Announcement::territory(Player::One, 0, 0), None);
+}
+
+#[test]
+fn test_phases_are_announced_for_whoever_plays_them() {
+    let fortify = Announcement::Phase(Phase::Fortify(Player::Two));
+
+    assert_eq!(fortify.text(), "Fortify!");
+    assert_eq!(fortify.player(), Some(Player::Two));
+    assert_eq!(Announcement::Wave(3).player(), None);
+}
@@ -0,0 +1,110 @@
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+
+use crate::{
+    building::StructureLayers,
+    challenge::in_challenge,
+    journal::EventJournal,
+    model::{AppState, Phase, PhaseTimers, Player, Rules, Settings},
+    network::following,
+    scenario::{Progress, Scenario},
+    terrain::Terrain,
+    units::{self, WaveDirector},
+};
+
+#[cfg(test)]
+mod tests;
+
+const AUTOSAVE_DIRECTORY: &str = "autosaves";
+/// Number of autosaves kept, the oldest is dropped to make room for each new
+/// one.
+const AUTOSAVES: usize = 5;
+
+/// Saves the game as each round begins, as a scenario that can be given to
+/// `--scenario` to pick up from there.
+pub struct AutosavePlugin;
+
+impl Plugin for AutosavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(Phase::Fortify(Player::One)),
+            autosave
+                .after(units::next_round)
+                .run_if(in_state(AppState::Game))
+                .run_if(not(in_challenge))
+                .run_if(not(following)),
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn autosave(
+    settings: Res<Settings>,
+    timers: Res<PhaseTimers>,
+    rules: Res<Rules>,
+    director: Res<WaveDirector>,
+    journal: Res<EventJournal>,
+    playing: Option<Res<Scenario>>,
+    terrain: Query<&Terrain>,
+    structures: Option<Res<StructureLayers>>,
+) {
+    let (Ok(terrain), Some(structures)) = (terrain.get_single(), structures) else {
+        return;
+    };
+
+    // Timers are only kept when a scenario fixed them, otherwise they carry on
+    // shrinking round by round.
+    let fixed = playing.is_some_and(|playing| playing.timers.is_some());
+
+    let scenario = Scenario {
+        timers: fixed.then(|| timers.clone()),
+        rules: Some(rules.clone()),
+        progress: Some(Progress {
+            round: director.round(),
+            scores: [Player::One, Player::Two]
+                .into_iter()
+                .map(|player| (player, journal.total(&player)))
+                .collect(),
+        }),
+        ..Scenario::capture(&settings, terrain, &structures)
+    };
+
+    let path = match rotate(Path::new(AUTOSAVE_DIRECTORY), AUTOSAVES) {
+        Ok(path) => path,
+        Err(e) => {
+            warn!("error rotating autosaves: {}", e);
+            return;
+        }
+    };
+
+    match scenario.save(&path) {
+        Ok(_) => info!(?path, "autosaved"),
+        Err(e) => warn!("error autosaving: {}", e),
+    }
+}
+
+fn autosave_path(directory: &Path, number: usize) -> PathBuf {
+    directory.join(format!("autosave-{}.ron", number))
+}
+
+/// Makes room for a new autosave by moving each of the existing ones back,
+/// dropping the oldest, and returns where the new one goes. The newest is
+/// always `autosave-1.ron`.
+fn rotate(directory: &Path, keep: usize) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(directory)?;
+
+    let oldest = autosave_path(directory, keep);
+    if oldest.exists() {
+        std::fs::remove_file(oldest)?;
+    }
+
+    for number in (1..keep).rev() {
+        let path = autosave_path(directory, number);
+        if path.exists() {
+            std::fs::rename(path, autosave_path(directory, number + 1))?;
+        }
+    }
+
+    Ok(autosave_path(directory, 1))
+}
@@ -0,0 +1,21 @@
+use super::{autosave_path, rotate};
+
+#[test]
+fn test_rotate_drops_oldest() {
+    let directory = std::env::temp_dir().join(format!("castle-autosaves-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+
+    for round in 1..=4 {
+        let path = rotate(&directory, 3).unwrap();
+        assert_eq!(path, autosave_path(&directory, 1));
+        std::fs::write(path, round.to_string()).unwrap();
+    }
+
+    let read = |number| std::fs::read_to_string(autosave_path(&directory, number)).ok();
+    assert_eq!(read(1), Some("4".to_owned()));
+    assert_eq!(read(2), Some("3".to_owned()));
+    assert_eq!(read(3), Some("2".to_owned()));
+    assert_eq!(read(4), None);
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
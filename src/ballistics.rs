@@ -0,0 +1,191 @@
+use bevy::math::Vec3;
+
+use crate::model::{GRAVITY, MAXIMUM_HORIZONTAL_DISTANCE, MINIMUM_FLIGHT_TIME, TILE_SIZE};
+
+#[cfg(test)]
+mod tests;
+
+/// Closest a target can be before firing at it is refused.
+const MINIMUM_DISTANCE: f32 = 1.0;
+
+/// Farthest a cannon can fire.
+const MAXIMUM_RANGE: f32 = 40.0 * TILE_SIZE;
+
+/// Step size when following a trajectory looking for where it lands.
+const STEP: f32 = 1.0 / 30.0;
+
+/// Longest trajectory that'll be followed looking for where it lands.
+const LONGEST_FLIGHT: f32 = 10.0;
+
+/// How to fire at something, aiming to reach the apex half way there.
+#[derive(Debug, Clone)]
+pub struct Launch {
+    pub direction: Vec3,
+    pub distance: f32,
+    pub velocity: Vec3,
+    pub time_of_flight: f32,
+}
+
+impl Launch {
+    /// Firing by hand at exactly `velocity`, for a shot that comes down at
+    /// `to`. Nothing when it's fired straight up.
+    pub fn aimed(from: Vec3, to: Vec3, velocity: Vec3) -> Option<Self> {
+        let zero_y = Vec3::new(1., 0., 1.);
+        let horizontal = velocity * zero_y;
+        if horizontal.length_squared() == 0.0 {
+            return None;
+        }
+
+        let distance = ((to - from) * zero_y).length();
+
+        Some(Self {
+            direction: horizontal.normalize(),
+            distance,
+            velocity,
+            time_of_flight: distance / horizontal.length(),
+        })
+    }
+}
+
+/// Degrees either side of the way a cannon faces that it fires without having
+/// to turn.
+pub const TRAVERSE: f32 = 60.0;
+
+/// The way something facing `facing` degrees clockwise from east, looking down
+/// on the map, is pointed.
+pub fn facing_direction(facing: u32) -> Vec3 {
+    let radians = (facing as f32).to_radians();
+    Vec3::new(radians.cos(), 0., radians.sin())
+}
+
+/// How many degrees a cannon facing `facing` has to turn beyond its traverse
+/// to fire at the target, nothing when it's within it.
+pub fn beyond_traverse(from: Vec3, to: Vec3, facing: u32) -> f32 {
+    let direction = (to - from) * Vec3::new(1., 0., 1.);
+    if direction.length_squared() == 0.0 {
+        return 0.0;
+    }
+
+    let angle = direction
+        .angle_between(facing_direction(facing))
+        .to_degrees();
+    (angle - TRAVERSE).max(0.0)
+}
+
+/// How far and how high something fires.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ordnance {
+    pub maximum_range: f32,
+    /// Times the usual time of flight, the higher the arc the longer shots
+    /// take to come down.
+    pub loft: f32,
+}
+
+/// Fires flat and far.
+pub const CANNON: Ordnance = Ordnance {
+    maximum_range: MAXIMUM_RANGE,
+    loft: 1.0,
+};
+
+/// Lobs shots high enough to come down over walls, though not nearly as far.
+pub const MORTAR: Ordnance = Ordnance {
+    maximum_range: 16.0 * TILE_SIZE,
+    loft: 3.0,
+};
+
+impl Ordnance {
+    pub fn in_range(&self, from: Vec3, to: Vec3) -> bool {
+        let distance = ((to - from) * Vec3::new(1., 0., 1.)).length();
+        (MINIMUM_DISTANCE..=self.maximum_range).contains(&distance)
+    }
+
+    /// We base all the math on a desired time of flight that looks appropriate
+    /// for the distance, so only the horizontal distance matters.
+    pub fn launch(&self, from: Vec3, to: Vec3) -> Option<Launch> {
+        let zero_y = Vec3::new(1., 0., 1.);
+        let direction = (to - from) * zero_y;
+        let distance = direction.length();
+        if distance < MINIMUM_DISTANCE {
+            return None;
+        }
+
+        let direction = direction.normalize();
+        let distance = distance - TILE_SIZE / 2.;
+        let time_of_flight =
+            ((distance / MAXIMUM_HORIZONTAL_DISTANCE) + MINIMUM_FLIGHT_TIME) * self.loft;
+        // Vertical velocity to reach apex half way through.
+        let vertical_velocity = GRAVITY * (time_of_flight / 2.0);
+        // Gotta go `distance` so however long that will take.
+        let horizontal_velocity = distance / time_of_flight;
+
+        Some(Launch {
+            direction,
+            distance,
+            velocity: (direction * horizontal_velocity) + Vec3::new(0., vertical_velocity, 0.),
+            time_of_flight,
+        })
+    }
+}
+
+/// Whether a cannon can reach, see `Ordnance::in_range` for anything else.
+pub fn in_range(from: Vec3, to: Vec3) -> bool {
+    CANNON.in_range(from, to)
+}
+
+/// How a cannon fires at something.
+pub fn launch(from: Vec3, to: Vec3) -> Option<Launch> {
+    CANNON.launch(from, to)
+}
+
+/// Fastest a shot leaves a cannon when aimed by hand, enough to reach as far
+/// as cannons can fire when fired at 45 degrees.
+pub fn maximum_speed() -> f32 {
+    (MAXIMUM_RANGE * GRAVITY).sqrt()
+}
+
+/// Velocity of a shot aimed by hand, `azimuth` degrees clockwise from east
+/// and `elevation` degrees above level, at `power` of the maximum speed.
+pub fn aimed(azimuth: f32, elevation: f32, power: f32) -> Vec3 {
+    let (azimuth, elevation) = (azimuth.to_radians(), elevation.to_radians());
+    let direction = Vec3::new(
+        azimuth.cos() * elevation.cos(),
+        elevation.sin(),
+        azimuth.sin() * elevation.cos(),
+    );
+
+    direction * power.clamp(0.0, 1.0) * maximum_speed()
+}
+
+/// Where something is `time` seconds after leaving `from`.
+pub fn position(from: Vec3, velocity: Vec3, time: f32) -> Vec3 {
+    from + velocity * time - Vec3::Y * (0.5 * GRAVITY * time * time)
+}
+
+/// Points along a trajectory from start to finish.
+pub fn path(from: Vec3, velocity: Vec3, time_of_flight: f32, steps: usize) -> Vec<Vec3> {
+    (0..=steps)
+        .map(|step| position(from, velocity, time_of_flight * step as f32 / steps as f32))
+        .collect()
+}
+
+/// Follows a trajectory until it drops below the surface, as given by
+/// `surface` for any position along the way. Positions without a surface,
+/// like those off the map, are flown through.
+pub fn impact(from: Vec3, velocity: Vec3, surface: impl Fn(Vec3) -> Option<f32>) -> Option<Vec3> {
+    let mut previous = from;
+    let mut time = STEP;
+    while time < LONGEST_FLIGHT {
+        let here = position(from, velocity, time);
+        if let Some(height) = surface(here) {
+            if here.y <= height {
+                // Close enough to split the difference with the last step.
+                let fraction = ((previous.y - height) / (previous.y - here.y)).clamp(0.0, 1.0);
+                return Some(previous.lerp(here, fraction));
+            }
+        }
+        previous = here;
+        time += STEP;
+    }
+
+    None
+}
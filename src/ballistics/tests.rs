@@ -0,0 +1,116 @@
+use bevy::math::Vec3;
+
+use super::*;
+
+#[test]
+fn test_in_range_only_counts_horizontal_distance() {
+    assert!(in_range(Vec3::ZERO, Vec3::new(10., 0., 0.)));
+    assert!(in_range(Vec3::ZERO, Vec3::new(10., 100., 0.)));
+    assert!(!in_range(Vec3::ZERO, Vec3::new(0.5, 0., 0.)));
+    assert!(!in_range(Vec3::ZERO, Vec3::new(MAXIMUM_RANGE + 1., 0., 0.)));
+}
+
+#[test]
+fn test_launch_refuses_targets_too_close() {
+    assert!(launch(Vec3::ZERO, Vec3::new(0.5, 0., 0.)).is_none());
+    assert!(launch(Vec3::ZERO, Vec3::new(10., 0., 0.)).is_some());
+}
+
+#[test]
+fn test_impact_on_flat_ground_short_of_target() {
+    let target = Vec3::new(10., 0., 0.);
+    let launch = launch(Vec3::ZERO, target).expect("launch");
+    let landed = impact(Vec3::ZERO, launch.velocity, |_| Some(0.0)).expect("impact");
+
+    assert!(landed.y.abs() < 0.1);
+    assert!(landed.x > 0.0 && landed.x <= target.x);
+    assert!(landed.z.abs() < 0.001);
+}
+
+#[test]
+fn test_facing_goes_clockwise_from_east() {
+    assert!(facing_direction(0).distance(Vec3::X) < 1e-5);
+    assert!(facing_direction(90).distance(Vec3::Z) < 1e-5);
+    assert!(facing_direction(180).distance(Vec3::NEG_X) < 1e-5);
+    assert!(facing_direction(270).distance(Vec3::NEG_Z) < 1e-5);
+}
+
+#[test]
+fn test_targets_within_traverse_need_no_turning() {
+    let cannon = Vec3::new(5., 1., 5.);
+
+    assert_eq!(
+        beyond_traverse(cannon, cannon + Vec3::new(10., 0., 0.), 0),
+        0.0
+    );
+    assert_eq!(
+        beyond_traverse(cannon, cannon + Vec3::new(10., 0., 10.), 0),
+        0.0
+    );
+    assert_eq!(
+        beyond_traverse(cannon, cannon + Vec3::new(0., 5., 0.), 0),
+        0.0
+    );
+
+    let behind = beyond_traverse(cannon, cannon + Vec3::new(-10., 0., 0.), 0);
+    assert!((behind - (180. - TRAVERSE)).abs() < 1e-3);
+
+    let beside = beyond_traverse(cannon, cannon + Vec3::new(0., 0., -10.), 0);
+    assert!((beside - (90. - TRAVERSE)).abs() < 1e-3);
+    assert_eq!(
+        beyond_traverse(cannon, cannon + Vec3::new(0., 0., -10.), 270),
+        0.0
+    );
+}
+
+#[test]
+fn test_aimed_goes_the_way_its_pointed() {
+    let east = aimed(0., 45., 1.0);
+    assert!(east.x > 0.0 && east.z.abs() < 1e-4 && (east.x - east.y).abs() < 1e-4);
+    assert!((east.length() - maximum_speed()).abs() < 1e-3);
+
+    let south = aimed(90., 30., 0.5);
+    assert!(south.z > 0.0 && south.x.abs() < 1e-4);
+    assert!((south.length() - maximum_speed() / 2.).abs() < 1e-3);
+
+    assert_eq!(aimed(0., 45., 2.0).length(), aimed(0., 45., 1.0).length());
+}
+
+#[test]
+fn test_aimed_at_full_power_reaches_maximum_range() {
+    let landing = impact(Vec3::ZERO, aimed(0., 45., 1.0), |_| Some(0.0)).unwrap();
+
+    assert!((landing.x - MAXIMUM_RANGE).abs() < 0.5);
+    assert!(in_range(Vec3::ZERO, landing - Vec3::X));
+}
+
+#[test]
+fn test_mortars_lob_shots_over_a_shorter_range() {
+    let target = Vec3::new(10., 0., 0.);
+    let apex = |ordnance: &Ordnance| {
+        let launch = ordnance.launch(Vec3::ZERO, target).unwrap();
+        position(Vec3::ZERO, launch.velocity, launch.time_of_flight / 2.).y
+    };
+
+    assert!(apex(&MORTAR) > apex(&CANNON) * 4.);
+
+    let launch = MORTAR.launch(Vec3::ZERO, target).unwrap();
+    let landing = impact(Vec3::ZERO, launch.velocity, |_| Some(0.0)).unwrap();
+    assert!((landing.x - launch.distance).abs() < 0.1);
+
+    let far = Vec3::new(MORTAR.maximum_range + 1., 0., 0.);
+    assert!(!MORTAR.in_range(Vec3::ZERO, far));
+    assert!(CANNON.in_range(Vec3::ZERO, far));
+}
+
+#[test]
+fn test_aimed_launch_keeps_the_velocity_given() {
+    let velocity = aimed(90.0, 45.0, 0.5);
+    let landed = impact(Vec3::ZERO, velocity, |_| Some(0.0)).expect("impact");
+    let launch = Launch::aimed(Vec3::ZERO, landed, velocity).expect("launch");
+
+    assert_eq!(launch.velocity, velocity);
+    assert!(launch.direction.distance(Vec3::Z) < 1e-5);
+    assert!(position(Vec3::ZERO, velocity, launch.time_of_flight).distance(landed) < 0.1);
+    assert!(Launch::aimed(Vec3::ZERO, Vec3::ZERO, Vec3::Y).is_none());
+}
@@ -1,18 +1,37 @@
 use bevy::prelude::*;
 use bevy_mod_picking::prelude::*;
 use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
 
+use catalog::Gun;
 use resources::BuildingResources;
 
 use super::model::*;
+use castle::territory;
 
+mod batching;
+mod brush;
+pub mod catalog;
+mod choosing;
 mod resources;
+pub mod sites;
+#[cfg(test)]
+mod tests;
 mod walls;
 
 use crate::{
+    collision::Collides,
+    controls::{typing, Action, Controls},
+    damage::Damaged,
+    firing::ExplosionEvent,
     helpers::GamePlayLifetime,
+    journal::{self, EventJournal, Scored},
     model::{Coordinates, GROUND_DEPTH, WALL_HEIGHT},
-    terrain::{SurveyedCell, Terrain},
+    network::{self, following, Host},
+    scenario::Scenario,
+    terrain::{Props, SurveyedCell, Terrain, Tide},
+    units::WaveDirector,
 };
 
 pub struct BuildingPlugin;
@@ -20,42 +39,531 @@ pub struct BuildingPlugin;
 impl Plugin for BuildingPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<StructureLayers>()
+            .init_resource::<Pieces>()
+            .init_resource::<brush::RepairBrush>()
+            .register_type::<Wall>()
+            .register_type::<Cannon>()
+            .register_type::<Chain>()
+            .register_type::<Structure>()
+            .register_type::<Operational>()
+            .init_resource::<choosing::CastleSites>()
+            .init_gizmo_group::<choosing::SiteGizmos>()
             .add_systems(PreStartup, resources::load)
+            .add_systems(
+                Update,
+                reload_scenes
+                    .run_if(in_state(AppState::Game))
+                    .run_if(on_event::<AssetEvent<Scene>>()),
+            )
             .add_event::<ConstructionEvent>()
-            .add_systems(OnEnter(AppState::Game), setup_structures)
-            .add_systems(Update, refresh_terrain.run_if(in_state(AppState::Game)))
+            .add_event::<DestructionEvent>()
+            .add_event::<DemolitionEvent>()
+            .add_event::<RestoreEvent>()
+            .add_event::<TurnEnded>()
+            .add_event::<CannonTurned>()
+            .configure_sets(
+                Update,
+                (
+                    ConstructionSet::Input,
+                    ConstructionSet::Apply,
+                    ConstructionSet::Refresh,
+                    ConstructionSet::Present,
+                )
+                    .chain(),
+            )
+            .add_systems(OnEnter(AppState::Game), (setup_structures, clear_pieces))
+            .add_systems(
+                Update,
+                (
+                    restore_structures,
+                    (shell_structures, ruin_structures).chain(),
+                    apply_construction,
+                    apply_turns,
+                    demolish_structures,
+                )
+                    .chain()
+                    .in_set(ConstructionSet::Apply)
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                Update,
+                (
+                    refresh_structures.run_if(resource_changed::<StructureLayers>),
+                    clear_props.run_if(resource_changed::<StructureLayers>),
+                    flood_structures.run_if(
+                        resource_changed::<Tide>.or_else(resource_changed::<StructureLayers>),
+                    ),
+                )
+                    .chain()
+                    .in_set(ConstructionSet::Refresh)
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                Update,
+                batching::batch_walls
+                    .after(refresh_structures)
+                    .in_set(ConstructionSet::Refresh)
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                Update,
+                operational_cannons
+                    .in_set(ConstructionSet::Present)
+                    .run_if(in_state(AppState::Game))
+                    .run_if(state_changed::<Phase>),
+            )
+            .add_systems(
+                Update,
+                (
+                    choosing::prepare_sites,
+                    choosing::hover_sites,
+                    choosing::draw_sites,
+                    choosing::choose_site,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Game))
+                    .run_if(choosing::choosing)
+                    .run_if(not(following)),
+            )
+            .add_systems(
+                Update,
+                deal_pieces
+                    .before(ConstructionSet::Input)
+                    .run_if(in_state(AppState::Game))
+                    .run_if(state_changed::<Phase>.or_else(resource_added::<StructureLayers>)),
+            )
+            .add_systems(
+                Update,
+                (
+                    brush::collect_rubble.after(ConstructionSet::Apply),
+                    brush::earn_repair_points.run_if(state_changed::<Phase>),
+                    brush::draw_brush.run_if(not(typing)),
+                )
+                    .run_if(in_state(AppState::Game)),
+            )
             .add_systems(OnEnter(Activity::Building), start_placing)
             .add_systems(OnExit(Activity::Building), stop_placing)
-            .add_systems(Update, placing.run_if(in_state(Activity::Building)))
-            .add_systems(Update, try_place.run_if(in_state(Activity::Building)));
+            .add_systems(
+                Update,
+                placing
+                    .in_set(ConstructionSet::Present)
+                    .run_if(in_state(Activity::Building)),
+            )
+            .add_systems(
+                Update,
+                switch_gun
+                    .run_if(in_state(Activity::Building))
+                    .run_if(not(typing)),
+            )
+            .add_systems(
+                Update,
+                turn_cannons
+                    .in_set(ConstructionSet::Input)
+                    .run_if(in_state(Activity::Building))
+                    .run_if(not(following))
+                    .run_if(not(typing)),
+            )
+            .add_systems(
+                Update,
+                (
+                    try_place,
+                    try_repair,
+                    discard_piece,
+                    hold_piece,
+                    brush::brush_repairs,
+                )
+                    .in_set(ConstructionSet::Input)
+                    .run_if(in_state(Activity::Building))
+                    .run_if(not(following))
+                    .run_if(not(typing)),
+            );
     }
 }
 
+/// Building goes through these in order every frame, so anything asked for is
+/// built and shown in the same frame it's asked for.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ConstructionSet {
+    /// Deciding what to build, whether by a player, the computer or a script.
+    Input,
+    /// Building and tearing down structures.
+    Apply,
+    /// Bringing the entities for structures in line with what's been built.
+    Refresh,
+    /// Anything shown that depends on those entities.
+    Present,
+}
+
 fn setup_structures(
     mut commands: Commands,
     resources: Res<BuildingResources>,
     settings: Res<Settings>,
+    scenario: Option<Res<Scenario>>,
+    mut next_phase: ResMut<NextState<Phase>>,
 ) {
     let mut structures = StructureLayers::new(settings.size());
-    structures.create_castle(IVec2::new(4, 4), IVec2::new(4, 4), Player::One);
-    structures.create_castle(IVec2::new(26, 26), IVec2::new(4, 4), Player::Two);
+    let predefined = match scenario {
+        Some(scenario) => {
+            for castle in scenario.castles.iter() {
+                structures.create_castle(castle.center(), castle.size(), castle.player);
+            }
+            for placed in scenario.structures.iter() {
+                structures.place(placed.location(), placed.structure.clone());
+            }
+            !scenario.castles.is_empty() || !scenario.structures.is_empty()
+        }
+        None => false,
+    };
     structures.refresh_entities(&mut commands, &resources);
 
+    // Without a scenario saying where castles go players choose for themselves,
+    // challenges say where play starts.
+    let challenge = scenario.and_then(|s| s.challenge.as_ref().map(|c| c.phase.clone()));
+    next_phase.set(match challenge {
+        Some(phase) => phase,
+        None if predefined => Phase::Fortify(Player::One),
+        None => Phase::Choose(Player::One),
+    });
+
     commands.insert_resource(structures);
+    commands.insert_resource(choosing::CastleSites::default());
 }
 
-fn refresh_terrain(
-    mut commands: Commands,
+fn apply_construction(
     mut modified: EventReader<ConstructionEvent>,
     mut structures: ResMut<StructureLayers>,
-    resources: Res<BuildingResources>,
 ) {
     for ev in modified.read() {
         info!("terrain-modified {:?}", ev);
 
         let grid = ev.coordinates().clone().into();
         structures.set(grid, ev.structure().clone());
-        structures.refresh_entities(&mut commands, &resources);
+    }
+}
+
+/// Spawns and respawns entities for whatever was built or torn down, once for
+/// everything that changed this frame. Doing so isn't itself a change.
+fn refresh_structures(
+    mut commands: Commands,
+    mut structures: ResMut<StructureLayers>,
+    resources: Res<BuildingResources>,
+) {
+    structures
+        .bypass_change_detection()
+        .refresh_entities(&mut commands, &resources);
+}
+
+/// Replaces every structure with those given, all at once.
+fn restore_structures(
+    mut commands: Commands,
+    mut restores: EventReader<RestoreEvent>,
+    mut structures: ResMut<StructureLayers>,
+) {
+    for restore in restores.read() {
+        info!(structures = restore.structures().len(), "restoring");
+
+        structures.clear(&mut commands);
+        for (grid, structure) in restore.structures() {
+            structures.place(*grid, structure.clone());
+        }
+    }
+}
+
+fn demolish_structures(
+    mut commands: Commands,
+    mut demolitions: EventReader<DemolitionEvent>,
+    mut structures: ResMut<StructureLayers>,
+) {
+    for demolition in demolitions.read() {
+        let grid = demolition.coordinates().clone().into();
+        info!(%grid, "demolished");
+        structures.remove(&mut commands, grid);
+    }
+}
+
+/// Anything built where there's a prop, like a castle chosen over a forest,
+/// clears it away.
+fn clear_props(mut commands: Commands, mut props: ResMut<Props>, structures: Res<StructureLayers>) {
+    for (grid, _) in structures.structures() {
+        props.clear(&mut commands, grid);
+    }
+}
+
+/// Respawns the corners and cannons already built whenever their scenes are
+/// saved again, while assets are being watched for changes.
+fn reload_scenes(
+    mut events: EventReader<AssetEvent<Scene>>,
+    mut scenes: Query<&mut Handle<Scene>>,
+    resources: Res<BuildingResources>,
+) {
+    let modified: Vec<AssetId<Scene>> = events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .filter(|id| *id == resources.corner.id() || *id == resources.cannon.id())
+        .collect();
+    if modified.is_empty() {
+        return;
+    }
+
+    let mut respawned = 0;
+    for mut scene in &mut scenes {
+        if modified.contains(&scene.id()) {
+            // A changed handle has the scene spawner replace the instance.
+            scene.set_changed();
+            respawned += 1;
+        }
+    }
+
+    info!(%respawned, "scenes-reloaded");
+}
+
+/// Structures stop working while the tide has them underwater, cannons can't
+/// fire and walls don't enclose anything.
+#[derive(Component, Debug)]
+pub struct Flooded;
+
+fn flood_structures(
+    mut commands: Commands,
+    mut structures: ResMut<StructureLayers>,
+    terrain: Query<&Terrain>,
+    flooded: Query<(), With<Flooded>>,
+) {
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    let mut underwater_cells = HashSet::default();
+
+    for (grid, item) in structures.entities.enumerate_cells() {
+        let StructureEntity::Current(structure, entity) = item else {
+            continue;
+        };
+
+        // Chains float, whatever the tide's doing.
+        if matches!(structure, Structure::Chain(_)) {
+            continue;
+        }
+
+        let underwater = terrain
+            .survey_grid(grid)
+            .map(|survey| matches!(survey.cell(), SurveyedCell::Water))
+            .unwrap_or_default();
+        if underwater {
+            underwater_cells.insert(grid);
+        }
+
+        match (underwater, flooded.contains(*entity)) {
+            (true, false) => {
+                info!(%grid, "flooded");
+                commands.entity(*entity).insert((Flooded, ColliderDisabled));
+            }
+            (false, true) => {
+                info!(%grid, "receded");
+                commands
+                    .entity(*entity)
+                    .remove::<(Flooded, ColliderDisabled)>();
+            }
+            _ => {}
+        }
+    }
+
+    // Only when it's changed, as anything else would have it run again.
+    if structures.flooded != underwater_cells {
+        structures.flooded = underwater_cells;
+    }
+}
+
+/// Damage done to a structure right at the center of an explosion, falling off
+/// to nothing at the edge of the blast.
+const SHELL_DAMAGE: f32 = 40.0;
+
+/// Chains float just above the water.
+const CHAIN_OFFSET: Vec3 = Vec3::new(0., 0.1, 0.);
+
+/// Damages structures caught in explosions, scoring whatever each one brings
+/// down of the other player's all together.
+fn shell_structures(
+    mut explosions: EventReader<ExplosionEvent>,
+    mut damaged: EventWriter<Damaged>,
+    mut scored: EventWriter<Scored>,
+    mut structures: Query<
+        (
+            &Transform,
+            &Player,
+            &mut Health,
+            Option<&Cannon>,
+            Has<Chain>,
+        ),
+        Or<(With<Wall>, With<Cannon>, With<Chain>)>,
+    >,
+    director: Res<WaveDirector>,
+    rules: Res<Rules>,
+) {
+    let sudden_death = rules.is_sudden_death(director.round());
+
+    for explosion in explosions.read() {
+        let mut destroyed = Vec::new();
+
+        for (transform, owner, mut health, cannon, chain) in &mut structures {
+            if !rules.damages(explosion.player(), owner) {
+                continue;
+            }
+
+            let distance = transform.translation.distance(explosion.world());
+            if distance < explosion.radius() {
+                let damage = if sudden_death {
+                    health.hp()
+                } else {
+                    SHELL_DAMAGE * (1.0 - distance / explosion.radius())
+                };
+                let standing = !health.dead();
+                health.damage(damage);
+                damaged.send(
+                    Damaged::new(transform.translation, damage, explosion.player().copied())
+                        .structure(*owner, health.dead()),
+                );
+
+                if standing && health.dead() && explosion.player() != Some(owner) {
+                    destroyed.push(match (cannon, chain) {
+                        (Some(cannon), _) => cannon.entry().points,
+                        (None, true) => catalog::CHAIN_POINTS,
+                        (None, false) => catalog::WALL_POINTS,
+                    });
+                }
+            }
+        }
+
+        let Some(player) = explosion.player() else {
+            continue;
+        };
+        if destroyed.is_empty() {
+            continue;
+        }
+
+        let points = catalog::combo(&destroyed);
+        info!(?player, destroyed = destroyed.len(), %points, "destruction-scored");
+        scored.send(Scored::new(*player, points, destroyed.len()));
+    }
+}
+
+/// Walls, cannons and chains that have taken all the damage they can are torn
+/// down.
+fn ruin_structures(
+    mut commands: Commands,
+    mut structures: ResMut<StructureLayers>,
+    mut destroyed: EventWriter<DestructionEvent>,
+    damaged: Query<
+        (Entity, &Health),
+        (Or<(With<Wall>, With<Cannon>, With<Chain>)>, Changed<Health>),
+    >,
+) {
+    let ruined: Vec<Entity> = damaged
+        .iter()
+        .filter(|(_, health)| health.dead())
+        .map(|(entity, _)| entity)
+        .collect();
+    if ruined.is_empty() {
+        return;
+    }
+
+    let grids: Vec<(IVec2, Structure)> = structures
+        .entities
+        .layout()
+        .into_iter()
+        .filter_map(|(grid, _, item)| match item {
+            StructureEntity::Current(structure, entity) if ruined.contains(entity) => {
+                Some((grid, structure.clone()))
+            }
+            _ => None,
+        })
+        .collect();
+
+    for (grid, structure) in grids {
+        info!(%grid, "ruined");
+        structures.remove(&mut commands, grid);
+        destroyed.send(DestructionEvent::new(grid.into(), structure));
+    }
+}
+
+/// Cannons that can fire, only those inside their player's territory.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct Operational;
+
+/// Shown over cannons that aren't operational.
+#[derive(Component)]
+struct Inoperable;
+
+/// Decides which cannons can fire as each phase ends, so anything left outside
+/// the walls when the fortifying is done sits out the bombardment.
+fn operational_cannons(
+    mut commands: Commands,
+    structures: Res<StructureLayers>,
+    resources: Res<BuildingResources>,
+    operational: Query<(), With<Operational>>,
+    overlays: Query<(Entity, &Parent), With<Inoperable>>,
+    rules: Res<Rules>,
+) {
+    let territories: Vec<(Player, SquareGrid<bool>)> = [Player::One, Player::Two]
+        .into_iter()
+        .map(|player| {
+            let territory = if rules.coop {
+                structures.shared_territory()
+            } else {
+                structures.territory(&player)
+            };
+            (player, territory)
+        })
+        .collect();
+
+    for (grid, item) in structures.entities.enumerate_cells() {
+        let StructureEntity::Current(Structure::Cannon(cannon), entity) = item else {
+            continue;
+        };
+
+        let enclosed = territories
+            .iter()
+            .find(|(player, _)| *player == cannon.player)
+            .and_then(|(_, territory)| territory.get(grid).copied())
+            .unwrap_or_default()
+            && !structures.flooded(grid);
+
+        match (enclosed, operational.contains(*entity)) {
+            (true, false) => {
+                info!(%grid, player = ?cannon.player, "operational");
+                commands.entity(*entity).insert(Operational);
+                for (overlay, parent) in &overlays {
+                    if parent.get() == *entity {
+                        commands.entity(overlay).despawn_recursive();
+                    }
+                }
+            }
+            (false, _) => {
+                if overlays.iter().any(|(_, parent)| parent.get() == *entity) {
+                    continue;
+                }
+                info!(%grid, player = ?cannon.player, "inoperable");
+                commands
+                    .entity(*entity)
+                    .remove::<Operational>()
+                    .with_children(|parent| {
+                        parent.spawn((
+                            Name::new("Cannon:Inoperable"),
+                            Inoperable,
+                            PbrBundle {
+                                mesh: resources.unknown.clone(),
+                                material: resources.inoperable.clone(),
+                                transform: Transform::from_scale(Vec3::splat(1.05)),
+                                ..default()
+                            },
+                        ));
+                    });
+            }
+            (true, true) => {}
+        }
     }
 }
 
@@ -97,6 +605,7 @@ fn placing(
     mut placing: Query<(&mut Placing, &mut Transform, &Handle<StandardMaterial>)>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     structures: Res<StructureLayers>,
+    props: Res<Props>,
     terrain: Query<&Terrain>,
 ) {
     if events.is_empty() {
@@ -111,7 +620,8 @@ fn placing(
         if let Some(position) = event.event.hit.position {
             if let Some(survey) = terrain.survey(position) {
                 for (mut placing, mut transform, mh) in &mut placing {
-                    let can_build = survey.can_build()
+                    let can_build = (survey.can_build() || terrain.shallow(survey.location()))
+                        && !props.blocked(survey.location())
                         && structures
                             .get(survey.location())
                             .map(|v| v.can_build())
@@ -126,50 +636,311 @@ fn placing(
     }
 }
 
+/// Hands out the pieces for whoever's turn it is, afresh every phase.
+fn deal_pieces(mut pieces: ResMut<Pieces>, phase: Res<State<Phase>>, rules: Res<Rules>) {
+    let piece = Piece::dealt(phase.get());
+    let dealt = rules.pieces(phase.get());
+    let discards = rules.discards(phase.get());
+    debug!(phase = ?phase.get(), ?piece, dealt, discards, "pieces-dealt");
+    pieces.deal((0..dealt).map(|_| piece), discards);
+}
+
+/// A new game begins with nothing held over from the last.
+fn clear_pieces(mut pieces: ResMut<Pieces>) {
+    *pieces = Pieces::default();
+}
+
+/// Throws away the piece being placed for the next one, as long as there are
+/// discards left this turn.
+fn discard_piece(
+    mut pieces: ResMut<Pieces>,
+    mut turns: EventWriter<TurnEnded>,
+    keys: Res<ButtonInput<KeyCode>>,
+    controls: Res<Controls>,
+    phase: Res<State<Phase>>,
+    settings: Res<Settings>,
+    host: Option<Res<Host>>,
+) {
+    if !controls.just_pressed(&keys, Action::Discard) {
+        return;
+    }
+
+    let player = phase.get().player();
+    if settings.computer(&player) || network::remote(host.as_deref(), &player) {
+        return;
+    }
+
+    let Some(left) = pieces.discard() else {
+        info!(?player, "unable to discard");
+        return;
+    };
+
+    info!(?player, left, discards = pieces.discards(), "discarded");
+
+    if left == 0 {
+        turns.send(TurnEnded::new(player));
+    }
+}
+
+/// Chooses between placing cannons and mortars while arming.
+fn switch_gun(
+    mut placing: Query<&mut Placing>,
+    keys: Res<ButtonInput<KeyCode>>,
+    controls: Res<Controls>,
+    phase: Res<State<Phase>>,
+) {
+    if !controls.just_pressed(&keys, Action::SwitchGun) || !matches!(phase.get(), Phase::Arm(_)) {
+        return;
+    }
+
+    for mut placing in &mut placing {
+        placing.gun = placing.gun.next();
+        info!(gun = ?placing.gun, "gun-switched");
+    }
+}
+
+/// Sets the piece being placed aside for later, swapping in whatever was set
+/// aside before. Setting aside the last piece with nothing to swap in ends the
+/// turn, the same as placing it.
+fn hold_piece(
+    mut pieces: ResMut<Pieces>,
+    mut turns: EventWriter<TurnEnded>,
+    keys: Res<ButtonInput<KeyCode>>,
+    controls: Res<Controls>,
+    phase: Res<State<Phase>>,
+    settings: Res<Settings>,
+    host: Option<Res<Host>>,
+) {
+    if !controls.just_pressed(&keys, Action::Hold) {
+        return;
+    }
+
+    let player = phase.get().player();
+    if settings.computer(&player) || network::remote(host.as_deref(), &player) {
+        return;
+    }
+
+    let Some(left) = pieces.hold() else {
+        info!(?player, "unable to hold");
+        return;
+    };
+
+    info!(?player, held = ?pieces.held(), left, "piece-held");
+
+    if left == 0 {
+        turns.send(TurnEnded::new(player));
+    }
+}
+
+/// Clicking places the next piece: a wall, or a chain across the shallows, or
+/// whichever gun is chosen inside the player's walls.
+#[allow(clippy::too_many_arguments)]
 fn try_place(
     terrain: Query<&Terrain>,
-    _placing: Query<&mut Placing>,
+    placing: Query<&Placing>,
+    mut pieces: ResMut<Pieces>,
     structures: Res<StructureLayers>,
+    props: Res<Props>,
     mut events: EventReader<Pointer<Click>>,
     mut modified: EventWriter<ConstructionEvent>,
+    mut turns: EventWriter<TurnEnded>,
+    keys: Res<ButtonInput<KeyCode>>,
+    controls: Res<Controls>,
+    phase: Res<State<Phase>>,
+    settings: Res<Settings>,
+    host: Option<Res<Host>>,
 ) {
     if events.is_empty() {
         return;
     }
 
+    // The computer builds for itself, as do players connected from elsewhere,
+    // and clicks with the repair brush are for repairing.
+    let player = phase.get().player();
+    if settings.computer(&player)
+        || network::remote(host.as_deref(), &player)
+        || controls.pressed(&keys, Action::RepairArea)
+    {
+        events.clear();
+        return;
+    }
+
     let Some(terrain) = terrain.get_single().ok() else {
         return;
     };
 
+    let gun = placing.get_single().map(|p| p.gun).unwrap_or_default();
+
     for event in events.read() {
-        if let Some(position) = event.event.hit.position {
-            if let Some(survey) = terrain.survey(position) {
-                let can_build = survey.can_build() && structures.get(survey.location()).is_none();
-
-                info!("{:#?}", survey);
-
-                if can_build {
-                    match survey.cell() {
-                        SurveyedCell::Ground(_cell) => {
-                            modified.send(ConstructionEvent::new(
-                                survey.location().into(),
-                                Structure::Wall(Wall {
-                                    player: Player::One,
-                                }),
-                            ));
-                        }
-                        SurveyedCell::Beach => {}
-                        SurveyedCell::Water => {}
-                    }
-                }
+        let Some(position) = event.event.hit.position else {
+            continue;
+        };
+        let Some(piece) = pieces.next() else {
+            break;
+        };
+        let placed = match piece {
+            Piece::Gun => armable(terrain, &structures, &props, &player, position)
+                .map(|grid| (grid, Structure::Cannon(Cannon::armed(player, gun)))),
+            Piece::Wall => buildable(terrain, &structures, &props, position)
+                .map(|(grid, footing)| (grid, footing.structure(player))),
+        };
+
+        if let Some((grid, structure)) = placed {
+            let Some(left) = pieces.take() else {
+                break;
+            };
+
+            modified.send(ConstructionEvent::new(grid.into(), structure));
+            if left == 0 {
+                turns.send(TurnEnded::new(player));
             }
         }
     }
 }
 
+/// Clicking on one of the player's own damaged walls or cannons patches it back
+/// up, when the rules allow repairs and the player has the points to pay.
+#[allow(clippy::too_many_arguments)]
+fn try_repair(
+    mut events: EventReader<Pointer<Click>>,
+    mut journal: ResMut<EventJournal>,
+    mut healths: Query<(&mut Health, Has<Cannon>)>,
+    terrain: Query<&Terrain>,
+    structures: Res<StructureLayers>,
+    phase: Res<State<Phase>>,
+    settings: Res<Settings>,
+    rules: Res<Rules>,
+    host: Option<Res<Host>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    controls: Res<Controls>,
+    time: Res<Time>,
+) {
+    let player = phase.get().player();
+    let Some(cost) = rules.repair_cost else {
+        events.clear();
+        return;
+    };
+    if settings.computer(&player)
+        || network::remote(host.as_deref(), &player)
+        || controls.pressed(&keys, Action::RepairArea)
+    {
+        events.clear();
+        return;
+    }
+
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    let clicked = events
+        .read()
+        .filter_map(|event| event.event.hit.position)
+        .filter_map(|position| terrain.survey(position))
+        .map(|survey| survey.location());
+
+    for grid in clicked {
+        let Some((_, entity)) = structures
+            .walls(&player)
+            .into_iter()
+            .chain(structures.cannons(&player))
+            .find(|(at, _)| *at == grid)
+        else {
+            continue;
+        };
+
+        let Ok((mut health, cannon)) = healths.get_mut(entity) else {
+            continue;
+        };
+
+        let full = if cannon { CANNON_HP } else { WALL_HP };
+        if health.hp() >= full {
+            continue;
+        }
+
+        // Playing together the points are shared, and so is the cost.
+        let payers = journal::shared(&rules, player);
+        if payers.iter().any(|payer| journal.total(payer) < cost) {
+            info!(%grid, ?player, cost, "unable to afford repair");
+            continue;
+        }
+        for payer in payers {
+            journal.spend(time.elapsed_seconds(), payer, cost);
+        }
+
+        info!(%grid, ?player, cost, "repaired");
+        *health = Health::new(full);
+    }
+}
+
+/// What a cell being built on is, walls going on the ground and chains across
+/// shallow water.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Footing {
+    Ground,
+    Shallows,
+}
+
+impl Footing {
+    /// Whatever the player builds here.
+    pub fn structure(&self, player: Player) -> Structure {
+        match self {
+            Footing::Ground => Structure::Wall(Wall::new(player)),
+            Footing::Shallows => Structure::Chain(Chain::new(player)),
+        }
+    }
+}
+
+/// Where a wall would go when building at the position, as long as there's
+/// ground there that's free to build on.
+pub fn buildable(
+    terrain: &Terrain,
+    structures: &StructureLayers,
+    props: &Props,
+    position: Vec3,
+) -> Option<(IVec2, Footing)> {
+    let survey = terrain.survey(position)?;
+    let location = survey.location();
+
+    debug!("{:#?}", survey);
+
+    if props.blocked(location) || structures.occupied(location) {
+        return None;
+    }
+
+    match survey.cell() {
+        SurveyedCell::Ground(_) => Some((location, Footing::Ground)),
+        SurveyedCell::Water if terrain.shallow(location) => Some((location, Footing::Shallows)),
+        _ => None,
+    }
+}
+
+/// Where a gun would go when arming at the position, as long as it's ground
+/// inside the player's walls that's free to build on.
+pub fn armable(
+    terrain: &Terrain,
+    structures: &StructureLayers,
+    props: &Props,
+    player: &Player,
+    position: Vec3,
+) -> Option<IVec2> {
+    let (grid, footing) = buildable(terrain, structures, props, position)?;
+    let inside = structures.territory(player).get(grid) == Some(&true);
+
+    (footing == Footing::Ground && inside).then_some(grid)
+}
+
+/// Whatever's about to be built, following the cursor around.
 #[derive(Clone, Debug, Component, Default)]
-struct Placing {
+pub struct Placing {
     allowed: bool,
+    /// Placed while arming.
+    gun: Gun,
+}
+
+impl Placing {
+    pub fn gun(&self) -> Gun {
+        self.gun
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -191,6 +962,207 @@ impl ConstructionEvent {
     }
 }
 
+/// A cannon turned to face another way, as it's to be from then on.
+#[derive(Clone, Debug)]
+pub struct CannonTurned(Coordinates, Cannon);
+
+impl Event for CannonTurned {}
+
+impl CannonTurned {
+    pub fn new(coordinates: Coordinates, cannon: Cannon) -> Self {
+        Self(coordinates, cannon)
+    }
+
+    pub fn coordinates(&self) -> &Coordinates {
+        &self.0
+    }
+
+    pub fn cannon(&self) -> &Cannon {
+        &self.1
+    }
+}
+
+/// Whoever's turn it is has placed the last of their pieces, which ends it.
+/// Kept apart from construction, which scripts and the editor do too without
+/// ending any turn.
+#[derive(Clone, Debug)]
+pub struct TurnEnded(Player);
+
+impl Event for TurnEnded {}
+
+impl TurnEnded {
+    pub fn new(player: Player) -> Self {
+        Self(player)
+    }
+
+    pub fn player(&self) -> &Player {
+        &self.0
+    }
+}
+
+/// Something dealt to a player to place.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Piece {
+    /// A wall, or a chain across the shallows.
+    Wall,
+    /// A cannon or a mortar, inside the player's walls.
+    Gun,
+}
+
+impl Piece {
+    /// What's dealt during `phase`, guns while arming and walls otherwise.
+    pub fn dealt(phase: &Phase) -> Self {
+        match phase {
+            Phase::Arm(_) => Piece::Gun,
+            _ => Piece::Wall,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Piece::Wall => "wall",
+            Piece::Gun => "gun",
+        }
+    }
+}
+
+/// The pieces whoever's turn it is has yet to place, in the order they're to
+/// be placed and dealt out again every phase. Placing the last of them ends
+/// the turn.
+#[derive(Debug, Default, Resource)]
+pub struct Pieces {
+    upcoming: VecDeque<Piece>,
+    /// Pieces that may still be thrown away this turn.
+    discards: u32,
+    /// Set aside to swap back in later this turn, and gone once it's over so
+    /// only what's dealt for a phase is ever placed in it.
+    held: Option<Piece>,
+    /// Whether a piece has been held since the last was placed, which it
+    /// can't be again until one is.
+    swapped: bool,
+}
+
+impl Pieces {
+    pub fn deal(&mut self, pieces: impl IntoIterator<Item = Piece>, discards: u32) {
+        self.upcoming = pieces.into_iter().collect();
+        self.discards = discards;
+        self.held = None;
+        self.swapped = false;
+    }
+
+    pub fn left(&self) -> u32 {
+        self.upcoming.len() as u32
+    }
+
+    pub fn discards(&self) -> u32 {
+        self.discards
+    }
+
+    /// The piece to place now, nothing once they've all been placed.
+    pub fn next(&self) -> Option<Piece> {
+        self.upcoming.front().copied()
+    }
+
+    /// Everything still to place, the next piece first.
+    pub fn upcoming(&self) -> impl Iterator<Item = Piece> + '_ {
+        self.upcoming.iter().copied()
+    }
+
+    /// Takes the next piece to place, returning how many are left after it or
+    /// None when they've all been placed.
+    pub fn take(&mut self) -> Option<u32> {
+        self.upcoming.pop_front()?;
+        self.swapped = false;
+        Some(self.left())
+    }
+
+    pub fn held(&self) -> Option<Piece> {
+        self.held
+    }
+
+    /// Sets the next piece aside, swapping in the one set aside before, if
+    /// any, and returning how many are left to place. Only once for every
+    /// piece placed.
+    pub fn hold(&mut self) -> Option<u32> {
+        if self.swapped {
+            return None;
+        }
+
+        let next = self.upcoming.pop_front()?;
+        if let Some(held) = self.held.replace(next) {
+            self.upcoming.push_front(held);
+        }
+        self.swapped = true;
+
+        Some(self.left())
+    }
+
+    /// Takes the next piece without placing it, the same as `take` as long as
+    /// there are discards left.
+    pub fn discard(&mut self) -> Option<u32> {
+        if self.discards == 0 {
+            return None;
+        }
+
+        let left = self.take()?;
+        self.discards -= 1;
+        Some(left)
+    }
+}
+
+/// A structure torn down after taking all the damage it could.
+#[derive(Clone, Debug)]
+pub struct DestructionEvent(Coordinates, Structure);
+
+impl Event for DestructionEvent {}
+
+impl DestructionEvent {
+    pub fn new(coordinates: Coordinates, structure: Structure) -> Self {
+        Self(coordinates, structure)
+    }
+
+    pub fn coordinates(&self) -> &Coordinates {
+        &self.0
+    }
+
+    pub fn structure(&self) -> &Structure {
+        &self.1
+    }
+}
+
+/// A structure torn down without being destroyed, for putting back the way
+/// things were at another point in a game.
+#[derive(Clone, Debug)]
+pub struct DemolitionEvent(Coordinates);
+
+impl Event for DemolitionEvent {}
+
+impl DemolitionEvent {
+    pub fn new(coordinates: Coordinates) -> Self {
+        Self(coordinates)
+    }
+
+    pub fn coordinates(&self) -> &Coordinates {
+        &self.0
+    }
+}
+
+/// Every structure there should be, replacing any that are there now.
+#[derive(Clone, Debug)]
+pub struct RestoreEvent(Vec<(IVec2, Structure)>);
+
+impl Event for RestoreEvent {}
+
+impl RestoreEvent {
+    pub fn new(structures: Vec<(IVec2, Structure)>) -> Self {
+        Self(structures)
+    }
+
+    pub fn structures(&self) -> &[(IVec2, Structure)] {
+        &self.0
+    }
+}
+
 #[derive(Default, Clone)]
 pub enum StructureEntity {
     #[default]
@@ -236,6 +1208,7 @@ pub struct CannonBundle {
     spatial: SpatialBundle,
     collider: Collider,
     collision_groups: CollisionGroups,
+    health: Health,
     player: Player,
     cannon: Cannon,
 }
@@ -243,14 +1216,16 @@ pub struct CannonBundle {
 impl CannonBundle {
     fn new(grid: IVec2, position: Vec3, cannon: Cannon) -> Self {
         Self {
-            name: Name::new(format!("Cannon-{:?}", &grid)),
+            name: Name::new(format!("{:?}-{:?}", cannon.gun, &grid)),
             lifetime: GamePlayLifetime,
             spatial: SpatialBundle {
-                transform: Transform::from_translation(position),
+                transform: Transform::from_translation(position)
+                    .with_rotation(turned(cannon.facing)),
                 ..default()
             },
             collider: Collider::cuboid(TILE_SIZE / 2., STRUCTURE_HEIGHT / 2., TILE_SIZE / 2.),
-            collision_groups: CollisionGroups::new(Group::all(), Group::all()),
+            collision_groups: Collides::Cannons.groups(),
+            health: Health::new(CANNON_HP),
             player: cannon.player.clone(),
             cannon,
         }
@@ -264,6 +1239,7 @@ pub struct WallBundle {
     spatial: SpatialBundle,
     collider: Collider,
     collision_groups: CollisionGroups,
+    health: Health,
     player: Player,
     wall: Wall,
 }
@@ -278,25 +1254,63 @@ impl WallBundle {
                 ..default()
             },
             collider: Collider::cuboid(TILE_SIZE / 2., STRUCTURE_HEIGHT / 2., TILE_SIZE / 2.),
-            collision_groups: CollisionGroups::new(Group::all(), Group::all()),
+            collision_groups: Collides::Structures.groups(),
+            health: Health::new(WALL_HP),
             player: wall.player.clone(),
             wall,
         }
     }
 }
 
+#[derive(Bundle)]
+pub struct ChainBundle {
+    name: Name,
+    lifetime: GamePlayLifetime,
+    spatial: SpatialBundle,
+    collider: Collider,
+    collision_groups: CollisionGroups,
+    health: Health,
+    player: Player,
+    chain: Chain,
+}
+
+impl ChainBundle {
+    fn new(grid: IVec2, position: Vec3, chain: Chain) -> Self {
+        Self {
+            name: Name::new(format!("Chain-{:?}", &grid)),
+            lifetime: GamePlayLifetime,
+            spatial: SpatialBundle {
+                transform: Transform::from_translation(position),
+                ..default()
+            },
+            collider: Collider::cuboid(TILE_SIZE / 2., 0.1, TILE_SIZE / 2.),
+            collision_groups: Collides::Structures.groups(),
+            health: Health::new(WALL_HP),
+            player: chain.player.clone(),
+            chain,
+        }
+    }
+}
+
 #[derive(Default, Resource)]
 pub struct StructureLayers {
     entities: SquareGrid<StructureEntity>,
+    /// Cells with structures the tide has covered.
+    flooded: HashSet<IVec2>,
 }
 
 impl StructureLayers {
     pub fn new(size: UVec2) -> Self {
         Self {
             entities: SquareGrid::new_flat(size),
+            flooded: HashSet::default(),
         }
     }
 
+    pub fn flooded(&self, grid: IVec2) -> bool {
+        self.flooded.contains(&grid)
+    }
+
     pub fn create_castle(&mut self, center: IVec2, size: IVec2, player: Player) {
         let (x0, y0) = (center.x - size.x / 2, center.y - size.y / 2);
         let (x1, y1) = (center.x + size.x / 2, center.y + size.y / 2);
@@ -309,16 +1323,208 @@ impl StructureLayers {
             })),
         );
 
+        // Facing whichever way most of the map is.
+        let towards = self.entities.size().as_ivec2() / 2 - center;
+        let facing = match (
+            towards.x.abs() >= towards.y.abs(),
+            towards.x >= 0,
+            towards.y >= 0,
+        ) {
+            (true, true, _) => 0,
+            (true, false, _) => 180,
+            (false, _, true) => 90,
+            (false, _, false) => 270,
+        };
+
         self.entities.set(
             IVec2::new(center.x as i32, center.y as i32),
-            StructureEntity::New(Structure::Cannon(Cannon { player })),
+            StructureEntity::New(Structure::Cannon(
+                Cannon::new(player).facing_towards(facing),
+            )),
         );
     }
 
+    /// The player's cannon at `grid`, once it's been built.
+    pub fn cannon(&self, grid: IVec2, player: &Player) -> Option<&Cannon> {
+        match self.entities.get(grid)? {
+            StructureEntity::Current(Structure::Cannon(cannon), _) if cannon.player == *player => {
+                Some(cannon)
+            }
+            _ => None,
+        }
+    }
+
+    /// Turns the cannon at `grid` to face the way `cannon` does, returning it
+    /// along with its entity when there's one of the same player's to turn.
+    pub fn face_cannon(&mut self, grid: IVec2, cannon: &Cannon) -> Option<(Entity, Cannon)> {
+        let StructureEntity::Current(Structure::Cannon(existing), entity) =
+            self.entities.get(grid)?
+        else {
+            return None;
+        };
+        if existing.player != cannon.player {
+            return None;
+        }
+
+        let (turned, entity) = (existing.clone().facing_towards(cannon.facing), *entity);
+        self.entities.set(
+            grid,
+            StructureEntity::Current(Structure::Cannon(turned.clone()), entity),
+        );
+
+        Some((entity, turned))
+    }
+
     fn get(&self, grid: IVec2) -> Option<&StructureEntity> {
         self.entities.get_xy(grid)
     }
 
+    /// Places a structure before any entities exist, unlike `set` there's no
+    /// need to refresh any neighbors.
+    fn place(&mut self, grid: IVec2, structure: Structure) {
+        if self.entities.get(grid).is_some() {
+            self.entities.set(grid, StructureEntity::New(structure));
+        } else {
+            warn!(%grid, "ignoring structure outside of map");
+        }
+    }
+
+    /// Where the player has walls, standing or about to be.
+    pub fn wall_grid(&self, player: &Player) -> SquareGrid<bool> {
+        self.entities.apply(|grid, item| match item {
+            StructureEntity::Empty => false,
+            StructureEntity::New(structure)
+            | StructureEntity::Affected(structure, _)
+            | StructureEntity::Current(structure, _) => {
+                matches!(structure, Structure::Wall(wall) if wall.player == *player)
+                    && !self.flooded(grid.as_ivec2())
+            }
+        })
+    }
+
+    /// Cells inside the player's walls.
+    pub fn territory(&self, player: &Player) -> SquareGrid<bool> {
+        territory::enclosed(&self.wall_grid(player))
+    }
+
+    /// Cells inside anyone's walls, which players holding the island together
+    /// share, walls they've built between them included.
+    pub fn shared_territory(&self) -> SquareGrid<bool> {
+        let walls = self.entities.apply(|grid, item| match item {
+            StructureEntity::Empty => false,
+            StructureEntity::New(structure)
+            | StructureEntity::Affected(structure, _)
+            | StructureEntity::Current(structure, _) => {
+                matches!(structure, Structure::Wall(_)) && !self.flooded(grid.as_ivec2())
+            }
+        });
+
+        territory::enclosed(&walls)
+    }
+
+    /// The player's walls that would open up their territory if destroyed,
+    /// along with how many cells would be lost.
+    pub fn breaches(&self, player: &Player) -> Vec<(IVec2, usize)> {
+        territory::breaches(&self.wall_grid(player))
+    }
+
+    /// The player's walls that have been built.
+    pub fn walls(&self, player: &Player) -> Vec<(IVec2, Entity)> {
+        self.entities
+            .layout()
+            .into_iter()
+            .filter_map(|(grid, _, item)| match item {
+                StructureEntity::Current(Structure::Wall(wall), entity)
+                    if wall.player == *player =>
+                {
+                    Some((grid, *entity))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The player's cannons that have been built.
+    pub fn cannons(&self, player: &Player) -> Vec<(IVec2, Entity)> {
+        self.entities
+            .layout()
+            .into_iter()
+            .filter_map(|(grid, _, item)| match item {
+                StructureEntity::Current(Structure::Cannon(cannon), entity)
+                    if cannon.player == *player =>
+                {
+                    Some((grid, *entity))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// What each of the player's built cannons is, whether cannon or mortar.
+    pub fn guns(&self, player: &Player) -> Vec<(IVec2, Gun)> {
+        self.entities
+            .layout()
+            .into_iter()
+            .filter_map(|(grid, _, item)| match item {
+                StructureEntity::Current(Structure::Cannon(cannon), _)
+                    if cannon.player == *player =>
+                {
+                    Some((grid, cannon.gun))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn occupied(&self, grid: IVec2) -> bool {
+        self.get(grid).is_some_and(|item| !item.can_build())
+    }
+
+    /// Whether there's a chain across the cell, keeping ships out.
+    pub fn chained(&self, grid: IVec2) -> bool {
+        is_chain(self.get(grid).cloned())
+    }
+
+    /// Tears down whatever is there, leaving the neighbors to be refreshed.
+    fn remove(&mut self, commands: &mut Commands, grid: IVec2) {
+        match self.entities.get(grid) {
+            Some(StructureEntity::Current(_, entity))
+            | Some(StructureEntity::Affected(_, entity)) => {
+                commands.entity(*entity).despawn_recursive();
+            }
+            Some(_) => {}
+            None => return,
+        }
+
+        self.entities.set(grid, StructureEntity::Empty);
+
+        for v in Around::centered(grid).to_vec().into_iter() {
+            if let Some(e) = self.entities.get(v) {
+                self.entities.set(v, e.affected());
+            }
+        }
+    }
+
+    /// Tears down everything, without refreshing anything.
+    fn clear(&mut self, commands: &mut Commands) {
+        for item in self.entities.iter() {
+            if let StructureEntity::Current(_, entity) | StructureEntity::Affected(_, entity) = item
+            {
+                commands.entity(*entity).despawn_recursive();
+            }
+        }
+
+        self.entities = SquareGrid::new_flat(self.entities.size());
+    }
+
+    pub fn structures(&self) -> Vec<(IVec2, Structure)> {
+        self.entities
+            .layout()
+            .into_iter()
+            .filter_map(|(grid, _, item)| item.clone().structure().map(|s| (grid, s)))
+            .collect()
+    }
+
     fn set(&mut self, grid: IVec2, structure: Structure) {
         self.entities.set(grid, StructureEntity::New(structure));
 
@@ -344,7 +1550,7 @@ impl StructureLayers {
                         let entity = self.create_entity(commands, grid, position, item, resources);
                         refreshing.push((grid, StructureEntity::Current(item.clone(), entity)))
                     }
-                    Structure::Cannon(_) => {
+                    Structure::Cannon(_) | Structure::Chain(_) => {
                         refreshing.push((grid, StructureEntity::Current(item.clone(), e.clone())))
                     }
                 },
@@ -378,57 +1584,62 @@ impl StructureLayers {
 
                 trace!(%grid, %position, %offset, "create-structure");
 
+                // Everything but corners is drawn along with the walls around
+                // them, by `batching`.
+                let mut entity = commands.spawn(WallBundle::new(grid, position, wall.clone()));
+                if let ConnectingWall::Corner(angle) = connecting {
+                    entity.with_children(|parent| {
+                        parent.spawn(SceneBundle {
+                            scene: resources.corner.clone(),
+                            transform: Transform::from_rotation(turned(angle)),
+                            ..default()
+                        });
+                    });
+                }
+
+                entity.insert(connecting).id()
+            }
+            Structure::Cannon(cannon) => {
+                let offset = Vec3::Y * (STRUCTURE_HEIGHT / 2.0);
+                let position = position + offset;
                 commands
-                    .spawn(WallBundle::new(grid, position, wall.clone()))
-                    .with_children(|parent| match connecting {
-                        ConnectingWall::Isolated => {
-                            parent.spawn(PbrBundle {
-                                mesh: resources.unknown.clone(),
-                                material: resources.simple.clone(),
-                                ..default()
-                            });
-                        }
-                        ConnectingWall::NorthSouth => {
-                            parent.spawn(PbrBundle {
-                                mesh: resources.north_south.clone(),
-                                material: resources.simple.clone(),
-                                ..default()
-                            });
-                        }
-                        ConnectingWall::EastWest => {
-                            parent.spawn(PbrBundle {
-                                mesh: resources.east_west.clone(),
-                                material: resources.simple.clone(),
-                                ..default()
-                            });
-                        }
-                        ConnectingWall::Corner(angle) => {
+                    .spawn(CannonBundle::new(grid, position, cannon.clone()))
+                    .with_children(|parent| match cannon.gun {
+                        Gun::Cannon => {
                             parent.spawn(SceneBundle {
-                                scene: resources.corner.clone(),
-                                transform: Transform::from_rotation(Quat::from_rotation_y(
-                                    -(angle as f32 * std::f32::consts::PI / 180.),
-                                )),
+                                scene: resources.cannon.clone(),
+                                transform: Transform::from_rotation(Quat::from_rotation_y(0.)),
                                 ..default()
                             });
                         }
-                        _ => {
+                        Gun::Mortar => {
                             parent.spawn(PbrBundle {
-                                mesh: resources.unknown.clone(),
+                                mesh: resources.mortar.clone(),
+                                material: resources.iron.clone(),
                                 ..default()
                             });
                         }
                     })
                     .id()
             }
-            Structure::Cannon(cannon) => {
-                let offset = Vec3::Y * (STRUCTURE_HEIGHT / 2.0);
-                let position = position + offset;
+            Structure::Chain(chain) => {
+                // Strung east to west unless there's more of it to the north
+                // or south.
+                let Around((_, n, _), (w, _, e), (_, s, _)) =
+                    self.entities.around(grid).map(is_chain);
+                let angle = if (n || s) && !(e || w) { 90 } else { 0 };
+
                 commands
-                    .spawn(CannonBundle::new(grid, position, cannon.clone()))
+                    .spawn(ChainBundle::new(
+                        grid,
+                        position + CHAIN_OFFSET,
+                        chain.clone(),
+                    ))
                     .with_children(|parent| {
-                        parent.spawn(SceneBundle {
-                            scene: resources.cannon.clone(),
-                            transform: Transform::from_rotation(Quat::from_rotation_y(0.)),
+                        parent.spawn(PbrBundle {
+                            mesh: resources.chain.clone(),
+                            material: resources.iron.clone(),
+                            transform: Transform::from_rotation(turned(angle)),
                             ..default()
                         });
                     })
@@ -438,38 +1649,142 @@ impl StructureLayers {
     }
 }
 
-#[derive(Component, Clone, Debug)]
+fn is_chain(v: Option<StructureEntity>) -> bool {
+    matches!(v.and_then(|v| v.structure()), Some(Structure::Chain(_)))
+}
+
+#[derive(Component, Clone, Debug, Serialize, Deserialize, Reflect)]
+#[reflect(Component)]
 pub struct Wall {
     player: Player,
 }
 
-#[derive(Component, Clone, Debug)]
+impl Wall {
+    pub fn new(player: Player) -> Self {
+        Self { player }
+    }
+}
+
+/// A boom stretched across shallow water, keeping ships out until it's shot
+/// through.
+#[derive(Component, Clone, Debug, Serialize, Deserialize, Reflect)]
+#[reflect(Component)]
+pub struct Chain {
+    player: Player,
+}
+
+impl Chain {
+    pub fn new(player: Player) -> Self {
+        Self { player }
+    }
+}
+
+#[derive(Component, Clone, Debug, Serialize, Deserialize, Reflect)]
+#[reflect(Component)]
 pub struct Cannon {
     player: Player,
+    /// Degrees clockwise from east, the middle of the arc it fires over
+    /// without turning.
+    #[serde(default)]
+    facing: u32,
+    #[serde(default)]
+    gun: Gun,
 }
 
-#[derive(Clone, Debug)]
+impl Cannon {
+    pub fn new(player: Player) -> Self {
+        Self {
+            player,
+            facing: 0,
+            gun: Gun::Cannon,
+        }
+    }
+
+    pub fn mortar(player: Player) -> Self {
+        Self::armed(player, Gun::Mortar)
+    }
+
+    pub fn armed(player: Player, gun: Gun) -> Self {
+        Self {
+            gun,
+            ..Self::new(player)
+        }
+    }
+
+    pub fn facing(&self) -> u32 {
+        self.facing
+    }
+
+    pub fn gun(&self) -> Gun {
+        self.gun
+    }
+
+    pub fn entry(&self) -> &'static catalog::Entry {
+        self.gun.entry()
+    }
+
+    pub fn facing_towards(self, facing: u32) -> Self {
+        Self {
+            facing: facing % 360,
+            ..self
+        }
+    }
+
+    /// A quarter turn clockwise.
+    pub fn turned(&self) -> Self {
+        self.clone().facing_towards(self.facing + 90)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
 pub enum Structure {
     Wall(Wall),
     Cannon(Cannon),
+    Chain(Chain),
 }
 
 impl Structure {
+    pub fn player(&self) -> Player {
+        match self {
+            Structure::Wall(wall) => wall.player,
+            Structure::Cannon(cannon) => cannon.player,
+            Structure::Chain(chain) => chain.player,
+        }
+    }
+
+    /// What it's called in messages and scripts.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Structure::Wall(_) => "wall",
+            Structure::Cannon(cannon) => cannon.entry().name,
+            Structure::Chain(_) => "chain",
+        }
+    }
+
     fn as_wall(self) -> Option<Structure> {
         match self {
             Structure::Wall(w) => Some(Structure::Wall(w)),
-            Structure::Cannon(_) => None,
+            Structure::Cannon(_) | Structure::Chain(_) => None,
         }
     }
 }
 
-#[derive(Debug)]
+/// How a wall joins up with those around it. Angles are in degrees clockwise
+/// from east, looking down on the map.
+#[derive(Component, Clone, Debug, PartialEq)]
 pub enum ConnectingWall {
     Isolated,
     NorthSouth,
     EastWest,
     Corner(u32),
-    Unknown,
+    /// The end of a run, which carries on the way it's facing.
+    End(u32),
+    /// Only touching others at the corners, running across the cell between
+    /// them.
+    Diagonal(u32),
+    /// Where three or four runs meet, whether there's one to the north, east,
+    /// south and west.
+    Junction([bool; 4]),
 }
 
 fn simplify(v: Option<StructureEntity>) -> Option<Structure> {
@@ -478,23 +1793,89 @@ fn simplify(v: Option<StructureEntity>) -> Option<Structure> {
 
 impl From<Around<Option<Structure>>> for ConnectingWall {
     fn from(value: Around<Option<Structure>>) -> Self {
-        match value {
-            Around((None, None, None), (None, _, Some(_)), (None, Some(_), None)) => {
-                Self::Corner(0)
-            } // Bottom Right
-            Around((None, None, None), (Some(_), _, None), (None, Some(_), None)) => {
-                Self::Corner(90)
-            } // Bottom Left
-            Around((None, Some(_), None), (Some(_), _, None), (None, None, None)) => {
-                Self::Corner(180)
-            } // Top Left
-            Around((None, Some(_), None), (None, _, Some(_)), (None, None, None)) => {
-                Self::Corner(270)
-            } // Top Right
-            Around((_, None, _), (Some(_), _, Some(_)), (_, None, _)) => Self::EastWest,
-            Around((_, Some(_), _), (None, _, None), (_, Some(_), _)) => Self::NorthSouth,
-            Around((None, None, None), (None, Some(_), None), (None, None, None)) => Self::Isolated,
-            Around((_, _, _), (_, _, _), (_, _, _)) => Self::Unknown,
+        let Around((nw, n, ne), (w, _, e), (sw, s, se)) = value.map(|v| v.is_some());
+
+        // Walls along the sides decide, those at the corners only matter when
+        // there aren't any.
+        match (n, e, s, w) {
+            (false, false, false, false) => match (ne || sw, nw || se) {
+                (true, false) => Self::Diagonal(135),
+                (false, true) => Self::Diagonal(45),
+                _ => Self::Isolated,
+            },
+            (false, true, false, true) => Self::EastWest,
+            (true, false, true, false) => Self::NorthSouth,
+            (false, true, true, false) => Self::Corner(0),
+            (false, false, true, true) => Self::Corner(90),
+            (true, false, false, true) => Self::Corner(180),
+            (true, true, false, false) => Self::Corner(270),
+            (false, true, false, false) => Self::End(0),
+            (false, false, true, false) => Self::End(90),
+            (false, false, false, true) => Self::End(180),
+            (true, false, false, false) => Self::End(270),
+            (n, e, s, w) => Self::Junction([n, e, s, w]),
+        }
+    }
+}
+
+/// Turns the cannon under the cursor while arming, so it faces whatever it's
+/// going to be firing at.
+#[allow(clippy::too_many_arguments)]
+fn turn_cannons(
+    mut turns: EventWriter<CannonTurned>,
+    structures: Res<StructureLayers>,
+    placing: Query<&Transform, With<Placing>>,
+    terrain: Query<&Terrain>,
+    keys: Res<ButtonInput<KeyCode>>,
+    controls: Res<Controls>,
+    phase: Res<State<Phase>>,
+    settings: Res<Settings>,
+    host: Option<Res<Host>>,
+) {
+    if !controls.just_pressed(&keys, Action::TurnCannon) || !matches!(phase.get(), Phase::Arm(_)) {
+        return;
+    }
+
+    let player = phase.get().player();
+    if settings.computer(&player) || network::remote(host.as_deref(), &player) {
+        return;
+    }
+
+    let Some(grid) = terrain.get_single().ok().and_then(|terrain| {
+        let cursor = placing.get_single().ok()?;
+        terrain.survey(cursor.translation).map(|s| s.location())
+    }) else {
+        return;
+    };
+
+    if let Some(cannon) = structures.cannon(grid, &player) {
+        turns.send(CannonTurned::new(grid.into(), cannon.turned()));
+    }
+}
+
+/// Turns cannons however they've been turned, whether here, by a player
+/// elsewhere or in a replay.
+fn apply_turns(
+    mut commands: Commands,
+    mut turns: EventReader<CannonTurned>,
+    mut structures: ResMut<StructureLayers>,
+    mut cannons: Query<&mut Transform, With<Cannon>>,
+) {
+    for event in turns.read() {
+        let grid = (*event.coordinates()).into();
+        let Some((entity, cannon)) = structures.face_cannon(grid, event.cannon()) else {
+            continue;
+        };
+
+        info!(%grid, facing = cannon.facing(), "cannon-turned");
+
+        if let Ok(mut transform) = cannons.get_mut(entity) {
+            transform.rotation = turned(cannon.facing());
         }
+        commands.entity(entity).insert(cannon);
     }
 }
+
+fn turned(angle: u32) -> Quat {
+    Quat::from_rotation_y(-(angle as f32).to_radians())
+}
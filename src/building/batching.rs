@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use bevy::{
+    math::primitives::Cuboid,
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
+        render_asset::RenderAssetUsages,
+    },
+};
+
+use crate::{
+    helpers::GamePlayLifetime,
+    model::{TILE_SIZE, WALL_HEIGHT, WALL_WIDTH},
+};
+
+use super::{resources::BuildingResources, turned, ConnectingWall, Wall};
+
+/// Cells along each side of the squares walls are drawn together in.
+pub const CHUNK: i32 = 16;
+
+/// Every wall in a square of the map drawn as one mesh, rather than a mesh
+/// for every cell. The walls themselves keep their own entities and colliders.
+#[derive(Component)]
+pub struct WallBatch {
+    chunk: IVec2,
+    walls: Vec<(Vec3, ConnectingWall)>,
+}
+
+/// Boxes making up a wall, placed relative to the middle of its cell. Corners
+/// are scenes of their own and so have none.
+pub fn pieces(connecting: &ConnectingWall) -> Vec<(Cuboid, Transform)> {
+    let straight = Cuboid::new(TILE_SIZE, WALL_HEIGHT, WALL_WIDTH);
+    let half = Cuboid::new(TILE_SIZE / 2., WALL_HEIGHT, WALL_WIDTH);
+    let cap = Cuboid::new(WALL_WIDTH * 1.25, WALL_HEIGHT * 1.1, WALL_WIDTH * 1.25);
+
+    match connecting {
+        ConnectingWall::Isolated => vec![(
+            Cuboid::new(TILE_SIZE, TILE_SIZE, TILE_SIZE),
+            Transform::IDENTITY,
+        )],
+        ConnectingWall::EastWest => vec![(straight, Transform::IDENTITY)],
+        ConnectingWall::NorthSouth => vec![(straight, Transform::from_rotation(turned(90)))],
+        ConnectingWall::Corner(_) => Vec::new(),
+        ConnectingWall::Diagonal(angle) => vec![(
+            straight,
+            Transform::from_rotation(turned(*angle)).with_scale(Vec3::new(
+                std::f32::consts::SQRT_2,
+                1.,
+                1.,
+            )),
+        )],
+        ConnectingWall::End(angle) => vec![(half, arm(*angle)), (cap, Transform::IDENTITY)],
+        ConnectingWall::Junction(arms) => [270, 0, 90, 180]
+            .into_iter()
+            .zip(arms)
+            .filter(|(_, present)| **present)
+            .map(|(angle, _)| (half, arm(angle)))
+            .chain([(cap, Transform::IDENTITY)])
+            .collect(),
+    }
+}
+
+/// Half a wall, reaching from the middle of the cell to its edge at `angle`.
+fn arm(angle: u32) -> Transform {
+    let rotation = turned(angle);
+
+    Transform::from_translation(rotation * Vec3::X * (TILE_SIZE / 4.)).with_rotation(rotation)
+}
+
+/// One mesh with every one of the boxes, each where its transform puts it.
+pub fn merge(parts: impl IntoIterator<Item = (Cuboid, Transform)>) -> Mesh {
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+    let mut uvs: Vec<[f32; 2]> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+
+    for (cuboid, transform) in parts {
+        let mesh = Mesh::from(cuboid);
+        let first = positions.len() as u32;
+
+        if let Some(VertexAttributeValues::Float32x3(values)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        {
+            positions.extend(
+                values
+                    .iter()
+                    .map(|p| transform.transform_point(Vec3::from(*p)).to_array()),
+            );
+        }
+        // Only ever scaled along an axis, so the normals just turn.
+        if let Some(VertexAttributeValues::Float32x3(values)) =
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+        {
+            normals.extend(
+                values
+                    .iter()
+                    .map(|n| (transform.rotation * Vec3::from(*n)).to_array()),
+            );
+        }
+        if let Some(VertexAttributeValues::Float32x2(values)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0)
+        {
+            uvs.extend(values.iter().copied());
+        }
+        if let Some(added) = mesh.indices() {
+            indices.extend(added.iter().map(|i| first + i as u32));
+        }
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+fn chunk(position: Vec3) -> IVec2 {
+    (position.xz() / (CHUNK as f32 * TILE_SIZE))
+        .floor()
+        .as_ivec2()
+}
+
+/// Redraws the squares with walls that have been built or torn down since,
+/// leaving the rest as they are.
+pub fn batch_walls(
+    mut commands: Commands,
+    mut batches: Query<(Entity, &mut WallBatch, &Handle<Mesh>)>,
+    walls: Query<(&Transform, &ConnectingWall), With<Wall>>,
+    added: Query<(), Added<ConnectingWall>>,
+    mut removed: RemovedComponents<ConnectingWall>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    resources: Res<BuildingResources>,
+) {
+    if added.is_empty() && removed.read().count() == 0 {
+        return;
+    }
+
+    let mut chunks: HashMap<IVec2, Vec<(Vec3, ConnectingWall)>> = HashMap::new();
+    for (transform, connecting) in walls.iter() {
+        chunks
+            .entry(chunk(transform.translation))
+            .or_default()
+            .push((transform.translation, connecting.clone()));
+    }
+    for walls in chunks.values_mut() {
+        walls.sort_by(|a, b| a.0.x.total_cmp(&b.0.x).then(a.0.z.total_cmp(&b.0.z)));
+    }
+
+    let draw = |walls: &[(Vec3, ConnectingWall)]| {
+        merge(walls.iter().flat_map(|(position, connecting)| {
+            pieces(connecting).into_iter().map(|(cuboid, transform)| {
+                (cuboid, Transform::from_translation(*position) * transform)
+            })
+        }))
+    };
+
+    for (entity, mut batch, mesh) in &mut batches {
+        match chunks.remove(&batch.chunk) {
+            Some(walls) if walls == batch.walls => {}
+            Some(walls) => {
+                meshes.insert(mesh, draw(&walls));
+                batch.walls = walls;
+            }
+            None => commands.entity(entity).despawn_recursive(),
+        }
+    }
+
+    for (chunk, walls) in chunks {
+        debug!(%chunk, walls = walls.len(), "wall-batch");
+        commands.spawn((
+            Name::new(format!("Walls-{:?}", chunk)),
+            GamePlayLifetime,
+            PbrBundle {
+                mesh: meshes.add(draw(&walls)),
+                material: resources.simple.clone(),
+                ..default()
+            },
+            WallBatch { chunk, walls },
+        ));
+    }
+}
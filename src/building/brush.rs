@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::*;
+
+use crate::{
+    controls::{Action, Controls},
+    model::{
+        Activity, Health, Phase, Player, Rules, Settings, CANNON_HP, TILE_SIZE, WALL_HEIGHT,
+        WALL_HP,
+    },
+    network::{self, Host},
+    terrain::{Props, Terrain},
+};
+
+use super::{Cannon, ConstructionEvent, DestructionEvent, Placing, Structure, StructureLayers};
+
+/// Cells out from the one clicked that the brush reaches, every way.
+pub const BRUSH_RADIUS: i32 = 2;
+
+/// Repair points it costs to use the brush.
+pub const BRUSH_COST: u32 = 5;
+
+/// Times the brush can be used every Fortify phase.
+const BRUSH_USES: u32 = 1;
+
+/// Cells of territory still held after a battle for every repair point earned.
+const TERRITORY_PER_POINT: usize = 10;
+
+const BRUSH_COLOR: Color = Color::rgb(0.3, 0.9, 0.4);
+
+/// Puts back everything of a player's around the cursor at once, whatever was
+/// knocked down in the last battle and whatever was damaged, paid for with
+/// points earned by holding onto territory.
+#[derive(Debug, Default, Resource)]
+pub struct RepairBrush {
+    points: HashMap<Player, u32>,
+    /// Uses left this turn.
+    uses: u32,
+    /// What's been destroyed since the last battle began, to be put back.
+    rubble: Vec<(IVec2, Structure)>,
+}
+
+impl RepairBrush {
+    pub fn points(&self, player: &Player) -> u32 {
+        self.points.get(player).copied().unwrap_or_default()
+    }
+
+    /// Pays out for the territory held coming out of a battle, and hands out
+    /// the uses for the turn.
+    pub fn earn(&mut self, player: Player, territory: usize) {
+        *self.points.entry(player).or_default() += (territory / TERRITORY_PER_POINT) as u32;
+        self.uses = BRUSH_USES;
+    }
+
+    pub fn affordable(&self, player: &Player) -> bool {
+        self.uses > 0 && self.points(player) >= BRUSH_COST
+    }
+
+    pub fn spend(&mut self, player: &Player) {
+        if let Some(points) = self.points.get_mut(player) {
+            *points = points.saturating_sub(BRUSH_COST);
+        }
+        self.uses = self.uses.saturating_sub(1);
+    }
+
+    pub fn destroyed(&mut self, grid: IVec2, structure: Structure) {
+        self.rubble.push((grid, structure));
+    }
+
+    /// A new battle, and so new rubble.
+    pub fn clear_rubble(&mut self) {
+        self.rubble.clear();
+    }
+
+    /// Takes the player's rubble in reach of `center`, to be built again.
+    /// Rubble where nothing can be built right now is left for another time.
+    pub fn sweep(
+        &mut self,
+        player: &Player,
+        center: IVec2,
+        free: impl Fn(IVec2) -> bool,
+    ) -> Vec<(IVec2, Structure)> {
+        let (swept, left): (Vec<_>, Vec<_>) =
+            self.rubble.drain(..).partition(|(grid, structure)| {
+                structure.player() == *player && reaches(center, *grid) && free(*grid)
+            });
+        self.rubble = left;
+        swept
+    }
+}
+
+/// Whether the brush over `center` reaches the cell.
+pub fn reaches(center: IVec2, grid: IVec2) -> bool {
+    (grid - center).abs().max_element() <= BRUSH_RADIUS
+}
+
+/// Keeps track of what's been knocked down, forgetting it all once the next
+/// battle starts.
+pub fn collect_rubble(
+    mut brush: ResMut<RepairBrush>,
+    mut destroyed: EventReader<DestructionEvent>,
+    phase: Res<State<Phase>>,
+) {
+    if phase.is_changed() && *phase.get() == Phase::Target(Player::One) {
+        brush.clear_rubble();
+    }
+
+    for event in destroyed.read() {
+        brush.destroyed((*event.coordinates()).into(), event.structure().clone());
+    }
+}
+
+/// Pays each player for the territory they come out of a battle with, as
+/// their turn to fortify begins.
+pub fn earn_repair_points(
+    mut brush: ResMut<RepairBrush>,
+    structures: Res<StructureLayers>,
+    phase: Res<State<Phase>>,
+    rules: Res<Rules>,
+) {
+    let Phase::Fortify(player) = phase.get() else {
+        return;
+    };
+    let player = *player;
+
+    let territory = if rules.coop {
+        structures.shared_territory()
+    } else {
+        structures.territory(&player)
+    };
+    let held = territory
+        .into_cells()
+        .into_iter()
+        .filter(|inside| *inside)
+        .count();
+
+    brush.earn(player, held);
+
+    info!(
+        ?player,
+        held,
+        points = brush.points(&player),
+        "repair-points-earned"
+    );
+}
+
+/// Clicking with the brush key held rebuilds the rubble and patches up the
+/// damage all around the cell clicked, when it can be paid for.
+#[allow(clippy::too_many_arguments)]
+pub fn brush_repairs(
+    mut events: EventReader<Pointer<Click>>,
+    mut brush: ResMut<RepairBrush>,
+    mut construction: EventWriter<ConstructionEvent>,
+    mut healths: Query<(&mut Health, Has<Cannon>)>,
+    terrain: Query<&Terrain>,
+    structures: Res<StructureLayers>,
+    props: Res<Props>,
+    keys: Res<ButtonInput<KeyCode>>,
+    controls: Res<Controls>,
+    phase: Res<State<Phase>>,
+    settings: Res<Settings>,
+    host: Option<Res<Host>>,
+) {
+    let player = phase.get().player();
+    if !matches!(phase.get(), Phase::Fortify(_))
+        || !controls.pressed(&keys, Action::RepairArea)
+        || settings.computer(&player)
+        || network::remote(host.as_deref(), &player)
+    {
+        events.clear();
+        return;
+    }
+
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    let clicked: Vec<IVec2> = events
+        .read()
+        .filter_map(|event| event.event.hit.position)
+        .filter_map(|position| terrain.survey(position))
+        .map(|survey| survey.location())
+        .collect();
+
+    for center in clicked {
+        if !brush.affordable(&player) {
+            info!(%center, ?player, points = brush.points(&player), "unable to afford brush");
+            continue;
+        }
+
+        let damaged: Vec<Entity> = structures
+            .walls(&player)
+            .into_iter()
+            .chain(structures.cannons(&player))
+            .filter(|(grid, _)| reaches(center, *grid))
+            .map(|(_, entity)| entity)
+            .filter(|entity| {
+                healths.get(*entity).is_ok_and(|(health, cannon)| {
+                    health.hp() < if cannon { CANNON_HP } else { WALL_HP }
+                })
+            })
+            .collect();
+
+        let rebuilt = brush.sweep(&player, center, |grid| {
+            !structures.occupied(grid) && !props.blocked(grid)
+        });
+
+        if damaged.is_empty() && rebuilt.is_empty() {
+            info!(%center, ?player, "nothing to brush");
+            continue;
+        }
+
+        brush.spend(&player);
+
+        info!(
+            %center,
+            ?player,
+            healed = damaged.len(),
+            rebuilt = rebuilt.len(),
+            points = brush.points(&player),
+            "brushed"
+        );
+
+        for entity in damaged {
+            if let Ok((mut health, cannon)) = healths.get_mut(entity) {
+                *health = Health::new(if cannon { CANNON_HP } else { WALL_HP });
+            }
+        }
+
+        for (grid, structure) in rebuilt {
+            construction.send(ConstructionEvent::new(grid.into(), structure));
+        }
+    }
+}
+
+/// Outlines what the brush would reach while its key is held.
+pub fn draw_brush(
+    mut gizmos: Gizmos,
+    placing: Query<&Transform, With<Placing>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    controls: Res<Controls>,
+    activity: Res<State<Activity>>,
+) {
+    if *activity.get() != Activity::Building || !controls.pressed(&keys, Action::RepairArea) {
+        return;
+    }
+
+    let flat = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
+    let across = TILE_SIZE * (BRUSH_RADIUS * 2 + 1) as f32;
+
+    for transform in &placing {
+        gizmos.rect(
+            transform.translation + Vec3::Y * (WALL_HEIGHT / 2.0),
+            flat,
+            Vec2::splat(across),
+            BRUSH_COLOR,
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use castle::ballistics::{self, Ordnance};
+
+/// The kinds of guns that can be built, each with its entry in the catalog.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect)]
+pub enum Gun {
+    #[default]
+    Cannon,
+    Mortar,
+}
+
+/// Everything that differs between the kinds of guns.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub name: &'static str,
+    pub ordnance: Ordnance,
+    /// Seconds after firing before it can again.
+    pub reload: f32,
+    /// Times the usual size of the blast where its shots land.
+    pub blast: f32,
+    /// Shots come down from high enough to clear any walls, so never need a
+    /// clear line of sight.
+    pub indirect: bool,
+    /// Earned by the other player for destroying one.
+    pub points: u32,
+}
+
+pub const CANNON: Entry = Entry {
+    name: "cannon",
+    ordnance: ballistics::CANNON,
+    reload: 3.0,
+    blast: 1.0,
+    indirect: false,
+    points: 10,
+};
+
+pub const MORTAR: Entry = Entry {
+    name: "mortar",
+    ordnance: ballistics::MORTAR,
+    reload: 8.0,
+    blast: 2.0,
+    indirect: true,
+    points: 15,
+};
+
+/// Earned by the other player for destroying a wall or a chain.
+pub const WALL_POINTS: u32 = 2;
+pub const CHAIN_POINTS: u32 = 3;
+
+/// Added to the multiplier for every structure past the first brought down by
+/// the same shot, up to the most it can be.
+const COMBO_STEP: f32 = 0.5;
+const COMBO_MOST: f32 = 3.0;
+
+/// Times the points for everything a single shot destroys.
+pub fn multiplier(destroyed: usize) -> f32 {
+    (1.0 + COMBO_STEP * destroyed.saturating_sub(1) as f32).min(COMBO_MOST)
+}
+
+/// Points for everything a single shot destroys, multiplied for bringing
+/// down more than one.
+pub fn combo(points: &[u32]) -> u32 {
+    (points.iter().sum::<u32>() as f32 * multiplier(points.len())).round() as u32
+}
+
+impl Gun {
+    pub fn entry(&self) -> &'static Entry {
+        match self {
+            Gun::Cannon => &CANNON,
+            Gun::Mortar => &MORTAR,
+        }
+    }
+
+    /// The next kind, going back around to the first.
+    pub fn next(&self) -> Self {
+        match self {
+            Gun::Cannon => Gun::Mortar,
+            Gun::Mortar => Gun::Cannon,
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::*;
+
+use crate::{
+    helpers::PlayerColor,
+    model::{Phase, Player, Rules, Settings, TILE_SIZE},
+    network::{self, Host},
+    terrain::Terrain,
+};
+
+use super::{resources::BuildingResources, sites, StructureLayers};
+
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct SiteGizmos;
+
+/// Where the player currently choosing may put their castle, along with the
+/// castles chosen so far so later players keep their distance.
+#[derive(Default, Resource)]
+pub struct CastleSites {
+    player: Option<Player>,
+    sites: HashSet<IVec2>,
+    chosen: Vec<IVec2>,
+    hovering: Option<IVec2>,
+}
+
+pub fn choosing(phase: Res<State<Phase>>) -> bool {
+    matches!(phase.get(), Phase::Choose(_))
+}
+
+pub fn prepare_sites(
+    phase: Res<State<Phase>>,
+    mut sites: ResMut<CastleSites>,
+    terrain: Query<&Terrain>,
+    rules: Res<Rules>,
+) {
+    let player = phase.get().player();
+    if sites.player == Some(player) {
+        return;
+    }
+
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    sites.sites = sites::valid_sites(terrain, &sites.chosen, rules.coop)
+        .into_iter()
+        .collect();
+    sites.player = Some(player);
+    sites.hovering = None;
+
+    info!(?player, sites = sites.sites.len(), "choosing");
+}
+
+pub fn hover_sites(
+    mut events: EventReader<Pointer<Move>>,
+    mut sites: ResMut<CastleSites>,
+    terrain: Query<&Terrain>,
+) {
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    for event in events.read() {
+        if let Some(survey) = event.event.hit.position.and_then(|p| terrain.survey(p)) {
+            let location = survey.location();
+            sites.hovering = sites.sites.contains(&location).then_some(location);
+        }
+    }
+}
+
+pub fn draw_sites(
+    mut gizmos: Gizmos<SiteGizmos>,
+    sites: Res<CastleSites>,
+    terrain: Query<&Terrain>,
+) {
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+    let Some(player) = sites.player else {
+        return;
+    };
+
+    let flat = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
+    let color = player.color();
+
+    for site in sites.sites.iter() {
+        if let Some(survey) = terrain.survey_grid(*site) {
+            gizmos.rect(
+                survey.world() + Vec3::Y * 0.05,
+                flat,
+                Vec2::splat(TILE_SIZE / 2.),
+                color.with_a(0.5),
+            );
+        }
+    }
+
+    if let Some(survey) = sites.hovering.and_then(|h| terrain.survey_grid(h)) {
+        let footprint = (sites::CASTLE_SIZE + IVec2::ONE).as_vec2() * TILE_SIZE;
+        gizmos.rect(survey.world() + Vec3::Y * 0.1, flat, footprint, color);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn choose_site(
+    mut commands: Commands,
+    mut events: EventReader<Pointer<Click>>,
+    mut sites: ResMut<CastleSites>,
+    mut structures: ResMut<StructureLayers>,
+    mut next_phase: ResMut<NextState<Phase>>,
+    resources: Res<BuildingResources>,
+    phase: Res<State<Phase>>,
+    terrain: Query<&Terrain>,
+    settings: Res<Settings>,
+    rules: Res<Rules>,
+    host: Option<Res<Host>>,
+) {
+    let player = phase.get().player();
+    if sites.player != Some(player) {
+        return;
+    }
+
+    // Players connected from elsewhere have their castle chosen for them the
+    // same way as the computer.
+    let automatic = settings.computer(&player) || network::remote(host.as_deref(), &player);
+    let chosen = if automatic && !sites.sites.is_empty() {
+        // The computer keeps as far from everyone else as it can, unless
+        // they're on the same side.
+        let distance = |site: &IVec2| {
+            sites
+                .chosen
+                .iter()
+                .map(|other| (*other - *site).length_squared())
+                .min()
+                .unwrap_or_default()
+        };
+        let key = |site: &&IVec2| (distance(site), site.x, site.y);
+        if rules.coop {
+            sites.sites.iter().min_by_key(key).copied()
+        } else {
+            sites.sites.iter().max_by_key(key).copied()
+        }
+    } else if sites.sites.is_empty() {
+        // Nowhere left that meets the requirements, so this player gets
+        // whatever's closest to the automatic selection, clear of castles
+        // already chosen.
+        let Ok(terrain) = terrain.get_single() else {
+            return;
+        };
+        Some(sites::fallback_site(
+            terrain,
+            player,
+            &sites.chosen,
+            rules.coop,
+        ))
+    } else {
+        let Ok(terrain) = terrain.get_single() else {
+            return;
+        };
+        events
+            .read()
+            .filter_map(|event| event.event.hit.position)
+            .filter_map(|position| terrain.survey(position))
+            .map(|survey| survey.location())
+            .find(|location| sites.sites.contains(location))
+    };
+
+    if let Some(center) = chosen {
+        info!(?player, %center, "castle-chosen");
+
+        structures.create_castle(center, sites::CASTLE_SIZE, player);
+        structures.refresh_entities(&mut commands, &resources);
+
+        sites.chosen.push(center);
+        sites.sites.clear();
+        sites.hovering = None;
+
+        next_phase.set(phase.get().next());
+    }
+}
@@ -1,22 +1,25 @@
 use bevy::{math::primitives, prelude::*};
 use bevy_mod_picking::prelude::*;
 
-use crate::model::*;
+use crate::{loading::RequiredAssets, model::*};
 
 #[derive(Resource)]
 pub struct BuildingResources {
     pub simple: Handle<StandardMaterial>,
+    pub inoperable: Handle<StandardMaterial>,
     pub unknown: Handle<Mesh>,
-    pub east_west: Handle<Mesh>,
-    pub north_south: Handle<Mesh>,
     pub corner: Handle<Scene>,
     pub cannon: Handle<Scene>,
+    pub mortar: Handle<Mesh>,
+    pub chain: Handle<Mesh>,
+    pub iron: Handle<StandardMaterial>,
 }
 
 pub fn load(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut required: ResMut<RequiredAssets>,
     asset_server: Res<AssetServer>,
 ) {
     let simple = materials.add(StandardMaterial {
@@ -24,27 +27,58 @@ pub fn load(
         perceptual_roughness: 1.0,
         ..default()
     });
+    let inoperable = materials.add(StandardMaterial {
+        base_color: Color::rgba(0.3, 0.3, 0.3, 0.6),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
     let unknown = meshes.add(Mesh::from(primitives::Cuboid::new(
         TILE_SIZE, TILE_SIZE, TILE_SIZE,
     )));
-    let north_south = meshes.add(Mesh::from(primitives::Cuboid::new(
-        WALL_WIDTH,
-        WALL_HEIGHT,
-        TILE_SIZE,
-    )));
-    let east_west = meshes.add(Mesh::from(primitives::Cuboid::new(
-        TILE_SIZE,
-        WALL_HEIGHT,
-        WALL_WIDTH,
-    )));
+
+    // A squat barrel pointing up out of a low mount.
+    let mortar = meshes.add(super::batching::merge([
+        (
+            primitives::Cuboid::new(0.8, 0.3, 0.8),
+            Transform::from_xyz(0., -0.35, 0.),
+        ),
+        (
+            primitives::Cuboid::new(0.4, 0.6, 0.4),
+            Transform::from_xyz(0.1, 0., 0.).with_rotation(Quat::from_rotation_z(-0.4)),
+        ),
+    ]));
+    // Links alternating flat and upright, running east to west.
+    let chain = meshes.add(super::batching::merge((0..4).map(|i| {
+        let x = (i as f32 - 1.5) * (TILE_SIZE / 4.);
+        let link = if i % 2 == 0 {
+            primitives::Cuboid::new(TILE_SIZE / 4., 0.05, 0.15)
+        } else {
+            primitives::Cuboid::new(TILE_SIZE / 4., 0.15, 0.05)
+        };
+        (link, Transform::from_xyz(x, 0., 0.))
+    })));
+    let iron = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.2, 0.2, 0.22),
+        metallic: 0.8,
+        perceptual_roughness: 0.5,
+        ..default()
+    });
+
+    let corner = asset_server.load("corner.glb#Scene0");
+    let cannon = asset_server.load("cannon.glb#Scene0");
+    required.require(&corner);
+    required.require(&cannon);
 
     commands.insert_resource(BuildingResources {
         simple,
+        inoperable,
         unknown,
-        east_west,
-        north_south,
-        corner: asset_server.load("corner.glb#Scene0"),
-        cannon: asset_server.load("cannon.glb#Scene0"),
+        corner,
+        cannon,
+        mortar,
+        chain,
+        iron,
     })
 }
 
@@ -0,0 +1,212 @@
+use bevy::prelude::*;
+
+use crate::{model::Player, terrain::Terrain};
+
+/// Each castle's walls run this far out from its center.
+pub const CASTLE_SIZE: IVec2 = IVec2::new(4, 4);
+
+/// Playing together castles go side by side, far enough apart that their walls
+/// leave room to build between them.
+const COOP_DISTANCE: f32 = 8.0;
+
+/// Increasingly lenient rules for what makes a good castle site, tried in
+/// order until one of them finds a site for every player.
+const REQUIREMENTS: [Requirement; 3] = [
+    Requirement {
+        flatness: Some(0.25),
+        buildable: 1.0,
+    },
+    Requirement {
+        flatness: None,
+        buildable: 1.0,
+    },
+    Requirement {
+        flatness: None,
+        buildable: 0.75,
+    },
+];
+
+#[derive(Debug, Clone, Copy)]
+struct Requirement {
+    /// Largest allowed difference in height across the castle, before scaling
+    /// by the terrain's height scale.
+    flatness: Option<f64>,
+    /// Fraction of the castle's cells that must be buildable.
+    buildable: f32,
+}
+
+/// Picks a center for each player's castle from the actual terrain. The first
+/// player gets the site closest to their usual corner and the second the site
+/// farthest from that, which must be at least a third of the map away. When
+/// the terrain doesn't allow that, castles fall back to fixed positions
+/// relative to the size of the map. Playing together, the second player gets
+/// the closest site instead.
+pub fn select_castle_sites(terrain: &Terrain, coop: bool) -> Vec<(Player, IVec2)> {
+    let map = terrain.size().as_ivec2();
+    let minimum_distance = minimum_distance(terrain);
+    let preferred = (map.as_vec2() * 0.2).as_ivec2();
+
+    for requirement in REQUIREMENTS.iter() {
+        let candidates = candidates(terrain, requirement);
+
+        let Some(first) = candidates
+            .iter()
+            .min_by_key(|c| (**c - preferred).length_squared())
+            .cloned()
+        else {
+            continue;
+        };
+
+        let distance = |c: &IVec2| (*c - first).length_squared();
+        let second = if coop {
+            candidates
+                .iter()
+                .filter(|c| c.as_vec2().distance(first.as_vec2()) >= COOP_DISTANCE)
+                .min_by_key(|c| distance(c))
+        } else {
+            candidates.iter().max_by_key(|c| distance(c))
+        };
+        let Some(second) = second.cloned() else {
+            continue;
+        };
+
+        if coop || first.as_vec2().distance(second.as_vec2()) >= minimum_distance {
+            debug!(?requirement, %first, %second, "castle-sites");
+            return vec![(Player::One, first), (Player::Two, second)];
+        }
+    }
+
+    warn!("no suitable castle sites, using fallback");
+
+    let margin = CASTLE_SIZE;
+    vec![
+        (Player::One, margin),
+        (Player::Two, map - margin - IVec2::ONE),
+    ]
+}
+
+/// Every site a castle could be built at that's far enough away from those
+/// that already exist, using the strictest requirement that leaves any.
+/// Playing together sites have to be near those that exist as well.
+pub fn valid_sites(terrain: &Terrain, existing: &[IVec2], coop: bool) -> Vec<IVec2> {
+    let minimum_distance = minimum_distance(terrain);
+
+    REQUIREMENTS
+        .iter()
+        .map(|requirement| {
+            candidates(terrain, requirement)
+                .into_iter()
+                .filter(|c| spaced(*c, existing, minimum_distance, coop))
+                .collect::<Vec<_>>()
+        })
+        .find(|sites| !sites.is_empty())
+        .unwrap_or_default()
+}
+
+/// Somewhere for a player's castle when no site meets the requirements,
+/// nearest where the automatic selection would put it while still keeping the
+/// same distance from castles already chosen, or at least not overlapping them.
+pub fn fallback_site(terrain: &Terrain, player: Player, existing: &[IVec2], coop: bool) -> IVec2 {
+    let map = terrain.size().as_ivec2();
+    let half = CASTLE_SIZE / 2;
+    let minimum_distance = minimum_distance(terrain);
+    let usual = select_castle_sites(terrain, coop)
+        .into_iter()
+        .find(|(p, _)| *p == player)
+        .map(|(_, center)| center)
+        .unwrap_or(half);
+
+    let free: Vec<IVec2> = (half.y..(map.y - half.y))
+        .flat_map(|y| (half.x..(map.x - half.x)).map(move |x| IVec2::new(x, y)))
+        .filter(|c| !existing.iter().any(|e| overlaps(*c, *e)))
+        .collect();
+
+    let key = |c: &&IVec2| ((**c - usual).length_squared(), c.x, c.y);
+    free.iter()
+        .filter(|c| spaced(**c, existing, minimum_distance, coop))
+        .min_by_key(key)
+        .or_else(|| free.iter().min_by_key(key))
+        .copied()
+        .unwrap_or(usual)
+}
+
+/// Whether two castles centered here would have walls on the same cells.
+pub fn overlaps(one: IVec2, other: IVec2) -> bool {
+    let apart = (one - other).abs();
+    apart.x <= CASTLE_SIZE.x && apart.y <= CASTLE_SIZE.y
+}
+
+/// Far enough from every existing castle, or playing together, close enough
+/// to them without being too close.
+fn spaced(site: IVec2, existing: &[IVec2], minimum_distance: f32, coop: bool) -> bool {
+    existing.iter().all(|e| {
+        let distance = e.as_vec2().distance(site.as_vec2());
+        if coop {
+            (COOP_DISTANCE..=COOP_DISTANCE * 2.0).contains(&distance)
+        } else {
+            distance >= minimum_distance
+        }
+    })
+}
+
+fn minimum_distance(terrain: &Terrain) -> f32 {
+    terrain.size().max_element() as f32 / 3.0
+}
+
+fn candidates(terrain: &Terrain, requirement: &Requirement) -> Vec<IVec2> {
+    let map = terrain.size().as_ivec2();
+    let half = CASTLE_SIZE / 2;
+
+    let mut candidates = Vec::default();
+
+    for y in half.y..(map.y - half.y) {
+        for x in half.x..(map.x - half.x) {
+            let center = IVec2::new(x, y);
+            if suitable(terrain, center, requirement) {
+                candidates.push(center);
+            }
+        }
+    }
+
+    candidates
+}
+
+fn suitable(terrain: &Terrain, center: IVec2, requirement: &Requirement) -> bool {
+    let half = CASTLE_SIZE / 2;
+    let mut cells = 0;
+    let mut buildable = 0;
+    let mut lowest = f64::MAX;
+    let mut highest = f64::MIN;
+
+    for y in (center.y - half.y)..=(center.y + half.y) {
+        for x in (center.x - half.x)..=(center.x + half.x) {
+            let grid = IVec2::new(x, y);
+            let Some(survey) = terrain.survey_grid(grid) else {
+                return false;
+            };
+
+            cells += 1;
+            if survey.can_build() {
+                buildable += 1;
+            }
+
+            for height in terrain.corners(grid).into_iter().flatten() {
+                lowest = lowest.min(height);
+                highest = highest.max(height);
+            }
+        }
+    }
+
+    // The center is where the cannon goes, so it has to be land regardless.
+    let center_buildable = terrain
+        .survey_grid(center)
+        .map(|s| s.can_build())
+        .unwrap_or_default();
+
+    let flat = requirement
+        .flatness
+        .map(|flatness| highest - lowest <= flatness * terrain.height_scale())
+        .unwrap_or(true);
+
+    center_buildable && flat && buildable as f32 >= cells as f32 * requirement.buildable
+}
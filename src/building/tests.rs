@@ -0,0 +1,448 @@
+use bevy::{
+    math::{primitives::Cuboid, IVec2, UVec2, Vec3},
+    render::mesh::{Mesh, VertexAttributeValues},
+    transform::components::Transform,
+};
+use proptest::prelude::*;
+
+use crate::model::{Around, Phase, Player};
+
+use super::{
+    batching::{merge, pieces},
+    brush::{reaches, RepairBrush, BRUSH_COST, BRUSH_RADIUS},
+    catalog::{combo, multiplier, Gun, WALL_POINTS},
+    sites::{overlaps, CASTLE_SIZE},
+    Cannon, ConnectingWall, Footing, Piece, Pieces, Structure, StructureLayers, Wall,
+};
+
+#[test]
+fn test_shared_territory_encloses_walls_built_together() {
+    let mut structures = StructureLayers::new(UVec2::new(8, 8));
+    structures.create_castle(IVec2::new(3, 3), IVec2::new(4, 4), Player::One);
+    structures.place(IVec2::new(5, 3), Structure::Wall(Wall::new(Player::Two)));
+
+    let territory = structures.territory(&Player::One);
+    assert!(territory.into_cells().iter().all(|cell| !cell));
+
+    let shared = structures.shared_territory();
+    assert_eq!(shared.get(IVec2::new(3, 3)), Some(&true));
+    assert_eq!(shared.get(IVec2::new(6, 6)), Some(&false));
+}
+
+#[test]
+fn test_flooded_walls_enclose_nothing() {
+    let mut structures = StructureLayers::new(UVec2::new(8, 8));
+    structures.create_castle(IVec2::new(3, 3), IVec2::new(4, 4), Player::One);
+    assert_eq!(
+        structures.territory(&Player::One).get(IVec2::new(3, 3)),
+        Some(&true)
+    );
+
+    structures.flooded.insert(IVec2::new(1, 3));
+
+    assert!(structures.flooded(IVec2::new(1, 3)));
+    assert_eq!(
+        structures.territory(&Player::One).get(IVec2::new(3, 3)),
+        Some(&false)
+    );
+    assert_eq!(
+        structures.shared_territory().get(IVec2::new(3, 3)),
+        Some(&false)
+    );
+}
+
+/// Walls around a cell, true wherever there's one, row by row from the north.
+type Neighbors = [[bool; 3]; 3];
+
+fn classify(neighbors: Neighbors) -> ConnectingWall {
+    let wall = |present: bool| present.then(|| Structure::Wall(Wall::new(Player::One)));
+    let row = |row: [bool; 3]| (wall(row[0]), wall(row[1]), wall(row[2]));
+
+    Around(row(neighbors[0]), row(neighbors[1]), row(neighbors[2])).into()
+}
+
+fn transpose(neighbors: Neighbors) -> Neighbors {
+    let mut transposed = neighbors;
+    for (y, row) in neighbors.iter().enumerate() {
+        for (x, present) in row.iter().enumerate() {
+            transposed[x][y] = *present;
+        }
+    }
+    transposed
+}
+
+fn neighbors() -> impl Strategy<Value = Neighbors> {
+    any::<[[bool; 3]; 3]>()
+}
+
+proptest! {
+    #[test]
+    fn test_walls_between_east_and_west_run_east_west(mut around in neighbors()) {
+        around[0][1] = false;
+        around[2][1] = false;
+        around[1][0] = true;
+        around[1][2] = true;
+
+        prop_assert!(matches!(classify(around), ConnectingWall::EastWest));
+    }
+
+    #[test]
+    fn test_walls_between_north_and_south_run_north_south(mut around in neighbors()) {
+        around[0][1] = true;
+        around[2][1] = true;
+        around[1][0] = false;
+        around[1][2] = false;
+
+        prop_assert!(matches!(classify(around), ConnectingWall::NorthSouth));
+    }
+
+    #[test]
+    fn test_transposing_swaps_straight_walls(around in neighbors()) {
+        let swapped = match classify(around) {
+            ConnectingWall::EastWest => Some(true),
+            ConnectingWall::NorthSouth => Some(false),
+            _ => None,
+        };
+        let transposed = match classify(transpose(around)) {
+            ConnectingWall::EastWest => Some(false),
+            ConnectingWall::NorthSouth => Some(true),
+            _ => None,
+        };
+
+        prop_assert_eq!(swapped, transposed);
+    }
+
+    #[test]
+    fn test_diagonals_only_matter_without_sides(around in neighbors()) {
+        let sides = |around: Neighbors| [around[0][1], around[1][0], around[1][2], around[2][1]];
+        let mut plain = around;
+        for (y, x) in [(0, 0), (0, 2), (2, 0), (2, 2)] {
+            plain[y][x] = false;
+        }
+
+        if sides(around).iter().any(|side| *side) {
+            prop_assert_eq!(classify(around), classify(plain));
+        } else {
+            prop_assert_eq!(classify(plain), ConnectingWall::Isolated);
+        }
+    }
+}
+
+#[test]
+fn test_each_corner_turns_its_own_way() {
+    let mut angles: Vec<u32> = [(0, 1), (1, 0), (1, 2), (2, 1)]
+        .iter()
+        .flat_map(|a| [(0, 1), (1, 0), (1, 2), (2, 1)].map(move |b| (*a, b)))
+        .filter(|(a, b)| a < b && (a.0 == 1) != (b.0 == 1))
+        .filter_map(|((ay, ax), (by, bx))| {
+            let mut around: Neighbors = Default::default();
+            around[ay][ax] = true;
+            around[by][bx] = true;
+            around[1][1] = true;
+            match classify(around) {
+                ConnectingWall::Corner(angle) => Some(angle),
+                _ => None,
+            }
+        })
+        .collect();
+    angles.sort();
+
+    assert_eq!(angles, vec![0, 90, 180, 270]);
+}
+
+#[test]
+fn test_runs_end_facing_the_rest_of_the_run() {
+    let end = |y: usize, x: usize| {
+        let mut around: Neighbors = Default::default();
+        around[y][x] = true;
+        classify(around)
+    };
+
+    assert_eq!(end(1, 2), ConnectingWall::End(0));
+    assert_eq!(end(2, 1), ConnectingWall::End(90));
+    assert_eq!(end(1, 0), ConnectingWall::End(180));
+    assert_eq!(end(0, 1), ConnectingWall::End(270));
+}
+
+#[test]
+fn test_walls_touching_at_corners_run_diagonally() {
+    let rising = [
+        [false, false, true],
+        [false, true, false],
+        [true, false, false],
+    ];
+    assert_eq!(classify(rising), ConnectingWall::Diagonal(135));
+    assert_eq!(classify(transpose(rising)), ConnectingWall::Diagonal(135));
+
+    let falling = [
+        [true, false, false],
+        [false, true, false],
+        [false, false, false],
+    ];
+    assert_eq!(classify(falling), ConnectingWall::Diagonal(45));
+
+    let crossed = [
+        [true, false, true],
+        [false, true, false],
+        [false, false, false],
+    ];
+    assert_eq!(classify(crossed), ConnectingWall::Isolated);
+}
+
+#[test]
+fn test_three_or_more_sides_meet_in_a_junction() {
+    let tee = [
+        [false, true, false],
+        [true, true, true],
+        [true, false, false],
+    ];
+    assert_eq!(
+        classify(tee),
+        ConnectingWall::Junction([true, true, false, true])
+    );
+
+    let cross = [
+        [false, true, false],
+        [true, true, true],
+        [false, true, false],
+    ];
+    assert_eq!(classify(cross), ConnectingWall::Junction([true; 4]));
+}
+
+#[test]
+fn test_merged_walls_keep_every_box_where_it_was_put() {
+    let cube = Cuboid::new(1., 1., 1.);
+    let merged = merge([
+        (cube, Transform::IDENTITY),
+        (cube, Transform::from_xyz(10., 0., 0.)),
+    ]);
+
+    let single = Mesh::from(cube);
+    assert_eq!(merged.count_vertices(), single.count_vertices() * 2);
+    assert_eq!(
+        merged.indices().map(|i| i.len()),
+        single.indices().map(|i| i.len() * 2)
+    );
+
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        merged.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        panic!("merged mesh without positions");
+    };
+    let (first, second) = positions.split_at(single.count_vertices());
+    assert!(first.iter().all(|p| p[0].abs() <= 0.5));
+    assert!(second.iter().all(|p| (p[0] - 10.).abs() <= 0.5));
+    assert!(merged
+        .indices()
+        .unwrap()
+        .iter()
+        .all(|i| i < merged.count_vertices()));
+}
+
+#[test]
+fn test_corners_are_left_out_of_merged_walls() {
+    assert!(pieces(&ConnectingWall::Corner(90)).is_empty());
+    assert_eq!(pieces(&ConnectingWall::End(0)).len(), 2);
+    assert_eq!(pieces(&ConnectingWall::Junction([true; 4])).len(), 5);
+
+    // The end of a run reaches back toward the rest of it, to the west.
+    let (_, end) = pieces(&ConnectingWall::End(180))[0];
+    assert!(end.translation.distance(Vec3::new(-0.25, 0., 0.)) < 1e-5);
+}
+
+#[test]
+fn test_cannons_saved_before_mortars_are_still_cannons() {
+    let cannon: Cannon = ron::from_str("(player: Two)").unwrap();
+
+    assert_eq!(cannon.gun(), Gun::Cannon);
+    assert_eq!(cannon.facing(), 0);
+
+    let mortar: Cannon =
+        ron::from_str(&ron::to_string(&Cannon::mortar(Player::One)).unwrap()).unwrap();
+    assert_eq!(mortar.gun(), Gun::Mortar);
+    assert!(mortar.entry().indirect);
+    assert!(mortar.entry().reload > cannon.entry().reload);
+}
+
+#[test]
+fn test_chains_go_across_shallows_and_never_join_walls() {
+    let mut structures = StructureLayers::new(UVec2::new(8, 8));
+    let chain = Footing::Shallows.structure(Player::One);
+    structures.place(IVec2::new(2, 2), chain.clone());
+    structures.place(IVec2::new(3, 2), Footing::Ground.structure(Player::One));
+
+    assert_eq!(chain.name(), "chain");
+    assert!(structures.chained(IVec2::new(2, 2)));
+    assert!(!structures.chained(IVec2::new(3, 2)));
+    assert!(structures.occupied(IVec2::new(2, 2)));
+    assert!(structures.wall_grid(&Player::One)[IVec2::new(3, 2)]);
+    assert!(!structures.wall_grid(&Player::One)[IVec2::new(2, 2)]);
+    assert!(chain.as_wall().is_none());
+}
+
+#[test]
+fn test_bringing_down_more_at_once_is_worth_more_each() {
+    assert_eq!(combo(&[WALL_POINTS]), WALL_POINTS);
+    assert_eq!(combo(&[WALL_POINTS; 3]), WALL_POINTS * 3 * 2);
+    assert_eq!(multiplier(0), 1.0);
+    assert_eq!(multiplier(50), 3.0);
+}
+
+#[test]
+fn test_placing_the_last_piece_leaves_none() {
+    let mut pieces = Pieces::default();
+    assert_eq!(pieces.take(), None);
+
+    pieces.deal([Piece::Wall; 2], 0);
+    assert_eq!(pieces.take(), Some(1));
+    assert_eq!(pieces.take(), Some(0));
+    assert_eq!(pieces.take(), None);
+    assert_eq!(pieces.left(), 0);
+
+    pieces.deal([Piece::Gun], 0);
+    assert_eq!(pieces.left(), 1);
+}
+
+#[test]
+fn test_pieces_are_placed_in_the_order_dealt() {
+    let mut pieces = Pieces::default();
+    assert_eq!(pieces.next(), None);
+
+    pieces.deal([Piece::Gun, Piece::Wall, Piece::Wall], 0);
+    assert_eq!(pieces.next(), Some(Piece::Gun));
+    assert_eq!(
+        pieces.upcoming().collect::<Vec<_>>(),
+        vec![Piece::Gun, Piece::Wall, Piece::Wall]
+    );
+
+    pieces.take();
+    assert_eq!(pieces.next(), Some(Piece::Wall));
+    assert_eq!(pieces.left(), 2);
+}
+
+#[test]
+fn test_holding_swaps_once_for_every_piece_placed() {
+    let mut pieces = Pieces::default();
+    pieces.deal([Piece::Wall, Piece::Wall, Piece::Wall], 0);
+
+    assert_eq!(pieces.hold(), Some(2));
+    assert_eq!(pieces.held(), Some(Piece::Wall));
+    assert_eq!(pieces.hold(), None);
+
+    pieces.take();
+    assert_eq!(pieces.hold(), Some(1));
+    assert_eq!(pieces.next(), Some(Piece::Wall));
+    assert_eq!(pieces.held(), Some(Piece::Wall));
+}
+
+#[test]
+fn test_pieces_held_while_fortifying_are_gone_when_arming() {
+    let mut pieces = Pieces::default();
+    pieces.deal([Piece::dealt(&Phase::Fortify(Player::One)); 2], 0);
+    assert_eq!(pieces.hold(), Some(1));
+
+    pieces.deal([Piece::dealt(&Phase::Arm(Player::One)); 3], 0);
+    assert_eq!(pieces.held(), None);
+    assert_eq!(pieces.hold(), Some(2));
+    assert_eq!(pieces.held(), Some(Piece::Gun));
+    assert!(pieces.upcoming().all(|piece| piece == Piece::Gun));
+
+    pieces.take();
+    assert_eq!(pieces.hold(), Some(1));
+    assert_eq!(pieces.next(), Some(Piece::Gun));
+}
+
+#[test]
+fn test_holding_the_last_piece_leaves_none() {
+    let mut pieces = Pieces::default();
+    assert_eq!(pieces.hold(), None);
+
+    pieces.deal([Piece::Gun], 0);
+    assert_eq!(pieces.hold(), Some(0));
+    assert_eq!(pieces.next(), None);
+    assert_eq!(pieces.held(), Some(Piece::Gun));
+}
+
+#[test]
+fn test_guns_are_dealt_only_while_arming() {
+    assert_eq!(Piece::dealt(&Phase::Arm(Player::One)), Piece::Gun);
+    assert_eq!(Piece::dealt(&Phase::Fortify(Player::One)), Piece::Wall);
+}
+
+#[test]
+fn test_discards_use_up_pieces_only_while_allowed() {
+    let mut pieces = Pieces::default();
+    pieces.deal([Piece::Wall; 3], 1);
+
+    assert_eq!(pieces.discard(), Some(2));
+    assert_eq!(pieces.discards(), 0);
+    assert_eq!(pieces.discard(), None);
+    assert_eq!(pieces.left(), 2);
+
+    pieces.deal([], 2);
+    assert_eq!(pieces.discard(), None);
+    assert_eq!(pieces.discards(), 2);
+}
+
+#[test]
+fn test_brush_is_paid_for_with_territory_held() {
+    let mut brush = RepairBrush::default();
+    assert!(!brush.affordable(&Player::One));
+
+    brush.earn(Player::One, 49);
+    assert_eq!(brush.points(&Player::One), 4);
+    assert!(!brush.affordable(&Player::One));
+
+    brush.earn(Player::One, 10);
+    assert!(brush.affordable(&Player::One));
+    assert!(!brush.affordable(&Player::Two));
+
+    brush.spend(&Player::One);
+    assert_eq!(brush.points(&Player::One), 5 - BRUSH_COST);
+    assert!(!brush.affordable(&Player::One));
+}
+
+#[test]
+fn test_brush_sweeps_only_the_players_rubble_in_reach() {
+    let mut brush = RepairBrush::default();
+    let center = IVec2::new(10, 10);
+    let edge = center + IVec2::new(BRUSH_RADIUS, -BRUSH_RADIUS);
+    let beyond = center + IVec2::new(BRUSH_RADIUS + 1, 0);
+
+    assert!(reaches(center, edge));
+    assert!(!reaches(center, beyond));
+
+    brush.destroyed(edge, Structure::Wall(Wall::new(Player::One)));
+    brush.destroyed(beyond, Structure::Wall(Wall::new(Player::One)));
+    brush.destroyed(center, Structure::Wall(Wall::new(Player::Two)));
+
+    let swept = brush.sweep(&Player::One, center, |_| true);
+    assert_eq!(swept.len(), 1);
+    assert_eq!(swept[0].0, edge);
+
+    assert!(brush.sweep(&Player::One, center, |_| true).is_empty());
+    assert_eq!(brush.sweep(&Player::Two, center, |_| true).len(), 1);
+
+    brush.clear_rubble();
+    assert!(brush.sweep(&Player::One, beyond, |_| true).is_empty());
+}
+
+#[test]
+fn test_brush_leaves_rubble_where_nothing_can_be_built() {
+    let mut brush = RepairBrush::default();
+    let center = IVec2::new(10, 10);
+    brush.destroyed(center, Structure::Wall(Wall::new(Player::One)));
+
+    assert!(brush.sweep(&Player::One, center, |_| false).is_empty());
+    assert_eq!(brush.sweep(&Player::One, center, |_| true).len(), 1);
+}
+
+#[test]
+fn test_castles_overlap_when_their_walls_would_meet() {
+    let center = IVec2::new(10, 10);
+
+    assert!(overlaps(center, center));
+    assert!(overlaps(center, center + CASTLE_SIZE));
+    assert!(!overlaps(center, center + IVec2::new(CASTLE_SIZE.x + 1, 0)));
+    assert!(!overlaps(center, center - IVec2::new(0, CASTLE_SIZE.y + 1)));
+}
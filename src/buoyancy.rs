@@ -0,0 +1,181 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+
+use crate::{
+    building::DestructionEvent,
+    collision::Collides,
+    helpers::{Expires, GamePlayLifetime},
+    model::{AppState, GRAVITY, TILE_SIZE, WALL_HEIGHT},
+    terrain::Terrain,
+    weather::Weather,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Depth at which something floating is as far under as it goes, twice how
+/// far under it settles.
+const FLOAT_DEPTH: f32 = 0.3;
+
+/// Fraction of how fast anything in the water is moving lost every second.
+const WATER_DRAG: f32 = 2.5;
+
+/// Sinking things are held up a little, going down slower than they fell.
+const SINKING_LIFT: f32 = 0.6;
+
+/// Times the wind's drift that floating things are carried along.
+const CURRENT: f32 = 0.5;
+
+/// Chunks thrown out by each structure destroyed, and how long they last.
+const DEBRIS_PIECES: usize = 3;
+const DEBRIS_SECONDS: f32 = 8.0;
+const DEBRIS_SIZE: f32 = TILE_SIZE * 0.15;
+const DEBRIS_SPEED: f32 = 4.0;
+
+/// Holds things up in the water, or lets them sink, rather than having them
+/// bounce off the surface.
+pub struct BuoyancyPlugin;
+
+impl Plugin for BuoyancyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup).add_systems(
+            Update,
+            (throw_debris, float_bodies).run_if(in_state(AppState::Game)),
+        );
+    }
+}
+
+/// What a dynamic body does once it's in the water.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Buoyancy {
+    Floats,
+    Sinks,
+}
+
+/// In the water, from the moment it went in.
+#[derive(Component)]
+pub struct Submerged;
+
+/// Change in upwards speed every second for something `depth` under water.
+pub fn lift(buoyancy: Buoyancy, depth: f32) -> f32 {
+    match buoyancy {
+        // Twice gravity all the way under, so it settles half way.
+        Buoyancy::Floats => 2.0 * GRAVITY * (depth / FLOAT_DEPTH).min(1.0),
+        Buoyancy::Sinks => SINKING_LIFT * GRAVITY,
+    }
+}
+
+/// Bits of whatever's been knocked down, which float off if they land in the
+/// water.
+#[derive(Component)]
+struct Debris;
+
+#[derive(Resource)]
+struct DebrisResources {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(DebrisResources {
+        mesh: meshes.add(Cuboid::new(DEBRIS_SIZE, DEBRIS_SIZE, DEBRIS_SIZE)),
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.45, 0.4, 0.35),
+            perceptual_roughness: 0.9,
+            ..default()
+        }),
+    });
+}
+
+fn throw_debris(
+    mut commands: Commands,
+    mut destroyed: EventReader<DestructionEvent>,
+    resources: Res<DebrisResources>,
+    terrain: Query<&Terrain>,
+) {
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    let mut rng = rand::thread_rng();
+
+    for event in destroyed.read() {
+        let Some(survey) = terrain.survey_grid((*event.coordinates()).into()) else {
+            continue;
+        };
+        let from = survey.world() + Vec3::Y * WALL_HEIGHT;
+
+        for _ in 0..DEBRIS_PIECES {
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let outwards = Vec3::new(angle.cos(), 1.0, angle.sin());
+
+            commands.spawn((
+                Name::new("Debris"),
+                Debris,
+                GamePlayLifetime,
+                Expires::after(DEBRIS_SECONDS),
+                PbrBundle {
+                    mesh: resources.mesh.clone(),
+                    material: resources.material.clone(),
+                    transform: Transform::from_translation(from),
+                    ..default()
+                },
+                RigidBody::Dynamic,
+                Collider::cuboid(DEBRIS_SIZE / 2., DEBRIS_SIZE / 2., DEBRIS_SIZE / 2.),
+                Collides::Debris.groups(),
+                Buoyancy::Floats,
+                Velocity {
+                    linvel: outwards * DEBRIS_SPEED * rng.gen_range(0.5..1.0),
+                    angvel: Vec3::new(rng.gen(), rng.gen(), rng.gen()) * 4.0,
+                },
+            ));
+        }
+    }
+}
+
+/// Pushes up on anything in the water and slows it down, carrying floating
+/// things along with the wind.
+fn float_bodies(
+    mut commands: Commands,
+    mut bodies: Query<
+        (Entity, &Transform, &Buoyancy, &mut Velocity, Has<Submerged>),
+        Without<RigidBodyDisabled>,
+    >,
+    terrain: Query<&Terrain>,
+    weather: Res<Weather>,
+    time: Res<Time>,
+) {
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    let dt = time.delta_seconds();
+    let slowing = (1.0 - WATER_DRAG * dt).max(0.0);
+
+    for (entity, transform, buoyancy, mut velocity, submerged) in &mut bodies {
+        let Some(depth) = terrain.submerged(transform.translation) else {
+            if submerged {
+                commands.entity(entity).remove::<Submerged>();
+            }
+            continue;
+        };
+
+        if !submerged {
+            debug!(?entity, ?buoyancy, "submerged");
+            commands.entity(entity).insert(Submerged);
+        }
+
+        velocity.linvel.y += lift(*buoyancy, depth) * dt;
+        velocity.linvel *= slowing;
+        velocity.angvel *= slowing;
+
+        if *buoyancy == Buoyancy::Floats {
+            velocity.linvel += weather.drift() * CURRENT * dt;
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use crate::model::GRAVITY;
+
+use super::{lift, Buoyancy, FLOAT_DEPTH};
+
+#[test]
+fn test_floating_settles_half_way_under() {
+    assert_eq!(lift(Buoyancy::Floats, FLOAT_DEPTH / 2.0), GRAVITY);
+    assert!(lift(Buoyancy::Floats, FLOAT_DEPTH / 4.0) < GRAVITY);
+    assert!(lift(Buoyancy::Floats, FLOAT_DEPTH) > GRAVITY);
+}
+
+#[test]
+fn test_floating_lift_stops_growing_once_under() {
+    assert_eq!(
+        lift(Buoyancy::Floats, FLOAT_DEPTH),
+        lift(Buoyancy::Floats, FLOAT_DEPTH * 10.0)
+    );
+}
+
+#[test]
+fn test_sinking_is_never_held_up() {
+    for depth in [0.01, FLOAT_DEPTH, 5.0] {
+        assert!(lift(Buoyancy::Sinks, depth) < GRAVITY);
+    }
+}
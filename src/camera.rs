@@ -1,13 +1,69 @@
-use bevy::{core_pipeline::bloom::BloomSettings, prelude::*};
+use bevy::{core_pipeline::bloom::BloomSettings, input::mouse::MouseMotion, prelude::*};
 use bevy_rts_camera::{RtsCamera, RtsCameraControls, RtsCameraPlugin};
 
+use crate::controls::{typing, Typing};
+
+#[cfg(test)]
+mod tests;
+
 #[derive(Debug, Clone, Default, Hash, PartialEq, Eq, States)]
 pub enum CameraMode {
     #[default]
     Normal,
     AllTopDown,
     AllAngled,
+    /// Looking down the barrel of a cannon.
     FirstPerson,
+    /// Flown anywhere, for watching rather than playing.
+    Free,
+}
+
+/// Units a second the free camera moves.
+const FLY_SPEED: f32 = 16.0;
+
+/// Radians turned for every pixel the mouse moves while looking around.
+const LOOK_SPEED: f32 = 0.004;
+
+/// Just short of straight up or down, past which looking around flips over.
+const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+
+/// Heights the camera has to rise above to be zoomed out and drop below to be
+/// zoomed back in, apart so hovering around one doesn't flicker between them.
+const FAR_HEIGHT: f32 = 22.0;
+const NEAR_HEIGHT: f32 = 18.0;
+
+/// How much is shown, depending on how far away the camera is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Detail {
+    /// Everything, models, health and all.
+    #[default]
+    Near,
+    /// Just enough to follow along from high up, like who holds what.
+    Far,
+}
+
+impl Detail {
+    /// Detail for the camera at `height`, staying as it was in between.
+    pub fn at(height: f32, previous: Detail) -> Detail {
+        if height > FAR_HEIGHT {
+            Detail::Far
+        } else if height < NEAR_HEIGHT {
+            Detail::Near
+        } else {
+            previous
+        }
+    }
+}
+
+/// How far the camera's zoomed out, for anything drawn differently up close.
+#[derive(Debug, Default, Resource)]
+pub struct CameraZoom {
+    pub height: f32,
+    pub detail: Detail,
+}
+
+pub fn zoomed_out(zoom: Res<CameraZoom>) -> bool {
+    zoom.detail == Detail::Far
 }
 
 fn setup_camera(
@@ -40,9 +96,101 @@ fn setup_camera(
             transform: Transform::from_xyz(0., 2., 0.).looking_at(Vec3::new(0., 2., -2.), Vec3::Y),
             ..default()
         },)),
+        CameraMode::Free => commands.spawn((
+            Camera3dBundle {
+                transform: Transform::from_xyz(0., 64., 32.)
+                    .looking_at(Vec3::new(0., 0., 6.), Vec3::Y),
+                ..default()
+            },
+            BloomSettings::default(),
+        )),
     };
 }
 
+/// Arrow keys move along where the camera is facing, page up and down rise
+/// and fall and holding the right mouse button looks around.
+pub fn fly_camera(
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut motion: EventReader<MouseMotion>,
+    mut camera: Query<&mut Transform, With<Camera>>,
+    // Real time, so the camera still moves while the game's paused.
+    time: Res<Time<Real>>,
+) {
+    let looked: Vec2 = motion.read().map(|event| event.delta).sum();
+
+    for mut transform in camera.iter_mut() {
+        if buttons.pressed(MouseButton::Right) {
+            let (yaw, pitch, _) = transform.rotation.to_euler(EulerRot::YXZ);
+            let yaw = yaw - looked.x * LOOK_SPEED;
+            let pitch = (pitch - looked.y * LOOK_SPEED).clamp(-MAX_PITCH, MAX_PITCH);
+            transform.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.);
+        }
+
+        let level = |direction: Vec3| Vec3::new(direction.x, 0., direction.z).normalize_or_zero();
+        let forward = level(*transform.forward());
+        let right = level(*transform.right());
+        let mut direction = Vec3::ZERO;
+
+        if keys.pressed(KeyCode::ArrowUp) {
+            direction += forward;
+        }
+        if keys.pressed(KeyCode::ArrowDown) {
+            direction -= forward;
+        }
+        if keys.pressed(KeyCode::ArrowRight) {
+            direction += right;
+        }
+        if keys.pressed(KeyCode::ArrowLeft) {
+            direction -= right;
+        }
+        if keys.pressed(KeyCode::PageUp) {
+            direction += Vec3::Y;
+        }
+        if keys.pressed(KeyCode::PageDown) {
+            direction -= Vec3::Y;
+        }
+
+        transform.translation += direction.normalize_or_zero() * FLY_SPEED * time.delta_seconds();
+    }
+}
+
+/// Takes the keys away from the camera while they're typing something, and
+/// gives them back afterwards.
+fn hold_camera_keys(
+    mut commands: Commands,
+    typing: Res<Typing>,
+    cameras: Query<(Entity, Has<RtsCameraControls>), With<RtsCamera>>,
+) {
+    for (entity, controlled) in &cameras {
+        match (*typing == Typing::Nothing, controlled) {
+            (false, true) => {
+                commands.entity(entity).remove::<RtsCameraControls>();
+            }
+            (true, false) => {
+                commands.entity(entity).insert(RtsCameraControls::default());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Follows the camera's height, however it's being moved.
+fn track_zoom(mut zoom: ResMut<CameraZoom>, cameras: Query<&Transform, With<Camera>>) {
+    let Ok(transform) = cameras.get_single() else {
+        return;
+    };
+
+    let height = transform.translation.y;
+    let detail = Detail::at(height, zoom.detail);
+    if detail != zoom.detail {
+        info!(?detail, %height, "detail-changed");
+        zoom.detail = detail;
+    }
+    // Only noticed as changed when it matters.
+    zoom.bypass_change_detection().height = height;
+}
+
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
@@ -52,6 +200,18 @@ impl Plugin for CameraPlugin {
             .add_systems(OnEnter(CameraMode::Normal), setup_camera)
             .add_systems(OnEnter(CameraMode::AllTopDown), setup_camera)
             .add_systems(OnEnter(CameraMode::AllAngled), setup_camera)
-            .add_systems(OnEnter(CameraMode::FirstPerson), setup_camera);
+            .add_systems(OnEnter(CameraMode::FirstPerson), setup_camera)
+            .add_systems(OnEnter(CameraMode::Free), setup_camera)
+            .init_resource::<CameraZoom>()
+            .add_systems(
+                Update,
+                (
+                    fly_camera
+                        .run_if(in_state(CameraMode::Free))
+                        .run_if(not(typing)),
+                    hold_camera_keys,
+                ),
+            )
+            .add_systems(PostUpdate, track_zoom);
     }
 }
@@ -0,0 +1,10 @@
+use super::Detail;
+
+#[test]
+fn test_detail_changes_only_once_well_past_either_height() {
+    assert_eq!(Detail::at(30.0, Detail::Near), Detail::Far);
+    assert_eq!(Detail::at(10.0, Detail::Far), Detail::Near);
+
+    assert_eq!(Detail::at(20.0, Detail::Near), Detail::Near);
+    assert_eq!(Detail::at(20.0, Detail::Far), Detail::Far);
+}
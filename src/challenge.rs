@@ -0,0 +1,410 @@
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    building::{ConstructionEvent, ConstructionSet, StructureLayers},
+    firing::TargetPicked,
+    helpers::GamePlayLifetime,
+    model::{Activity, AppState, Phase, Player, Seed, Settings},
+    scenario::Scenario,
+    units::{Raid, Ship, Unit},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Puzzles played from hand made scenarios, chosen from a list at the start
+/// rather than going straight into a game.
+pub struct ChallengePlugin;
+
+impl Plugin for ChallengePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChallengeProgress>()
+            .add_systems(
+                OnEnter(AppState::Menu),
+                spawn_select.run_if(resource_exists::<Challenges>),
+            )
+            .add_systems(OnExit(AppState::Menu), despawn_select)
+            .add_systems(
+                Update,
+                select
+                    .run_if(in_state(AppState::Menu))
+                    .run_if(resource_exists::<Challenges>),
+            )
+            .add_systems(OnEnter(AppState::Game), begin.run_if(in_challenge))
+            .add_systems(
+                Update,
+                (evaluate.after(ConstructionSet::Apply), update_status)
+                    .chain()
+                    .run_if(in_state(AppState::Game))
+                    .run_if(in_challenge),
+            );
+    }
+}
+
+/// What has to be done to complete a challenge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Goal {
+    /// Have some territory inside the player's walls.
+    Enclose,
+    /// Sink every ship sent in.
+    SinkShips,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Challenge {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub player: Player,
+    /// The phase play starts, and stays, in.
+    pub phase: Phase,
+    pub goal: Goal,
+    #[serde(default)]
+    pub seconds: Option<f32>,
+    /// Walls that may be placed.
+    #[serde(default)]
+    pub pieces: Option<u32>,
+    /// Targets that may be picked.
+    #[serde(default)]
+    pub shots: Option<u32>,
+    /// Ship classes sent against the player as the challenge begins.
+    #[serde(default)]
+    pub ships: Vec<String>,
+}
+
+impl Challenge {
+    fn activity(&self) -> Activity {
+        match self.phase {
+            Phase::Fortify(_) | Phase::Arm(_) => Activity::Building,
+            Phase::Target(_) => Activity::Firing,
+            Phase::Choose(_) => Activity::Observing,
+        }
+    }
+}
+
+pub fn in_challenge(scenario: Option<Res<Scenario>>) -> bool {
+    scenario.is_some_and(|scenario| scenario.challenge.is_some())
+}
+
+/// Challenges to choose from, every scenario with one in the directory given
+/// on the command line.
+#[derive(Debug, Default, Resource)]
+pub struct Challenges {
+    available: Vec<(PathBuf, Scenario)>,
+}
+
+impl Challenges {
+    pub fn load(directory: impl AsRef<Path>) -> Self {
+        let mut available: Vec<(PathBuf, Scenario)> = match std::fs::read_dir(directory) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|e| e == "ron"))
+                .filter_map(|path| match Scenario::load(&path) {
+                    Ok(scenario) if scenario.challenge.is_some() => Some((path, scenario)),
+                    Ok(_) => None,
+                    Err(e) => {
+                        warn!(?path, "error loading challenge: {}", e);
+                        None
+                    }
+                })
+                .collect(),
+            Err(e) => {
+                warn!("error listing challenges: {}", e);
+                Vec::new()
+            }
+        };
+        available.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Self { available }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Outcome {
+    Success,
+    Failure(&'static str),
+}
+
+/// Pieces and shots used can't run out the moment the last is used, they need
+/// a moment to be built or to land.
+const SETTLE_SECONDS: f32 = 0.5;
+const LANDING_SECONDS: f32 = 5.0;
+
+#[derive(Debug, Default, Resource)]
+struct ChallengeProgress {
+    elapsed: f32,
+    pieces: u32,
+    shots: u32,
+    /// When the last of the pieces or shots were used.
+    exhausted: Option<f32>,
+    /// Ships have to show up before they can all be sunk.
+    ships_seen: bool,
+    outcome: Option<Outcome>,
+}
+
+#[derive(Component)]
+struct ChallengeSelect;
+
+#[derive(Component)]
+struct ChallengeButton(usize);
+
+#[derive(Component)]
+struct ChallengeStatus;
+
+const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.2);
+const HOVERED_COLOR: Color = Color::rgb(0.25, 0.25, 0.35);
+
+fn spawn_select(mut commands: Commands, challenges: Res<Challenges>) {
+    let text = |value: &str, font_size: f32| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+    };
+
+    commands
+        .spawn((
+            Name::new("Challenges"),
+            ChallengeSelect,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(12.),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("Challenges", 32.));
+
+            if challenges.available.is_empty() {
+                parent.spawn(text("None found", 18.));
+            }
+
+            for (index, (_, scenario)) in challenges.available.iter().enumerate() {
+                let Some(challenge) = &scenario.challenge else {
+                    continue;
+                };
+                parent
+                    .spawn((
+                        ChallengeButton(index),
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(480.),
+                                padding: UiRect::all(Val::Px(8.)),
+                                flex_direction: FlexDirection::Column,
+                                ..default()
+                            },
+                            background_color: BUTTON_COLOR.into(),
+                            ..default()
+                        },
+                    ))
+                    .with_children(|button| {
+                        button.spawn(text(&challenge.title, 22.));
+                        button.spawn(text(&challenge.description, 16.));
+                    });
+            }
+        });
+}
+
+fn despawn_select(mut commands: Commands, screens: Query<Entity, With<ChallengeSelect>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn select(
+    mut commands: Commands,
+    mut buttons: Query<
+        (&Interaction, &ChallengeButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    mut settings: ResMut<Settings>,
+    mut app_state: ResMut<NextState<AppState>>,
+    challenges: Res<Challenges>,
+) {
+    for (interaction, button, mut color) in &mut buttons {
+        match interaction {
+            Interaction::Pressed => {
+                let Some((path, scenario)) = challenges.available.get(button.0) else {
+                    continue;
+                };
+
+                info!(?path, "challenge");
+
+                settings.seed = Seed::new(scenario.seed);
+                settings.size = UVec2::new(scenario.size.0, scenario.size.1);
+
+                commands.insert_resource(scenario.timers.clone().unwrap_or_default());
+                if let Some(rules) = scenario.rules.clone() {
+                    commands.insert_resource(rules);
+                }
+                commands.insert_resource(scenario.clone());
+
+                app_state.set(AppState::Generating);
+            }
+            Interaction::Hovered => *color = HOVERED_COLOR.into(),
+            Interaction::None => *color = BUTTON_COLOR.into(),
+        }
+    }
+}
+
+fn begin(
+    mut commands: Commands,
+    mut progress: ResMut<ChallengeProgress>,
+    mut activity: ResMut<NextState<Activity>>,
+    mut raids: EventWriter<Raid>,
+    scenario: Res<Scenario>,
+) {
+    let Some(challenge) = &scenario.challenge else {
+        return;
+    };
+
+    info!(title = %challenge.title, goal = ?challenge.goal, "challenge-begin");
+
+    *progress = ChallengeProgress::default();
+    activity.set(challenge.activity());
+
+    if !challenge.ships.is_empty() {
+        raids.send(Raid::new(challenge.player, challenge.ships.clone()));
+    }
+
+    commands.spawn((
+        Name::new("Challenge"),
+        GamePlayLifetime,
+        ChallengeStatus,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.),
+            left: Val::Px(8.),
+            ..default()
+        }),
+    ));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn evaluate(
+    mut progress: ResMut<ChallengeProgress>,
+    mut activity: ResMut<NextState<Activity>>,
+    mut construction: EventReader<ConstructionEvent>,
+    mut picks: EventReader<TargetPicked>,
+    structures: Option<Res<StructureLayers>>,
+    ships: Query<(), (With<Ship>, With<Unit>)>,
+    scenario: Res<Scenario>,
+    time: Res<Time>,
+) {
+    let Some(challenge) = &scenario.challenge else {
+        return;
+    };
+
+    if progress.outcome.is_some() {
+        construction.clear();
+        picks.clear();
+        return;
+    }
+
+    progress.elapsed += time.delta_seconds();
+    progress.pieces += construction.read().count() as u32;
+    progress.shots += picks
+        .read()
+        .filter(|pick| pick.player() == challenge.player)
+        .count() as u32;
+
+    let ships = ships.iter().count();
+    progress.ships_seen |= ships > 0;
+
+    let met = match challenge.goal {
+        Goal::Enclose => structures.is_some_and(|structures| {
+            structures
+                .territory(&challenge.player)
+                .iter()
+                .any(|inside| *inside)
+        }),
+        Goal::SinkShips => progress.ships_seen && ships == 0,
+    };
+
+    let out_of_pieces = challenge.pieces.is_some_and(|n| progress.pieces >= n);
+    let out_of_shots = challenge.shots.is_some_and(|n| progress.shots >= n);
+    if (out_of_pieces || out_of_shots) && progress.exhausted.is_none() {
+        progress.exhausted = Some(progress.elapsed);
+    }
+    let waited = |seconds: f32| {
+        progress
+            .exhausted
+            .is_some_and(|at| progress.elapsed - at > seconds)
+    };
+
+    let outcome = if met {
+        Some(Outcome::Success)
+    } else if challenge.seconds.is_some_and(|s| progress.elapsed > s) {
+        Some(Outcome::Failure("Out of time"))
+    } else if out_of_pieces && waited(SETTLE_SECONDS) {
+        Some(Outcome::Failure("Out of pieces"))
+    } else if out_of_shots && waited(LANDING_SECONDS) {
+        Some(Outcome::Failure("Out of shots"))
+    } else {
+        None
+    };
+
+    if let Some(outcome) = outcome {
+        info!(title = %challenge.title, ?outcome, elapsed = progress.elapsed, "challenge-over");
+        activity.set(Activity::Observing);
+        progress.outcome = Some(outcome);
+    }
+}
+
+fn update_status(
+    progress: Res<ChallengeProgress>,
+    scenario: Res<Scenario>,
+    mut labels: Query<&mut Text, With<ChallengeStatus>>,
+) {
+    let Some(challenge) = &scenario.challenge else {
+        return;
+    };
+
+    if !progress.is_changed() {
+        return;
+    }
+
+    let mut parts = vec![challenge.title.clone()];
+    match &progress.outcome {
+        Some(Outcome::Success) => parts.push("Success!".to_owned()),
+        Some(Outcome::Failure(reason)) => parts.push(format!("Failed: {}", reason)),
+        None => {
+            if let Some(seconds) = challenge.seconds {
+                parts.push(format!("{:.0}s", (seconds - progress.elapsed).max(0.)));
+            }
+            if let Some(pieces) = challenge.pieces {
+                parts.push(format!("{} pieces", pieces.saturating_sub(progress.pieces)));
+            }
+            if let Some(shots) = challenge.shots {
+                parts.push(format!("{} shots", shots.saturating_sub(progress.shots)));
+            }
+        }
+    }
+
+    for mut text in &mut labels {
+        text.sections[0].value = parts.join("  ");
+    }
+}
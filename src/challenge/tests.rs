@@ -0,0 +1,8 @@
+use super::Challenges;
+
+#[test]
+fn test_bundled_challenges_load() {
+    let challenges = Challenges::load(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/challenges"));
+
+    assert_eq!(challenges.available.len(), 2);
+}
@@ -0,0 +1,58 @@
+use bevy_rapier3d::prelude::{CollisionGroups, Group};
+
+pub const TERRAIN_GROUP: Group = Group::GROUP_1;
+pub const DEBRIS_GROUP: Group = Group::GROUP_2;
+pub const STRUCTURES_GROUP: Group = Group::GROUP_3;
+pub const CANNONS_GROUP: Group = Group::GROUP_4;
+pub const PROPS_GROUP: Group = Group::GROUP_5;
+pub const PROJECTILES_GROUP: Group = Group::GROUP_6;
+pub const SHIPS_GROUP: Group = Group::GROUP_7;
+pub const TROOPS_GROUP: Group = Group::GROUP_8;
+
+/// The kinds of things that have colliders, each with its own group and the
+/// groups it'll interact with. Both sides of a contact have to agree before
+/// rapier reports it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collides {
+    Terrain,
+    Debris,
+    Structures,
+    Cannons,
+    Props,
+    Projectiles,
+    Ships,
+}
+
+impl Collides {
+    pub fn membership(&self) -> Group {
+        match self {
+            Collides::Terrain => TERRAIN_GROUP,
+            Collides::Debris => DEBRIS_GROUP,
+            Collides::Structures => STRUCTURES_GROUP,
+            Collides::Cannons => CANNONS_GROUP,
+            Collides::Props => PROPS_GROUP,
+            Collides::Projectiles => PROJECTILES_GROUP,
+            Collides::Ships => SHIPS_GROUP,
+        }
+    }
+
+    pub fn filter(&self) -> Group {
+        match self {
+            Collides::Terrain => PROJECTILES_GROUP | SHIPS_GROUP | TROOPS_GROUP | DEBRIS_GROUP,
+            // Only resting on things, nothing gets in the way of a shot.
+            Collides::Debris => TERRAIN_GROUP | STRUCTURES_GROUP,
+            Collides::Structures => PROJECTILES_GROUP | SHIPS_GROUP | TROOPS_GROUP | DEBRIS_GROUP,
+            Collides::Cannons => TROOPS_GROUP,
+            Collides::Props => PROJECTILES_GROUP | TROOPS_GROUP,
+            // Cannons are left out so shots leave the muzzle cleanly.
+            Collides::Projectiles => {
+                TERRAIN_GROUP | STRUCTURES_GROUP | PROPS_GROUP | PROJECTILES_GROUP | SHIPS_GROUP
+            }
+            Collides::Ships => PROJECTILES_GROUP,
+        }
+    }
+
+    pub fn groups(&self) -> CollisionGroups {
+        CollisionGroups::new(self.membership(), self.filter())
+    }
+}
@@ -0,0 +1,284 @@
+use bevy::prelude::*;
+
+use crate::{
+    helpers::GamePlayLifetime,
+    model::{Activity, AppState, Phase},
+    profile::{Aiming, Profile},
+};
+
+#[cfg(test)]
+mod tests;
+
+const HINT_COLOR: Color = Color::rgb(0.8, 0.8, 0.8);
+
+/// Keeps the keys for everything that can be done in one place, and shows
+/// what can be done right now along the bottom of the screen.
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Controls>()
+            .init_resource::<Typing>()
+            .add_systems(OnEnter(AppState::Game), (spawn_hint_bar, stop_typing))
+            .add_systems(Update, refresh_hints.run_if(in_state(AppState::Game)));
+    }
+}
+
+/// Things done with the keyboard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Turns the cannon under the cursor a quarter turn while arming.
+    TurnCannon,
+    /// Switches between placing cannons and mortars while arming.
+    SwitchGun,
+    /// Switches between clicking on targets, aiming by hand and looking down
+    /// the barrel.
+    ToggleAiming,
+    LowerElevation,
+    RaiseElevation,
+    /// Held when picking a target to fire every cannon that can reach.
+    Volley,
+    /// Stops and starts the game's clock, playing alone.
+    Pause,
+    /// Cycles through how fast the game runs, playing alone.
+    Faster,
+    /// Whether the camera turns to follow the other player's shots.
+    FollowShots,
+    /// Moves on to looking down the barrel of the next cannon.
+    NextCannon,
+    /// Shows and hides ratings and the best played on each seed.
+    Leaderboard,
+    /// Throws away the piece being placed for the next one while fortifying.
+    Discard,
+    /// Sets the piece being placed aside, swapping in the one set aside before.
+    Hold,
+    /// Held when clicking to repair everything around the cell at once.
+    RepairArea,
+    /// Shows and hides the display settings.
+    Settings,
+}
+
+/// Which keys do what, any of them doing it.
+#[derive(Debug, Clone, Resource)]
+pub struct Controls {
+    bindings: Vec<(Action, Vec<KeyCode>)>,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Self {
+            bindings: vec![
+                (Action::TurnCannon, vec![KeyCode::KeyQ]),
+                (Action::SwitchGun, vec![KeyCode::KeyA]),
+                (Action::ToggleAiming, vec![KeyCode::KeyH]),
+                (Action::LowerElevation, vec![KeyCode::BracketLeft]),
+                (Action::RaiseElevation, vec![KeyCode::BracketRight]),
+                (
+                    Action::Volley,
+                    vec![KeyCode::ShiftLeft, KeyCode::ShiftRight],
+                ),
+                (Action::Pause, vec![KeyCode::F5]),
+                (Action::Faster, vec![KeyCode::F6]),
+                (Action::FollowShots, vec![KeyCode::F7]),
+                (Action::NextCannon, vec![KeyCode::Tab]),
+                (Action::Leaderboard, vec![KeyCode::F4]),
+                (Action::Discard, vec![KeyCode::Delete]),
+                (Action::Hold, vec![KeyCode::KeyD]),
+                (
+                    Action::RepairArea,
+                    vec![KeyCode::ControlLeft, KeyCode::ControlRight],
+                ),
+                (Action::Settings, vec![KeyCode::F2]),
+            ],
+        }
+    }
+}
+
+impl Controls {
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map(|(_, keys)| keys.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Binds the action to these keys instead of whatever it was.
+    pub fn bind(&mut self, action: Action, keys: Vec<KeyCode>) {
+        info!(?action, ?keys, "control-bound");
+        match self.bindings.iter_mut().find(|(bound, _)| *bound == action) {
+            Some((_, bound)) => *bound = keys,
+            None => self.bindings.push((action, keys)),
+        }
+    }
+
+    pub fn pressed(&self, input: &ButtonInput<KeyCode>, action: Action) -> bool {
+        input.any_pressed(self.keys(action).iter().copied())
+    }
+
+    pub fn just_pressed(&self, input: &ButtonInput<KeyCode>, action: Action) -> bool {
+        input.any_just_pressed(self.keys(action).iter().copied())
+    }
+
+    /// What to call the keys for an action, the first of any that look the
+    /// same, like either shift.
+    pub fn describe(&self, action: Action) -> String {
+        let mut names: Vec<String> = Vec::new();
+        for name in self.keys(action).iter().map(key_name) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+
+        if names.is_empty() {
+            "unbound".to_owned()
+        } else {
+            names.join("/")
+        }
+    }
+}
+
+/// What keys are going into instead of doing things, while anything is.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource)]
+pub enum Typing {
+    #[default]
+    Nothing,
+    /// A line of chat, in networked games.
+    Chat,
+    /// A note for a bookmarked seed.
+    Note,
+}
+
+/// Whether keys are being typed into something, so shouldn't act as controls.
+pub fn typing(typing: Res<Typing>) -> bool {
+    *typing != Typing::Nothing
+}
+
+fn stop_typing(mut typing: ResMut<Typing>) {
+    *typing = Typing::Nothing;
+}
+
+/// A short name for a key, as printed on it.
+pub fn key_name(key: &KeyCode) -> String {
+    match key {
+        KeyCode::ShiftLeft | KeyCode::ShiftRight => "Shift".to_owned(),
+        KeyCode::ControlLeft | KeyCode::ControlRight => "Ctrl".to_owned(),
+        KeyCode::AltLeft | KeyCode::AltRight => "Alt".to_owned(),
+        KeyCode::BracketLeft => "[".to_owned(),
+        KeyCode::BracketRight => "]".to_owned(),
+        key => {
+            let name = format!("{:?}", key);
+            name.strip_prefix("Key")
+                .or_else(|| name.strip_prefix("Digit"))
+                .unwrap_or(&name)
+                .to_owned()
+        }
+    }
+}
+
+/// What can be done right now, with the keys to do it.
+pub fn hints(
+    activity: &Activity,
+    phase: &Phase,
+    aiming: Aiming,
+    controls: &Controls,
+) -> Vec<String> {
+    let key = |action| controls.describe(action);
+
+    match (activity, phase) {
+        (Activity::Building, Phase::Choose(_)) => vec!["LMB: choose castle".to_owned()],
+        (Activity::Building, Phase::Fortify(_)) => vec![
+            "LMB: place piece".to_owned(),
+            format!("{}: discard piece", key(Action::Discard)),
+            format!("{}: hold piece", key(Action::Hold)),
+        ],
+        (Activity::Building, Phase::Arm(_)) => vec![
+            "LMB: place gun".to_owned(),
+            format!("{}: cannon or mortar", key(Action::SwitchGun)),
+            format!("{}: turn cannon", key(Action::TurnCannon)),
+            format!("{}: hold piece", key(Action::Hold)),
+        ],
+        (Activity::Firing, _) => match aiming {
+            Aiming::Click => vec![
+                "LMB: fire".to_owned(),
+                format!("{}+LMB: volley", key(Action::Volley)),
+                format!("{}: aim by hand", key(Action::ToggleAiming)),
+            ],
+            Aiming::Manual => vec![
+                "Drag: aim and fire".to_owned(),
+                format!(
+                    "{} {}: elevation",
+                    key(Action::LowerElevation),
+                    key(Action::RaiseElevation)
+                ),
+                format!("{}+release: volley", key(Action::Volley)),
+                format!("{}: first person", key(Action::ToggleAiming)),
+            ],
+            Aiming::FirstPerson => vec![
+                "Mouse: aim".to_owned(),
+                "LMB: fire".to_owned(),
+                format!("{}+LMB: volley", key(Action::Volley)),
+                format!("{}: next cannon", key(Action::NextCannon)),
+                format!("{}: click to aim", key(Action::ToggleAiming)),
+            ],
+        },
+        _ => Vec::new(),
+    }
+}
+
+#[derive(Component)]
+struct HintBar;
+
+fn spawn_hint_bar(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Hints"),
+            GamePlayLifetime,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    bottom: Val::Px(12.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                HintBar,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 16.,
+                        color: HINT_COLOR,
+                        ..default()
+                    },
+                ),
+            ));
+        });
+}
+
+/// Rewrites the hints whenever what can be done, or the keys for it, change.
+fn refresh_hints(
+    mut bars: Query<&mut Text, With<HintBar>>,
+    activity: Res<State<Activity>>,
+    phase: Res<State<Phase>>,
+    profile: Res<Profile>,
+    controls: Res<Controls>,
+) {
+    let changed = activity.is_changed()
+        || phase.is_changed()
+        || profile.is_changed()
+        || controls.is_changed();
+
+    for mut text in &mut bars {
+        if !changed && !text.is_added() {
+            continue;
+        }
+
+        let hints = hints(activity.get(), phase.get(), profile.aiming, &controls);
+        text.sections[0].value = hints.join("   ");
+    }
+}
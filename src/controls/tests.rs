@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+use crate::{
+    model::{Activity, Phase, Player},
+    profile::Aiming,
+};
+
+use super::{hints, Action, Controls};
+
+#[test]
+fn test_hints_follow_whatever_keys_are_bound() {
+    let mut controls = Controls::default();
+    let arm = Phase::Arm(Player::One);
+
+    assert!(hints(&Activity::Building, &arm, Aiming::Click, &controls)
+        .contains(&"Q: turn cannon".to_owned()));
+
+    controls.bind(Action::TurnCannon, vec![KeyCode::KeyR]);
+
+    assert!(hints(&Activity::Building, &arm, Aiming::Click, &controls)
+        .contains(&"R: turn cannon".to_owned()));
+}
+
+#[test]
+fn test_discarding_is_offered_while_fortifying() {
+    let controls = Controls::default();
+    let fortify = Phase::Fortify(Player::One);
+
+    assert!(
+        hints(&Activity::Building, &fortify, Aiming::Click, &controls)
+            .contains(&"Delete: discard piece".to_owned())
+    );
+}
+
+#[test]
+fn test_switching_guns_is_offered_while_arming() {
+    let controls = Controls::default();
+    let arm = Phase::Arm(Player::Two);
+
+    assert!(hints(&Activity::Building, &arm, Aiming::Click, &controls)
+        .contains(&"A: cannon or mortar".to_owned()));
+}
+
+#[test]
+fn test_holding_is_offered_whenever_placing() {
+    let controls = Controls::default();
+
+    for phase in [Phase::Fortify(Player::One), Phase::Arm(Player::One)] {
+        assert!(hints(&Activity::Building, &phase, Aiming::Click, &controls)
+            .contains(&"D: hold piece".to_owned()));
+    }
+}
+
+#[test]
+fn test_either_shift_is_described_once() {
+    let controls = Controls::default();
+
+    assert_eq!(controls.describe(Action::Volley), "Shift");
+    assert_eq!(controls.describe(Action::LowerElevation), "[");
+}
+
+#[test]
+fn test_hints_change_with_how_targets_are_picked() {
+    let controls = Controls::default();
+    let target = Phase::Target(Player::Two);
+
+    let clicking = hints(&Activity::Firing, &target, Aiming::Click, &controls);
+    let manual = hints(&Activity::Firing, &target, Aiming::Manual, &controls);
+
+    assert!(clicking.contains(&"H: aim by hand".to_owned()));
+    assert!(manual.contains(&"[ ]: elevation".to_owned()));
+    assert!(hints(&Activity::Observing, &target, Aiming::Click, &controls).is_empty());
+}
@@ -0,0 +1,257 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    display::DisplaySettings,
+    helpers::{Expires, GamePlayLifetime, PlayerColor},
+    model::{AppState, Player, Settings},
+    network::{self, Host},
+};
+
+/// Seconds each number is shown for.
+const NUMBER_SECONDS: f32 = 1.2;
+
+/// How quickly numbers drift up from where the damage was done.
+const RISE_SPEED: f32 = 1.5;
+
+/// Damage dealt by anyone other than a player, like raiding soldiers.
+const UNOWNED_COLOR: Color = Color::rgb(0.8, 0.8, 0.8);
+
+/// Seconds a hit marker is shown for.
+const MARKER_SECONDS: f32 = 0.4;
+
+/// Tones played for a hit, and higher for a structure destroyed.
+const HIT_HZ: f32 = 660.0;
+const DESTROYED_HZ: f32 = 990.0;
+
+const TONE_SECONDS: f32 = 0.12;
+
+/// Shows how much damage was done wherever something takes it, drifting up
+/// and fading away, and confirms hits on the other player's structures.
+pub struct DamagePlugin;
+
+impl Plugin for DamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Damaged>()
+            .add_systems(Startup, load_hit_sounds)
+            .add_systems(
+                PostUpdate,
+                (
+                    spawn_numbers.run_if(showing_numbers),
+                    float_numbers,
+                    confirm_hits,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+/// Damage done to something at `world`, by the player who did it when there
+/// was one.
+#[derive(Clone, Debug)]
+pub struct Damaged {
+    world: Vec3,
+    amount: f32,
+    player: Option<Player>,
+    structure: Option<Struck>,
+}
+
+impl Event for Damaged {}
+
+/// A structure that was damaged, whose it was and whether that was the end of
+/// it.
+#[derive(Clone, Copy, Debug)]
+struct Struck {
+    owner: Player,
+    destroyed: bool,
+}
+
+impl Damaged {
+    pub fn new(world: Vec3, amount: f32, player: Option<Player>) -> Self {
+        Self {
+            world,
+            amount,
+            player,
+            structure: None,
+        }
+    }
+
+    /// Done to one of `owner`'s structures.
+    pub fn structure(self, owner: Player, destroyed: bool) -> Self {
+        Self {
+            structure: Some(Struck { owner, destroyed }),
+            ..self
+        }
+    }
+
+    /// Whether this was a player hitting one of the other's structures, and
+    /// if so whether it was destroyed.
+    pub fn confirmed(&self) -> Option<(Player, bool)> {
+        let player = self.player?;
+        let struck = self.structure?;
+        (struck.owner != player).then_some((player, struck.destroyed))
+    }
+}
+
+#[derive(Component)]
+struct FloatingNumber {
+    world: Vec3,
+    age: f32,
+}
+
+fn showing_numbers(display: Res<DisplaySettings>) -> bool {
+    display.damage_numbers
+}
+
+fn spawn_numbers(mut commands: Commands, mut damaged: EventReader<Damaged>) {
+    for damaged in damaged.read() {
+        // Chip damage rounding down to nothing isn't worth showing.
+        let amount = damaged.amount.round();
+        if amount < 1.0 {
+            continue;
+        }
+
+        let color = damaged
+            .player
+            .map(|player| player.color())
+            .unwrap_or(UNOWNED_COLOR);
+
+        commands.spawn((
+            Name::new("Damage"),
+            FloatingNumber {
+                world: damaged.world,
+                age: 0.,
+            },
+            Expires::after(NUMBER_SECONDS),
+            GamePlayLifetime,
+            TextBundle::from_section(
+                format!("{:.0}", amount),
+                TextStyle {
+                    font_size: 20.,
+                    color,
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                ..default()
+            }),
+        ));
+    }
+}
+
+/// Keeps each number over where the damage was done, facing the camera
+/// wherever it's looking from, as it rises and fades.
+fn float_numbers(
+    mut numbers: Query<(&mut FloatingNumber, &mut Style, &mut Text, &mut Visibility)>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    display: Res<DisplaySettings>,
+    timer: Res<Time>,
+) {
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+
+    for (mut number, mut style, mut text, mut visibility) in &mut numbers {
+        number.age += timer.delta_seconds();
+        if !display.reduce_motion {
+            number.world.y += RISE_SPEED * timer.delta_seconds();
+        }
+
+        match camera.world_to_viewport(camera_transform, number.world) {
+            Some(position) => {
+                *visibility = Visibility::Visible;
+                style.left = Val::Px(position.x);
+                style.top = Val::Px(position.y);
+            }
+            // Behind the camera.
+            None => *visibility = Visibility::Hidden,
+        }
+
+        let alpha = (1.0 - number.age / NUMBER_SECONDS).clamp(0., 1.);
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(alpha);
+        }
+    }
+}
+
+#[derive(Resource)]
+struct HitSounds {
+    hit: Handle<Pitch>,
+    destroyed: Handle<Pitch>,
+}
+
+fn load_hit_sounds(mut commands: Commands, mut pitches: ResMut<Assets<Pitch>>) {
+    let tone = |hz| Pitch::new(hz, Duration::from_secs_f32(TONE_SECONDS));
+
+    commands.insert_resource(HitSounds {
+        hit: pitches.add(tone(HIT_HZ)),
+        destroyed: pitches.add(tone(DESTROYED_HZ)),
+    });
+}
+
+/// Lets whoever's playing here know their shot hit the other player's
+/// structures, with a marker and a tone, whether or not they can see where it
+/// landed. Shots from the computer or from across the network go unremarked.
+fn confirm_hits(
+    mut commands: Commands,
+    mut damaged: EventReader<Damaged>,
+    sounds: Res<HitSounds>,
+    settings: Res<Settings>,
+    host: Option<Res<Host>>,
+) {
+    let confirmed = damaged
+        .read()
+        .filter_map(|damaged| damaged.confirmed())
+        .filter(|(player, _)| {
+            !settings.computer(player) && !network::remote(host.as_deref(), player)
+        })
+        .fold(None, |all: Option<(Player, bool)>, (player, destroyed)| {
+            Some((player, destroyed || all.is_some_and(|(_, d)| d)))
+        });
+
+    // One marker and tone however many things a volley hit.
+    let Some((player, destroyed)) = confirmed else {
+        return;
+    };
+
+    info!(?player, %destroyed, "hit-confirmed");
+
+    commands.spawn(AudioSourceBundle {
+        source: if destroyed {
+            sounds.destroyed.clone()
+        } else {
+            sounds.hit.clone()
+        },
+        settings: PlaybackSettings::DESPAWN,
+    });
+
+    commands
+        .spawn((
+            Name::new("Hit"),
+            Expires::after(MARKER_SECONDS),
+            GamePlayLifetime,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    top: Val::Percent(40.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                if destroyed { "DESTROYED" } else { "HIT" },
+                TextStyle {
+                    font_size: if destroyed { 32. } else { 24. },
+                    color: player.color(),
+                    ..default()
+                },
+            ));
+        });
+}
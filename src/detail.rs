@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+
+use crate::{
+    building::{Cannon, Chain, Wall},
+    camera::{zoomed_out, CameraZoom, Detail},
+    helpers::PlayerColor,
+    model::{AppState, Health, Player, CANNON_HP, TILE_SIZE, WALL_HEIGHT, WALL_HP},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Lengths of health bars, and how far above what they're for.
+const BAR_LENGTH: f32 = TILE_SIZE * 0.8;
+const BAR_HEIGHT: f32 = WALL_HEIGHT * 0.75;
+
+const BAR_EMPTY: Color = Color::rgb(0.8, 0.1, 0.1);
+const BAR_FULL: Color = Color::rgb(0.1, 0.8, 0.1);
+
+/// Draws things differently as the camera zooms in and out, just who holds
+/// what from far away and models and health up close.
+pub struct DetailPlugin;
+
+impl Plugin for DetailPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_gizmo_group::<DetailGizmos>().add_systems(
+            Update,
+            (
+                show_models,
+                draw_simplified.run_if(zoomed_out),
+                draw_health.run_if(not(zoomed_out)),
+            )
+                .run_if(in_state(AppState::Game)),
+        );
+    }
+}
+
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct DetailGizmos;
+
+/// Hides the models on corners and cannons from far away, where the walls
+/// themselves and the colors drawn over them say enough, and brings them
+/// back up close.
+fn show_models(
+    zoom: Res<CameraZoom>,
+    mut models: Query<(&Parent, &mut Visibility), With<Handle<Scene>>>,
+    structures: Query<(), Or<(With<Wall>, With<Cannon>)>>,
+    added: Query<(), Added<Handle<Scene>>>,
+) {
+    if !zoom.is_changed() && added.is_empty() {
+        return;
+    }
+
+    let shown = match zoom.detail {
+        Detail::Near => Visibility::Inherited,
+        Detail::Far => Visibility::Hidden,
+    };
+
+    for (parent, mut visibility) in &mut models {
+        if structures.contains(parent.get()) && *visibility != shown {
+            *visibility = shown;
+        }
+    }
+}
+
+/// Every structure as a square in its player's color.
+fn draw_simplified(
+    mut gizmos: Gizmos<DetailGizmos>,
+    structures: Query<(&GlobalTransform, &Player), Or<(With<Wall>, With<Cannon>, With<Chain>)>>,
+) {
+    let flat = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
+
+    for (transform, player) in &structures {
+        gizmos.rect(
+            transform.translation() + Vec3::Y * (WALL_HEIGHT / 2.0),
+            flat,
+            Vec2::splat(TILE_SIZE * 0.8),
+            player.color(),
+        );
+    }
+}
+
+/// A bar over everything that's been damaged, turned to face the camera.
+fn draw_health(
+    mut gizmos: Gizmos<DetailGizmos>,
+    structures: Query<(&GlobalTransform, &Health, Has<Cannon>), Or<(With<Wall>, With<Cannon>)>>,
+    cameras: Query<&GlobalTransform, With<Camera>>,
+) {
+    let Ok(camera) = cameras.get_single() else {
+        return;
+    };
+
+    let across = camera.right() * (BAR_LENGTH / 2.0);
+
+    for (transform, health, cannon) in &structures {
+        let full = if cannon { CANNON_HP } else { WALL_HP };
+        let Some(left) = remaining(health, full) else {
+            continue;
+        };
+
+        let middle = transform.translation() + Vec3::Y * BAR_HEIGHT;
+        let start = middle - across;
+        let end = start + across * 2.0 * left;
+        gizmos.line(start, middle + across, BAR_EMPTY);
+        gizmos.line(start, end, BAR_FULL);
+    }
+}
+
+/// How much health is left, nothing for anything undamaged.
+fn remaining(health: &Health, full: f32) -> Option<f32> {
+    (health.hp() < full).then(|| (health.hp() / full).clamp(0.0, 1.0))
+}
@@ -0,0 +1,10 @@
+use crate::model::Health;
+
+use super::remaining;
+
+#[test]
+fn test_only_damaged_structures_have_health_shown() {
+    assert_eq!(remaining(&Health::new(100.0), 100.0), None);
+    assert_eq!(remaining(&Health::new(25.0), 100.0), Some(0.25));
+    assert_eq!(remaining(&Health::new(-5.0), 100.0), Some(0.0));
+}
@@ -1,11 +1,17 @@
 use bevy::{pbr::wireframe::WireframeConfig, prelude::*};
+use bevy_rapier3d::render::{DebugRenderContext, RapierDebugRenderPlugin};
 
 use crate::{
     camera::CameraMode,
+    controls::typing,
     helpers::ExpirationControl,
-    model::{Activity, AppState},
+    model::{Activity, AppState, Seed, Settings},
+    scenario::Scenario,
 };
 
+mod grid;
+mod perf;
+
 pub struct DeveloperPlugin;
 
 impl Plugin for DeveloperPlugin {
@@ -14,15 +20,56 @@ impl Plugin for DeveloperPlugin {
             .add_plugins(bevy::diagnostic::EntityCountDiagnosticsPlugin)
             .add_plugins(bevy::diagnostic::SystemInformationDiagnosticsPlugin)
             .add_plugins(iyes_perf_ui::PerfUiPlugin)
+            .add_plugins(perf::PerfPlugin)
+            .add_plugins(grid::GridOverlayPlugin)
+            .add_plugins(RapierDebugRenderPlugin {
+                enabled: false,
+                ..default()
+            })
             .add_systems(
                 Update,
-                manual_camera.run_if(not(in_state(CameraMode::Normal))),
+                manual_camera
+                    .run_if(not(typing))
+                    .run_if(not(in_state(CameraMode::Normal)))
+                    .run_if(not(in_state(CameraMode::FirstPerson)))
+                    .run_if(not(in_state(CameraMode::Free))),
+            )
+            .add_event::<RegenerateWorld>()
+            .add_systems(Update, developer_keyboard.run_if(not(typing)))
+            .add_systems(
+                Update,
+                regenerate_world
+                    .after(developer_keyboard)
+                    .run_if(in_state(AppState::Game)),
             )
-            .add_systems(Update, developer_keyboard)
             .add_systems(Update, standard_gizmos);
     }
 }
 
+/// Tears down the world and generates it again from the seed, or a new one,
+/// going through the same cleanup as leaving a game.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct RegenerateWorld(pub Option<Seed<u32>>);
+
+fn regenerate_world(
+    mut events: EventReader<RegenerateWorld>,
+    mut settings: ResMut<Settings>,
+    mut app_state: ResMut<NextState<AppState>>,
+    scenario: Option<Res<Scenario>>,
+) {
+    let Some(RegenerateWorld(seed)) = events.read().last() else {
+        return;
+    };
+
+    settings.seed = seed.unwrap_or_else(|| Seed::new(rand::random()));
+    if scenario.is_some_and(|s| s.heights.is_some()) {
+        warn!("scenario heights are kept, only props change with the seed");
+    }
+
+    info!(seed = ?settings.seed(), "regenerating");
+    app_state.set(AppState::Menu);
+}
+
 fn standard_gizmos(mut gizmos: Gizmos, lights: Query<(&PointLight, &GlobalTransform)>) {
     for (_light, transform) in lights.iter() {
         gizmos.sphere(transform.translation(), Quat::IDENTITY, 0.5, Color::RED);
@@ -56,6 +103,7 @@ fn manual_camera(keys: Res<ButtonInput<KeyCode>>, mut camera: Query<(&Camera, &m
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn developer_keyboard(
     keys: Res<ButtonInput<KeyCode>>,
     camera_mode: Res<State<CameraMode>>,
@@ -66,6 +114,8 @@ fn developer_keyboard(
     mut wireframe_config: ResMut<WireframeConfig>,
     mut new_expiration_control: ResMut<NextState<ExpirationControl>>,
     mut config_store: ResMut<GizmoConfigStore>,
+    mut rapier_debug: ResMut<DebugRenderContext>,
+    mut regenerate: EventWriter<RegenerateWorld>,
 ) {
     if keys.just_pressed(KeyCode::Space) {
         info!("{:?}", KeyCode::Space);
@@ -83,6 +133,18 @@ fn developer_keyboard(
         config.enabled = !config.enabled;
         info!("gizmo-config: {:?}", config.enabled);
     }
+    if keys.just_pressed(KeyCode::Digit2) {
+        rapier_debug.enabled = !rapier_debug.enabled;
+        info!("rapier-debug: {:?}", rapier_debug.enabled);
+    }
+    if keys.just_pressed(KeyCode::Digit3) {
+        let (config, _) = config_store.config_mut::<grid::GridGizmos>();
+        config.enabled = !config.enabled;
+        info!("grid-overlay: {:?}", config.enabled);
+    }
+    if keys.just_pressed(KeyCode::KeyG) {
+        regenerate.send(RegenerateWorld(None));
+    }
     if keys.just_pressed(KeyCode::KeyR) {
         info!("resetting");
         app_state.set(AppState::Menu);
@@ -99,12 +161,17 @@ fn developer_keyboard(
         info!("building");
         activity.set(Activity::Building);
     }
+    if keys.just_pressed(KeyCode::KeyT) {
+        info!("editing");
+        activity.set(Activity::Editing);
+    }
     if keys.just_pressed(KeyCode::KeyC) {
         let mode = match camera_mode.get() {
             CameraMode::Normal => CameraMode::AllTopDown,
             CameraMode::AllTopDown => CameraMode::AllAngled,
             CameraMode::AllAngled => CameraMode::FirstPerson,
-            CameraMode::FirstPerson => CameraMode::Normal,
+            CameraMode::FirstPerson => CameraMode::Free,
+            CameraMode::Free => CameraMode::Normal,
         };
         info!("camera: {:?}", mode);
         new_camera_mode.set(mode);
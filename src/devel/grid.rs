@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+
+use crate::{
+    building::{Structure, StructureLayers},
+    helpers::PlayerColor,
+    model::{Player, TILE_SIZE},
+    terrain::Terrain,
+};
+
+/// Everything `StructureLayers` knows about each cell, drawn over the map.
+/// Off until toggled from the developer keys.
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct GridGizmos;
+
+pub struct GridOverlayPlugin;
+
+impl Plugin for GridOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_gizmo_group(
+            GridGizmos,
+            GizmoConfig {
+                enabled: false,
+                depth_bias: -1.0,
+                ..default()
+            },
+        )
+        .add_systems(Update, draw_grid);
+    }
+}
+
+/// Territory is shaded in the owner's color, walls are squares and cannons
+/// circles above that, and walls that would open up territory if destroyed
+/// are outlined in red.
+fn draw_grid(
+    mut gizmos: Gizmos<GridGizmos>,
+    config_store: Res<GizmoConfigStore>,
+    structures: Option<Res<StructureLayers>>,
+    terrain: Query<&Terrain>,
+) {
+    let (config, _) = config_store.config::<GridGizmos>();
+    if !config.enabled {
+        return;
+    }
+    let (Ok(terrain), Some(structures)) = (terrain.get_single(), structures) else {
+        return;
+    };
+
+    let flat = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
+    let above = |grid: IVec2, height: f32| {
+        terrain
+            .survey_grid(grid)
+            .map(|survey| survey.world() + Vec3::Y * height)
+    };
+
+    for player in [Player::One, Player::Two] {
+        let color = player.color();
+
+        for (grid, enclosed) in structures.territory(&player).enumerate_cells() {
+            if !*enclosed {
+                continue;
+            }
+            if let Some(position) = above(grid, 0.05) {
+                gizmos.rect(
+                    position,
+                    flat,
+                    Vec2::splat(TILE_SIZE * 0.9),
+                    color.with_a(0.3),
+                );
+            }
+        }
+
+        for (grid, _) in structures.breaches(&player) {
+            if let Some(position) = above(grid, 1.6) {
+                gizmos.rect(position, flat, Vec2::splat(TILE_SIZE * 0.8), Color::RED);
+            }
+        }
+    }
+
+    for (grid, structure) in structures.structures() {
+        let Some(position) = above(grid, 1.5) else {
+            continue;
+        };
+        let color = structure.player().color();
+        match structure {
+            Structure::Wall(_) => gizmos.rect(position, flat, Vec2::splat(TILE_SIZE * 0.5), color),
+            Structure::Cannon(_) => {
+                gizmos.circle(position, Direction3d::Y, TILE_SIZE * 0.3, color);
+            }
+            Structure::Chain(_) => {
+                gizmos.line(
+                    position - Vec3::X * TILE_SIZE * 0.4,
+                    position + Vec3::X * TILE_SIZE * 0.4,
+                    color,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_hanabi::prelude::*;
+use iyes_perf_ui::PerfUiRoot;
+
+use crate::{
+    building::{Cannon, Wall},
+    firing::RoundShot,
+};
+
+/// Number of frames shown in the frame time graph.
+const HISTORY: usize = 90;
+
+/// Frame time that fills the graph, anything slower is clipped.
+const GRAPH_MAXIMUM_MS: f32 = 50.0;
+const GRAPH_HEIGHT: f32 = 40.0;
+
+pub struct PerfPlugin;
+
+impl Plugin for PerfPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FrameHistory>()
+            .add_systems(Update, (record_frame_times, toggle_perf_overlay))
+            .add_systems(
+                Update,
+                update_perf_overlay
+                    .after(record_frame_times)
+                    .run_if(any_with_component::<PerfOverlay>),
+            );
+    }
+}
+
+#[derive(Default, Resource)]
+struct FrameHistory(VecDeque<f32>);
+
+#[derive(Component)]
+struct PerfOverlay;
+
+#[derive(Component)]
+struct PerfStats;
+
+#[derive(Component)]
+struct FrameBar(usize);
+
+fn record_frame_times(time: Res<Time>, mut history: ResMut<FrameHistory>) {
+    history.0.push_back(time.delta_seconds() * 1000.);
+    while history.0.len() > HISTORY {
+        history.0.pop_front();
+    }
+}
+
+fn toggle_perf_overlay(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    overlays: Query<Entity, Or<(With<PerfOverlay>, With<PerfUiRoot>)>>,
+) {
+    if !keys.just_pressed(KeyCode::F3) {
+        return;
+    }
+
+    if !overlays.is_empty() {
+        info!("perf-overlay: false");
+        for entity in overlays.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    info!("perf-overlay: true");
+
+    commands.spawn(iyes_perf_ui::PerfUiCompleteBundle::default());
+
+    commands
+        .spawn((
+            Name::new("Perf:Overlay"),
+            PerfOverlay,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(12.),
+                    left: Val::Px(12.),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.),
+                    padding: UiRect::all(Val::Px(6.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        height: Val::Px(GRAPH_HEIGHT),
+                        align_items: AlignItems::FlexEnd,
+                        column_gap: Val::Px(1.),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|graph| {
+                    for index in 0..HISTORY {
+                        graph.spawn((
+                            FrameBar(index),
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Px(2.),
+                                    height: Val::Px(0.),
+                                    ..default()
+                                },
+                                background_color: Color::GREEN.into(),
+                                ..default()
+                            },
+                        ));
+                    }
+                });
+
+            parent.spawn((
+                PerfStats,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 14.,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+            ));
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_perf_overlay(
+    history: Res<FrameHistory>,
+    entities: Query<()>,
+    projectiles: Query<(), With<RoundShot>>,
+    effects: Query<(), With<ParticleEffect>>,
+    lights: Query<(), With<PointLight>>,
+    structures: Query<(), Or<(With<Wall>, With<Cannon>)>>,
+    mut stats: Query<&mut Text, With<PerfStats>>,
+    mut bars: Query<(&FrameBar, &mut Style, &mut BackgroundColor)>,
+) {
+    let frames = &history.0;
+    let last = frames.back().cloned().unwrap_or_default();
+    let slowest = frames.iter().cloned().fold(0.0, f32::max);
+    let average = frames.iter().sum::<f32>() / frames.len().max(1) as f32;
+
+    for mut text in &mut stats {
+        text.sections[0].value = format!(
+            "frame {:.1}ms avg {:.1}ms max {:.1}ms\nentities {} structures {} projectiles {} effects {} lights {}",
+            last,
+            average,
+            slowest,
+            entities.iter().count(),
+            structures.iter().count(),
+            projectiles.iter().count(),
+            effects.iter().count(),
+            lights.iter().count(),
+        );
+    }
+
+    // Oldest frames on the left, so the graph scrolls towards it.
+    let offset = HISTORY - frames.len();
+    for (bar, mut style, mut color) in &mut bars {
+        let ms = bar
+            .0
+            .checked_sub(offset)
+            .and_then(|index| frames.get(index))
+            .cloned()
+            .unwrap_or_default();
+        let fraction = (ms / GRAPH_MAXIMUM_MS).min(1.0);
+
+        style.height = Val::Px(fraction * GRAPH_HEIGHT);
+        *color = if ms > 1000. / 30. {
+            Color::RED
+        } else if ms > 1000. / 60. {
+            Color::YELLOW
+        } else {
+            Color::GREEN
+        }
+        .into();
+    }
+}
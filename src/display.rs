@@ -0,0 +1,252 @@
+use bevy::{
+    prelude::*,
+    window::{PresentMode, PrimaryWindow, WindowMode, WindowResolution},
+};
+use bevy_inspector_egui::bevy_egui::EguiSettings;
+
+mod menu;
+mod screenshot;
+#[cfg(test)]
+mod tests;
+
+pub struct DisplayPlugin;
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(screenshot::ScreenshotPlugin)
+            .add_plugins(menu::SettingsMenuPlugin)
+            .add_systems(Update, (display_keyboard, scale_keyboard))
+            .add_systems(
+                Update,
+                apply_display.run_if(resource_changed::<DisplaySettings>),
+            )
+            .add_systems(PostUpdate, scale_fonts);
+    }
+}
+
+/// Steps taken by the keys for the size of everything on screen and of text
+/// alone, and how far either can go.
+const UI_SCALE_STEP: f32 = 0.25;
+const UI_SCALES: (f32, f32) = (0.5, 3.0);
+const FONT_SCALE_STEP: f32 = 0.1;
+const FONT_SCALES: (f32, f32) = (0.5, 2.5);
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    fn next(&self) -> Self {
+        match self {
+            DisplayMode::Windowed => DisplayMode::Borderless,
+            DisplayMode::Borderless => DisplayMode::Fullscreen,
+            DisplayMode::Fullscreen => DisplayMode::Windowed,
+        }
+    }
+}
+
+impl From<DisplayMode> for WindowMode {
+    fn from(value: DisplayMode) -> Self {
+        match value {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen,
+            DisplayMode::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Resource)]
+pub struct DisplaySettings {
+    pub vsync: bool,
+    pub mode: DisplayMode,
+    pub resolution: UVec2,
+    /// Showing how much damage is done wherever it's done.
+    pub damage_numbers: bool,
+    /// Size of everything on screen, as a multiple of the usual, for high
+    /// resolution screens.
+    pub ui_scale: f32,
+    /// Size of text on its own, on top of `ui_scale`.
+    pub font_scale: f32,
+    /// Keeping things still for anyone bothered by motion: water without
+    /// waves, smaller explosions without flashes and text that doesn't slide
+    /// around. Anything that shakes or sweeps the camera should check too.
+    pub reduce_motion: bool,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            vsync: false,
+            mode: DisplayMode::default(),
+            resolution: UVec2::new(1312, 768),
+            damage_numbers: true,
+            ui_scale: 1.0,
+            font_scale: 1.0,
+            reduce_motion: false,
+        }
+    }
+}
+
+impl DisplaySettings {
+    fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        }
+    }
+
+    pub fn window(&self) -> Window {
+        Window {
+            title: "Castle".to_string(),
+            resolution: WindowResolution::new(self.resolution.x as f32, self.resolution.y as f32),
+            present_mode: self.present_mode(),
+            mode: self.mode.into(),
+            ..default()
+        }
+    }
+}
+
+pub fn reducing_motion(settings: Res<DisplaySettings>) -> bool {
+    settings.reduce_motion
+}
+
+/// A resolution given as `WIDTHxHEIGHT`, like `1280x720`.
+pub fn parse_resolution(value: &str) -> Result<UVec2, String> {
+    let (width, height) = value
+        .split_once(['x', 'X'])
+        .ok_or_else(|| format!("expected WIDTHxHEIGHT, not {}", value))?;
+    let parse = |value: &str| {
+        value
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|v| *v > 0)
+            .ok_or_else(|| format!("invalid size {}", value))
+    };
+
+    Ok(UVec2::new(parse(width)?, parse(height)?))
+}
+
+fn apply_display(
+    settings: Res<DisplaySettings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut ui_scale: ResMut<UiScale>,
+    egui: Option<ResMut<EguiSettings>>,
+) {
+    ui_scale.0 = settings.ui_scale;
+    if let Some(mut egui) = egui {
+        egui.scale_factor = settings.ui_scale;
+    }
+
+    for mut window in &mut windows {
+        window.present_mode = settings.present_mode();
+        window.mode = settings.mode.into();
+        if settings.mode == DisplayMode::Windowed {
+            window
+                .resolution
+                .set(settings.resolution.x as f32, settings.resolution.y as f32);
+        }
+    }
+
+    info!(?settings, "display");
+}
+
+fn display_keyboard(keys: Res<ButtonInput<KeyCode>>, mut settings: ResMut<DisplaySettings>) {
+    if keys.just_pressed(KeyCode::F8) {
+        settings.reduce_motion = !settings.reduce_motion;
+    }
+    if keys.just_pressed(KeyCode::F9) {
+        settings.damage_numbers = !settings.damage_numbers;
+    }
+    if keys.just_pressed(KeyCode::F10) {
+        settings.vsync = !settings.vsync;
+    }
+    if keys.just_pressed(KeyCode::F11) {
+        settings.mode = settings.mode.next();
+    }
+}
+
+/// Control with plus and minus changes the size of everything on screen,
+/// holding shift as well only the text.
+fn scale_keyboard(keys: Res<ButtonInput<KeyCode>>, mut settings: ResMut<DisplaySettings>) {
+    if !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    let direction = match (
+        keys.just_pressed(KeyCode::Equal),
+        keys.just_pressed(KeyCode::Minus),
+    ) {
+        (true, false) => 1.0,
+        (false, true) => -1.0,
+        _ => return,
+    };
+
+    if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        settings.font_scale = stepped(
+            settings.font_scale,
+            direction * FONT_SCALE_STEP,
+            FONT_SCALES,
+        );
+    } else {
+        settings.ui_scale = stepped(settings.ui_scale, direction * UI_SCALE_STEP, UI_SCALES);
+    }
+}
+
+fn stepped(value: f32, step: f32, (lowest, highest): (f32, f32)) -> f32 {
+    // Rounded so repeated steps land back on the same values.
+    ((value + step) * 100.)
+        .round()
+        .clamp(lowest * 100., highest * 100.)
+        / 100.
+}
+
+/// Font sizes as they were given, before `font_scale`, and as they were last
+/// set, to tell when text has been replaced since.
+#[derive(Component, Debug, Clone, PartialEq)]
+struct ScaledFont {
+    given: Vec<f32>,
+    applied: Vec<f32>,
+}
+
+impl ScaledFont {
+    /// Scaled from sizes found on text, which are those given unless they're
+    /// what was last applied.
+    fn rescale(previous: Option<&ScaledFont>, found: Vec<f32>, scale: f32) -> Self {
+        let given = match previous {
+            Some(previous) if previous.applied == found => previous.given.clone(),
+            _ => found,
+        };
+        let applied = given.iter().map(|size| size * scale).collect();
+
+        Self { given, applied }
+    }
+}
+
+fn scale_fonts(
+    mut commands: Commands,
+    mut texts: Query<(Entity, &mut Text, Option<&ScaledFont>)>,
+    settings: Res<DisplaySettings>,
+) {
+    for (entity, mut text, previous) in &mut texts {
+        if !text.is_changed() && previous.is_some() && !settings.is_changed() {
+            continue;
+        }
+
+        let found: Vec<f32> = text.sections.iter().map(|s| s.style.font_size).collect();
+        let scaled = ScaledFont::rescale(previous, found.clone(), settings.font_scale);
+        if scaled.applied != found {
+            for (section, size) in text.sections.iter_mut().zip(scaled.applied.iter()) {
+                section.style.font_size = *size;
+            }
+        }
+        if previous != Some(&scaled) {
+            commands.entity(entity).insert(scaled);
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use bevy::prelude::*;
+
+use crate::controls::{typing, Action, Controls};
+
+use super::{DisplayMode, DisplaySettings};
+
+/// Sizes offered for the window, from small laptops up.
+pub const RESOLUTIONS: [UVec2; 6] = [
+    UVec2::new(1280, 720),
+    UVec2::new(1312, 768),
+    UVec2::new(1600, 900),
+    UVec2::new(1920, 1080),
+    UVec2::new(2560, 1440),
+    UVec2::new(3840, 2160),
+];
+
+const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.2);
+const HOVERED_COLOR: Color = Color::rgb(0.25, 0.25, 0.35);
+
+/// A panel of display settings, shown and hidden with its own key, changing
+/// them as they're clicked.
+pub struct SettingsMenuPlugin;
+
+impl Plugin for SettingsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                toggle_settings.run_if(not(typing)),
+                settings_input,
+                refresh_labels.run_if(resource_changed::<DisplaySettings>),
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettingsButton {
+    Vsync,
+    Mode,
+    Resolution,
+    DamageNumbers,
+    ReduceMotion,
+}
+
+impl SettingsButton {
+    const ALL: [SettingsButton; 5] = [
+        SettingsButton::Vsync,
+        SettingsButton::Mode,
+        SettingsButton::Resolution,
+        SettingsButton::DamageNumbers,
+        SettingsButton::ReduceMotion,
+    ];
+
+    pub fn label(&self, settings: &DisplaySettings) -> String {
+        let on = |value: bool| if value { "on" } else { "off" };
+
+        match self {
+            SettingsButton::Vsync => format!("VSync: {}", on(settings.vsync)),
+            SettingsButton::Mode => format!("Window: {:?}", settings.mode),
+            SettingsButton::Resolution => format!(
+                "Resolution: {}x{}",
+                settings.resolution.x, settings.resolution.y
+            ),
+            SettingsButton::DamageNumbers => {
+                format!("Damage numbers: {}", on(settings.damage_numbers))
+            }
+            SettingsButton::ReduceMotion => {
+                format!("Reduce motion: {}", on(settings.reduce_motion))
+            }
+        }
+    }
+
+    pub fn change(&self, settings: &mut DisplaySettings) {
+        match self {
+            SettingsButton::Vsync => settings.vsync = !settings.vsync,
+            SettingsButton::Mode => settings.mode = settings.mode.next(),
+            SettingsButton::Resolution => {
+                settings.resolution = next_resolution(settings.resolution);
+                // Only windows have a size of their own.
+                settings.mode = DisplayMode::Windowed;
+            }
+            SettingsButton::DamageNumbers => settings.damage_numbers = !settings.damage_numbers,
+            SettingsButton::ReduceMotion => settings.reduce_motion = !settings.reduce_motion,
+        }
+    }
+}
+
+/// The next size up from the one given, back around to the smallest after the
+/// largest, sizes given on the command line included.
+pub fn next_resolution(current: UVec2) -> UVec2 {
+    let area = |size: &UVec2| size.x * size.y;
+
+    RESOLUTIONS
+        .iter()
+        .find(|size| area(size) > area(&current))
+        .copied()
+        .unwrap_or(RESOLUTIONS[0])
+}
+
+#[derive(Component)]
+struct SettingsPanel;
+
+#[derive(Component)]
+struct SettingsLabel(SettingsButton);
+
+fn toggle_settings(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    controls: Res<Controls>,
+    settings: Res<DisplaySettings>,
+    panels: Query<Entity, With<SettingsPanel>>,
+) {
+    if !controls.just_pressed(&keys, Action::Settings) {
+        return;
+    }
+
+    if !panels.is_empty() {
+        for entity in &panels {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    commands
+        .spawn((
+            Name::new("Settings"),
+            SettingsPanel,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    top: Val::Percent(20.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(6.),
+                        padding: UiRect::all(Val::Px(12.)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0., 0., 0., 0.7).into(),
+                    ..default()
+                })
+                .with_children(|panel| {
+                    panel.spawn(text("Settings".to_owned()));
+
+                    for button in SettingsButton::ALL {
+                        panel
+                            .spawn((
+                                button,
+                                ButtonBundle {
+                                    style: Style {
+                                        padding: UiRect::axes(Val::Px(12.), Val::Px(6.)),
+                                        ..default()
+                                    },
+                                    background_color: BUTTON_COLOR.into(),
+                                    ..default()
+                                },
+                            ))
+                            .with_children(|parent| {
+                                parent
+                                    .spawn((SettingsLabel(button), text(button.label(&settings))));
+                            });
+                    }
+                });
+        });
+}
+
+fn text(value: String) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font_size: 18.,
+            color: Color::WHITE,
+            ..default()
+        },
+    )
+}
+
+fn settings_input(
+    mut buttons: Query<(&Interaction, &SettingsButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut settings: ResMut<DisplaySettings>,
+) {
+    for (interaction, button, mut color) in &mut buttons {
+        match interaction {
+            Interaction::Pressed => {
+                button.change(&mut settings);
+                info!(?button, "display-setting-changed");
+            }
+            Interaction::Hovered => *color = HOVERED_COLOR.into(),
+            Interaction::None => *color = BUTTON_COLOR.into(),
+        }
+    }
+}
+
+fn refresh_labels(mut labels: Query<(&mut Text, &SettingsLabel)>, settings: Res<DisplaySettings>) {
+    for (mut text, label) in &mut labels {
+        text.sections[0].value = label.0.label(&settings);
+    }
+}
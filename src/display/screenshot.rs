@@ -0,0 +1,82 @@
+use std::{
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{prelude::*, render::view::screenshot::ScreenshotManager, window::PrimaryWindow};
+
+const SCREENSHOTS_PATH: &str = "screenshots";
+
+/// F12 saves what's on screen, holding shift leaves the interface out of it.
+pub struct ScreenshotPlugin;
+
+impl Plugin for ScreenshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HiddenUi>()
+            .add_systems(Update, (restore_ui, take_screenshot).chain());
+    }
+}
+
+/// Interface hidden for a screenshot along with how it was showing before,
+/// put back the frame after.
+#[derive(Debug, Default, Resource)]
+struct HiddenUi(Vec<(Entity, Visibility)>);
+
+/// Named for when it was taken, so they sort in order and never overwrite
+/// each other.
+fn file_name(since_epoch: Duration) -> String {
+    format!(
+        "screenshot-{}-{:03}.png",
+        since_epoch.as_secs(),
+        since_epoch.subsec_millis()
+    )
+}
+
+fn take_screenshot(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    mut hidden: ResMut<HiddenUi>,
+    mut interface: Query<(Entity, &mut Visibility), (With<Node>, Without<Parent>)>,
+    windows: Query<Entity, With<PrimaryWindow>>,
+) {
+    if !keys.just_pressed(KeyCode::F12) {
+        return;
+    }
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    if let Err(e) = std::fs::create_dir_all(SCREENSHOTS_PATH) {
+        warn!("error creating {}: {}", SCREENSHOTS_PATH, e);
+        return;
+    }
+
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let path = Path::new(SCREENSHOTS_PATH).join(file_name(since_epoch));
+
+    if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        for (entity, mut visibility) in interface.iter_mut() {
+            hidden.0.push((entity, *visibility));
+            *visibility = Visibility::Hidden;
+        }
+    }
+
+    match screenshots.save_screenshot_to_disk(window, &path) {
+        Ok(_) => info!(path = %path.display(), "screenshot"),
+        Err(e) => warn!("error taking screenshot: {}", e),
+    }
+}
+
+fn restore_ui(mut hidden: ResMut<HiddenUi>, mut visibilities: Query<&mut Visibility>) {
+    if hidden.0.is_empty() {
+        return;
+    }
+
+    for (entity, visibility) in hidden.0.drain(..) {
+        if let Ok(mut current) = visibilities.get_mut(entity) {
+            *current = visibility;
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use bevy::math::UVec2;
+
+use super::{
+    menu::{next_resolution, SettingsButton, RESOLUTIONS},
+    parse_resolution, stepped, DisplayMode, DisplaySettings, ScaledFont,
+};
+
+#[test]
+fn test_parse_resolution() {
+    assert_eq!(parse_resolution("1280x720"), Ok(UVec2::new(1280, 720)));
+    assert_eq!(parse_resolution("1920X1080"), Ok(UVec2::new(1920, 1080)));
+    assert_eq!(parse_resolution("800 x 600"), Ok(UVec2::new(800, 600)));
+}
+
+#[test]
+fn test_parse_resolution_rejects_nonsense() {
+    assert!(parse_resolution("1280").is_err());
+    assert!(parse_resolution("0x720").is_err());
+    assert!(parse_resolution("widex720").is_err());
+    assert!(parse_resolution("1280x-1").is_err());
+}
+
+#[test]
+fn test_scaling_steps_stay_within_range() {
+    assert_eq!(stepped(1.0, 0.25, (0.5, 3.0)), 1.25);
+    assert_eq!(stepped(0.5, -0.25, (0.5, 3.0)), 0.5);
+    assert_eq!(stepped(1.2, 0.1, (0.5, 2.5)), 1.3);
+}
+
+#[test]
+fn test_fonts_scale_from_the_sizes_given() {
+    let scaled = ScaledFont::rescale(None, vec![10., 20.], 1.5);
+    assert_eq!(scaled.applied, vec![15., 30.]);
+
+    // Left alone since, scaling again starts from what was given.
+    let again = ScaledFont::rescale(Some(&scaled), scaled.applied.clone(), 2.0);
+    assert_eq!(again.given, vec![10., 20.]);
+    assert_eq!(again.applied, vec![20., 40.]);
+
+    // Replaced with new text, that's what's been given now.
+    let replaced = ScaledFont::rescale(Some(&again), vec![18.], 2.0);
+    assert_eq!(replaced.given, vec![18.]);
+    assert_eq!(replaced.applied, vec![36.]);
+}
+
+#[test]
+fn test_resolutions_step_up_and_wrap_around() {
+    assert_eq!(next_resolution(RESOLUTIONS[0]), RESOLUTIONS[1]);
+    assert_eq!(
+        next_resolution(UVec2::new(1400, 800)),
+        UVec2::new(1600, 900)
+    );
+    assert_eq!(
+        next_resolution(RESOLUTIONS[RESOLUTIONS.len() - 1]),
+        RESOLUTIONS[0]
+    );
+}
+
+#[test]
+fn test_changing_resolution_from_the_menu_returns_to_a_window() {
+    let mut settings = DisplaySettings {
+        vsync: false,
+        mode: DisplayMode::Fullscreen,
+        resolution: RESOLUTIONS[0],
+        damage_numbers: true,
+        ui_scale: 1.0,
+        font_scale: 1.0,
+        reduce_motion: false,
+    };
+
+    SettingsButton::Resolution.change(&mut settings);
+    assert_eq!(settings.resolution, RESOLUTIONS[1]);
+    assert_eq!(settings.mode, DisplayMode::Windowed);
+
+    SettingsButton::Vsync.change(&mut settings);
+    assert_eq!(SettingsButton::Vsync.label(&settings), "VSync: on");
+}
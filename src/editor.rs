@@ -0,0 +1,214 @@
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::*;
+
+use crate::{
+    building::{
+        catalog::Gun, Cannon, ConstructionEvent, ConstructionSet, Structure, StructureLayers, Wall,
+    },
+    helpers::GamePlayLifetime,
+    model::{Activity, Player, Settings, TILE_SIZE},
+    scenario::Scenario,
+    terrain::{Sculpt, Terrain, TerrainEdited},
+};
+
+const SCENARIO_PATH: &str = "scenario.ron";
+const MAXIMUM_RADIUS: i32 = 8;
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Brush>()
+            .add_systems(OnEnter(Activity::Editing), start_editing)
+            .add_systems(OnExit(Activity::Editing), stop_editing)
+            .add_systems(
+                Update,
+                (
+                    editor_keyboard,
+                    hover,
+                    apply_brush.in_set(ConstructionSet::Input),
+                )
+                    .run_if(in_state(Activity::Editing)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tool {
+    Sculpt(Sculpt),
+    Wall,
+    Cannon(Gun),
+}
+
+#[derive(Debug, Resource)]
+pub struct Brush {
+    tool: Tool,
+    radius: i32,
+    player: Player,
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Self {
+            tool: Tool::Sculpt(Sculpt::Raise),
+            radius: 2,
+            player: Player::One,
+        }
+    }
+}
+
+impl Brush {
+    /// Structures are stamped one cell at a time.
+    fn radius(&self) -> i32 {
+        match self.tool {
+            Tool::Sculpt(_) => self.radius,
+            Tool::Wall | Tool::Cannon(_) => 0,
+        }
+    }
+}
+
+#[derive(Component)]
+struct BrushCursor;
+
+fn start_editing(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    brush: Res<Brush>,
+) {
+    info!(?brush, "editing");
+
+    commands.spawn((
+        Name::new("Brush"),
+        Pickable::IGNORE,
+        GamePlayLifetime,
+        BrushCursor,
+        PbrBundle {
+            mesh: meshes.add(Cylinder::new(TILE_SIZE / 2., 0.1)),
+            material: materials.add(StandardMaterial {
+                base_color: Color::rgba(1., 1., 1., 0.5),
+                alpha_mode: AlphaMode::Blend,
+                ..default()
+            }),
+            transform: Transform::from_translation(Vec3::Y),
+            ..default()
+        },
+    ));
+}
+
+fn stop_editing(mut commands: Commands, cursor: Query<Entity, With<BrushCursor>>) {
+    for entity in cursor.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn hover(
+    mut events: EventReader<Pointer<Move>>,
+    mut cursor: Query<&mut Transform, With<BrushCursor>>,
+    brush: Res<Brush>,
+    terrain: Query<&Terrain>,
+) {
+    let Some(terrain) = terrain.get_single().ok() else {
+        return;
+    };
+
+    for event in events.read() {
+        if let Some(survey) = event.event.hit.position.and_then(|p| terrain.survey(p)) {
+            let diameter = (brush.radius() * 2 + 1) as f32;
+            for mut transform in &mut cursor {
+                *transform = Transform::from_translation(survey.world())
+                    .with_scale(Vec3::new(diameter, 1., diameter));
+            }
+        }
+    }
+}
+
+fn apply_brush(
+    mut events: EventReader<Pointer<Click>>,
+    mut terrain: Query<&mut Terrain>,
+    mut edited: EventWriter<TerrainEdited>,
+    mut construction: EventWriter<ConstructionEvent>,
+    brush: Res<Brush>,
+) {
+    let Some(mut terrain) = terrain.get_single_mut().ok() else {
+        return;
+    };
+
+    for event in events.read() {
+        let Some(survey) = event.event.hit.position.and_then(|p| terrain.survey(p)) else {
+            continue;
+        };
+
+        let structure = match brush.tool {
+            Tool::Sculpt(sculpt) => {
+                let cells = terrain.sculpt(survey.location(), brush.radius, sculpt);
+                edited.send(TerrainEdited::new(cells));
+                None
+            }
+            Tool::Wall => Some(Structure::Wall(Wall::new(brush.player))),
+            Tool::Cannon(Gun::Cannon) => Some(Structure::Cannon(Cannon::new(brush.player))),
+            Tool::Cannon(Gun::Mortar) => Some(Structure::Cannon(Cannon::mortar(brush.player))),
+        };
+
+        if let Some(structure) = structure {
+            construction.send(ConstructionEvent::new(survey.location().into(), structure));
+        }
+    }
+}
+
+fn editor_keyboard(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut brush: ResMut<Brush>,
+    settings: Res<Settings>,
+    terrain: Query<&Terrain>,
+    structures: Res<StructureLayers>,
+) {
+    let tool = if keys.just_pressed(KeyCode::KeyZ) {
+        Some(Tool::Sculpt(Sculpt::Raise))
+    } else if keys.just_pressed(KeyCode::KeyX) {
+        Some(Tool::Sculpt(Sculpt::Lower))
+    } else if keys.just_pressed(KeyCode::KeyV) {
+        Some(Tool::Sculpt(Sculpt::Flatten))
+    } else if keys.just_pressed(KeyCode::KeyN) {
+        Some(Tool::Wall)
+    } else if keys.just_pressed(KeyCode::KeyM) {
+        // Again for a mortar, and again for a cannon.
+        match brush.tool {
+            Tool::Cannon(gun) => Some(Tool::Cannon(gun.next())),
+            _ => Some(Tool::Cannon(Gun::Cannon)),
+        }
+    } else {
+        None
+    };
+
+    if let Some(tool) = tool {
+        brush.tool = tool;
+        info!(?brush, "brush");
+    }
+    if keys.just_pressed(KeyCode::KeyP) {
+        brush.player = brush.player.next();
+        info!(?brush, "brush");
+    }
+    if keys.just_pressed(KeyCode::BracketLeft) {
+        brush.radius = (brush.radius - 1).max(0);
+        info!(?brush, "brush");
+    }
+    if keys.just_pressed(KeyCode::BracketRight) {
+        brush.radius = (brush.radius + 1).min(MAXIMUM_RADIUS);
+        info!(?brush, "brush");
+    }
+
+    let control = keys.pressed(KeyCode::ControlLeft) || keys.pressed(KeyCode::ControlRight);
+    if control && keys.just_pressed(KeyCode::KeyS) {
+        let Some(terrain) = terrain.get_single().ok() else {
+            return;
+        };
+
+        let scenario = Scenario::capture(&settings, terrain, &structures);
+
+        match scenario.save(SCENARIO_PATH) {
+            Ok(_) => info!("saved {}", SCENARIO_PATH),
+            Err(e) => warn!("error saving scenario: {}", e),
+        }
+    }
+}
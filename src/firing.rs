@@ -1,35 +1,166 @@
 use bevy::math::primitives;
 use bevy::prelude::*;
 use bevy_hanabi::prelude::*;
-use bevy_hanabi::{EffectAsset, Gradient};
+use bevy_hanabi::EffectAsset;
 use bevy_mod_picking::prelude::*;
 use bevy_rapier3d::prelude::*;
+use rand::Rng;
 
-use crate::helpers::GamePlayLifetime;
-use crate::terrain::Terrain;
-use crate::{building::Cannon, helpers};
+use crate::building::{catalog, Cannon, Flooded, Operational, Wall};
+use crate::buoyancy::{Buoyancy, Submerged};
+use crate::camera::CameraMode;
+use crate::collision::{Collides, STRUCTURES_GROUP, TERRAIN_GROUP};
+use crate::controls::{typing, Action, Controls};
+use crate::display::DisplaySettings;
+use crate::helpers::{self, GamePlayLifetime, PlayerColor};
+use crate::loading::RequiredAssets;
+use crate::network::{self, following, Host};
+use crate::profile::aiming_manually;
+use crate::terrain::{Terrain, TerrainChunk};
+use crate::weather::Weather;
 
 use super::model::*;
+use castle::ballistics;
+
+mod aiming;
+mod budget;
+mod effects;
+mod reticle;
+mod shadows;
+mod sighting;
+
+use budget::{Budgeted, EffectsBudget};
+use effects::{ExplosionKind, ExplosionResources};
 
 pub struct FiringPlugin;
 
 impl Plugin for FiringPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<ExplosionEvent>()
+        app.register_type::<RoundShot>()
+            .add_event::<ExplosionEvent>()
+            .add_event::<Detonation>()
+            .add_event::<Bombardment>()
+            .add_event::<ShotFired>()
+            .init_resource::<EffectsBudget>()
             .add_systems(Startup, setup)
-            .add_systems(Update, pick_target.run_if(in_state(Activity::Firing)))
-            .add_systems(Update, check_collisions.run_if(in_state(Activity::Firing)));
+            .add_systems(OnEnter(AppState::Game), prime_explosions)
+            .add_event::<TargetPicked>()
+            .add_systems(
+                Update,
+                (
+                    pick_target
+                        .run_if(not(following))
+                        .run_if(not(typing))
+                        .run_if(not(aiming_manually))
+                        .run_if(not(in_state(CameraMode::FirstPerson))),
+                    sighting::fire_sighted
+                        .run_if(not(following))
+                        .run_if(not(typing))
+                        .run_if(in_state(CameraMode::FirstPerson)),
+                    (aiming::adjust_elevation, aiming::drag_aim)
+                        .run_if(not(following))
+                        .run_if(not(typing))
+                        .run_if(aiming_manually),
+                    order_cannons,
+                )
+                    .chain()
+                    .run_if(in_state(Activity::Firing)),
+            )
+            .init_resource::<aiming::ManualAim>()
+            .init_resource::<sighting::Sighting>()
+            .add_systems(
+                Update,
+                sighting::follow_aiming.run_if(in_state(AppState::Game)),
+            )
+            .add_systems(OnExit(AppState::Game), sighting::dismount)
+            .add_systems(OnEnter(CameraMode::FirstPerson), sighting::begin_sighting)
+            .add_systems(OnExit(CameraMode::FirstPerson), sighting::end_sighting)
+            .add_systems(
+                Update,
+                sighting::mount_camera
+                    .run_if(in_state(CameraMode::FirstPerson))
+                    .run_if(not(typing))
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                Update,
+                aiming::toggle_aiming
+                    .run_if(in_state(AppState::Game))
+                    .run_if(not(typing)),
+            )
+            .add_systems(OnEnter(Activity::Firing), aiming::spawn_elevation_slider)
+            .add_systems(OnExit(Activity::Firing), aiming::despawn_elevation_slider)
+            .add_systems(
+                Update,
+                aiming::update_elevation_slider.run_if(in_state(Activity::Firing)),
+            )
+            .add_systems(Update, fire_cannons.run_if(in_state(AppState::Game)))
+            .add_systems(Update, bombard.run_if(in_state(Activity::Firing)))
+            .add_systems(
+                Update,
+                (check_collisions, burn_fuses, detonate)
+                    .chain()
+                    .run_if(in_state(Activity::Firing)),
+            )
+            .add_systems(Update, knockback.after(detonate))
+            .init_gizmo_group::<LandingGizmos>()
+            .add_systems(Update, reload_cannons.run_if(in_state(AppState::Game)))
+            .add_systems(OnEnter(Phase::Target(Player::One)), restock_cannons)
+            .add_systems(OnEnter(Activity::Firing), reticle::spawn_reticle)
+            .add_systems(OnExit(Activity::Firing), reticle::despawn_reticle)
+            .init_gizmo_group::<reticle::TrajectoryGizmos>()
+            .add_systems(
+                Update,
+                (
+                    reticle::move_reticle.run_if(not(aiming_manually)),
+                    reticle::draw_trajectory,
+                    reticle::draw_traverse,
+                )
+                    .chain()
+                    .run_if(in_state(Activity::Firing)),
+            )
+            .add_systems(Update, draw_landings.run_if(in_state(AppState::Game)))
+            .add_systems(
+                Update,
+                (stray_projectiles, sink_projectiles, park_projectiles)
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                Update,
+                (shadows::attach_shadows, shadows::move_shadows)
+                    .chain()
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                Update,
+                budget::shadowed_lights
+                    .after(fire_cannons)
+                    .after(detonate)
+                    .run_if(in_state(AppState::Game)),
+            );
     }
 }
 
-pub trait Projectile {}
+pub trait Projectile {
+    /// How this goes off when it doesn't land on something more interesting.
+    fn blast(&self) -> ExplosionKind;
+}
 
-#[derive(Component, Clone, Debug)]
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component)]
 pub struct RoundShot {
     target: Vec3,
+    /// Fired by a ship rather than one of the players' cannons.
+    bombardment: bool,
+    /// Times the usual size of the blast where it lands.
+    blast_scale: f32,
 }
 
-impl Projectile for RoundShot {}
+impl Projectile for RoundShot {
+    fn blast(&self) -> ExplosionKind {
+        ExplosionKind::SmallBlast
+    }
+}
 
 #[derive(Debug, Clone)]
 struct PickedCoordinates {
@@ -48,9 +179,16 @@ fn get_picked_coordinates(mut events: EventReader<Pointer<Click>>) -> Option<Pic
     None
 }
 
+#[derive(Component)]
+struct MuzzleFlash;
+
 #[derive(Bundle)]
 struct MuzzleFlashBundle {
     name: Name,
+    flash: MuzzleFlash,
+    budgeted: Budgeted,
+    pooled: helpers::Pooled,
+    lifetime: GamePlayLifetime,
     expiration: helpers::Expires,
     light: PointLightBundle,
 }
@@ -59,6 +197,10 @@ impl MuzzleFlashBundle {
     fn new(position: Vec3) -> Self {
         Self {
             name: Name::new("Muzzle:Flash"),
+            flash: MuzzleFlash,
+            budgeted: Budgeted,
+            pooled: helpers::Pooled::default(),
+            lifetime: GamePlayLifetime,
             expiration: helpers::Expires::after(0.05),
             light: PointLightBundle {
                 transform: Transform::from_translation(position + Vec3::new(0., 1., 0.)),
@@ -83,11 +225,17 @@ struct RoundShotBundle {
     mass: ColliderMassProperties,
     body: RigidBody,
     lifetime: GamePlayLifetime,
+    pooled: helpers::Pooled,
+    expiration: helpers::Expires,
     active_events: ActiveEvents,
     projectile: RoundShot,
     player: Player,
     collider: Collider,
+    collision_groups: CollisionGroups,
+    restitution: Restitution,
+    friction: Friction,
     velocity: Velocity,
+    buoyancy: Buoyancy,
 }
 
 impl RoundShotBundle {
@@ -115,24 +263,114 @@ impl RoundShotBundle {
             mass: ColliderMassProperties::Mass(mass),
             body: RigidBody::Dynamic,
             lifetime: GamePlayLifetime,
+            pooled: helpers::Pooled::default(),
+            expiration: helpers::Expires::after(PROJECTILE_LIFETIME),
             active_events: ActiveEvents::COLLISION_EVENTS,
-            projectile: RoundShot { target },
+            projectile: RoundShot {
+                target,
+                bombardment: false,
+                blast_scale: 1.0,
+            },
             player,
             collider: Collider::ball(ROUND_SHOT_DIAMETER / 2.),
+            collision_groups: Collides::Projectiles.groups(),
+            restitution: Restitution::default(),
+            friction: Friction::default(),
+            buoyancy: Buoyancy::Sinks,
             velocity: Velocity {
                 linvel: velocity,
                 angvel: Vec3::ZERO,
             },
         }
     }
+
+    /// Fused shells bounce and roll along the ground before going off.
+    fn fused(self, fused: bool) -> Self {
+        if !fused {
+            return self;
+        }
+
+        Self {
+            restitution: Restitution::coefficient(0.4),
+            friction: Friction::coefficient(0.8),
+            ..self
+        }
+    }
+
+    fn blast_scale(self, blast_scale: f32) -> Self {
+        Self {
+            projectile: RoundShot {
+                blast_scale,
+                ..self.projectile
+            },
+            ..self
+        }
+    }
+
+    fn bombardment(self, bombardment: bool) -> Self {
+        Self {
+            projectile: RoundShot {
+                bombardment,
+                ..self.projectile
+            },
+            ..self
+        }
+    }
+}
+
+/// How far from the target shots in a volley may land.
+const VOLLEY_SPREAD: f32 = TILE_SIZE;
+
+/// Longest a cannon in a volley waits before firing.
+const VOLLEY_STAGGER: f32 = 0.6;
+
+/// Seconds a cannon takes to turn a degree beyond its traverse before firing.
+const TRAVERSE_SECONDS_PER_DEGREE: f32 = 0.02;
+
+/// A player choosing where to fire, by clicking or otherwise.
+#[derive(Clone, Debug)]
+pub struct TargetPicked {
+    player: Player,
+    target: Vec3,
+    volley: bool,
+    /// The cannon and velocity of a shot aimed by hand.
+    aimed: Option<(Entity, Vec3)>,
+}
+
+impl Event for TargetPicked {}
+
+impl TargetPicked {
+    pub fn new(player: Player, target: Vec3, volley: bool) -> Self {
+        Self {
+            player,
+            target,
+            volley,
+            aimed: None,
+        }
+    }
+
+    /// Fired from `cannon` at exactly `velocity`, rather than from whichever
+    /// cannon is best placed to reach the target.
+    pub fn aimed(self, cannon: Entity, velocity: Vec3) -> Self {
+        Self {
+            aimed: Some((cannon, velocity)),
+            ..self
+        }
+    }
+
+    pub fn player(&self) -> Player {
+        self.player
+    }
 }
 
 fn pick_target(
     events: EventReader<Pointer<Click>>,
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    mut cannons: Query<(Entity, &mut Transform, &Player), With<Cannon>>,
+    mut picks: EventWriter<TargetPicked>,
+    keys: Res<ButtonInput<KeyCode>>,
+    controls: Res<Controls>,
+    phase: Res<State<Phase>>,
+    settings: Res<Settings>,
+    host: Option<Res<Host>>,
 ) {
     let picked: Option<PickedCoordinates> = get_picked_coordinates(events);
     if picked.is_none() {
@@ -141,225 +379,764 @@ fn pick_target(
 
     let picked = picked.expect("No picked");
 
-    let mesh: Handle<Mesh> = meshes.add(primitives::Sphere::default());
+    let firing = phase.get().player();
+    if settings.computer(&firing) || network::remote(host.as_deref(), &firing) {
+        return;
+    }
 
-    let black = materials.add(StandardMaterial {
-        base_color: Color::BLACK,
-        perceptual_roughness: 0.3,
-        ..default()
-    });
+    picks.send(TargetPicked::new(
+        firing,
+        picked.transform.translation,
+        controls.pressed(&keys, Action::Volley),
+    ));
+}
 
-    let target = picked.transform.translation;
+#[allow(clippy::too_many_arguments)]
+fn order_cannons(
+    mut picks: EventReader<TargetPicked>,
+    mut commands: Commands,
+    cannons: Query<
+        (Entity, &Transform, &Player, &Cannon, Option<&Fired>),
+        (With<Operational>, Without<Reloading>, Without<Flooded>),
+    >,
+    walls: Query<&Player, With<Wall>>,
+    terrain: Query<&Terrain>,
+    context: Res<RapierContext>,
+    weather: Res<Weather>,
+    rules: Res<Rules>,
+    mut rng: ResMut<GameRng>,
+) {
+    for picked in picks.read() {
+        let TargetPicked {
+            player: firing,
+            target,
+            volley,
+            aimed,
+        } = picked.clone();
+        let volley = volley && rules.volleys;
 
-    match cannons.iter_mut().next() {
-        Some((_e, mut cannon, player)) => {
-            let zero_y = Vec3::new(1., 0., 1.);
-            let direction = (target - cannon.translation) * zero_y;
-            let distance = direction.length();
-            let direction = direction.normalize();
+        // Always fire at the middle of a cell, same as the reticle shows.
+        let target = match terrain.get_single().ok().and_then(|t| t.survey(target)) {
+            Some(survey) => survey.world(),
+            None => target,
+        };
 
-            if distance < 1. {
-                info!(%distance, "safety engaged");
-                return;
+        // Shots aimed by hand leave the cannon they were aimed from, or not at all.
+        if let Some((entity, velocity)) = aimed {
+            let Ok((entity, transform, player, cannon, fired)) = cannons.get(entity) else {
+                info!(%target, player = ?firing, "aimed cannon not ready");
+                continue;
+            };
+            let fired = fired.map_or(0, |fired| fired.0);
+            if *player != firing
+                || !rules.loaded(fired)
+                || !cannon
+                    .entry()
+                    .ordnance
+                    .in_range(transform.translation, target)
+            {
+                info!(%target, player = ?firing, "aimed cannon can't fire");
+                continue;
             }
 
-            let distance = distance - TILE_SIZE / 2.;
-            let desired_time_of_flight =
-                (distance / MAXIMUM_HORIZONTAL_DISTANCE) + MINIMUM_FLIGHT_TIME;
-            // Vertical velocity to reach apex half way through.
-            let vertical_velocity = GRAVITY * (desired_time_of_flight / 2.0);
-            // Gotta go `distance` so however long that will take.
-            let horizontal_velocity = distance / desired_time_of_flight;
+            info!(%target, player = ?firing, %velocity, "ordered aimed");
 
-            let mass = 20.0;
+            let delay = ballistics::beyond_traverse(transform.translation, target, cannon.facing())
+                * TRAVERSE_SECONDS_PER_DEGREE;
+            commands.entity(entity).insert((
+                FireOrder::new(target, delay).aimed(velocity),
+                Reloading(Timer::from_seconds(cannon.entry().reload, TimerMode::Once)),
+                Fired(fired + 1),
+            ));
+            continue;
+        }
 
-            // Final velocity is horizontal plus vertical.
-            let velocity = (direction * horizontal_velocity) + Vec3::new(0., vertical_velocity, 0.);
+        // The player's loaded cannons that can reach, those already facing the
+        // target first and then the closest.
+        let mut ready: Vec<_> = cannons
+            .iter()
+            .filter(|(_, transform, player, cannon, fired)| {
+                **player == firing
+                    && rules.loaded(fired.map_or(0, |fired| fired.0))
+                    && cannon
+                        .entry()
+                        .ordnance
+                        .in_range(transform.translation, target)
+            })
+            .filter(|(entity, transform, player, cannon, _)| {
+                // Mortars lob their shots over anything in the way.
+                let entry = cannon.entry();
+                let clear = !rules.line_of_sight
+                    || entry.indirect
+                    || trajectory(transform.translation, target, &entry.ordnance, &weather)
+                        .is_some_and(|path| !obstructed(&context, &path, player, &walls));
+                if !clear {
+                    debug!(?entity, %target, "obstructed");
+                }
+                clear
+            })
+            .collect();
+        let turning = |transform: &Transform, cannon: &Cannon| {
+            ballistics::beyond_traverse(transform.translation, target, cannon.facing())
+        };
+        ready.sort_by(|(_, a, _, ac, _), (_, b, _, bc, _)| {
+            let facing = (turning(a, ac) > 0.0).cmp(&(turning(b, bc) > 0.0));
+            let a = a.translation.distance_squared(target);
+            let b = b.translation.distance_squared(target);
+            facing.then(a.total_cmp(&b))
+        });
 
-            // This may need an offset to account for the mesh.
-            // TODO Animate?
-            let aim_angle = direction.angle_between(Vec3::new(-1., 0., 0.));
-            cannon.rotation = Quat::from_rotation_y(aim_angle);
+        if ready.is_empty() {
+            info!(%target, player = ?firing, "no cannons in range");
+            continue;
+        }
 
-            let vertical_offset =
-                Vec3::new(0., (WALL_HEIGHT / 2.0) + (ROUND_SHOT_DIAMETER / 2.0), 0.);
-            let initial = cannon.translation + vertical_offset;
+        if !volley {
+            ready.truncate(1);
+        }
 
-            info!(%distance, %velocity, %initial, ?player, "firing");
+        info!(%target, player = ?firing, cannons = ready.len(), volley, "ordered");
 
-            commands.spawn(MuzzleFlashBundle::new(initial));
+        let rng = rng.rng();
+        for (entity, transform, _, cannon, fired) in ready {
+            // Cannons facing away have to turn before they can fire.
+            let delay = turning(transform, cannon) * TRAVERSE_SECONDS_PER_DEGREE;
+            let order = if volley {
+                let spread = Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+                let spread = spread.clamp_length_max(1.0) * VOLLEY_SPREAD;
+                FireOrder::new(
+                    target + Vec3::new(spread.x, 0., spread.y),
+                    delay + rng.gen_range(0.0..VOLLEY_STAGGER),
+                )
+            } else {
+                FireOrder::new(target, delay)
+            };
 
-            commands.spawn(RoundShotBundle::new(
-                initial,
-                target,
-                velocity,
-                mass,
-                player.clone(),
-                mesh,
-                black,
+            commands.entity(entity).insert((
+                order,
+                Reloading(Timer::from_seconds(cannon.entry().reload, TimerMode::Once)),
+                Fired(fired.map_or(0, |fired| fired.0) + 1),
+            ));
+        }
+    }
+}
+
+/// Where shots leave a cannon.
+fn muzzle(cannon: Vec3) -> Vec3 {
+    cannon + Vec3::new(0., (WALL_HEIGHT / 2.0) + (ROUND_SHOT_DIAMETER / 2.0), 0.)
+}
+
+/// Number of segments trajectories are checked and drawn with.
+const TRAJECTORY_STEPS: usize = 24;
+
+/// Shots may come down through anything this close to their target.
+const TRAJECTORY_CLEARANCE: f32 = 1.5 * TILE_SIZE;
+
+/// The arc a shot fired from a cannon at a target would fly, wind included.
+fn trajectory(
+    cannon: Vec3,
+    target: Vec3,
+    ordnance: &ballistics::Ordnance,
+    weather: &Weather,
+) -> Option<Vec<Vec3>> {
+    let launch = ordnance.launch(cannon, target)?;
+    Some(ballistics::path(
+        muzzle(cannon),
+        launch.velocity + weather.drift(),
+        launch.time_of_flight,
+        TRAJECTORY_STEPS,
+    ))
+}
+
+/// Whether the terrain or any of the player's own walls are in the way along a
+/// trajectory. Other players' walls are what's being fired at.
+fn obstructed(
+    context: &RapierContext,
+    path: &[Vec3],
+    player: &Player,
+    walls: &Query<&Player, With<Wall>>,
+) -> bool {
+    let Some(end) = path.last() else {
+        return false;
+    };
+
+    let blocking = |entity: Entity| walls.get(entity).map_or(true, |owner| owner == player);
+    let filter = QueryFilter::new()
+        .groups(CollisionGroups::new(
+            Group::ALL,
+            TERRAIN_GROUP | STRUCTURES_GROUP,
+        ))
+        .predicate(&blocking);
+
+    path.windows(2)
+        .filter(|segment| segment[1].xz().distance(end.xz()) > TRAJECTORY_CLEARANCE)
+        .any(|segment| {
+            let ray = segment[1] - segment[0];
+            context
+                .cast_ray(segment[0], ray.normalize(), ray.length(), true, filter)
+                .is_some()
+        })
+}
+
+/// Where a cannon has been told to fire, once it's done waiting.
+#[derive(Component)]
+struct FireOrder {
+    target: Vec3,
+    /// How fast and which way to fire when aimed by hand.
+    velocity: Option<Vec3>,
+    delay: Timer,
+}
+
+impl FireOrder {
+    fn new(target: Vec3, delay: f32) -> Self {
+        Self {
+            target,
+            velocity: None,
+            delay: Timer::from_seconds(delay, TimerMode::Once),
+        }
+    }
+
+    fn aimed(self, velocity: Vec3) -> Self {
+        Self {
+            velocity: Some(velocity),
+            ..self
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn fire_cannons(
+    mut commands: Commands,
+    mut cannons: Query<(Entity, &mut Transform, &Player, &Cannon, &mut FireOrder)>,
+    mut flashes: Query<(Entity, &mut helpers::Pooled), (With<MuzzleFlash>, Without<RoundShot>)>,
+    mut shots: Query<(Entity, &mut helpers::Pooled), (With<RoundShot>, Without<MuzzleFlash>)>,
+    mut fired: EventWriter<ShotFired>,
+    resources: Res<FiringResources>,
+    weather: Res<Weather>,
+    rules: Res<Rules>,
+    timer: Res<Time>,
+) {
+    for (entity, mut transform, player, cannon, mut order) in &mut cannons {
+        if !order.delay.tick(timer.delta()).finished() {
+            continue;
+        }
+
+        commands.entity(entity).remove::<FireOrder>();
+
+        let Some(direction) = discharge(
+            &mut commands,
+            &mut flashes,
+            &mut shots,
+            &resources,
+            &weather,
+            &rules,
+            cannon.entry(),
+            muzzle(transform.translation),
+            order.target,
+            order.velocity,
+            player.clone(),
+            false,
+        ) else {
+            continue;
+        };
+
+        fired.send(ShotFired::new(
+            muzzle(transform.translation),
+            order.target,
+            player.clone(),
+        ));
+
+        // This may need an offset to account for the mesh.
+        // TODO Animate?
+        let aim_angle = direction.angle_between(Vec3::new(-1., 0., 0.));
+        transform.rotation = Quat::from_rotation_y(aim_angle);
+    }
+}
+
+/// Ships, or anything else that isn't one of the players' cannons, firing on
+/// a player's castle.
+#[derive(Clone, Debug)]
+pub struct Bombardment {
+    from: Vec3,
+    target: Vec3,
+    player: Player,
+}
+
+impl Event for Bombardment {}
+
+impl Bombardment {
+    /// Shots are fired on behalf of `player`, the target's opponent.
+    pub fn new(from: Vec3, target: Vec3, player: Player) -> Self {
+        Self {
+            from,
+            target,
+            player,
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn bombard(
+    mut commands: Commands,
+    mut bombardments: EventReader<Bombardment>,
+    mut flashes: Query<(Entity, &mut helpers::Pooled), (With<MuzzleFlash>, Without<RoundShot>)>,
+    mut shots: Query<(Entity, &mut helpers::Pooled), (With<RoundShot>, Without<MuzzleFlash>)>,
+    mut fired: EventWriter<ShotFired>,
+    resources: Res<FiringResources>,
+    weather: Res<Weather>,
+    rules: Res<Rules>,
+) {
+    for bombardment in bombardments.read() {
+        let direction = discharge(
+            &mut commands,
+            &mut flashes,
+            &mut shots,
+            &resources,
+            &weather,
+            &rules,
+            &catalog::CANNON,
+            bombardment.from,
+            bombardment.target,
+            None,
+            bombardment.player.clone(),
+            true,
+        );
+
+        if direction.is_some() {
+            fired.send(ShotFired::new(
+                bombardment.from,
+                bombardment.target,
+                bombardment.player.clone(),
             ));
         }
-        None => warn!("no cannons"),
+    }
+}
+
+/// A round shot leaving a cannon or a ship, aimed at `target`.
+#[derive(Clone, Debug)]
+pub struct ShotFired {
+    from: Vec3,
+    target: Vec3,
+    player: Player,
+}
+
+impl Event for ShotFired {}
+
+impl ShotFired {
+    pub fn new(from: Vec3, target: Vec3, player: Player) -> Self {
+        Self {
+            from,
+            target,
+            player,
+        }
+    }
+
+    pub fn origin(&self) -> Vec3 {
+        self.from
+    }
+
+    pub fn target(&self) -> Vec3 {
+        self.target
+    }
+
+    pub fn player(&self) -> Player {
+        self.player
+    }
+}
+
+/// Flash and round shot leaving `initial` for `target` as fired from the
+/// `gun`, at `aimed` when aimed by hand, reusing pooled ones when possible,
+/// from a ship when `bombardment`. Returns the direction fired in or None when
+/// the target is out of reach.
+#[allow(clippy::too_many_arguments)]
+fn discharge(
+    commands: &mut Commands,
+    flashes: &mut Query<(Entity, &mut helpers::Pooled), (With<MuzzleFlash>, Without<RoundShot>)>,
+    shots: &mut Query<(Entity, &mut helpers::Pooled), (With<RoundShot>, Without<MuzzleFlash>)>,
+    resources: &FiringResources,
+    weather: &Weather,
+    rules: &Rules,
+    gun: &catalog::Entry,
+    initial: Vec3,
+    target: Vec3,
+    aimed: Option<Vec3>,
+    player: Player,
+    bombardment: bool,
+) -> Option<Vec3> {
+    let launch = match aimed {
+        Some(velocity) => ballistics::Launch::aimed(initial, target, velocity),
+        None => gun.ordnance.launch(initial, target),
+    };
+    let Some(ballistics::Launch {
+        direction,
+        distance,
+        velocity,
+        time_of_flight,
+    }) = launch
+    else {
+        info!(%target, "safety engaged");
+        return None;
+    };
+
+    let mass = 20.0;
+
+    // The wind pushes shots off course along with where they'll land.
+    let velocity = velocity + weather.drift();
+    let target = target + weather.drift() * time_of_flight;
+
+    info!(%distance, %velocity, %initial, ?player, "firing");
+
+    let flash = MuzzleFlashBundle::new(initial);
+    match flashes.iter_mut().find(|(_, pooled)| pooled.idle()) {
+        Some((entity, mut pooled)) => {
+            pooled.take();
+            commands.entity(entity).insert((
+                flash.light.transform,
+                flash.expiration,
+                Visibility::Visible,
+            ));
+        }
+        None => {
+            commands.spawn(flash);
+        }
+    }
+
+    let shot = RoundShotBundle::new(
+        initial,
+        target,
+        velocity,
+        mass,
+        player,
+        resources.shot_mesh.clone(),
+        resources.shot_material.clone(),
+    )
+    .fused(rules.fused_shells)
+    .blast_scale(gun.blast)
+    .bombardment(bombardment);
+    match shots.iter_mut().find(|(_, pooled)| pooled.idle()) {
+        Some((entity, mut pooled)) => {
+            pooled.take();
+            commands
+                .entity(entity)
+                .remove::<(RigidBodyDisabled, ColliderDisabled, Fuse, Submerged)>()
+                .insert((
+                    shot.pbr.transform,
+                    shot.projectile,
+                    shot.player,
+                    shot.velocity,
+                    shot.expiration,
+                    shot.restitution,
+                    shot.friction,
+                    Visibility::Visible,
+                ));
+        }
+        None => {
+            commands.spawn(shot);
+        }
+    }
+
+    Some(direction)
+}
+
+/// Cannons that have fired and can't again until this runs out.
+#[derive(Component)]
+struct Reloading(Timer);
+
+/// Shots a cannon has been ordered to fire this round, for limiting ammo.
+#[derive(Component)]
+struct Fired(u32);
+
+/// Every round's bombardment begins with full ammo.
+fn restock_cannons(mut commands: Commands, cannons: Query<Entity, With<Fired>>) {
+    for entity in &cannons {
+        commands.entity(entity).remove::<Fired>();
+    }
+}
+
+fn reload_cannons(
+    mut commands: Commands,
+    mut cannons: Query<(Entity, &mut Reloading)>,
+    timer: Res<Time>,
+) {
+    for (entity, mut reloading) in &mut cannons {
+        if reloading.0.tick(timer.delta()).just_finished() {
+            commands.entity(entity).remove::<Reloading>();
+        }
+    }
+}
+
+#[derive(Default, Reflect, GizmoConfigGroup)]
+struct LandingGizmos;
+
+/// Rings where shots in flight are going to come down, so they can be seen
+/// coming. These follow the shots rather than where they were aimed, so are
+/// thrown off by anything that pushes them around along the way.
+fn draw_landings(
+    mut gizmos: Gizmos<LandingGizmos>,
+    projectiles: Query<(&Transform, &Velocity, &Player, &helpers::Pooled), With<RoundShot>>,
+    terrain: Query<&Terrain>,
+) {
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    for (transform, velocity, player, pooled) in &projectiles {
+        if pooled.idle() {
+            continue;
+        }
+
+        if let Some(landing) = ballistics::impact(transform.translation, velocity.linvel, |p| {
+            terrain.surface_height(p)
+        }) {
+            gizmos.circle(
+                landing + Vec3::Y * 0.05,
+                Direction3d::Y,
+                TILE_SIZE / 3.,
+                player.color(),
+            );
+        }
+    }
+}
+
+/// Longest a projectile can be in the air, well beyond any real flight.
+const PROJECTILE_LIFETIME: f32 = 15.0;
+
+/// Projectiles below this have gone through the ground.
+const KILL_PLANE: f32 = -5.0;
+
+/// How long a shot that's gone into the water takes to sink out of sight.
+const SINK_SECONDS: f32 = 2.0;
+
+/// How far past the edge of the map projectiles are allowed to go.
+const OUT_OF_BOUNDS_MARGIN: f32 = 4.0 * TILE_SIZE;
+
+/// Shots that missed everything go back to the pool, those that stay in bounds
+/// are eventually caught by their expiration instead.
+fn stray_projectiles(
+    mut commands: Commands,
+    mut projectiles: Query<(Entity, &Transform, &mut helpers::Pooled), With<RoundShot>>,
+    terrain: Query<&Terrain>,
+) {
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    let edge = terrain.bounds() / 2.0 + OUT_OF_BOUNDS_MARGIN;
+
+    for (entity, transform, mut pooled) in &mut projectiles {
+        if pooled.idle() {
+            continue;
+        }
+
+        let position = transform.translation;
+        let below = position.y < KILL_PLANE;
+        let outside = position.xz().abs().cmpgt(edge).any();
+        if below || outside {
+            debug!(%position, below, outside, "projectile-stray");
+            pooled.release(&mut commands.entity(entity));
+        }
+    }
+}
+
+/// Shots that land in the water go under rather than off, sinking through the
+/// bottom and back to the pool.
+fn sink_projectiles(
+    mut commands: Commands,
+    projectiles: Query<(Entity, &Transform, &helpers::Pooled), (With<RoundShot>, Added<Submerged>)>,
+) {
+    for (entity, transform, pooled) in &projectiles {
+        if pooled.idle() {
+            continue;
+        }
+
+        info!(position = %transform.translation, "projectile-sunk");
+        commands
+            .entity(entity)
+            .remove::<Fuse>()
+            .insert((ColliderDisabled, helpers::Expires::after(SINK_SECONDS)));
+    }
+}
+
+/// Idle projectiles stop taking part in the simulation, however they got back
+/// to the pool.
+fn park_projectiles(
+    mut commands: Commands,
+    projectiles: Query<(Entity, &helpers::Pooled), (With<RoundShot>, Without<RigidBodyDisabled>)>,
+) {
+    for (entity, pooled) in &projectiles {
+        if pooled.idle() {
+            commands
+                .entity(entity)
+                .insert((RigidBodyDisabled, ColliderDisabled));
+        }
     }
 }
 
 #[derive(Resource)]
-struct ExplosionResources {
-    effect: Handle<EffectAsset>,
+struct FiringResources {
+    shot_mesh: Handle<Mesh>,
+    shot_material: Handle<StandardMaterial>,
+    shadow_mesh: Handle<Mesh>,
+    shadow_material: Handle<StandardMaterial>,
+    reticle_mesh: Handle<Mesh>,
+    reticle_valid: Handle<StandardMaterial>,
+    reticle_invalid: Handle<StandardMaterial>,
 }
 
+/// The parts of an explosion, which are pooled together as one. Rigs are only
+/// ever reused for the same kind of explosion.
+#[derive(Component)]
+struct ExplosionRig(ExplosionKind);
+
+#[derive(Component)]
+struct ExplosionBurst;
+
+#[derive(Component)]
+struct ExplosionLight;
+
 fn setup(
     mut commands: Commands,
     mut effects: ResMut<Assets<EffectAsset>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut required: ResMut<RequiredAssets>,
     asset_server: ResMut<AssetServer>,
 ) {
-    let mut colors = Gradient::new();
-    colors.add_key(0.0, Vec4::new(4.0, 4.0, 4.0, 1.0));
-    colors.add_key(0.1, Vec4::new(4.0, 4.0, 0.0, 1.0));
-    colors.add_key(0.9, Vec4::new(4.0, 0.0, 0.0, 1.0));
-    colors.add_key(1.0, Vec4::new(4.0, 0.0, 0.0, 0.0));
-
-    let mut sizes = Gradient::new();
-    sizes.add_key(0.0, Vec2::splat(0.15));
-    sizes.add_key(0.3, Vec2::splat(0.1));
-    sizes.add_key(0.8, Vec2::splat(0.01));
-    sizes.add_key(1.0, Vec2::splat(0.0));
-
-    let mut module = Module::default();
-    let init_position = SetPositionSphereModifier {
-        dimension: ShapeDimension::Volume,
-        center: module.lit(Vec3::ZERO),
-        radius: module.lit(0.5),
-    };
-    let init_velocity = SetVelocitySphereModifier {
-        center: module.lit(Vec3::ZERO),
-        speed: module.lit(20.),
-    };
-    let init_lifetime = SetAttributeModifier::new(Attribute::LIFETIME, module.lit(2.0));
-    let update_accel = AccelModifier::new(module.lit(Vec3::new(0., -9.8, 0.)));
-    let update_drag = LinearDragModifier::new(module.lit(1.5));
-
     let circle: Handle<Image> = asset_server.load("circle.png");
+    required.require(&circle);
 
-    let particle_texture_modifier = ParticleTextureModifier {
-        texture: circle,
-        sample_mapping: ImageSampleMapping::Modulate,
+    let mut reticle = |color: Color| {
+        materials.add(StandardMaterial {
+            base_color: color,
+            base_color_texture: Some(circle.clone()),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        })
     };
+    let reticle_valid = reticle(Color::rgba(1.0, 1.0, 1.0, 0.8));
+    let reticle_invalid = reticle(Color::rgba(1.0, 0.2, 0.2, 0.5));
 
-    let effect = effects.add(
-        EffectAsset::new(256, Spawner::once(256.0.into(), true), module)
-            .init(init_position)
-            .init(init_velocity)
-            .init(init_lifetime)
-            .update(update_drag)
-            .update(update_accel)
-            .render(ColorOverLifetimeModifier { gradient: colors })
-            .render(SizeOverLifetimeModifier {
-                gradient: sizes,
-                screen_space_size: false,
-            })
-            .render(particle_texture_modifier),
-    );
-
-    // Hack! For some reason the first one of these spawned never does anything.
-    // I believe there's something going on with `Spawner::once` and the
-    // initialization of the effect. Either way, this fixes things and will be
-    // easy to delete if I can ever figure out what's going on. I spawn well
-    // outside of any visible area.
-    commands.spawn((
-        Name::new("Explosion:Burst"),
-        ParticleEffectBundle {
-            effect: ParticleEffect::new(effect.clone())
-                // You can actually change this so nothing is emitted and it'll still fix the issue.
-                .with_spawner(Spawner::once(256.0.into(), true)),
-            transform: Transform::from_translation(Vec3::Y * 1000.0),
-            ..Default::default()
-        },
-    ));
+    commands.insert_resource(FiringResources {
+        shot_mesh: meshes.add(primitives::Sphere::default()),
+        shot_material: materials.add(StandardMaterial {
+            base_color: Color::BLACK,
+            perceptual_roughness: 0.3,
+            ..default()
+        }),
+        shadow_mesh: meshes.add(Plane3d::default().mesh().size(TILE_SIZE, TILE_SIZE)),
+        shadow_material: materials.add(StandardMaterial {
+            base_color: Color::rgba(0.0, 0.0, 0.0, 0.5),
+            base_color_texture: Some(circle.clone()),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+        reticle_mesh: meshes.add(Plane3d::default().mesh().size(TILE_SIZE, TILE_SIZE)),
+        reticle_valid,
+        reticle_invalid,
+    });
 
-    commands.insert_resource(ExplosionResources { effect });
+    commands.insert_resource(ExplosionResources::new(&mut effects, circle));
 
     info!("explosions-ready");
 }
 
+/// A projectile going off, however that came about.
+#[derive(Clone, Debug)]
+struct Detonation {
+    projectile: Entity,
+    target: Option<Entity>,
+    collision_at: Vec3,
+    explosion_at: Vec3,
+}
+
+impl Event for Detonation {}
+
+/// Lit on fused shells when they first touch the ground, they go off wherever
+/// they've rolled to once it burns down.
+#[derive(Component)]
+struct Fuse(Timer);
+
+/// How long fused shells roll around before going off.
+const FUSE_SECONDS: f32 = 1.5;
+
+#[allow(clippy::too_many_arguments)]
 fn check_collisions(
     mut commands: Commands,
     mut collision_events: EventReader<CollisionEvent>,
     mut contact_force_events: EventReader<ContactForceEvent>,
-    mut explosions: EventWriter<ExplosionEvent>,
-    _terrain: Query<&Terrain>,
+    mut detonations: EventWriter<Detonation>,
     projectiles: Query<Option<&RoundShot>>,
+    fuses: Query<(), With<Fuse>>,
+    targets: Query<(Has<TerrainChunk>, Option<&RigidBody>)>,
     transforms: Query<&Transform>,
-    names: Query<&Name>,
-    resources: Res<ExplosionResources>,
+    rules: Res<Rules>,
 ) {
     for collision_event in collision_events.read() {
-        match collision_event {
-            CollisionEvent::Started(first, second, _) => {
-                let (target, projectile, round_shot) = match (
-                    projectiles.get(*first).expect("Projectile check failed"),
-                    projectiles.get(*second).expect("Projectile check failed"),
-                ) {
-                    (None, Some(projectile)) => (first, second, projectile),
-                    (Some(projectile), None) => (second, first, projectile),
-                    (Some(_), Some(_)) => todo!(),
-                    (None, None) => todo!(),
-                };
-
-                let showtime = transforms.get(*projectile).expect("No collision entity");
-                let collision_at = showtime.translation;
-                let explosion_at = round_shot.target;
-
-                explosions.send(ExplosionEvent::new(explosion_at));
-
-                commands.entity(*projectile).despawn_recursive();
-
-                info!(
-                    %collision_at,
-                    %explosion_at,
-                    "collision: target={:?} projectile={:?} ({:?})",
-                    names.get(*target).ok().map(|s| s.as_str()),
-                    names.get(*projectile).ok().map(|s| s.as_str()),
-                    explosion_at - collision_at
-                );
-
-                commands
-                    .spawn((
-                        Name::new("Explosion"),
-                        helpers::Expires::after(2.5),
-                        SpatialBundle {
-                            transform: Transform::from_translation(explosion_at),
-                            ..default()
-                        },
-                    ))
-                    .with_children(|child_builder| {
-                        child_builder.spawn((
-                            Name::new("Explosion:Burst"),
-                            ParticleEffectBundle {
-                                effect: ParticleEffect::new(resources.effect.clone()),
-                                transform: Transform::IDENTITY,
-                                ..Default::default()
-                            },
-                        ));
-                        child_builder.spawn((
-                            Name::new("Explosion:Light"),
-                            helpers::Expires::after(0.05),
-                            PointLightBundle {
-                                transform: Transform::from_translation(Vec3::Y * 1.),
-                                point_light: PointLight {
-                                    // 1,000,000 lumens is a very large "cinema light" capable of registering brightly at Bevy's
-                                    // default "very overcast day" exposure level. For "indoor lighting" with a lower exposure,
-                                    // this would be way too bright.
-                                    intensity: 1_000_000.0,
-                                    shadows_enabled: true,
-                                    ..default()
-                                },
-                                ..default()
-                            },
-                        ));
-                    });
+        let CollisionEvent::Started(first, second, _) = collision_event else {
+            debug!("collision(stopped): {:?}", collision_event);
+            continue;
+        };
+
+        // Entities may have been despawned since the collision happened, those
+        // are no different than something that isn't a projectile.
+        let shot = |entity: &Entity| projectiles.get(*entity).ok().flatten().cloned();
+
+        // Each projectile involved goes off, shots hitting each other do so in
+        // mid air rather than over their targets.
+        let hits = match (shot(first), shot(second)) {
+            (None, Some(round_shot)) => vec![(*second, round_shot, Some(*first))],
+            (Some(round_shot), None) => vec![(*first, round_shot, Some(*second))],
+            (Some(one), Some(two)) => vec![(*first, one, None), (*second, two, None)],
+            (None, None) => {
+                debug!("collision(ignored): {:?}", collision_event);
+                continue;
+            }
+        };
+
+        for (projectile, round_shot, target) in hits {
+            let Ok(showtime) = transforms.get(projectile) else {
+                warn!(?projectile, "collision with missing projectile");
+                continue;
+            };
+            let collision_at = showtime.translation;
+
+            let (ground, body) = target
+                .and_then(|target| targets.get(target).ok())
+                .unwrap_or_default();
+
+            if rules.fused_shells && ground {
+                if !fuses.contains(projectile) {
+                    debug!(?projectile, %collision_at, "fuse-lit");
+                    commands
+                        .entity(projectile)
+                        .insert(Fuse(Timer::from_seconds(FUSE_SECONDS, TimerMode::Once)));
+                }
+                continue;
             }
-            CollisionEvent::Stopped(_, _, _) => debug!("collision(stopped): {:?}", collision_event),
+
+            // Things that move, like ships, are hit where they are now and not
+            // where the shot was aimed.
+            let moving = body.is_some_and(|body| {
+                matches!(
+                    body,
+                    RigidBody::Dynamic
+                        | RigidBody::KinematicPositionBased
+                        | RigidBody::KinematicVelocityBased
+                )
+            });
+            let explosion_at = match target {
+                Some(_) if !moving => round_shot.target,
+                _ => collision_at,
+            };
+
+            detonations.send(Detonation {
+                projectile,
+                target,
+                collision_at,
+                explosion_at,
+            });
         }
     }
 
@@ -368,21 +1145,290 @@ fn check_collisions(
     }
 }
 
+fn burn_fuses(
+    mut commands: Commands,
+    mut detonations: EventWriter<Detonation>,
+    mut fuses: Query<(Entity, &mut Fuse, &Transform)>,
+    timer: Res<Time>,
+) {
+    for (projectile, mut fuse, transform) in &mut fuses {
+        if fuse.0.tick(timer.delta()).just_finished() {
+            commands.entity(projectile).remove::<Fuse>();
+            detonations.send(Detonation {
+                projectile,
+                target: None,
+                collision_at: transform.translation,
+                explosion_at: transform.translation,
+            });
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn detonate(
+    mut commands: Commands,
+    mut detonations: EventReader<Detonation>,
+    mut explosions: EventWriter<ExplosionEvent>,
+    terrain: Query<&Terrain>,
+    mut projectiles: Query<(&RoundShot, &Player, &mut helpers::Pooled)>,
+    structures: Query<(), Or<(With<Wall>, With<Cannon>)>>,
+    mut rigs: Query<
+        (Entity, &ExplosionRig, &mut helpers::Pooled, &Children),
+        (With<ExplosionRig>, Without<RoundShot>),
+    >,
+    mut bursts: Query<&mut EffectSpawner, With<ExplosionBurst>>,
+    mut lights: Query<
+        &mut helpers::Pooled,
+        (
+            With<ExplosionLight>,
+            Without<ExplosionRig>,
+            Without<RoundShot>,
+        ),
+    >,
+    names: Query<&Name>,
+    resources: Res<ExplosionResources>,
+    budget: Res<EffectsBudget>,
+    display: Res<DisplaySettings>,
+) {
+    for detonation in detonations.read() {
+        let Detonation {
+            projectile,
+            target,
+            collision_at,
+            explosion_at,
+        } = *detonation;
+
+        let Ok((round_shot, player, mut pooled)) = projectiles.get_mut(projectile) else {
+            warn!(?projectile, "detonation of missing projectile");
+            continue;
+        };
+
+        // Back to the pool, ignoring any other collisions this frame.
+        if pooled.idle() {
+            continue;
+        }
+        let mut entity = commands.entity(projectile);
+        pooled.release(&mut entity);
+        entity
+            .remove::<Fuse>()
+            .insert((RigidBodyDisabled, ColliderDisabled));
+
+        let surface = terrain
+            .get_single()
+            .ok()
+            .and_then(|terrain| terrain.survey(explosion_at));
+        let kind = ExplosionKind::choose(
+            round_shot.blast(),
+            surface.as_ref().map(|s| s.cell()),
+            target.is_some_and(|target| structures.contains(target)),
+        );
+        let active = rigs
+            .iter()
+            .filter(|(_, _, pooled, _)| !pooled.idle())
+            .count();
+        let kind = budget.explosion(kind, active);
+
+        // Ships fire on behalf of a player, but the shots aren't theirs.
+        let explosion = ExplosionEvent::new(explosion_at, kind.radius() * round_shot.blast_scale);
+        explosions.send(if round_shot.bombardment {
+            explosion
+        } else {
+            explosion.by(*player)
+        });
+
+        info!(
+            %collision_at,
+            %explosion_at,
+            ?kind,
+            "collision: target={:?} projectile={:?} ({:?})",
+            target.and_then(|target| names.get(target).ok()).map(|s| s.as_str()),
+            names.get(projectile).ok().map(|s| s.as_str()),
+            explosion_at - collision_at
+        );
+
+        // Only how it looks is toned down, never how far it reaches.
+        let flash = !display.reduce_motion;
+        let kind = if display.reduce_motion {
+            kind.calmer()
+        } else {
+            kind
+        };
+
+        match rigs
+            .iter_mut()
+            .find(|(_, rig, pooled, _)| rig.0 == kind && pooled.idle())
+        {
+            Some((entity, _, mut pooled, children)) => {
+                pooled.take();
+                commands.entity(entity).insert((
+                    Transform::from_translation(explosion_at),
+                    helpers::Expires::after(kind.duration()),
+                    Visibility::Visible,
+                ));
+
+                for child in children.iter() {
+                    if let Ok(mut spawner) = bursts.get_mut(*child) {
+                        spawner.reset();
+                    }
+                    if let Some(mut pooled) = lights.get_mut(*child).ok().filter(|_| flash) {
+                        pooled.take();
+                        commands
+                            .entity(*child)
+                            .insert((helpers::Expires::after(0.05), Visibility::Inherited));
+                    }
+                }
+            }
+            None => {
+                spawn_explosion(&mut commands, kind, explosion_at, flash, &resources);
+            }
+        }
+    }
+}
+
+/// Impulse given to a body right at the center of an explosion, falling off to
+/// nothing at the edge of the blast.
+const KNOCKBACK: f32 = 150.0;
+
+/// Pushes dynamic bodies away from explosions, slightly upwards so things
+/// resting on the ground get thrown rather than dragged.
+fn knockback(
+    mut commands: Commands,
+    mut explosions: EventReader<ExplosionEvent>,
+    bodies: Query<(Entity, &GlobalTransform, &RigidBody), Without<RigidBodyDisabled>>,
+) {
+    for explosion in explosions.read() {
+        for (entity, transform, body) in &bodies {
+            if !matches!(body, RigidBody::Dynamic) {
+                continue;
+            }
+
+            let offset = transform.translation() - explosion.world();
+            let distance = offset.length();
+            if distance >= explosion.radius() {
+                continue;
+            }
+
+            let falloff = 1.0 - distance / explosion.radius();
+            let direction = (offset.normalize_or_zero() + Vec3::Y * 0.5).normalize();
+            let impulse = direction * KNOCKBACK * explosion.radius() * falloff;
+
+            debug!(?entity, %impulse, "knockback");
+
+            commands.entity(entity).insert(ExternalImpulse {
+                impulse,
+                torque_impulse: Vec3::ZERO,
+            });
+        }
+    }
+}
+
+/// Number of explosions of each kind readied in the pool when a game starts.
+const PRIMED_EXPLOSIONS: usize = 3;
+
+/// The very first instance of an effect never seems to emit anything, so every
+/// game starts with a pool of explosions that have already been through that.
+/// Explosions taken from the pool have their spawners reset, which reliably
+/// emits them again.
+fn prime_explosions(mut commands: Commands, resources: Res<ExplosionResources>) {
+    for kind in ExplosionKind::ALL {
+        for _ in 0..PRIMED_EXPLOSIONS {
+            let rig = spawn_explosion(&mut commands, kind, Vec3::ZERO, true, &resources);
+            commands
+                .entity(rig)
+                .remove::<helpers::Expires>()
+                .insert((helpers::Pooled::parked(), Visibility::Hidden));
+        }
+    }
+
+    debug!(explosions = PRIMED_EXPLOSIONS, "explosions-primed");
+}
+
+fn spawn_explosion(
+    commands: &mut Commands,
+    kind: ExplosionKind,
+    explosion_at: Vec3,
+    flash: bool,
+    resources: &ExplosionResources,
+) -> Entity {
+    commands
+        .spawn((
+            Name::new(format!("Explosion:{:?}", kind)),
+            ExplosionRig(kind),
+            GamePlayLifetime,
+            helpers::Pooled::default(),
+            helpers::Expires::after(kind.duration()),
+            SpatialBundle {
+                transform: Transform::from_translation(explosion_at),
+                ..default()
+            },
+        ))
+        .with_children(|child_builder| {
+            child_builder.spawn((
+                Name::new("Explosion:Burst"),
+                ExplosionBurst,
+                ParticleEffectBundle {
+                    effect: ParticleEffect::new(resources.effect(kind)),
+                    transform: Transform::IDENTITY,
+                    ..Default::default()
+                },
+            ));
+            if let Some(intensity) = kind.flash().filter(|_| flash) {
+                child_builder.spawn((
+                    Name::new("Explosion:Light"),
+                    ExplosionLight,
+                    Budgeted,
+                    helpers::Pooled::default(),
+                    helpers::Expires::after(0.05),
+                    PointLightBundle {
+                        transform: Transform::from_translation(Vec3::Y * 1.),
+                        point_light: PointLight {
+                            intensity,
+                            shadows_enabled: true,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ));
+            }
+        })
+        .id()
+}
+
 #[derive(Clone, Debug)]
 pub struct ExplosionEvent {
-    #[allow(dead_code)]
     world: Vec3,
+    radius: f32,
+    /// Whoever fired the shot from one of their cannons, if anyone.
+    player: Option<Player>,
 }
 
 impl Event for ExplosionEvent {}
 
 impl ExplosionEvent {
-    pub fn new(world: Vec3) -> Self {
-        Self { world }
+    pub fn new(world: Vec3, radius: f32) -> Self {
+        Self {
+            world,
+            radius,
+            player: None,
+        }
+    }
+
+    pub fn by(self, player: Player) -> Self {
+        Self {
+            player: Some(player),
+            ..self
+        }
+    }
+
+    pub fn player(&self) -> Option<&Player> {
+        self.player.as_ref()
     }
 
-    #[allow(dead_code)]
     pub fn world(&self) -> Vec3 {
         self.world
     }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
 }
@@ -0,0 +1,298 @@
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::*;
+
+use crate::building::{Cannon, Operational};
+use crate::controls::{Action, Controls};
+use crate::helpers::GamePlayLifetime;
+use crate::model::{Phase, Player, Settings, TILE_SIZE};
+use crate::network::{self, Host};
+use crate::profile::{Aiming, Profile};
+use crate::terrain::Terrain;
+
+use super::reticle::{self, Reticle};
+use super::{ballistics, muzzle, FiringResources, Reloading, TargetPicked};
+
+/// Degrees the elevation changes with each press.
+const ELEVATION_STEP: f32 = 5.0;
+
+const LOWEST_ELEVATION: f32 = 15.0;
+
+const HIGHEST_ELEVATION: f32 = 75.0;
+
+/// How far to drag for a shot at full power.
+const FULL_POWER_DRAG: f32 = 8.0 * TILE_SIZE;
+
+/// Aiming by hand, artillery style: holding the mouse down near a cannon and
+/// dragging the way to fire, further for more power. The shot leaves that
+/// cannon just as it was aimed, coming down wherever the reticle showed.
+#[derive(Debug, Resource)]
+pub struct ManualAim {
+    /// Degrees above level.
+    elevation: f32,
+    drag: Option<Drag>,
+}
+
+impl Default for ManualAim {
+    fn default() -> Self {
+        Self {
+            elevation: 45.0,
+            drag: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Drag {
+    entity: Entity,
+    cannon: Vec3,
+    ordnance: ballistics::Ordnance,
+    from: Vec3,
+}
+
+impl ManualAim {
+    /// How a shot dragged from `from` to `to` would leave the cannon, nothing
+    /// until it's been dragged anywhere.
+    fn velocity(&self, from: Vec3, to: Vec3) -> Option<Vec3> {
+        let dragged = (to - from) * Vec3::new(1., 0., 1.);
+        if dragged.length_squared() == 0.0 {
+            return None;
+        }
+
+        let azimuth = dragged.z.atan2(dragged.x).to_degrees();
+        let power = dragged.length() / FULL_POWER_DRAG;
+
+        Some(ballistics::aimed(azimuth, self.elevation, power))
+    }
+}
+
+pub fn toggle_aiming(
+    keys: Res<ButtonInput<KeyCode>>,
+    controls: Res<Controls>,
+    mut profile: ResMut<Profile>,
+) {
+    if controls.just_pressed(&keys, Action::ToggleAiming) {
+        profile.aiming = profile.aiming.toggled();
+        info!(aiming = ?profile.aiming, "aiming-changed");
+    }
+}
+
+pub fn adjust_elevation(
+    keys: Res<ButtonInput<KeyCode>>,
+    controls: Res<Controls>,
+    mut aim: ResMut<ManualAim>,
+) {
+    let change = match (
+        controls.just_pressed(&keys, Action::LowerElevation),
+        controls.just_pressed(&keys, Action::RaiseElevation),
+    ) {
+        (true, false) => -ELEVATION_STEP,
+        (false, true) => ELEVATION_STEP,
+        _ => return,
+    };
+
+    aim.elevation = (aim.elevation + change).clamp(LOWEST_ELEVATION, HIGHEST_ELEVATION);
+}
+
+/// Follows the drag from the player's closest cannon, showing where it'd come
+/// down on the reticle and firing it as aimed once the mouse is let go.
+#[allow(clippy::too_many_arguments)]
+pub fn drag_aim(
+    mut aim: ResMut<ManualAim>,
+    mut cursor: Local<Option<Vec3>>,
+    mut moves: EventReader<Pointer<Move>>,
+    mut picks: EventWriter<TargetPicked>,
+    mut reticles: Query<(
+        &mut Reticle,
+        &mut Transform,
+        &mut Visibility,
+        &mut Handle<StandardMaterial>,
+    )>,
+    cannons: Query<
+        (Entity, &Transform, &Player, &Cannon),
+        (With<Operational>, Without<Reloading>, Without<Reticle>),
+    >,
+    terrain: Query<&Terrain>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    phase: Res<State<Phase>>,
+    settings: Res<Settings>,
+    host: Option<Res<Host>>,
+    resources: Res<FiringResources>,
+) {
+    if let Some(position) = moves.read().filter_map(|e| e.event.hit.position).last() {
+        *cursor = Some(position);
+    }
+
+    let firing = phase.get().player();
+    if settings.computer(&firing) || network::remote(host.as_deref(), &firing) {
+        aim.drag = None;
+        return;
+    }
+
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    if buttons.just_pressed(MouseButton::Left) {
+        aim.drag = cursor.and_then(|from| {
+            let (entity, transform, _, cannon) = cannons
+                .iter()
+                .filter(|(_, _, owner, _)| **owner == firing)
+                .min_by(|(_, a, _, _), (_, b, _, _)| {
+                    a.translation
+                        .distance_squared(from)
+                        .total_cmp(&b.translation.distance_squared(from))
+                })?;
+            Some(Drag {
+                entity,
+                cannon: transform.translation,
+                ordnance: cannon.entry().ordnance,
+                from,
+            })
+        });
+    }
+
+    let Some(drag) = aim.drag else {
+        return;
+    };
+
+    // Showing the middle of wherever it comes down, as clicking there would.
+    let velocity = cursor.and_then(|to| aim.velocity(drag.from, to));
+    let target = velocity
+        .and_then(|velocity| {
+            ballistics::impact(muzzle(drag.cannon), velocity, |p| terrain.surface_height(p))
+        })
+        .and_then(|landing| terrain.survey(landing))
+        .map(|survey| survey.world());
+    let reachable = target.is_some_and(|target| drag.ordnance.in_range(drag.cannon, target));
+
+    for (mut reticle, mut transform, mut visibility, mut material) in &mut reticles {
+        reticle::show(
+            &mut reticle,
+            &mut transform,
+            &mut visibility,
+            &mut material,
+            target,
+            reachable,
+            &resources,
+        );
+    }
+
+    if buttons.just_released(MouseButton::Left) {
+        aim.drag = None;
+
+        match target.zip(velocity).filter(|_| reachable) {
+            Some((target, velocity)) => {
+                picks.send(TargetPicked::new(firing, target, false).aimed(drag.entity, velocity));
+            }
+            None => info!(player = ?firing, "aim-abandoned"),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ElevationSlider;
+
+#[derive(Component)]
+pub struct ElevationLabel;
+
+#[derive(Component)]
+pub struct ElevationBar;
+
+pub fn spawn_elevation_slider(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Elevation"),
+            ElevationSlider,
+            GamePlayLifetime,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(12.),
+                    right: Val::Px(12.),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ElevationLabel,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 18.,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(160.),
+                        height: Val::Px(8.),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.2, 0.2, 0.2).into(),
+                    ..default()
+                })
+                .with_children(|bar| {
+                    bar.spawn((
+                        ElevationBar,
+                        NodeBundle {
+                            style: Style {
+                                height: Val::Percent(100.),
+                                ..default()
+                            },
+                            background_color: Color::WHITE.into(),
+                            ..default()
+                        },
+                    ));
+                });
+        });
+}
+
+pub fn despawn_elevation_slider(
+    mut commands: Commands,
+    sliders: Query<Entity, With<ElevationSlider>>,
+) {
+    for entity in sliders.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Shows the elevation while aiming by hand, and only then.
+pub fn update_elevation_slider(
+    aim: Res<ManualAim>,
+    profile: Res<Profile>,
+    controls: Res<Controls>,
+    mut sliders: Query<&mut Visibility, With<ElevationSlider>>,
+    mut labels: Query<&mut Text, With<ElevationLabel>>,
+    mut bars: Query<&mut Style, With<ElevationBar>>,
+) {
+    let visible = profile.aiming == Aiming::Manual;
+    for mut visibility in &mut sliders {
+        *visibility = if visible {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    for mut text in &mut labels {
+        text.sections[0].value = format!(
+            "Elevation {:.0}° ({} and {})",
+            aim.elevation,
+            controls.describe(Action::LowerElevation),
+            controls.describe(Action::RaiseElevation)
+        );
+    }
+
+    let fraction = (aim.elevation - LOWEST_ELEVATION) / (HIGHEST_ELEVATION - LOWEST_ELEVATION);
+    for mut style in &mut bars {
+        style.width = Val::Percent(fraction * 100.);
+    }
+}
@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+
+use crate::helpers::Pooled;
+
+use super::effects::ExplosionKind;
+
+/// Limits on how much is going on at once during heavy bombardment. Lights
+/// over the budget still light things up, they just stop casting shadows, and
+/// explosions over the budget are toned down.
+#[derive(Resource, Debug)]
+pub struct EffectsBudget {
+    shadowed_lights: usize,
+    explosions: usize,
+}
+
+impl Default for EffectsBudget {
+    fn default() -> Self {
+        Self {
+            shadowed_lights: 4,
+            explosions: 6,
+        }
+    }
+}
+
+impl EffectsBudget {
+    /// The explosion to use given how many are already going off.
+    pub fn explosion(&self, kind: ExplosionKind, active: usize) -> ExplosionKind {
+        if active < self.explosions {
+            return kind;
+        }
+
+        match kind {
+            ExplosionKind::LargeBlast => ExplosionKind::SmallBlast,
+            kind => kind,
+        }
+    }
+}
+
+/// Marks the short lived lights that count against the budget.
+#[derive(Component)]
+pub struct Budgeted;
+
+pub fn shadowed_lights(
+    budget: Res<EffectsBudget>,
+    mut lights: Query<(&mut PointLight, &Pooled), With<Budgeted>>,
+) {
+    let mut shadowed = 0;
+    for (mut light, pooled) in &mut lights {
+        if pooled.idle() {
+            continue;
+        }
+
+        let shadows = shadowed < budget.shadowed_lights;
+        if shadows {
+            shadowed += 1;
+        }
+        if light.shadows_enabled != shadows {
+            light.shadows_enabled = shadows;
+        }
+    }
+}
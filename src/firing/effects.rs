@@ -0,0 +1,216 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_hanabi::prelude::*;
+use bevy_hanabi::{EffectAsset, Gradient};
+
+use crate::terrain::SurveyedCell;
+
+/// The different looks of an explosion, each backed by its own effect.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ExplosionKind {
+    SmallBlast,
+    LargeBlast,
+    Splash,
+    Plume,
+}
+
+impl ExplosionKind {
+    pub const ALL: [ExplosionKind; 4] = [
+        ExplosionKind::SmallBlast,
+        ExplosionKind::LargeBlast,
+        ExplosionKind::Splash,
+        ExplosionKind::Plume,
+    ];
+
+    /// Projectiles landing in water or on soft ground throw that up instead of
+    /// going off in their usual blast, and anything hitting a structure gets
+    /// the big one.
+    pub fn choose(blast: ExplosionKind, surface: Option<&SurveyedCell>, structure: bool) -> Self {
+        match (surface, structure) {
+            (_, true) => ExplosionKind::LargeBlast,
+            (Some(SurveyedCell::Water), _) => ExplosionKind::Splash,
+            (Some(SurveyedCell::Ground(_)), _) | (Some(SurveyedCell::Beach), _) => {
+                ExplosionKind::Plume
+            }
+            _ => blast,
+        }
+    }
+
+    /// Intensity of the flash of light, only blasts have one.
+    pub fn flash(&self) -> Option<f32> {
+        match self {
+            ExplosionKind::SmallBlast => Some(500_000.0),
+            ExplosionKind::LargeBlast => Some(1_500_000.0),
+            ExplosionKind::Splash | ExplosionKind::Plume => None,
+        }
+    }
+
+    /// How far the blast reaches, for pushing things around.
+    pub fn radius(&self) -> f32 {
+        match self {
+            ExplosionKind::SmallBlast => 1.5,
+            ExplosionKind::LargeBlast => 3.0,
+            ExplosionKind::Splash => 1.0,
+            ExplosionKind::Plume => 1.5,
+        }
+    }
+
+    /// A smaller version for anyone bothered by motion, without the blasts
+    /// that fill the screen.
+    pub fn calmer(&self) -> Self {
+        match self {
+            ExplosionKind::LargeBlast => ExplosionKind::SmallBlast,
+            kind => *kind,
+        }
+    }
+
+    /// How long until the last of the particles are gone.
+    pub fn duration(&self) -> f32 {
+        Burst::of(*self).lifetime + 0.5
+    }
+}
+
+struct Burst {
+    particles: u32,
+    radius: f32,
+    center: Vec3,
+    speed: f32,
+    lifetime: f32,
+    drag: f32,
+    size: f32,
+    colors: Gradient<Vec4>,
+}
+
+impl Burst {
+    fn of(kind: ExplosionKind) -> Self {
+        match kind {
+            ExplosionKind::SmallBlast => Self {
+                particles: 128,
+                radius: 0.3,
+                center: Vec3::ZERO,
+                speed: 12.,
+                lifetime: 1.2,
+                drag: 1.5,
+                size: 0.1,
+                colors: fire(),
+            },
+            ExplosionKind::LargeBlast => Self {
+                particles: 384,
+                radius: 0.6,
+                center: Vec3::ZERO,
+                speed: 22.,
+                lifetime: 2.0,
+                drag: 1.5,
+                size: 0.15,
+                colors: fire(),
+            },
+            // Centered below the surface so most of it goes up.
+            ExplosionKind::Splash => Self {
+                particles: 256,
+                radius: 0.4,
+                center: Vec3::new(0., -1., 0.),
+                speed: 8.,
+                lifetime: 1.5,
+                drag: 0.5,
+                size: 0.12,
+                colors: fade(Vec4::new(0.8, 0.9, 1.0, 1.0), Vec4::new(0.3, 0.5, 0.8, 1.0)),
+            },
+            ExplosionKind::Plume => Self {
+                particles: 192,
+                radius: 0.4,
+                center: Vec3::new(0., -0.5, 0.),
+                speed: 10.,
+                lifetime: 1.8,
+                drag: 2.5,
+                size: 0.15,
+                colors: fade(
+                    Vec4::new(0.5, 0.4, 0.3, 1.0),
+                    Vec4::new(0.3, 0.25, 0.2, 1.0),
+                ),
+            },
+        }
+    }
+
+    fn build(self, texture: Handle<Image>) -> EffectAsset {
+        let mut sizes = Gradient::new();
+        sizes.add_key(0.0, Vec2::splat(self.size));
+        sizes.add_key(0.3, Vec2::splat(self.size * 0.66));
+        sizes.add_key(0.8, Vec2::splat(self.size * 0.1));
+        sizes.add_key(1.0, Vec2::splat(0.0));
+
+        let mut module = Module::default();
+        let init_position = SetPositionSphereModifier {
+            dimension: ShapeDimension::Volume,
+            center: module.lit(Vec3::ZERO),
+            radius: module.lit(self.radius),
+        };
+        let init_velocity = SetVelocitySphereModifier {
+            center: module.lit(self.center),
+            speed: module.lit(self.speed),
+        };
+        let init_lifetime =
+            SetAttributeModifier::new(Attribute::LIFETIME, module.lit(self.lifetime));
+        let update_accel = AccelModifier::new(module.lit(Vec3::new(0., -9.8, 0.)));
+        let update_drag = LinearDragModifier::new(module.lit(self.drag));
+
+        let particle_texture_modifier = ParticleTextureModifier {
+            texture,
+            sample_mapping: ImageSampleMapping::Modulate,
+        };
+
+        EffectAsset::new(
+            self.particles,
+            Spawner::once((self.particles as f32).into(), true),
+            module,
+        )
+        .init(init_position)
+        .init(init_velocity)
+        .init(init_lifetime)
+        .update(update_drag)
+        .update(update_accel)
+        .render(ColorOverLifetimeModifier {
+            gradient: self.colors,
+        })
+        .render(SizeOverLifetimeModifier {
+            gradient: sizes,
+            screen_space_size: false,
+        })
+        .render(particle_texture_modifier)
+    }
+}
+
+fn fire() -> Gradient<Vec4> {
+    let mut colors = Gradient::new();
+    colors.add_key(0.0, Vec4::new(4.0, 4.0, 4.0, 1.0));
+    colors.add_key(0.1, Vec4::new(4.0, 4.0, 0.0, 1.0));
+    colors.add_key(0.9, Vec4::new(4.0, 0.0, 0.0, 1.0));
+    colors.add_key(1.0, Vec4::new(4.0, 0.0, 0.0, 0.0));
+    colors
+}
+
+fn fade(start: Vec4, end: Vec4) -> Gradient<Vec4> {
+    let mut colors = Gradient::new();
+    colors.add_key(0.0, start);
+    colors.add_key(0.8, end);
+    colors.add_key(1.0, end.truncate().extend(0.0));
+    colors
+}
+
+#[derive(Resource)]
+pub struct ExplosionResources {
+    effects: HashMap<ExplosionKind, Handle<EffectAsset>>,
+}
+
+impl ExplosionResources {
+    pub fn new(effects: &mut Assets<EffectAsset>, texture: Handle<Image>) -> Self {
+        Self {
+            effects: ExplosionKind::ALL
+                .iter()
+                .map(|kind| (*kind, effects.add(Burst::of(*kind).build(texture.clone()))))
+                .collect(),
+        }
+    }
+
+    pub fn effect(&self, kind: ExplosionKind) -> Handle<EffectAsset> {
+        self.effects[&kind].clone()
+    }
+}
@@ -0,0 +1,203 @@
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::building::{Cannon, Operational, Wall};
+use crate::helpers::PlayerColor;
+use crate::model::{Phase, Player, Rules};
+use crate::terrain::Terrain;
+use crate::weather::Weather;
+
+use super::{ballistics, obstructed, trajectory, FiringResources, Reloading};
+
+/// Shows the cell that'd be fired on, and whether anything can reach it.
+#[derive(Component, Default)]
+pub struct Reticle {
+    target: Option<Vec3>,
+}
+
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct TrajectoryGizmos;
+
+pub fn spawn_reticle(mut commands: Commands, resources: Res<FiringResources>) {
+    commands.spawn((
+        Name::new("Reticle"),
+        Reticle::default(),
+        PbrBundle {
+            mesh: resources.reticle_mesh.clone(),
+            material: resources.reticle_invalid.clone(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        NotShadowCaster,
+        Pickable::IGNORE,
+    ));
+}
+
+pub fn despawn_reticle(mut commands: Commands, reticles: Query<Entity, With<Reticle>>) {
+    for entity in &reticles {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn move_reticle(
+    mut events: EventReader<Pointer<Move>>,
+    mut reticles: Query<(
+        &mut Reticle,
+        &mut Transform,
+        &mut Visibility,
+        &mut Handle<StandardMaterial>,
+    )>,
+    cannons: Query<
+        (&Transform, &Player, &Cannon),
+        (With<Operational>, Without<Reloading>, Without<Reticle>),
+    >,
+    terrain: Query<&Terrain>,
+    phase: Res<State<Phase>>,
+    resources: Res<FiringResources>,
+) {
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+    let Some(position) = events.read().filter_map(|e| e.event.hit.position).last() else {
+        return;
+    };
+
+    let player = phase.get().player();
+
+    for (mut reticle, mut transform, mut visibility, mut material) in &mut reticles {
+        let target = terrain.survey(position).map(|survey| survey.world());
+        let reachable = target.is_some_and(|target| {
+            cannons
+                .iter()
+                .filter(|(_, owner, _)| **owner == player)
+                .any(|(transform, _, cannon)| {
+                    cannon
+                        .entry()
+                        .ordnance
+                        .in_range(transform.translation, target)
+                })
+        });
+
+        show(
+            &mut reticle,
+            &mut transform,
+            &mut visibility,
+            &mut material,
+            target,
+            reachable,
+            &resources,
+        );
+    }
+}
+
+/// Moves the reticle over the target, hiding it when there isn't one.
+pub fn show(
+    reticle: &mut Reticle,
+    transform: &mut Transform,
+    visibility: &mut Visibility,
+    material: &mut Handle<StandardMaterial>,
+    target: Option<Vec3>,
+    reachable: bool,
+    resources: &FiringResources,
+) {
+    reticle.target = target;
+
+    let Some(target) = target else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    transform.translation = target + Vec3::Y * 0.05;
+    *visibility = Visibility::Visible;
+    *material = if reachable {
+        resources.reticle_valid.clone()
+    } else {
+        resources.reticle_invalid.clone()
+    };
+}
+
+/// Previews the arc from the closest cannon that can reach the reticle, red if
+/// something's in the way and line of sight is required.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_trajectory(
+    mut gizmos: Gizmos<TrajectoryGizmos>,
+    reticles: Query<&Reticle>,
+    cannons: Query<(&Transform, &Player, &Cannon), (With<Operational>, Without<Reloading>)>,
+    walls: Query<&Player, With<Wall>>,
+    phase: Res<State<Phase>>,
+    context: Res<RapierContext>,
+    weather: Res<Weather>,
+    rules: Res<Rules>,
+) {
+    let player = phase.get().player();
+
+    for target in reticles.iter().filter_map(|reticle| reticle.target) {
+        let closest = cannons
+            .iter()
+            .filter(|(transform, owner, cannon)| {
+                **owner == player
+                    && cannon
+                        .entry()
+                        .ordnance
+                        .in_range(transform.translation, target)
+            })
+            .min_by(|(a, _, _), (b, _, _)| {
+                let a = a.translation.distance_squared(target);
+                let b = b.translation.distance_squared(target);
+                a.total_cmp(&b)
+            });
+
+        let Some((path, indirect)) = closest.and_then(|(transform, _, cannon)| {
+            let entry = cannon.entry();
+            trajectory(transform.translation, target, &entry.ordnance, &weather)
+                .map(|path| (path, entry.indirect))
+        }) else {
+            continue;
+        };
+
+        let color =
+            if rules.line_of_sight && !indirect && obstructed(&context, &path, &player, &walls) {
+                Color::RED
+            } else {
+                player.color()
+            };
+
+        gizmos.linestrip(path, color);
+    }
+}
+
+/// Steps each traverse is drawn with.
+const TRAVERSE_STEPS: usize = 12;
+
+/// How far out from each cannon its traverse is drawn, well short of how far
+/// they can reach so as to not cover the map.
+const TRAVERSE_RADIUS: f32 = 3.0;
+
+/// Shows the arc each of the firing player's cannons covers without turning.
+pub fn draw_traverse(
+    mut gizmos: Gizmos<TrajectoryGizmos>,
+    cannons: Query<(&Transform, &Player, &Cannon), With<Operational>>,
+    phase: Res<State<Phase>>,
+) {
+    let player = phase.get().player();
+
+    for (transform, _, cannon) in cannons.iter().filter(|(_, owner, _)| **owner == player) {
+        let center = transform.translation;
+        let facing = cannon.facing() as f32;
+        let edge = (0..=TRAVERSE_STEPS).map(|step| {
+            let angle = facing - ballistics::TRAVERSE
+                + 2. * ballistics::TRAVERSE * step as f32 / TRAVERSE_STEPS as f32;
+            let radians = angle.to_radians();
+            center + Vec3::new(radians.cos(), 0., radians.sin()) * TRAVERSE_RADIUS
+        });
+
+        gizmos.linestrip(
+            std::iter::once(center)
+                .chain(edge)
+                .chain(std::iter::once(center)),
+            player.color().with_a(0.4),
+        );
+    }
+}
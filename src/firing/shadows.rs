@@ -0,0 +1,88 @@
+use bevy::pbr::{NotShadowCaster, NotShadowReceiver};
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::*;
+
+use crate::helpers::{GamePlayLifetime, Pooled};
+use crate::terrain::Terrain;
+
+use super::{FiringResources, RoundShot};
+
+/// Just above the ground, so the blob isn't lost in it.
+const SHADOW_LIFT: f32 = 0.03;
+
+/// Heights at which the blob is at its largest and has shrunk all it's going
+/// to, as the shot climbs away from the ground.
+const SHADOW_FADE_HEIGHT: f32 = 12.0;
+const SHADOW_SMALLEST: f32 = 0.4;
+
+/// A soft dark blob on the ground under a shot in flight, which is easier to
+/// judge where it's coming down by than the real shadow from high up at an
+/// angle.
+#[derive(Component)]
+pub struct BlobShadow {
+    shot: Entity,
+}
+
+/// Has its blob, which stays with it as it goes back and forth from the pool.
+#[derive(Component)]
+pub struct Shadowed;
+
+pub fn attach_shadows(
+    mut commands: Commands,
+    shots: Query<Entity, (With<RoundShot>, Without<Shadowed>)>,
+    resources: Res<FiringResources>,
+) {
+    for shot in &shots {
+        commands.entity(shot).insert(Shadowed);
+        commands.spawn((
+            Name::new("Projectile:Shadow"),
+            BlobShadow { shot },
+            GamePlayLifetime,
+            PbrBundle {
+                mesh: resources.shadow_mesh.clone(),
+                material: resources.shadow_material.clone(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            NotShadowCaster,
+            NotShadowReceiver,
+            Pickable::IGNORE,
+        ));
+    }
+}
+
+/// Keeps each blob under its shot, smaller the higher it is, and out of sight
+/// while the shot's idle.
+pub fn move_shadows(
+    mut commands: Commands,
+    mut shadows: Query<(Entity, &BlobShadow, &mut Transform, &mut Visibility), Without<Shadowed>>,
+    shots: Query<(&Transform, &Pooled), With<Shadowed>>,
+    terrain: Query<&Terrain>,
+) {
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    for (entity, shadow, mut transform, mut visibility) in &mut shadows {
+        let Ok((shot, pooled)) = shots.get(shadow.shot) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        let ground = terrain
+            .surface_height(shot.translation)
+            .filter(|_| !pooled.idle());
+        let Some(ground) = ground else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        let height = (shot.translation.y - ground).max(0.0);
+        let shrunk = (height / SHADOW_FADE_HEIGHT).min(1.0);
+
+        *visibility = Visibility::Visible;
+        transform.translation =
+            Vec3::new(shot.translation.x, ground + SHADOW_LIFT, shot.translation.z);
+        transform.scale = Vec3::splat(1.0 - (1.0 - SHADOW_SMALLEST) * shrunk);
+    }
+}
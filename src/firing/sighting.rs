@@ -0,0 +1,241 @@
+use bevy::{
+    input::mouse::MouseMotion,
+    prelude::*,
+    window::{CursorGrabMode, PrimaryWindow},
+};
+use bevy_rapier3d::prelude::*;
+
+use crate::building::{Cannon, Operational};
+use crate::camera::CameraMode;
+use crate::collision::{STRUCTURES_GROUP, TERRAIN_GROUP};
+use crate::controls::{Action, Controls};
+use crate::helpers::GamePlayLifetime;
+use crate::model::{Activity, Phase, Player, Settings};
+use crate::network::{self, Host};
+use crate::profile::{Aiming, Profile};
+
+use super::{muzzle, TargetPicked};
+
+/// Radians turned for every pixel the mouse moves.
+const LOOK_SPEED: f32 = 0.003;
+
+/// Furthest up or down the barrel can be looked along.
+const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_3;
+
+/// How far above the muzzle the camera sits, so the barrel isn't in the way.
+const EYE_HEIGHT: f32 = 0.6;
+
+/// Furthest anything is looked for along the sights.
+const SIGHT_RANGE: f32 = 500.0;
+
+/// Looking down the barrel of one of the firing player's cannons, turned with
+/// the mouse and firing wherever it's pointed.
+#[derive(Debug, Default, Resource)]
+pub struct Sighting {
+    cannon: Option<Entity>,
+    yaw: f32,
+    pitch: f32,
+    /// Switched to the first person camera for aiming, and so to switch back.
+    mounted: bool,
+}
+
+#[derive(Component)]
+pub struct Crosshair;
+
+/// Switches to looking down a barrel while firing that way, and back again.
+pub fn follow_aiming(
+    mut sighting: ResMut<Sighting>,
+    mut camera_mode: ResMut<NextState<CameraMode>>,
+    mode: Res<State<CameraMode>>,
+    activity: Res<State<Activity>>,
+    profile: Res<Profile>,
+) {
+    let wanted = *activity.get() == Activity::Firing && profile.aiming == Aiming::FirstPerson;
+
+    match mode.get() {
+        CameraMode::Normal if wanted && !sighting.mounted => {
+            sighting.mounted = true;
+            camera_mode.set(CameraMode::FirstPerson);
+        }
+        CameraMode::FirstPerson if !wanted && sighting.mounted => {
+            sighting.mounted = false;
+            camera_mode.set(CameraMode::Normal);
+        }
+        _ => {}
+    }
+}
+
+pub fn dismount(mut sighting: ResMut<Sighting>, mut camera_mode: ResMut<NextState<CameraMode>>) {
+    if sighting.mounted {
+        sighting.mounted = false;
+        camera_mode.set(CameraMode::Normal);
+    }
+}
+
+pub fn begin_sighting(
+    mut commands: Commands,
+    mut sighting: ResMut<Sighting>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    sighting.cannon = None;
+
+    for mut window in &mut windows {
+        window.cursor.grab_mode = CursorGrabMode::Locked;
+        window.cursor.visible = false;
+    }
+
+    commands
+        .spawn((
+            Name::new("Crosshair"),
+            Crosshair,
+            GamePlayLifetime,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "+",
+                TextStyle {
+                    font_size: 32.,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+pub fn end_sighting(
+    mut commands: Commands,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    crosshairs: Query<Entity, With<Crosshair>>,
+) {
+    for mut window in &mut windows {
+        window.cursor.grab_mode = CursorGrabMode::None;
+        window.cursor.visible = true;
+    }
+
+    for entity in &crosshairs {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Keeps the camera on the sighted cannon, the firing player's when they have
+/// one, turning with the mouse and moving on to the next cannon when asked.
+pub fn mount_camera(
+    mut sighting: ResMut<Sighting>,
+    mut motion: EventReader<MouseMotion>,
+    mut camera: Query<&mut Transform, (With<Camera>, Without<Cannon>)>,
+    cannons: Query<(Entity, &Transform, &Player), (With<Cannon>, With<Operational>)>,
+    keys: Res<ButtonInput<KeyCode>>,
+    controls: Res<Controls>,
+    phase: Res<State<Phase>>,
+) {
+    let firing = phase.get().player();
+    let mut available: Vec<(Entity, &Transform)> = cannons
+        .iter()
+        .filter(|(_, _, player)| **player == firing)
+        .map(|(entity, transform, _)| (entity, transform))
+        .collect();
+    if available.is_empty() {
+        available = cannons
+            .iter()
+            .map(|(entity, transform, _)| (entity, transform))
+            .collect();
+    }
+    available.sort_by_key(|(entity, _)| *entity);
+
+    let current = sighting
+        .cannon
+        .and_then(|cannon| available.iter().position(|(entity, _)| *entity == cannon));
+    let next = match current {
+        Some(index) if controls.just_pressed(&keys, Action::NextCannon) => {
+            Some((index + 1) % available.len())
+        }
+        Some(index) => Some(index),
+        None if !available.is_empty() => Some(0),
+        None => None,
+    };
+    let Some((cannon, cannon_transform)) = next.map(|index| available[index]) else {
+        return;
+    };
+
+    if sighting.cannon != Some(cannon) {
+        info!(?cannon, "sighting");
+        sighting.cannon = Some(cannon);
+        let (yaw, _, _) = cannon_transform.rotation.to_euler(EulerRot::YXZ);
+        sighting.yaw = yaw;
+        sighting.pitch = 0.;
+    }
+
+    let looked: Vec2 = motion.read().map(|event| event.delta).sum();
+    sighting.yaw -= looked.x * LOOK_SPEED;
+    sighting.pitch = (sighting.pitch - looked.y * LOOK_SPEED).clamp(-MAX_PITCH, MAX_PITCH);
+
+    for mut transform in &mut camera {
+        transform.translation = muzzle(cannon_transform.translation) + Vec3::Y * EYE_HEIGHT;
+        transform.rotation = Quat::from_euler(EulerRot::YXZ, sighting.yaw, sighting.pitch, 0.);
+    }
+}
+
+/// Fires wherever the sights are on when the mouse is clicked, same as
+/// clicking there would.
+#[allow(clippy::too_many_arguments)]
+pub fn fire_sighted(
+    mut picks: EventWriter<TargetPicked>,
+    sighting: Res<Sighting>,
+    camera: Query<&Transform, With<Camera>>,
+    context: Res<RapierContext>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    controls: Res<Controls>,
+    phase: Res<State<Phase>>,
+    settings: Res<Settings>,
+    host: Option<Res<Host>>,
+) {
+    if !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let firing = phase.get().player();
+    if settings.computer(&firing) || network::remote(host.as_deref(), &firing) {
+        return;
+    }
+
+    let Ok(transform) = camera.get_single() else {
+        return;
+    };
+
+    let mut filter = QueryFilter::new().groups(CollisionGroups::new(
+        Group::ALL,
+        TERRAIN_GROUP | STRUCTURES_GROUP,
+    ));
+    // Looking past the barrel being looked down.
+    if let Some(cannon) = sighting.cannon {
+        filter = filter.exclude_collider(cannon);
+    }
+    let Some((_, distance)) = context.cast_ray(
+        transform.translation,
+        *transform.forward(),
+        SIGHT_RANGE,
+        true,
+        filter,
+    ) else {
+        info!(player = ?firing, "sighted-nothing");
+        return;
+    };
+
+    picks.send(TargetPicked::new(
+        firing,
+        transform.translation + *transform.forward() * distance,
+        controls.pressed(&keys, Action::Volley),
+    ));
+}
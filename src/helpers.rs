@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::model::AppState;
+use crate::model::{AppState, Player};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, States, Default)]
 pub enum ExpirationControl {
@@ -14,6 +14,9 @@ pub struct HelpersPlugin;
 impl Plugin for HelpersPlugin {
     fn build(&self, app: &mut App) {
         app.insert_state(ExpirationControl::default())
+            .register_type::<Expires>()
+            .register_type::<Pooled>()
+            .register_type::<GamePlayLifetime>()
             .add_systems(
                 PostUpdate,
                 expirations.run_if(in_state(ExpirationControl::Running)),
@@ -32,7 +35,8 @@ fn expanding(mut expandables: Query<(&mut Transform, &Expandable)>, timer: Res<T
     }
 }
 
-#[derive(Component, Clone)]
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component)]
 pub struct Expires {
     lifetime: f32,
     expiration: Option<f32>,
@@ -47,17 +51,49 @@ impl Expires {
     }
 }
 
+/// Entities that are reused rather than despawned, for things that come and go
+/// constantly, like projectiles and explosions. Once expired or released they
+/// are hidden and left idle until taken again.
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct Pooled {
+    idle: bool,
+}
+
+impl Pooled {
+    /// Already idle, for entities created ahead of time.
+    pub fn parked() -> Self {
+        Self { idle: true }
+    }
+
+    pub fn idle(&self) -> bool {
+        self.idle
+    }
+
+    pub fn take(&mut self) {
+        self.idle = false;
+    }
+
+    pub fn release(&mut self, entity: &mut bevy::ecs::system::EntityCommands) {
+        self.idle = true;
+        entity.remove::<Expires>().insert(Visibility::Hidden);
+    }
+}
+
 fn expirations(
     mut commands: Commands,
-    mut expires: Query<(Entity, &mut Expires, Option<&Name>)>,
+    mut expires: Query<(Entity, &mut Expires, Option<&mut Pooled>, Option<&Name>)>,
     timer: Res<Time>,
 ) {
-    for (entity, mut expires, name) in &mut expires {
+    for (entity, mut expires, pooled, name) in &mut expires {
         match expires.expiration {
             Some(expiration) => {
                 if timer.elapsed_seconds() > expiration {
                     debug!("expiring '{:?}'", name);
-                    commands.entity(entity).despawn_recursive();
+                    match pooled {
+                        Some(mut pooled) => pooled.release(&mut commands.entity(entity)),
+                        None => commands.entity(entity).despawn_recursive(),
+                    }
                 }
             }
             None => {
@@ -78,7 +114,19 @@ where
     }
 }
 
-#[derive(Default, Component)]
+#[derive(Default, Component, Reflect)]
+#[reflect(Component)]
 pub struct GamePlayLifetime;
 
 impl Lifetime for GamePlayLifetime {}
+
+/// How each player is drawn.
+pub trait PlayerColor {
+    fn color(&self) -> Color;
+}
+
+impl PlayerColor for Player {
+    fn color(&self) -> Color {
+        Color::hex(self.hex()).expect("player color")
+    }
+}
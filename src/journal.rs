@@ -0,0 +1,331 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    building::{
+        Cannon, CannonTurned, ConstructionEvent, DestructionEvent, Structure, StructureLayers,
+    },
+    firing::{ExplosionEvent, ShotFired},
+    model::{AppState, Phase, Player, Rules},
+    scenario::Scenario,
+    units::Casualty,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Playing together, every this many cells held earns a point each round.
+const TERRITORY_CELLS: usize = 10;
+
+/// Keeps a record of everything that happens during a game, for anything that
+/// needs to look back over it rather than react as it happens.
+pub struct JournalPlugin;
+
+impl Plugin for JournalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EventJournal>()
+            .add_event::<Scored>()
+            .add_systems(OnEnter(AppState::Game), open_journal)
+            .add_systems(
+                Update,
+                (
+                    record_phases,
+                    record_construction,
+                    record_turns,
+                    record_destruction,
+                    record_shots,
+                    record_explosions,
+                    record_casualties,
+                    record_scores,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                OnEnter(Phase::Target(Player::One)),
+                record_territory.run_if(coop),
+            );
+    }
+}
+
+/// Something that happened during a game.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Recorded {
+    /// Play moved on to another phase.
+    Phase(Phase),
+    Constructed(IVec2, Structure),
+    Destroyed(IVec2, Structure),
+    /// A cannon turned, as it faces afterwards.
+    Turned(IVec2, Cannon),
+    /// A round shot fired on behalf of a player from one position at another.
+    Shot(Player, Vec3, Vec3),
+    /// An explosion and its radius.
+    Explosion(Vec3, f32),
+    /// A unit killed, by name.
+    Killed(String),
+    /// Points earned by a player, along with their total afterwards.
+    Score(Player, u32, u32),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    /// Seconds since the game began.
+    pub at: f32,
+    pub recorded: Recorded,
+}
+
+/// Everything recorded during the current game, oldest first. Entries are only
+/// ever added, until the next game begins.
+#[derive(Debug, Default, Resource)]
+pub struct EventJournal {
+    started: f32,
+    entries: Vec<Entry>,
+}
+
+impl EventJournal {
+    /// Starts over for a new game beginning at `now`.
+    pub fn open(&mut self, now: f32) {
+        self.started = now;
+        self.entries.clear();
+    }
+
+    /// Seconds from the beginning of the game until `now`.
+    pub fn elapsed(&self, now: f32) -> f32 {
+        (now - self.started).max(0.0)
+    }
+
+    pub fn record(&mut self, now: f32, recorded: Recorded) {
+        debug!(?recorded, "journal");
+
+        self.entries.push(Entry {
+            at: self.elapsed(now),
+            recorded,
+        });
+    }
+
+    /// Records points earned by a player, keeping track of their total.
+    pub fn score(&mut self, now: f32, player: Player, points: u32) {
+        let total = self.total(&player) + points;
+        self.record(now, Recorded::Score(player, points, total));
+    }
+
+    /// Takes back points the player has earned, as when paying for something,
+    /// recorded as a change of nothing down to what's left. False without
+    /// enough to pay.
+    pub fn spend(&mut self, now: f32, player: Player, points: u32) -> bool {
+        let total = self.total(&player);
+        if total < points {
+            return false;
+        }
+
+        self.record(now, Recorded::Score(player, 0, total - points));
+
+        true
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Puts a player's total back to what it was, as when restoring a
+    /// snapshot, recorded as a change of nothing.
+    pub fn restore_score(&mut self, now: f32, player: Player, total: u32) {
+        if self.total(&player) != total {
+            self.record(now, Recorded::Score(player, 0, total));
+        }
+    }
+
+    /// Entries recorded after the first `seen`, for following along without
+    /// going over the whole journal every frame.
+    pub fn since(&self, seen: usize) -> &[Entry] {
+        &self.entries[seen.min(self.entries.len())..]
+    }
+
+    /// Points the player has earned so far.
+    pub fn total(&self, player: &Player) -> u32 {
+        self.entries
+            .iter()
+            .rev()
+            .find_map(|entry| match &entry.recorded {
+                Recorded::Score(scorer, _, total) if scorer == player => Some(*total),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Saved games pick up with the scores they had.
+fn open_journal(
+    mut journal: ResMut<EventJournal>,
+    scenario: Option<Res<Scenario>>,
+    time: Res<Time>,
+) {
+    journal.open(time.elapsed_seconds());
+
+    let progress = scenario.and_then(|scenario| scenario.progress.clone());
+    for (player, total) in progress.map(|p| p.scores).unwrap_or_default() {
+        journal.restore_score(time.elapsed_seconds(), player, total);
+    }
+}
+
+fn record_phases(mut journal: ResMut<EventJournal>, phase: Res<State<Phase>>, time: Res<Time>) {
+    if phase.is_changed() {
+        journal.record(time.elapsed_seconds(), Recorded::Phase(phase.get().clone()));
+    }
+}
+
+fn record_construction(
+    mut journal: ResMut<EventJournal>,
+    mut construction: EventReader<ConstructionEvent>,
+    time: Res<Time>,
+) {
+    for event in construction.read() {
+        journal.record(
+            time.elapsed_seconds(),
+            Recorded::Constructed((*event.coordinates()).into(), event.structure().clone()),
+        );
+    }
+}
+
+fn record_turns(
+    mut journal: ResMut<EventJournal>,
+    mut turns: EventReader<CannonTurned>,
+    time: Res<Time>,
+) {
+    for event in turns.read() {
+        journal.record(
+            time.elapsed_seconds(),
+            Recorded::Turned((*event.coordinates()).into(), event.cannon().clone()),
+        );
+    }
+}
+
+fn record_destruction(
+    mut journal: ResMut<EventJournal>,
+    mut destruction: EventReader<DestructionEvent>,
+    time: Res<Time>,
+) {
+    for event in destruction.read() {
+        journal.record(
+            time.elapsed_seconds(),
+            Recorded::Destroyed((*event.coordinates()).into(), event.structure().clone()),
+        );
+    }
+}
+
+fn record_shots(
+    mut journal: ResMut<EventJournal>,
+    mut shots: EventReader<ShotFired>,
+    time: Res<Time>,
+) {
+    for shot in shots.read() {
+        journal.record(
+            time.elapsed_seconds(),
+            Recorded::Shot(shot.player(), shot.origin(), shot.target()),
+        );
+    }
+}
+
+fn record_explosions(
+    mut journal: ResMut<EventJournal>,
+    mut explosions: EventReader<ExplosionEvent>,
+    time: Res<Time>,
+) {
+    for explosion in explosions.read() {
+        journal.record(
+            time.elapsed_seconds(),
+            Recorded::Explosion(explosion.world(), explosion.radius()),
+        );
+    }
+}
+
+fn record_casualties(
+    mut journal: ResMut<EventJournal>,
+    mut casualties: EventReader<Casualty>,
+    rules: Res<Rules>,
+    time: Res<Time>,
+) {
+    for casualty in casualties.read() {
+        let now = time.elapsed_seconds();
+        journal.record(now, Recorded::Killed(casualty.name().to_owned()));
+        if let Some((player, points)) = casualty.award() {
+            for player in shared(&rules, player) {
+                journal.score(now, player, points);
+            }
+        }
+    }
+}
+
+/// Points earned all at once, for however many things it took.
+#[derive(Clone, Debug)]
+pub struct Scored {
+    player: Player,
+    points: u32,
+    /// Things destroyed together to earn them.
+    combo: usize,
+}
+
+impl Event for Scored {}
+
+impl Scored {
+    pub fn new(player: Player, points: u32, combo: usize) -> Self {
+        Self {
+            player,
+            points,
+            combo,
+        }
+    }
+}
+
+fn record_scores(
+    mut journal: ResMut<EventJournal>,
+    mut scored: EventReader<Scored>,
+    rules: Res<Rules>,
+    time: Res<Time>,
+) {
+    for scored in scored.read() {
+        debug!(player = ?scored.player, points = scored.points, combo = scored.combo, "scored");
+        let now = time.elapsed_seconds();
+        for player in shared(&rules, scored.player) {
+            journal.score(now, player, scored.points);
+        }
+    }
+}
+
+fn coop(rules: Res<Rules>) -> bool {
+    rules.coop
+}
+
+/// Playing together, whatever the players hold between them once they're done
+/// building scores for both.
+fn record_territory(
+    mut journal: ResMut<EventJournal>,
+    structures: Res<StructureLayers>,
+    time: Res<Time>,
+) {
+    let cells = structures
+        .shared_territory()
+        .iter()
+        .filter(|cell| **cell)
+        .count();
+    let points = (cells / TERRITORY_CELLS) as u32;
+    if points == 0 {
+        return;
+    }
+
+    info!(%cells, %points, "territory-scored");
+
+    let now = time.elapsed_seconds();
+    for player in [Player::One, Player::Two] {
+        journal.score(now, player, points);
+    }
+}
+
+/// Who shares in points earned by `player`, everyone when playing together.
+pub fn shared(rules: &Rules, player: Player) -> Vec<Player> {
+    if rules.coop {
+        vec![Player::One, Player::Two]
+    } else {
+        vec![player]
+    }
+}
@@ -0,0 +1,66 @@
+use crate::model::{Phase, Player, Rules};
+
+use super::{shared, EventJournal, Recorded};
+
+#[test]
+fn test_entries_timed_from_opening() {
+    let mut journal = EventJournal::default();
+    journal.record(1.0, Recorded::Phase(Phase::Choose(Player::One)));
+    journal.open(10.0);
+    journal.record(12.5, Recorded::Phase(Phase::Fortify(Player::One)));
+
+    let entries = journal.since(0);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].at, 2.5);
+    assert!(matches!(
+        entries[0].recorded,
+        Recorded::Phase(Phase::Fortify(Player::One))
+    ));
+}
+
+#[test]
+fn test_since_past_the_end_is_empty() {
+    let mut journal = EventJournal::default();
+    journal.record(0.0, Recorded::Killed("Ship".to_owned()));
+    journal.record(1.0, Recorded::Killed("Ship".to_owned()));
+
+    assert_eq!(journal.since(1).len(), 1);
+    assert!(journal.since(5).is_empty());
+}
+
+#[test]
+fn test_scores_total_per_player() {
+    let mut journal = EventJournal::default();
+    journal.score(1.0, Player::One, 10);
+    journal.score(2.0, Player::Two, 5);
+    journal.score(3.0, Player::One, 20);
+
+    assert_eq!(journal.total(&Player::One), 30);
+    assert_eq!(journal.total(&Player::Two), 5);
+    assert!(matches!(
+        journal.since(2)[0].recorded,
+        Recorded::Score(Player::One, 20, 30)
+    ));
+}
+
+#[test]
+fn test_spending_points() {
+    let mut journal = EventJournal::default();
+    journal.score(0.0, Player::One, 10);
+
+    assert!(!journal.spend(1.0, Player::One, 15));
+    assert!(journal.spend(1.0, Player::One, 4));
+    assert_eq!(journal.total(&Player::One), 6);
+    assert!(!journal.spend(1.0, Player::Two, 1));
+}
+
+#[test]
+fn test_points_shared_playing_together() {
+    assert_eq!(shared(&Rules::default(), Player::Two), vec![Player::Two]);
+
+    let rules = Rules {
+        coop: true,
+        ..Rules::default()
+    };
+    assert_eq!(shared(&rules, Player::Two), vec![Player::One, Player::Two]);
+}
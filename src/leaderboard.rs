@@ -0,0 +1,233 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    controls::{typing, Action, Controls},
+    helpers::GamePlayLifetime,
+    model::{AppState, Difficulty},
+    profile::Profile,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Where every rating starts, level with the computer on normal.
+const STARTING_RATING: f32 = 1200.0;
+
+/// Most a rating moves after a single match.
+const RATING_K: f32 = 32.0;
+
+/// Seeds kept, dropping those with the lowest best scores.
+const SEEDS_KEPT: usize = 100;
+
+/// Seeds listed on the leaderboard.
+const SEEDS_SHOWN: usize = 10;
+
+/// Shows ratings against the computer and the best played on each seed,
+/// kept in the profile.
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            toggle_leaderboard
+                .run_if(in_state(AppState::Game))
+                .run_if(not(typing)),
+        );
+    }
+}
+
+/// How the computer plays at each difficulty, as a rating to play against.
+pub fn computer_rating(difficulty: Difficulty) -> f32 {
+    match difficulty {
+        Difficulty::Easy => 1000.0,
+        Difficulty::Normal => 1200.0,
+        Difficulty::Hard => 1400.0,
+    }
+}
+
+/// Chance of winning against an opponent with the other rating.
+pub fn expected(rating: f32, opponent: f32) -> f32 {
+    1.0 / (1.0 + 10f32.powf((opponent - rating) / 400.0))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rating {
+    pub difficulty: Difficulty,
+    pub rating: f32,
+    pub matches: u32,
+}
+
+impl Rating {
+    fn new(difficulty: Difficulty) -> Self {
+        Self {
+            difficulty,
+            rating: STARTING_RATING,
+            matches: 0,
+        }
+    }
+
+    fn update(&mut self, won: bool) {
+        let score = if won { 1.0 } else { 0.0 };
+        let opponent = computer_rating(self.difficulty);
+        self.rating += RATING_K * (score - expected(self.rating, opponent));
+        self.matches += 1;
+    }
+}
+
+/// The best anyone's done playing a seed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeedRecord {
+    pub seed: u32,
+    pub best_score: u32,
+    /// Seconds, nothing until it's been won.
+    pub fastest_win: Option<f32>,
+}
+
+/// How a finished match against the computer went.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Outcome {
+    pub difficulty: Difficulty,
+    pub seed: u32,
+    pub won: bool,
+    pub score: u32,
+    pub seconds: f32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Leaderboard {
+    pub ratings: Vec<Rating>,
+    /// Best scores first.
+    pub seeds: Vec<SeedRecord>,
+}
+
+impl Leaderboard {
+    pub fn rating(&self, difficulty: Difficulty) -> Option<&Rating> {
+        self.ratings.iter().find(|r| r.difficulty == difficulty)
+    }
+
+    pub fn record(&mut self, outcome: &Outcome) {
+        let rating = match self
+            .ratings
+            .iter_mut()
+            .position(|r| r.difficulty == outcome.difficulty)
+        {
+            Some(index) => &mut self.ratings[index],
+            None => {
+                self.ratings.push(Rating::new(outcome.difficulty));
+                self.ratings.last_mut().expect("rating")
+            }
+        };
+        rating.update(outcome.won);
+
+        let win = outcome.won.then_some(outcome.seconds);
+        match self.seeds.iter_mut().find(|s| s.seed == outcome.seed) {
+            Some(record) => {
+                record.best_score = record.best_score.max(outcome.score);
+                record.fastest_win = match (record.fastest_win, win) {
+                    (Some(fastest), Some(win)) => Some(fastest.min(win)),
+                    (fastest, win) => fastest.or(win),
+                };
+            }
+            None => self.seeds.push(SeedRecord {
+                seed: outcome.seed,
+                best_score: outcome.score,
+                fastest_win: win,
+            }),
+        }
+
+        self.seeds.sort_by(|a, b| b.best_score.cmp(&a.best_score));
+        self.seeds.truncate(SEEDS_KEPT);
+    }
+
+    fn describe(&self) -> Vec<String> {
+        let mut lines = vec!["Leaderboard".to_owned()];
+
+        for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+            let rating = match self.rating(difficulty) {
+                Some(rating) => format!("{:.0} ({} played)", rating.rating, rating.matches),
+                None => "-".to_owned(),
+            };
+            lines.push(format!("{:?}: {}", difficulty, rating));
+        }
+
+        for record in self.seeds.iter().take(SEEDS_SHOWN) {
+            let fastest = match record.fastest_win {
+                Some(seconds) => format!("{}:{:02}", seconds as u32 / 60, seconds as u32 % 60),
+                None => "-".to_owned(),
+            };
+            lines.push(format!(
+                "Seed {}: best {}, fastest win {}",
+                record.seed, record.best_score, fastest
+            ));
+        }
+
+        lines
+    }
+}
+
+#[derive(Component)]
+struct LeaderboardPanel;
+
+fn toggle_leaderboard(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    controls: Res<Controls>,
+    profile: Res<Profile>,
+    panels: Query<Entity, With<LeaderboardPanel>>,
+) {
+    if !controls.just_pressed(&keys, Action::Leaderboard) {
+        return;
+    }
+
+    if !panels.is_empty() {
+        for entity in &panels {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    commands
+        .spawn((
+            Name::new("Leaderboard"),
+            LeaderboardPanel,
+            GamePlayLifetime,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    top: Val::Percent(20.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(6.),
+                        padding: UiRect::all(Val::Px(12.)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0., 0., 0., 0.7).into(),
+                    ..default()
+                })
+                .with_children(|panel| {
+                    for line in profile.leaderboard.describe() {
+                        panel.spawn(TextBundle::from_section(
+                            line,
+                            TextStyle {
+                                font_size: 20.,
+                                color: Color::WHITE,
+                                ..default()
+                            },
+                        ));
+                    }
+                });
+        });
+}
@@ -0,0 +1,75 @@
+use crate::model::Difficulty;
+
+use super::{computer_rating, expected, Leaderboard, Outcome, STARTING_RATING};
+
+fn outcome(seed: u32, won: bool, score: u32, seconds: f32) -> Outcome {
+    Outcome {
+        difficulty: Difficulty::Normal,
+        seed,
+        won,
+        score,
+        seconds,
+    }
+}
+
+#[test]
+fn test_evenly_matched_is_a_coin_toss() {
+    assert_eq!(expected(1200.0, 1200.0), 0.5);
+    assert!(expected(1400.0, 1200.0) > 0.5);
+    assert!(expected(1000.0, 1200.0) < 0.5);
+}
+
+#[test]
+fn test_winning_raises_rating_and_losing_lowers_it() {
+    let mut leaderboard = Leaderboard::default();
+
+    leaderboard.record(&outcome(1, true, 20, 300.0));
+    let rating = leaderboard.rating(Difficulty::Normal).unwrap();
+    assert_eq!(rating.rating, STARTING_RATING + 16.0);
+    assert_eq!(rating.matches, 1);
+
+    leaderboard.record(&outcome(1, false, 10, 200.0));
+    let rating = leaderboard.rating(Difficulty::Normal).unwrap();
+    assert!(rating.rating < STARTING_RATING + 16.0);
+    assert_eq!(rating.matches, 2);
+
+    assert!(leaderboard.rating(Difficulty::Hard).is_none());
+}
+
+#[test]
+fn test_beating_harder_computers_is_worth_more() {
+    let mut easy = Leaderboard::default();
+    easy.record(&Outcome {
+        difficulty: Difficulty::Easy,
+        ..outcome(1, true, 0, 0.0)
+    });
+
+    let mut hard = Leaderboard::default();
+    hard.record(&Outcome {
+        difficulty: Difficulty::Hard,
+        ..outcome(1, true, 0, 0.0)
+    });
+
+    assert!(computer_rating(Difficulty::Hard) > computer_rating(Difficulty::Easy));
+    assert!(
+        hard.rating(Difficulty::Hard).unwrap().rating
+            > easy.rating(Difficulty::Easy).unwrap().rating
+    );
+}
+
+#[test]
+fn test_keeps_best_score_and_fastest_win_per_seed() {
+    let mut leaderboard = Leaderboard::default();
+
+    leaderboard.record(&outcome(7, false, 30, 100.0));
+    assert_eq!(leaderboard.seeds[0].fastest_win, None);
+
+    leaderboard.record(&outcome(7, true, 20, 400.0));
+    leaderboard.record(&outcome(7, true, 10, 500.0));
+    leaderboard.record(&outcome(3, true, 50, 200.0));
+
+    assert_eq!(leaderboard.seeds.len(), 2);
+    assert_eq!(leaderboard.seeds[0].seed, 3);
+    assert_eq!(leaderboard.seeds[1].best_score, 30);
+    assert_eq!(leaderboard.seeds[1].fastest_win, Some(400.0));
+}
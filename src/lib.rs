@@ -0,0 +1,7 @@
+//! Rules of the game that don't need anything drawn, so they can be tested on
+//! their own and shared with anything playing without a window, like a
+//! dedicated server. Everything shown on screen stays with the game itself.
+
+pub mod ballistics;
+pub mod model;
+pub mod territory;
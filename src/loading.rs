@@ -0,0 +1,62 @@
+use bevy::{asset::RecursiveDependencyLoadState, prelude::*};
+
+use crate::{model::AppState, ui::LoadingProgress};
+
+/// Holds off on the menu until everything loaded at startup is ready, so
+/// nothing is ever spawned without its models or textures.
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RequiredAssets>()
+            .add_systems(Update, track_loading.run_if(in_state(AppState::Loading)));
+    }
+}
+
+/// Assets that have to be loaded before anything else happens, added to by
+/// whatever loads them at startup.
+#[derive(Debug, Default, Resource)]
+pub struct RequiredAssets(Vec<UntypedHandle>);
+
+impl RequiredAssets {
+    pub fn require<A: Asset>(&mut self, handle: &Handle<A>) {
+        self.0.push(handle.clone().untyped());
+    }
+}
+
+/// How many of the assets are done with, loaded or failed.
+fn finished(asset_server: &AssetServer, required: &RequiredAssets) -> usize {
+    required
+        .0
+        .iter()
+        .filter(
+            |handle| match asset_server.recursive_dependency_load_state(handle.id()) {
+                RecursiveDependencyLoadState::Loaded => true,
+                RecursiveDependencyLoadState::Failed => {
+                    warn!(path = ?handle.path(), "error loading asset");
+                    true
+                }
+                _ => false,
+            },
+        )
+        .count()
+}
+
+/// Moves on to the menu once every required asset is done with. Those that
+/// fail are left out rather than keeping everyone waiting.
+fn track_loading(
+    asset_server: Res<AssetServer>,
+    required: Res<RequiredAssets>,
+    mut loading: ResMut<LoadingProgress>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let total = required.0.len();
+    let finished = finished(&asset_server, &required);
+
+    loading.set("Loading", finished as f32 / total.max(1) as f32);
+
+    if finished == total {
+        info!(assets = total, "assets-ready");
+        next_state.set(AppState::Menu);
+    }
+}
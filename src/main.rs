@@ -1,4 +1,3 @@
-use bevy::window::WindowResolution;
 use bevy::{
     input::common_conditions::input_toggle_active,
     pbr::wireframe::{WireframeConfig, WireframePlugin},
@@ -8,17 +7,51 @@ use bevy_hanabi::prelude::*;
 use bevy_mod_picking::prelude::*;
 use bevy_rapier3d::prelude::*;
 use bevy_tweening::TweeningPlugin;
+use castle::model;
 use clap::Parser;
-use model::Settings;
+use display::{DisplayMode, DisplaySettings};
+use model::{Difficulty, NoiseSettings, Rules, Settings, Symmetry, TerrainStyle};
+use scenario::Scenario;
+use std::path::PathBuf;
 
+mod ai;
+mod announcer;
+mod autosave;
 mod building;
+mod buoyancy;
 mod camera;
+mod challenge;
+mod collision;
+mod controls;
+mod damage;
+mod detail;
 mod devel;
+mod display;
+mod editor;
 mod firing;
 mod helpers;
-mod model;
+mod journal;
+mod leaderboard;
+mod loading;
+mod network;
+mod observer;
+mod profile;
+mod replay;
+mod scenario;
+mod scripting;
+mod simulation;
+mod snapshot;
+mod speed;
+mod summary;
+mod telemetry;
 mod terrain;
 mod ui;
+mod units;
+mod weather;
+
+/// Logs shown unless `--log-filter` says otherwise.
+const LOG_FILTER: &str =
+    "castle=debug,wgpu=error,naga=warn,bevy_hanabi=warn,bevy_winit=warn,bevy_window=warn";
 
 #[derive(Parser, Resource)]
 struct Options {
@@ -26,6 +59,97 @@ struct Options {
     seed: Option<u32>,
     #[arg(long, default_value_t = 64)]
     size: u32,
+    #[arg(long, value_enum, default_value_t = TerrainStyle::default())]
+    style: TerrainStyle,
+    #[arg(long, value_enum, default_value_t = Symmetry::default())]
+    symmetry: Symmetry,
+    #[arg(long, default_value_t = 1)]
+    octaves: usize,
+    #[arg(long, default_value_t = 1.0)]
+    frequency: f64,
+    #[arg(long, value_delimiter = ',', allow_negative_numbers = true, default_values_t = [-1.0, -0.5, 0.1, 1.0])]
+    terraces: Vec<f64>,
+    #[arg(long, default_value_t = 1.0)]
+    height_scale: f64,
+    #[arg(long)]
+    scenario: Option<PathBuf>,
+    #[arg(long)]
+    challenges: Option<PathBuf>,
+    /// Watches a recorded game rather than playing.
+    #[arg(long)]
+    replay: Option<PathBuf>,
+    /// Hosts the game for another player to join on this port.
+    #[arg(long)]
+    host: Option<u16>,
+    /// Joins a game hosted elsewhere, as `address:port`, or one found on the
+    /// local network when no address is given.
+    #[arg(long, conflicts_with_all = ["host", "replay"])]
+    join: Option<Option<String>>,
+    /// Watches the joined game rather than playing in it.
+    #[arg(long, requires = "join")]
+    spectate: bool,
+    /// Appends a record of every match played to this file, as JSON lines.
+    #[arg(long)]
+    telemetry: Option<PathBuf>,
+    /// Plays this many turns of scripted input without a window, checking
+    /// nothing goes wrong along the way, then exits.
+    #[arg(long, conflicts_with_all = ["host", "join", "replay"])]
+    simulate: Option<u32>,
+    #[arg(long, value_enum, default_value_t = Difficulty::default())]
+    difficulty: Difficulty,
+    #[arg(long, value_enum)]
+    ai: Option<Difficulty>,
+    #[arg(long, default_value_t = false)]
+    fused_shells: bool,
+    #[arg(long, default_value_t = false)]
+    line_of_sight: bool,
+    /// Shots leave the walls and cannons of whoever fired them alone.
+    #[arg(long, default_value_t = false)]
+    no_friendly_fire: bool,
+    /// Cannons are only ever fired one at a time.
+    #[arg(long, default_value_t = false)]
+    no_volleys: bool,
+    /// Most shots each cannon gets a round.
+    #[arg(long)]
+    ammo: Option<u32>,
+    /// Points it costs to repair a damaged wall or cannon, no repairs without.
+    #[arg(long)]
+    repair_cost: Option<u32>,
+    /// Round from which every hit destroys what it hits.
+    #[arg(long)]
+    sudden_death: Option<u32>,
+    /// Both players defend one island together against the ships.
+    #[arg(long, default_value_t = false)]
+    coop: bool,
+    /// Pieces dealt to each player every time they fortify.
+    #[arg(long)]
+    pieces: Option<u32>,
+    #[arg(long, default_value_t = false)]
+    vsync: bool,
+    #[arg(long, value_enum, default_value_t = DisplayMode::default())]
+    display: DisplayMode,
+    /// The same as `--display fullscreen`.
+    #[arg(long, conflicts_with = "display")]
+    fullscreen: bool,
+    /// Leaves out the numbers shown wherever damage is done.
+    #[arg(long, default_value_t = false)]
+    no_damage_numbers: bool,
+    /// Keeps the water still and explosions small, for anyone bothered by
+    /// motion.
+    #[arg(long, default_value_t = false)]
+    reduce_motion: bool,
+    /// Size of everything on screen, as a multiple of the usual.
+    #[arg(long, default_value_t = 1.0)]
+    ui_scale: f32,
+    /// Size of text on top of `--ui-scale`, as a multiple of the usual.
+    #[arg(long, default_value_t = 1.0)]
+    font_scale: f32,
+    /// Size of the window, as `WIDTHxHEIGHT`.
+    #[arg(long, value_parser = display::parse_resolution, default_value = "1312x768")]
+    resolution: UVec2,
+    /// Which logs are shown, in the same form as `RUST_LOG`.
+    #[arg(long, default_value = LOG_FILTER)]
+    log_filter: String,
 }
 
 impl Options {
@@ -33,56 +157,229 @@ impl Options {
         self.seed.map(model::Seed::new)
     }
 
-    fn settings(self) -> Settings {
+    fn display(&self) -> DisplaySettings {
+        DisplaySettings {
+            vsync: self.vsync,
+            mode: if self.fullscreen {
+                DisplayMode::Fullscreen
+            } else {
+                self.display
+            },
+            resolution: self.resolution,
+            damage_numbers: !self.no_damage_numbers,
+            ui_scale: self.ui_scale,
+            font_scale: self.font_scale,
+            reduce_motion: self.reduce_motion,
+        }
+    }
+
+    /// Scenarios that come with their own rules are played by those instead.
+    fn rules(&self, scenario: Option<&Scenario>) -> Rules {
+        if let Some(rules) = scenario.and_then(|scenario| scenario.rules.clone()) {
+            return rules;
+        }
+
+        Rules {
+            fused_shells: self.fused_shells,
+            line_of_sight: self.line_of_sight,
+            friendly_fire: !self.no_friendly_fire,
+            volleys: !self.no_volleys,
+            ammo: self.ammo,
+            repair_cost: self.repair_cost,
+            sudden_death: self.sudden_death,
+            coop: self.coop,
+            pieces: self.pieces,
+        }
+    }
+
+    fn scenario(&self) -> Option<Scenario> {
+        self.scenario
+            .as_ref()
+            .map(|path| match Scenario::load(path) {
+                Ok(scenario) => scenario,
+                Err(e) => exit_with(format!("error loading scenario {:?}: {}", path, e)),
+            })
+    }
+
+    fn replay(&self) -> Option<replay::Replay> {
+        self.replay
+            .as_ref()
+            .map(|path| match replay::Replay::load(path) {
+                Ok(replay) => replay,
+                Err(e) => exit_with(format!("error loading replay {:?}: {}", path, e)),
+            })
+    }
+
+    /// Scenarios take precedence over the seed and size given on the command
+    /// line, as their heights and structures only make sense for their own.
+    fn settings(self, scenario: Option<&Scenario>) -> Settings {
+        let (seed, size) = match scenario {
+            Some(scenario) => (
+                Some(model::Seed::new(scenario.seed)),
+                UVec2::new(scenario.size.0, scenario.size.1),
+            ),
+            None => (self.seed(), UVec2::new(self.size, self.size)),
+        };
+
         Settings {
-            seed: self.seed().unwrap_or_else(|| model::Seed::system_time()),
-            size: UVec2::new(self.size, self.size),
-            ..default()
+            seed: seed.unwrap_or_else(|| model::Seed::system_time()),
+            size,
+            style: self.style,
+            symmetry: self.symmetry,
+            noise: NoiseSettings {
+                octaves: self.octaves,
+                frequency: self.frequency,
+                terraces: self.terraces,
+                height_scale: self.height_scale,
+            },
+            ai: self
+                .ai
+                .map(|difficulty| model::AiSettings::new(model::Player::Two, difficulty)),
         }
     }
 }
 
 fn main() {
     let options = Options::parse();
+    if let Some(turns) = options.simulate {
+        let rules = options.rules(None);
+        // Both players are scripted, there's nobody for the computer to play.
+        let settings = Settings {
+            ai: None,
+            ..options.settings(None)
+        };
+        std::process::exit(simulate(turns, settings, rules));
+    }
 
-    App::new()
-        .add_plugins(
-            DefaultPlugins
-                .set(bevy::log::LogPlugin {
-                    filter: "castle=debug,wgpu=error,naga=warn,bevy_hanabi=warn,bevy_winit=warn,bevy_window=warn"
-                        .to_string(),
-                    ..default()
-                })
-                .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        title: "Castle".to_string(),
-                        resolution: WindowResolution::new(1024. + 256. + 32., 768.0),
-                        present_mode: bevy::window::PresentMode::AutoNoVsync,
-                        ..default()
-                    }),
-                    ..default()
-                }),
-        )
-        .add_plugins(HanabiPlugin)
-        .add_plugins(DefaultPickingPlugins)
-        .add_plugins(TweeningPlugin)
-        .add_plugins(WireframePlugin)
-        .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
-        .add_plugins(bevy_inspector_egui::quick::WorldInspectorPlugin::new().run_if(input_toggle_active(false, KeyCode::KeyI)))
-        .add_plugins(helpers::HelpersPlugin)
-        .add_plugins(AppStatePlugin)
-        .add_plugins(camera::CameraPlugin)
-        .add_plugins(devel::DeveloperPlugin)
-        .add_plugins(building::BuildingPlugin)
-        .add_plugins(firing::FiringPlugin)
-        .add_plugins(terrain::TerrainPlugin)
-        .add_systems(Update, progress_game)
-        .add_systems(PostUpdate, bevy::window::close_on_esc)
-        .insert_resource(ClearColor(Color::hex("152238").unwrap()))
-        .insert_resource(WireframeConfig::default())
-        .insert_resource(options.settings())
-        .insert_state(model::Phase::default())
-        .run();
+    let display = options.display();
+    let log_filter = options.log_filter.clone();
+    let replay = options.replay();
+    // Replays are played on the terrain they were recorded on.
+    let scenario = match &replay {
+        Some(replay) => Some(replay.scenario.clone()),
+        None => options.scenario(),
+    };
+    let challenges = options.challenges.clone();
+    let host = options.host;
+    let join = options.join.clone();
+    let spectate = options.spectate;
+    let telemetry = options.telemetry.clone();
+    let rules = options.rules(scenario.as_ref());
+    let director = units::WaveDirector::new(options.difficulty);
+    let settings = options.settings(scenario.as_ref());
+
+    let mut app = App::new();
+
+    app.add_plugins(
+        DefaultPlugins
+            .set(bevy::log::LogPlugin {
+                filter: log_filter,
+                ..default()
+            })
+            .set(WindowPlugin {
+                primary_window: Some(display.window()),
+                ..default()
+            })
+            .set(AssetPlugin {
+                // Models and textures are reloaded as they're saved, in
+                // development only.
+                watch_for_changes_override: Some(cfg!(debug_assertions)),
+                ..default()
+            }),
+    )
+    .add_plugins(HanabiPlugin)
+    .add_plugins(WireframePlugin)
+    .add_plugins(
+        bevy_inspector_egui::quick::WorldInspectorPlugin::new()
+            .run_if(input_toggle_active(false, KeyCode::KeyI)),
+    )
+    .add_plugins(GamePlugin)
+    .add_plugins(autosave::AutosavePlugin)
+    .add_plugins(devel::DeveloperPlugin)
+    .add_plugins(editor::EditorPlugin)
+    .add_systems(PostUpdate, bevy::window::close_on_esc)
+    .insert_resource(ClearColor(Color::hex("152238").unwrap()))
+    .insert_resource(WireframeConfig::default())
+    .insert_resource(settings)
+    .insert_resource(rules)
+    .insert_resource(director)
+    .insert_resource(display);
+
+    if let Some(directory) = &challenges {
+        app.insert_resource(challenge::Challenges::load(directory));
+    }
+
+    if let Some(path) = telemetry {
+        app.insert_resource(telemetry::Telemetry::new(path));
+    }
+
+    if let Some(replay) = replay {
+        app.insert_resource(replay::Playback::new(replay));
+    }
+
+    if let Some(port) = host {
+        match network::Host::listen(port) {
+            Ok(host) => app.insert_resource(host),
+            Err(e) => exit_with(format!("error hosting game on port {}: {}", port, e)),
+        };
+    }
+
+    if let Some(address) = join {
+        app.insert_resource(network::Client::new(address));
+        if spectate {
+            app.insert_resource(network::LocalPlayer::spectator());
+        }
+    }
+
+    if let Some(scenario) = scenario {
+        if let Some(timers) = scenario.timers.clone() {
+            app.insert_resource(timers);
+        }
+        app.insert_resource(scenario);
+    }
+
+    app.run();
+}
+
+/// Everything played, whether in a window or without one, leaving out what's
+/// only for someone sitting in front of it like developer tools and saving.
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(DefaultPickingPlugins)
+            .add_plugins(TweeningPlugin)
+            .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
+            .add_plugins(helpers::HelpersPlugin)
+            .add_plugins(AppStatePlugin)
+            .add_plugins(camera::CameraPlugin)
+            .add_plugins(challenge::ChallengePlugin)
+            .add_plugins(controls::ControlsPlugin)
+            .add_plugins(damage::DamagePlugin)
+            .add_plugins(detail::DetailPlugin)
+            .add_plugins(display::DisplayPlugin)
+            .add_plugins(ai::AiPlugin)
+            .add_plugins(announcer::AnnouncerPlugin)
+            .add_plugins(building::BuildingPlugin)
+            .add_plugins(buoyancy::BuoyancyPlugin)
+            .add_plugins(firing::FiringPlugin)
+            .add_plugins(journal::JournalPlugin)
+            .add_plugins(leaderboard::LeaderboardPlugin)
+            .add_plugins(loading::LoadingPlugin)
+            .add_plugins(network::NetworkPlugin)
+            .add_plugins(observer::ObserverPlugin)
+            .add_plugins(profile::ProfilePlugin)
+            .add_plugins(replay::ReplayPlugin)
+            .add_plugins(scripting::ScriptingPlugin)
+            .add_plugins(snapshot::SnapshotPlugin)
+            .add_plugins(speed::SpeedPlugin)
+            .add_plugins(summary::SummaryPlugin)
+            .add_plugins(telemetry::TelemetryPlugin)
+            .add_plugins(terrain::TerrainPlugin)
+            .add_plugins(ui::UiPlugin)
+            .add_plugins(units::UnitsPlugin)
+            .add_plugins(weather::WeatherPlugin);
+    }
 }
 
 pub struct AppStatePlugin;
@@ -91,33 +388,109 @@ impl Plugin for AppStatePlugin {
     fn build(&self, app: &mut App) {
         app.insert_state(model::AppState::default())
             .insert_state(model::Activity::default())
-            .add_systems(Startup, enter_game)
-            .add_systems(OnEnter(model::AppState::Menu), enter_game);
+            .insert_state(model::Phase::default())
+            .init_resource::<model::PhaseTimers>()
+            .init_resource::<model::GameRng>()
+            .register_type::<model::Player>()
+            .register_type::<model::Coordinates>()
+            .register_type::<model::Health>()
+            .add_systems(
+                Update,
+                time_phases
+                    .run_if(in_state(model::AppState::Game))
+                    .run_if(not(challenge::in_challenge))
+                    .run_if(not(summary::showing_summary))
+                    .run_if(not(network::following)),
+            )
+            .add_systems(
+                Update,
+                progress_game
+                    .after(building::ConstructionSet::Apply)
+                    .run_if(not(challenge::in_challenge))
+                    .run_if(not(network::following)),
+            )
+            .add_systems(
+                OnEnter(model::AppState::Menu),
+                enter_game
+                    .run_if(not(resource_exists::<challenge::Challenges>))
+                    .run_if(not(network::networked))
+                    .run_if(not(ai::choosing_opponent)),
+            );
     }
 }
 
+/// Gives up before the game has started, when there's no log to report to yet.
+fn exit_with(message: String) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1)
+}
+
 fn enter_game(
-    mut commands: Commands,
     mut app_state: ResMut<NextState<model::AppState>>,
     mut activity: ResMut<NextState<model::Activity>>,
 ) {
-    commands.insert_resource(model::Settings::default());
-    app_state.set(model::AppState::Game);
+    app_state.set(model::AppState::Generating);
     activity.set(model::Activity::Observing);
-    commands.spawn(iyes_perf_ui::PerfUiCompleteBundle::default());
 }
 
+/// Moves on to the next phase once whoever's turn it is has ended it, only the
+/// once however many times they have.
 fn progress_game(
     phase: Res<State<model::Phase>>,
     mut next_phase: ResMut<NextState<model::Phase>>,
-    mut modified: EventReader<building::ConstructionEvent>,
+    mut turns: EventReader<building::TurnEnded>,
 ) {
-    for event in modified.read() {
-        println!("{:?}", event);
-        println!("{:?}", phase);
-        let before = &phase.get();
+    let before = phase.get();
+    let ended = turns
+        .read()
+        .filter(|turn| *turn.player() == before.player())
+        .count();
+    if ended > 0 {
         let after = before.next();
         info!("{:?} -> {:?}", before, after);
         next_phase.set(after);
     }
 }
+
+/// Advances to the next phase once the current one has run for as long as the
+/// timers allow. Untimed phases only end through play.
+fn time_phases(
+    time: Res<Time>,
+    timers: Res<model::PhaseTimers>,
+    phase: Res<State<model::Phase>>,
+    mut next_phase: ResMut<NextState<model::Phase>>,
+    mut remaining: Local<Option<f32>>,
+) {
+    if phase.is_changed() {
+        *remaining = timers.duration(phase.get());
+    }
+
+    if let Some(seconds) = remaining.as_mut() {
+        *seconds -= time.delta_seconds();
+        if *seconds <= 0.0 {
+            let after = phase.get().next();
+            info!("{:?} -> {:?} (timer)", phase.get(), after);
+            next_phase.set(after);
+            *remaining = None;
+        }
+    }
+}
+
+/// Plays `turns` turns of scripted input on the terrain for `settings`,
+/// returning the exit code: failure when anything went wrong or play got stuck.
+fn simulate(turns: u32, settings: Settings, rules: Rules) -> i32 {
+    let seed = u32::from(settings.seed());
+    let mut app = simulation::headless(settings, rules, turns as usize);
+    app.add_plugins(bevy::log::LogPlugin::default());
+
+    simulation::run(&mut app, turns);
+
+    let outcome = app.world.resource::<simulation::Outcome>();
+    info!(%seed, "simulated {}", outcome.describe());
+
+    if outcome.turns == turns && outcome.violations.is_empty() {
+        0
+    } else {
+        1
+    }
+}
@@ -1,7 +1,10 @@
 use bevy::{
-    ecs::{component::Component, schedule::States, system::Resource},
+    ecs::{component::Component, reflect::ReflectComponent, schedule::States, system::Resource},
     math::{IVec2, UVec2},
+    reflect::Reflect,
 };
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
 mod grid;
 #[cfg(test)]
@@ -14,9 +17,10 @@ pub const GROUND_DEPTH: f32 = 0.2;
 pub const WALL_HEIGHT: f32 = 0.6;
 pub const WALL_WIDTH: f32 = 0.4;
 pub const TILE_SIZE: f32 = 1.0;
-pub const HEIGHT_SCALE: f32 = 1.0;
 pub const ROUND_SHOT_DIAMETER: f32 = 0.25;
 pub const BRICK_COLOR: &str = "e7444a";
+pub const WALL_HP: f32 = 100.0;
+pub const CANNON_HP: f32 = 150.0;
 
 // We base all the math on a desired time of flight that
 // looks appropriate for the distance.
@@ -62,7 +66,8 @@ impl From<u32> for Seed<u32> {
     }
 }
 
-#[derive(Component, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Component, Copy, Clone, Debug, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component)]
 pub struct Coordinates(IVec2);
 
 impl Coordinates {
@@ -83,7 +88,10 @@ impl From<Coordinates> for IVec2 {
     }
 }
 
-#[derive(Component, Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
+#[derive(
+    Component, Copy, Clone, Default, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect,
+)]
+#[reflect(Component)]
 pub enum Player {
     #[default]
     One,
@@ -91,13 +99,20 @@ pub enum Player {
 }
 
 impl Player {
-    #[allow(dead_code)]
     pub fn next(&self) -> Self {
         match self {
             Player::One => Player::Two,
             Player::Two => Player::One,
         }
     }
+
+    /// Color the player is shown in, as hex.
+    pub fn hex(&self) -> &'static str {
+        match self {
+            Player::One => "3a86ff",
+            Player::Two => "ffbe0b",
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, States, Default)]
@@ -106,10 +121,13 @@ pub enum Activity {
     Observing,
     Building,
     Firing,
+    Editing,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, States)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, States, Serialize, Deserialize)]
 pub enum Phase {
+    /// Each player picks where their castle goes, only at the start.
+    Choose(Player),
     Fortify(Player),
     Arm(Player),
     Target(Player),
@@ -117,13 +135,15 @@ pub enum Phase {
 
 impl Default for Phase {
     fn default() -> Self {
-        Phase::Fortify(Player::default())
+        Phase::Choose(Player::default())
     }
 }
 
 impl Phase {
     pub fn next(&self) -> Self {
         match self {
+            Self::Choose(Player::One) => Self::Choose(Player::Two),
+            Self::Choose(Player::Two) => Self::Fortify(Player::One),
             Self::Fortify(Player::One) => Self::Arm(Player::One),
             Self::Arm(Player::One) => Self::Fortify(Player::Two),
             Self::Fortify(Player::Two) => Self::Arm(Player::Two),
@@ -133,9 +153,9 @@ impl Phase {
         }
     }
 
-    #[allow(dead_code)]
     pub fn player(&self) -> Player {
         match self {
+            Self::Choose(player) => player.clone(),
             Self::Fortify(player) => player.clone(),
             Self::Arm(player) => player.clone(),
             Self::Target(player) => player.clone(),
@@ -143,17 +163,306 @@ impl Phase {
     }
 }
 
+/// How long each kind of phase lasts, in seconds. Phases without a duration
+/// last until they're ended through play.
+#[derive(Debug, Clone, Default, PartialEq, Resource, Serialize, Deserialize)]
+pub struct PhaseTimers {
+    #[serde(default)]
+    pub fortify: Option<f32>,
+    #[serde(default)]
+    pub arm: Option<f32>,
+    #[serde(default)]
+    pub target: Option<f32>,
+}
+
+impl PhaseTimers {
+    pub fn duration(&self, phase: &Phase) -> Option<f32> {
+        match phase {
+            Phase::Choose(_) => None,
+            Phase::Fortify(_) => self.fortify,
+            Phase::Arm(_) => self.arm,
+            Phase::Target(_) => self.target,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash, States, Default)]
 pub enum AppState {
+    /// Waiting on models and textures, before anything else.
     #[default]
+    Loading,
     Menu,
+    /// Waiting on the terrain, which is generated in the background.
+    Generating,
     Game,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum TerrainStyle {
+    /// Unmodified noise, usually one or two large landmasses.
+    #[default]
+    Continent,
+    /// Several separate landmasses so each player starts on their own island.
+    Islands,
+}
+
+/// How hard the attacking waves press each castle, chosen when starting a game.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum,
+)]
+pub enum Difficulty {
+    /// Fewer, weaker ships and more time to build.
+    Easy,
+    #[default]
+    Normal,
+    /// More and tougher ships with less time to repair between them.
+    Hard,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Symmetry {
+    #[default]
+    None,
+    /// Reflected across the diagonal running between the two empty corners.
+    Mirror,
+    /// Rotated 180 degrees around the center of the map.
+    Rotational,
+}
+
+/// Knobs for the noise behind terrain generation. The defaults reproduce a
+/// single octave of Perlin noise terraced into water, beach, and two levels of
+/// land.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoiseSettings {
+    pub octaves: usize,
+    pub frequency: f64,
+    pub terraces: Vec<f64>,
+    pub height_scale: f64,
+}
+
+impl Default for NoiseSettings {
+    fn default() -> Self {
+        Self {
+            octaves: 1,
+            frequency: 1.0,
+            terraces: vec![-1.0, -0.5, 0.1, 1.0],
+            height_scale: 1.0,
+        }
+    }
+}
+
+impl NoiseSettings {
+    /// Terracing needs at least two control points, so fewer than that falls
+    /// back to the defaults.
+    pub fn terraces(&self) -> Vec<f64> {
+        if self.terraces.len() < 2 {
+            Self::default().terraces
+        } else {
+            self.terraces.clone()
+        }
+    }
+}
+
+/// How much damage something can take before it's destroyed.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component)]
+pub struct Health {
+    hp: f32,
+}
+
+impl Health {
+    pub fn new(hp: f32) -> Self {
+        Self { hp }
+    }
+
+    pub fn hp(&self) -> f32 {
+        self.hp
+    }
+
+    pub fn damage(&mut self, amount: f32) {
+        self.hp -= amount;
+    }
+
+    pub fn dead(&self) -> bool {
+        self.hp <= 0.0
+    }
+}
+
+/// Chance that decides how the game plays out, like the weather and where
+/// shots in a volley land. Reseeded every round so it can be put back the way
+/// it was from a snapshot.
+#[derive(Debug, Resource)]
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// What the generator was last seeded with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn reseed(&mut self, seed: u64) {
+        *self = Self::new(seed);
+    }
+
+    /// The seed for a round of a game, so the same map plays out the same way.
+    pub fn round_seed(seed: Seed<u32>, round: u32) -> u64 {
+        ((u32::from(seed) as u64) << 32) | round as u64
+    }
+
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+}
+
+/// Pieces each player may throw away every time they fortify.
+pub const DISCARDS: u32 = 2;
+
+/// House rules, for changing how the game plays. Given on the command line, by
+/// the scenario or by the host of a networked game, anything left out of a
+/// scenario plays as usual.
+#[derive(Debug, Clone, PartialEq, Eq, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    /// Round shots bounce and roll when they land, going off on a fuse rather
+    /// than on impact.
+    pub fused_shells: bool,
+    /// Shots have to clear the terrain and the player's own walls on their way
+    /// to the target.
+    pub line_of_sight: bool,
+    /// Shots damage the walls and cannons of whoever fired them.
+    pub friendly_fire: bool,
+    /// Cannons can be fired together as a volley, rather than one at a time.
+    pub volleys: bool,
+    /// Most shots each cannon gets a round, unlimited when None.
+    pub ammo: Option<u32>,
+    /// Points it costs to patch up a damaged wall or cannon by clicking on it
+    /// while fortifying, no repairs when None.
+    pub repair_cost: Option<u32>,
+    /// Round from which every hit on a wall or cannon destroys it outright.
+    pub sudden_death: Option<u32>,
+    /// Both players hold one island together against the ships rather than
+    /// fighting each other, sharing their territory and their points.
+    pub coop: bool,
+    /// Pieces each player is dealt to build with every Fortify phase, which
+    /// ends once they've all been placed. Just the one when None.
+    pub pieces: Option<u32>,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            fused_shells: false,
+            line_of_sight: false,
+            friendly_fire: true,
+            volleys: true,
+            ammo: None,
+            repair_cost: None,
+            sudden_death: None,
+            coop: false,
+            pieces: None,
+        }
+    }
+}
+
+impl Rules {
+    /// Whether shots from `shooter` damage what `owner` has built.
+    pub fn damages(&self, shooter: Option<&Player>, owner: &Player) -> bool {
+        match shooter {
+            // Playing together only the ships can do any damage.
+            Some(_) if self.coop => false,
+            Some(shooter) => self.friendly_fire || shooter != owner,
+            None => true,
+        }
+    }
+
+    /// Whether a cannon that's fired `fired` times this round can fire again.
+    pub fn loaded(&self, fired: u32) -> bool {
+        self.ammo.map_or(true, |ammo| fired < ammo)
+    }
+
+    /// Pieces to place during `phase`, the last of them ending the turn.
+    pub fn pieces(&self, phase: &Phase) -> u32 {
+        match phase {
+            Phase::Fortify(_) => self.pieces.unwrap_or(1),
+            Phase::Choose(_) | Phase::Arm(_) => 1,
+            Phase::Target(_) => 0,
+        }
+    }
+
+    /// Pieces that may be thrown away rather than placed during `phase`, when
+    /// they won't fit anywhere useful.
+    pub fn discards(&self, phase: &Phase) -> u32 {
+        match phase {
+            Phase::Fortify(_) => DISCARDS,
+            _ => 0,
+        }
+    }
+
+    /// Whether `round` is played to the death.
+    pub fn is_sudden_death(&self, round: u32) -> bool {
+        self.sudden_death.is_some_and(|from| round >= from)
+    }
+}
+
+/// How well the computer opponent plays.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AiSettings {
+    /// The player the computer is playing as.
+    pub player: Player,
+    /// What it was set up for, to rate how the match went.
+    pub difficulty: Difficulty,
+    /// Chance of a piece going somewhere that leaves a gap in the walls rather
+    /// than where it's needed.
+    pub gap_chance: f32,
+    /// How far from what it's aiming at shots may be sent.
+    pub aim_error: f32,
+    /// Seconds before acting, after a phase begins and between shots.
+    pub reaction: f32,
+}
+
+impl AiSettings {
+    pub fn new(player: Player, difficulty: Difficulty) -> Self {
+        let (gap_chance, aim_error, reaction) = match difficulty {
+            Difficulty::Easy => (0.5, 3.0 * TILE_SIZE, 3.0),
+            Difficulty::Normal => (0.2, 1.5 * TILE_SIZE, 1.5),
+            Difficulty::Hard => (0.05, 0.5 * TILE_SIZE, 0.75),
+        };
+
+        Self {
+            player,
+            difficulty,
+            gap_chance,
+            aim_error,
+            reaction,
+        }
+    }
+}
+
 #[derive(Debug, Resource)]
 pub struct Settings {
     pub size: UVec2,
     pub seed: Seed<u32>,
+    pub style: TerrainStyle,
+    pub symmetry: Symmetry,
+    pub noise: NoiseSettings,
+    /// Computer opponent, when not playing against another person.
+    pub ai: Option<AiSettings>,
 }
 
 impl Default for Settings {
@@ -161,6 +470,10 @@ impl Default for Settings {
         Self {
             seed: Seed::system_time(),
             size: UVec2::new(64, 64),
+            style: TerrainStyle::default(),
+            symmetry: Symmetry::default(),
+            noise: NoiseSettings::default(),
+            ai: None,
         }
     }
 }
@@ -173,4 +486,25 @@ impl Settings {
     pub fn size(&self) -> UVec2 {
         self.size
     }
+
+    pub fn style(&self) -> TerrainStyle {
+        self.style
+    }
+
+    pub fn symmetry(&self) -> Symmetry {
+        self.symmetry
+    }
+
+    pub fn noise(&self) -> &NoiseSettings {
+        &self.noise
+    }
+
+    pub fn ai(&self) -> Option<&AiSettings> {
+        self.ai.as_ref()
+    }
+
+    /// True when the player's moves are made by the computer.
+    pub fn computer(&self, player: &Player) -> bool {
+        self.ai.as_ref().is_some_and(|ai| ai.player == *player)
+    }
 }
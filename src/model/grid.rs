@@ -35,6 +35,34 @@ impl<T> SquareGrid<T> {
             .and_then(|index| self.cells.get(index))
     }
 
+    pub fn get_mut(&mut self, p: IVec2) -> Option<&mut T> {
+        self.coordinates_to_index(p)
+            .and_then(|index| self.cells.get_mut(index))
+    }
+
+    /// Every cell, row by row.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.cells.iter_mut()
+    }
+
+    /// Every cell along with where it is, row by row.
+    pub fn enumerate_cells(&self) -> impl Iterator<Item = (IVec2, &T)> {
+        let width = self.size.x as usize;
+        self.cells.iter().enumerate().map(move |(index, value)| {
+            let grid = IVec2::new((index % width) as i32, (index / width) as i32);
+            (grid, value)
+        })
+    }
+
+    /// Each row of cells, the first first.
+    pub fn rows(&self) -> std::slice::Chunks<'_, T> {
+        self.cells.chunks(self.size.x.max(1) as usize)
+    }
+
     pub fn apply<V>(&self, mut map_fn: impl FnMut(UVec2, &T) -> V) -> SquareGrid<V> {
         let cells = self
             .cells
@@ -79,6 +107,96 @@ impl<T> SquareGrid<T> {
         }
     }
 
+    /// Every cell from one corner to the other, corners included. Unlike
+    /// `outline`, this and the other shapes leave out whatever is off the grid.
+    pub fn fill_rect(&mut self, p0: IVec2, p1: IVec2, value: T)
+    where
+        T: Clone,
+    {
+        let (min, max) = (p0.min(p1), p0.max(p1));
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                self.set_inside(IVec2::new(x, y), value.clone());
+            }
+        }
+    }
+
+    /// Cells along a straight line between the two, both ends included.
+    pub fn line(&mut self, p0: IVec2, p1: IVec2, value: T)
+    where
+        T: Clone,
+    {
+        let delta = (p1 - p0).abs();
+        let step = (p1 - p0).signum();
+        let mut error = delta.x - delta.y;
+        let mut p = p0;
+
+        loop {
+            self.set_inside(p, value.clone());
+            if p == p1 {
+                break;
+            }
+
+            let doubled = 2 * error;
+            if doubled > -delta.y {
+                error -= delta.y;
+                p.x += step.x;
+            }
+            if doubled < delta.x {
+                error += delta.x;
+                p.y += step.y;
+            }
+        }
+    }
+
+    /// Cells whose centers are no further than `radius` cells from the
+    /// center of `center`.
+    pub fn circle(&mut self, center: IVec2, radius: i32, value: T)
+    where
+        T: Clone,
+    {
+        for y in -radius..=radius {
+            for x in -radius..=radius {
+                if x * x + y * y <= radius * radius {
+                    self.set_inside(center + IVec2::new(x, y), value.clone());
+                }
+            }
+        }
+    }
+
+    /// Replaces the cell and every cell connected to it, across edges, with
+    /// the same value as it. Returns how many were replaced.
+    pub fn flood_fill(&mut self, start: IVec2, value: T) -> usize
+    where
+        T: Clone + PartialEq,
+    {
+        let Some(target) = self.get(start).cloned() else {
+            return 0;
+        };
+        if target == value {
+            return 0;
+        }
+
+        let mut filled = 0;
+        let mut pending = vec![start];
+        while let Some(p) = pending.pop() {
+            match self.get_mut(p) {
+                Some(cell) if *cell == target => *cell = value.clone(),
+                _ => continue,
+            }
+            filled += 1;
+            pending.extend([IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y].map(|d| p + d));
+        }
+
+        filled
+    }
+
+    fn set_inside(&mut self, p: IVec2, value: T) {
+        if let Some(cell) = self.get_mut(p) {
+            *cell = value;
+        }
+    }
+
     fn coordinates_to_index(&self, p: IVec2) -> Option<usize> {
         if p.x < 0 || p.y < 0 || p.x + 1 > self.size.x as i32 || p.y + 1 > self.size.y as i32 {
             None
@@ -88,25 +206,53 @@ impl<T> SquareGrid<T> {
     }
 }
 
-/// Functions dealing with geometry. I kind of feel like these should be some
-/// place else, SquareGrid doesn't quite feel right. One glaring red flag is the
-/// TILE_SIZE dependency.
-impl<T> SquareGrid<T> {
-    pub fn world_to_local(&self) -> Vec3 {
-        let size = self.size.as_vec2();
-        let half_tile_size = Vec2::splat(TILE_SIZE) / 2.0;
-        (Vec3::new(size.x, 0., size.y) / 2.0) - Vec3::new(half_tile_size.x, 0., half_tile_size.y)
+/// Converts between positions in the world and the cells of a grid laid out
+/// around the origin, `TILE_SIZE` to a cell. Everything placed on a grid goes
+/// through here, so cells are found the same way everywhere.
+pub trait WorldGrid {
+    fn grid_size(&self) -> UVec2;
+
+    /// Where the corner of the first cell is.
+    fn world_origin(&self) -> Vec2 {
+        -self.grid_size().as_vec2() * TILE_SIZE / 2.0
+    }
+
+    /// The middle of the cell, at a height of zero.
+    fn grid_to_world(&self, grid: IVec2) -> Vec3 {
+        let center = self.world_origin() + (grid.as_vec2() + 0.5) * TILE_SIZE;
+        Vec3::new(center.x, 0., center.y)
+    }
+
+    /// The corner the cell shares with those before it, at a height of zero.
+    fn corner_to_world(&self, corner: IVec2) -> Vec3 {
+        let corner = self.world_origin() + corner.as_vec2() * TILE_SIZE;
+        Vec3::new(corner.x, 0., corner.y)
     }
 
-    pub fn local_to_world(&self) -> Vec3 {
-        -self.world_to_local()
+    /// The cell the position is over, whether or not that's on the grid. Edges
+    /// between cells belong to the cell after them.
+    fn world_to_cell(&self, position: Vec3) -> IVec2 {
+        ((position.xz() - self.world_origin()) / TILE_SIZE)
+            .floor()
+            .as_ivec2()
     }
 
-    pub fn grid_to_world(&self, grid: IVec2) -> Vec3 {
-        let grid = grid.as_vec2();
-        Vec3::new(grid.x, 0., grid.y) + self.local_to_world()
+    /// The cell the position is over, nothing when that's off the grid.
+    fn world_to_grid(&self, position: Vec3) -> Option<IVec2> {
+        let cell = self.world_to_cell(position);
+        let size = self.grid_size().as_ivec2();
+
+        (cell.cmpge(IVec2::ZERO).all() && cell.cmplt(size).all()).then_some(cell)
+    }
+}
+
+impl<T> WorldGrid for SquareGrid<T> {
+    fn grid_size(&self) -> UVec2 {
+        self.size
     }
+}
 
+impl<T> SquareGrid<T> {
     pub fn layout(&self) -> Vec<(IVec2, Vec3, &T)> {
         self.cells
             .iter()
@@ -139,6 +285,143 @@ where
     }
 }
 
+/// Which cells are counted as touching, when grouping them into regions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// North, south, east and west only, so cells touching diagonally are
+    /// kept apart.
+    Edges,
+    /// Diagonally as well.
+    Corners,
+}
+
+impl Connectivity {
+    fn neighbors(&self) -> &'static [IVec2] {
+        const EDGES: [IVec2; 4] = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+        const CORNERS: [IVec2; 8] = [
+            IVec2::X,
+            IVec2::NEG_X,
+            IVec2::Y,
+            IVec2::NEG_Y,
+            IVec2::ONE,
+            IVec2::NEG_ONE,
+            IVec2::new(1, -1),
+            IVec2::new(-1, 1),
+        ];
+
+        match self {
+            Connectivity::Edges => &EDGES,
+            Connectivity::Corners => &CORNERS,
+        }
+    }
+}
+
+/// Cells grouped into connected regions, numbered in the order they're found
+/// going row by row.
+#[derive(Debug, Clone)]
+pub struct Regions {
+    labels: SquareGrid<Option<usize>>,
+    sizes: Vec<usize>,
+}
+
+impl Regions {
+    /// The region the cell is in, nothing when it isn't in any of them.
+    pub fn label(&self, p: IVec2) -> Option<usize> {
+        self.labels.get(p).copied().flatten()
+    }
+
+    /// Cells in each of the regions, by label.
+    pub fn sizes(&self) -> &[usize] {
+        &self.sizes
+    }
+
+    pub fn len(&self) -> usize {
+        self.sizes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sizes.is_empty()
+    }
+
+    /// The biggest region, the first found when there's a tie.
+    pub fn largest(&self) -> Option<usize> {
+        (0..self.len()).rev().max_by_key(|label| self.sizes[*label])
+    }
+
+    /// Every cell in the region, row by row.
+    pub fn cells(&self, label: usize) -> Vec<IVec2> {
+        self.labels
+            .enumerate_cells()
+            .filter(|(_, l)| **l == Some(label))
+            .map(|(p, _)| p)
+            .collect()
+    }
+
+    /// Whether each region, by label, reaches the edge of the grid.
+    pub fn touching_edges(&self) -> Vec<bool> {
+        let last = self.labels.size().as_ivec2() - IVec2::ONE;
+        let mut touching = vec![false; self.len()];
+        for (p, label) in self.labels.enumerate_cells() {
+            let edge = p.x == 0 || p.y == 0 || p.x == last.x || p.y == last.y;
+            if let (true, Some(label)) = (edge, label) {
+                touching[*label] = true;
+            }
+        }
+
+        touching
+    }
+}
+
+impl<T> SquareGrid<T> {
+    /// Groups the cells that are `member` into regions of those connected to
+    /// one another.
+    pub fn regions(&self, connectivity: Connectivity, member: impl Fn(&T) -> bool) -> Regions {
+        let mut labels: SquareGrid<Option<usize>> = SquareGrid::new_flat(self.size);
+        let mut sizes = Vec::new();
+
+        for (start, value) in self.enumerate_cells() {
+            if !member(value) || labels[start].is_some() {
+                continue;
+            }
+
+            let label = sizes.len();
+            let mut size = 0;
+            let mut pending = vec![start];
+            labels[start] = Some(label);
+            while let Some(p) = pending.pop() {
+                size += 1;
+                for n in connectivity.neighbors() {
+                    let q = p + *n;
+                    if let (Some(value), Some(None)) = (self.get(q), labels.get(q)) {
+                        if member(value) {
+                            labels[q] = Some(label);
+                            pending.push(q);
+                        }
+                    }
+                }
+            }
+            sizes.push(size);
+        }
+
+        Regions { labels, sizes }
+    }
+}
+
+/// Panics outside of the grid, like indexing a slice.
+impl<T> std::ops::Index<IVec2> for SquareGrid<T> {
+    type Output = T;
+
+    fn index(&self, p: IVec2) -> &Self::Output {
+        self.get(p).expect("index coordinates")
+    }
+}
+
+impl<T> std::ops::IndexMut<IVec2> for SquareGrid<T> {
+    fn index_mut(&mut self, p: IVec2) -> &mut Self::Output {
+        self.get_mut(p).expect("index coordinates")
+    }
+}
+
 impl<T> XyIndex<T> for SquareGrid<T> {
     fn get_xy(&self, p: IVec2) -> Option<&T> {
         self.coordinates_to_index(p).map(|index| &self.cells[index])
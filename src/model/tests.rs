@@ -1,4 +1,5 @@
-use bevy::math::UVec2;
+use bevy::math::{UVec2, Vec3};
+use proptest::prelude::*;
 
 use crate::{model::Around, model::AroundCenter};
 
@@ -27,3 +28,405 @@ fn test_around_square_grid() {
         ))
     );
 }
+
+#[test]
+fn test_rules_friendly_fire() {
+    let rules = Rules::default();
+    assert!(rules.damages(Some(&Player::One), &Player::One));
+
+    let rules = Rules {
+        friendly_fire: false,
+        ..Rules::default()
+    };
+    assert!(!rules.damages(Some(&Player::One), &Player::One));
+    assert!(rules.damages(Some(&Player::Two), &Player::One));
+    assert!(rules.damages(None, &Player::One));
+}
+
+#[test]
+fn test_rules_coop_only_ships_do_damage() {
+    let rules = Rules {
+        coop: true,
+        ..Rules::default()
+    };
+    assert!(!rules.damages(Some(&Player::One), &Player::One));
+    assert!(!rules.damages(Some(&Player::Two), &Player::One));
+    assert!(rules.damages(None, &Player::Two));
+}
+
+#[test]
+fn test_rules_ammo_and_sudden_death() {
+    let rules = Rules::default();
+    assert!(rules.loaded(100));
+    assert!(!rules.is_sudden_death(100));
+
+    let rules = Rules {
+        ammo: Some(2),
+        sudden_death: Some(5),
+        ..Rules::default()
+    };
+    assert!(rules.loaded(1));
+    assert!(!rules.loaded(2));
+    assert!(!rules.is_sudden_death(4));
+    assert!(rules.is_sudden_death(5));
+}
+
+#[test]
+fn test_rules_pieces_only_while_fortifying() {
+    let rules = Rules::default();
+    assert_eq!(rules.pieces(&Phase::Fortify(Player::One)), 1);
+
+    let rules = Rules {
+        pieces: Some(5),
+        ..Rules::default()
+    };
+    assert_eq!(rules.pieces(&Phase::Fortify(Player::Two)), 5);
+    assert_eq!(rules.pieces(&Phase::Arm(Player::Two)), 1);
+    assert_eq!(rules.pieces(&Phase::Target(Player::Two)), 0);
+    assert_eq!(rules.discards(&Phase::Fortify(Player::Two)), DISCARDS);
+    assert_eq!(rules.discards(&Phase::Arm(Player::Two)), 0);
+}
+
+#[test]
+fn test_rules_left_out_play_as_usual() {
+    let rules: Rules = ron::from_str("(ammo: Some(3))").unwrap();
+
+    assert_eq!(
+        rules,
+        Rules {
+            ammo: Some(3),
+            ..Rules::default()
+        }
+    );
+}
+
+/// Grids of up to 32 on a side, along with a cell somewhere on them.
+fn grid_and_cell() -> impl Strategy<Value = (UVec2, IVec2)> {
+    (1u32..32, 1u32..32).prop_flat_map(|(x, y)| {
+        (
+            Just(UVec2::new(x, y)),
+            (0..x as i32, 0..y as i32).prop_map(|(x, y)| IVec2::new(x, y)),
+        )
+    })
+}
+
+/// Grids along with a rectangle that fits on them, as opposite corners.
+fn grid_and_rectangle() -> impl Strategy<Value = (UVec2, IVec2, IVec2)> {
+    (1u32..32, 1u32..32).prop_flat_map(|(x, y)| {
+        (0..x as i32, 0..y as i32).prop_flat_map(move |(x0, y0)| {
+            (
+                Just(UVec2::new(x, y)),
+                Just(IVec2::new(x0, y0)),
+                (x0..x as i32, y0..y as i32).prop_map(|(x1, y1)| IVec2::new(x1, y1)),
+            )
+        })
+    })
+}
+
+proptest! {
+    #[test]
+    fn test_grid_set_then_get((size, cell) in grid_and_cell(), value: u32) {
+        let mut grid: SquareGrid<u32> = SquareGrid::new_flat(size);
+        grid.set(cell, value);
+
+        prop_assert_eq!(grid.get(cell), Some(&value));
+        prop_assert_eq!(grid.get_xy(cell), Some(&value));
+
+        let matching = grid.into_cells().into_iter().filter(|v| *v == value).count();
+        let expected = if value == 0 { (size.x * size.y) as usize } else { 1 };
+        prop_assert_eq!(matching, expected);
+    }
+
+    #[test]
+    fn test_grid_outside_is_none((size, _) in grid_and_cell(), x in -4i32..36, y in -4i32..36) {
+        let grid: SquareGrid<u32> = SquareGrid::new_flat(size);
+        let inside = x >= 0 && y >= 0 && x < size.x as i32 && y < size.y as i32;
+
+        prop_assert_eq!(grid.get(IVec2::new(x, y)).is_some(), inside);
+    }
+
+    #[test]
+    fn test_around_matches_get((size, center) in grid_and_cell()) {
+        let grid = SquareGrid::new_flat(size).map(|p, _: u32| p.x * 100 + p.y);
+        let around = grid.around(center);
+
+        prop_assert_eq!(around.center(), &grid.get(center).cloned());
+        let positions = Around::centered(center).to_vec();
+        for (value, position) in around.to_vec().into_iter().zip(positions) {
+            prop_assert_eq!(value, grid.get(position).cloned());
+        }
+    }
+
+    #[test]
+    fn test_outline_is_the_rectangle_border((size, p0, p1) in grid_and_rectangle()) {
+        let mut grid: SquareGrid<bool> = SquareGrid::new_flat(size);
+        grid.outline(p0, p1, true);
+
+        for (p, _, value) in grid.layout() {
+            let within = (p0.x..=p1.x).contains(&p.x) && (p0.y..=p1.y).contains(&p.y);
+            let border = p.x == p0.x || p.x == p1.x || p.y == p0.y || p.y == p1.y;
+            prop_assert_eq!(*value, within && border, "at {}", p);
+        }
+    }
+}
+
+#[test]
+fn test_world_grid_edges() {
+    let grid: SquareGrid<u32> = SquareGrid::new_flat(UVec2::new(4, 2));
+    let half = TILE_SIZE / 2.0;
+
+    assert_eq!(
+        grid.grid_to_world(IVec2::ZERO),
+        Vec3::new(-1.5, 0., -0.5) * TILE_SIZE
+    );
+    assert_eq!(
+        grid.corner_to_world(IVec2::ZERO),
+        Vec3::new(-2.0, 0., -1.0) * TILE_SIZE
+    );
+
+    // Edges belong to the cell after them, the far edges to no cell at all.
+    assert_eq!(
+        grid.world_to_grid(grid.corner_to_world(IVec2::ZERO)),
+        Some(IVec2::ZERO)
+    );
+    assert_eq!(
+        grid.world_to_grid(grid.corner_to_world(IVec2::new(1, 1))),
+        Some(IVec2::ONE)
+    );
+    assert_eq!(
+        grid.world_to_grid(grid.corner_to_world(IVec2::new(4, 1))),
+        None
+    );
+    assert_eq!(
+        grid.world_to_grid(grid.corner_to_world(IVec2::new(3, 2))),
+        None
+    );
+    assert_eq!(
+        grid.world_to_grid(grid.corner_to_world(IVec2::new(4, 2)) - Vec3::splat(0.01)),
+        Some(IVec2::new(3, 1))
+    );
+
+    // Just before the first edge is off the grid, rather than rounding to it.
+    assert_eq!(
+        grid.world_to_cell(grid.grid_to_world(IVec2::ZERO) - Vec3::new(half + 0.01, 0., 0.)),
+        IVec2::new(-1, 0)
+    );
+    assert_eq!(
+        grid.world_to_grid(Vec3::new(-2.01, 0., 0.) * TILE_SIZE),
+        None
+    );
+}
+
+proptest! {
+    #[test]
+    fn test_world_grid_round_trip(
+        (size, cell) in grid_and_cell(),
+        dx in -0.49f32..0.49,
+        dz in -0.49f32..0.49,
+    ) {
+        let grid: SquareGrid<u32> = SquareGrid::new_flat(size);
+        let offset = Vec3::new(dx, 0., dz) * TILE_SIZE;
+
+        prop_assert_eq!(grid.world_to_grid(grid.grid_to_world(cell) + offset), Some(cell));
+    }
+
+    #[test]
+    fn test_world_grid_cells_off_the_grid(
+        (size, _) in grid_and_cell(),
+        x in -40i32..40,
+        y in -40i32..40,
+    ) {
+        let grid: SquareGrid<u32> = SquareGrid::new_flat(size);
+        let cell = IVec2::new(x, y);
+        let inside = grid.get(cell).is_some();
+
+        prop_assert_eq!(grid.world_to_cell(grid.grid_to_world(cell)), cell);
+        prop_assert_eq!(grid.world_to_grid(grid.grid_to_world(cell)), inside.then_some(cell));
+    }
+}
+
+#[test]
+fn test_grid_iterators_go_row_by_row() {
+    let mut grid = SquareGrid::new_flat(UVec2::new(3, 2)).map(|p, _: u32| p.y * 10 + p.x);
+
+    assert_eq!(
+        grid.iter().copied().collect::<Vec<_>>(),
+        vec![0, 1, 2, 10, 11, 12]
+    );
+    assert_eq!(
+        grid.rows().collect::<Vec<_>>(),
+        vec![&[0, 1, 2][..], &[10, 11, 12][..]]
+    );
+    assert!(grid
+        .enumerate_cells()
+        .all(|(p, v)| *v == p.y as u32 * 10 + p.x as u32));
+
+    for value in grid.iter_mut() {
+        *value += 100;
+    }
+    grid[IVec2::new(2, 1)] = 7;
+
+    assert_eq!(grid[IVec2::new(0, 0)], 100);
+    assert_eq!(grid.get(IVec2::new(2, 1)), Some(&7));
+    assert_eq!(grid.get_mut(IVec2::new(3, 0)), None);
+}
+
+#[test]
+#[should_panic]
+fn test_grid_index_outside_panics() {
+    let grid: SquareGrid<u32> = SquareGrid::new_flat(UVec2::new(3, 2));
+
+    let _ = grid[IVec2::new(0, 2)];
+}
+
+fn drawn(grid: &SquareGrid<bool>) -> Vec<IVec2> {
+    grid.enumerate_cells()
+        .filter(|(_, value)| **value)
+        .map(|(p, _)| p)
+        .collect()
+}
+
+#[test]
+fn test_fill_rect_is_clipped() {
+    let mut grid: SquareGrid<bool> = SquareGrid::new_flat(UVec2::new(4, 4));
+    grid.fill_rect(IVec2::new(5, 1), IVec2::new(2, 2), true);
+
+    assert_eq!(
+        drawn(&grid),
+        vec![
+            IVec2::new(2, 1),
+            IVec2::new(3, 1),
+            IVec2::new(2, 2),
+            IVec2::new(3, 2)
+        ]
+    );
+}
+
+#[test]
+fn test_circle() {
+    let mut grid: SquareGrid<bool> = SquareGrid::new_flat(UVec2::new(5, 5));
+    grid.circle(IVec2::new(2, 2), 1, true);
+
+    assert_eq!(
+        drawn(&grid),
+        vec![
+            IVec2::new(2, 1),
+            IVec2::new(1, 2),
+            IVec2::new(2, 2),
+            IVec2::new(3, 2),
+            IVec2::new(2, 3)
+        ]
+    );
+}
+
+#[test]
+fn test_flood_fill_stops_at_walls() {
+    let mut grid: SquareGrid<u32> = SquareGrid::new_flat(UVec2::new(5, 5));
+    grid.outline(IVec2::new(1, 1), IVec2::new(3, 3), 1);
+
+    assert_eq!(grid.flood_fill(IVec2::new(2, 2), 2), 1);
+    assert_eq!(grid[IVec2::new(2, 2)], 2);
+    assert_eq!(grid.flood_fill(IVec2::ZERO, 3), 16);
+    assert_eq!(grid.flood_fill(IVec2::ZERO, 3), 0);
+    assert_eq!(grid.flood_fill(IVec2::new(9, 9), 3), 0);
+    assert_eq!(grid.iter().filter(|v| **v == 1).count(), 8);
+}
+
+proptest! {
+    #[test]
+    fn test_line_is_connected(
+        (size, p0) in grid_and_cell(),
+        x in -4i32..36,
+        y in -4i32..36,
+    ) {
+        let p1 = IVec2::new(x, y);
+        let mut grid: SquareGrid<bool> = SquareGrid::new_flat(size);
+        grid.line(p0, p1, true);
+
+        let inside = drawn(&grid);
+        prop_assert!(inside.contains(&p0));
+        prop_assert_eq!(inside.contains(&p1), grid.get(p1).is_some());
+
+        // Without clipping there's one cell for each step along the longer
+        // axis, and one more for where it starts.
+        let mut full: SquareGrid<bool> = SquareGrid::new_flat(UVec2::splat(80));
+        let offset = IVec2::splat(40);
+        full.line(p0 + offset, p1 + offset, true);
+        let delta = (p1 - p0).abs();
+        prop_assert_eq!(drawn(&full).len() as i32, delta.x.max(delta.y) + 1);
+    }
+}
+
+/// A grid of `#` and `.` rows, first row first.
+fn pattern(rows: &[&str]) -> SquareGrid<bool> {
+    let size = UVec2::new(rows[0].len() as u32, rows.len() as u32);
+    let cells = rows
+        .iter()
+        .flat_map(|row| row.chars().map(|c| c == '#'))
+        .collect();
+
+    SquareGrid::new(size, cells)
+}
+
+#[test]
+fn test_regions_diagonal_cells() {
+    let grid = pattern(&["#.", ".#"]);
+
+    let edges = grid.regions(Connectivity::Edges, |v| *v);
+    assert_eq!(edges.sizes(), &[1, 1]);
+    assert_eq!(edges.label(IVec2::new(0, 0)), Some(0));
+    assert_eq!(edges.label(IVec2::new(1, 1)), Some(1));
+    assert_eq!(edges.label(IVec2::new(1, 0)), None);
+
+    let corners = grid.regions(Connectivity::Corners, |v| *v);
+    assert_eq!(corners.sizes(), &[2]);
+    assert_eq!(corners.label(IVec2::new(1, 1)), Some(0));
+}
+
+#[test]
+fn test_regions_checkerboard() {
+    let grid = pattern(&["#.#.", ".#.#", "#.#.", ".#.#"]);
+
+    let edges = grid.regions(Connectivity::Edges, |v| *v);
+    assert_eq!(edges.len(), 8);
+    assert!(edges.sizes().iter().all(|size| *size == 1));
+
+    let corners = grid.regions(Connectivity::Corners, |v| *v);
+    assert_eq!(corners.sizes(), &[8]);
+
+    // The gaps in between join up diagonally too.
+    let open = grid.regions(Connectivity::Corners, |v| !*v);
+    assert_eq!(open.sizes(), &[8]);
+}
+
+#[test]
+fn test_regions_diagonal_wall_closes_a_gap() {
+    // A diagonal line of walls only keeps the corners apart when the open
+    // cells can't step across it diagonally.
+    let walls = pattern(&["..#", ".#.", "#.."]);
+
+    let edges = walls.regions(Connectivity::Edges, |wall| !wall);
+    assert_eq!(edges.sizes(), &[3, 3]);
+    assert_eq!(
+        edges.cells(1),
+        vec![IVec2::new(2, 1), IVec2::new(1, 2), IVec2::new(2, 2)]
+    );
+
+    let corners = walls.regions(Connectivity::Corners, |wall| !wall);
+    assert_eq!(corners.sizes(), &[6]);
+}
+
+#[test]
+fn test_regions_largest_and_edges() {
+    let grid = pattern(&["##...", ".....", "..#..", ".....", "...##"]);
+    let regions = grid.regions(Connectivity::Edges, |v| *v);
+
+    assert_eq!(regions.sizes(), &[2, 1, 2]);
+    assert_eq!(regions.largest(), Some(0));
+    assert_eq!(regions.touching_edges(), vec![true, false, true]);
+    assert_eq!(regions.label(IVec2::new(-1, 0)), None);
+
+    let empty = grid.regions(Connectivity::Edges, |_| false);
+    assert!(empty.is_empty());
+    assert_eq!(empty.largest(), None);
+}
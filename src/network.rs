@@ -0,0 +1,799 @@
+use std::{
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::*;
+
+use crate::{
+    building::{
+        CannonTurned, ConstructionEvent, ConstructionSet, Piece, Pieces, Placing, StructureLayers,
+        TurnEnded,
+    },
+    camera::zoomed_out,
+    controls::{typing, Action, Controls},
+    firing::TargetPicked,
+    journal::EventJournal,
+    model::{Activity, AppState, GameRng, Phase, PhaseTimers, Player, Rules, Seed, Settings},
+    replay::{FollowJournal, Playback},
+    scenario::Scenario,
+    snapshot::{self, Capture, RestoreSnapshot, Snapshot, Snapshots},
+    terrain::{Props, Terrain},
+    units::WaveDirector,
+    weather::Weather,
+};
+
+mod chat;
+mod discovery;
+mod lobby;
+mod protocol;
+mod spectating;
+#[cfg(test)]
+mod tests;
+mod validation;
+
+use chat::Chat;
+pub use lobby::LocalPlayer;
+use lobby::{Lobby, LobbyPlayer};
+pub use protocol::{Message, Peer};
+pub use spectating::spectating;
+use validation::{Rejection, Request};
+
+/// The player whoever has the seat in a hosted game plays as.
+pub const REMOTE: Player = Player::Two;
+
+/// How long to wait between attempts to reach the host.
+const RETRY_SECONDS: f32 = 2.0;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Plays a game across the network, hosted with `--host` and joined with
+/// `--join`. The host plays the game, clients follow along through its journal
+/// and send their input back, except for those joining with `--spectate` who
+/// only watch.
+pub struct NetworkPlugin;
+
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<discovery::DiscoveredHosts>()
+            .init_resource::<LocalPlayer>()
+            .init_resource::<chat::Chat>()
+            .add_systems(OnEnter(AppState::Game), chat::spawn_chat.run_if(networked))
+            .add_systems(
+                Update,
+                (chat::type_chat, chat::send_chat, chat::update_chat)
+                    .chain()
+                    .run_if(in_state(AppState::Game))
+                    .run_if(networked),
+            )
+            .add_systems(Startup, open_lobby.run_if(resource_exists::<Host>))
+            .add_systems(
+                Update,
+                (
+                    lobby::spawn_lobby,
+                    lobby::lobby_input,
+                    lobby::type_name,
+                    lobby::update_lobby
+                        .run_if(resource_changed::<Lobby>.or_else(resource_changed::<LocalPlayer>)),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Menu))
+                    .run_if(resource_exists::<Lobby>),
+            )
+            .add_systems(Startup, lobby::load_local_player)
+            .add_systems(
+                OnExit(AppState::Menu),
+                (lobby::despawn_lobby, lobby::remember_local_player),
+            )
+            .add_systems(
+                Update,
+                (host_lobby, start_match)
+                    .chain()
+                    .run_if(in_state(AppState::Menu))
+                    .run_if(resource_exists::<Host>),
+            )
+            .add_systems(
+                Update,
+                introduce.after(connect).run_if(resource_exists::<Client>),
+            )
+            .init_gizmo_group::<spectating::TerritoryGizmos>()
+            .add_systems(
+                OnEnter(AppState::Game),
+                spectating::free_camera.run_if(spectating),
+            )
+            .add_systems(
+                Update,
+                spectating::draw_territories
+                    .run_if(in_state(AppState::Game))
+                    .run_if(spectating.or_else(zoomed_out)),
+            )
+            .add_systems(
+                Startup,
+                discovery::start_announcing.run_if(resource_exists::<Host>),
+            )
+            .add_systems(
+                Update,
+                discovery::announce.run_if(resource_exists::<discovery::Announcer>),
+            )
+            .add_systems(
+                Startup,
+                discovery::start_discovery.run_if(discovery::looking),
+            )
+            .add_systems(
+                OnEnter(AppState::Menu),
+                lobby::spawn_host_list.run_if(discovery::looking),
+            )
+            .add_systems(OnExit(AppState::Menu), lobby::despawn_host_list)
+            .add_systems(
+                Update,
+                (
+                    discovery::discover.run_if(resource_exists::<discovery::Discovery>),
+                    lobby::update_host_list.run_if(resource_changed::<discovery::DiscoveredHosts>),
+                    lobby::join_host,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Menu))
+                    .run_if(discovery::looking),
+            )
+            .add_systems(
+                Update,
+                (
+                    accept_peers,
+                    (host_requests.in_set(ConstructionSet::Input), catch_up_peers)
+                        .run_if(in_state(AppState::Game)),
+                )
+                    .chain()
+                    .run_if(resource_exists::<Host>),
+            )
+            .add_systems(
+                Update,
+                (
+                    connect,
+                    follow_host.before(snapshot::restore_snapshot),
+                    resume_game.run_if(in_state(AppState::Game)),
+                    follow_pieces.run_if(in_state(AppState::Game)),
+                    forward_input
+                        .run_if(in_state(AppState::Game))
+                        .run_if(synced)
+                        .run_if(not(spectating))
+                        .run_if(not(typing)),
+                )
+                    .chain()
+                    .run_if(resource_exists::<Client>),
+            );
+    }
+}
+
+/// True when the game here follows one played elsewhere, a replay or a game
+/// hosted by someone else, rather than being played here.
+pub fn following(playback: Option<Res<Playback>>, client: Option<Res<Client>>) -> bool {
+    playback.is_some() || client.is_some()
+}
+
+/// Whether the game is played across the network, hosted here or elsewhere.
+pub fn networked(host: Option<Res<Host>>, client: Option<Res<Client>>) -> bool {
+    host.is_some() || client.is_some()
+}
+
+/// Whether the player's turns are taken by someone connected to this host.
+pub fn remote(host: Option<&Host>, player: &Player) -> bool {
+    host.is_some() && *player == REMOTE
+}
+
+fn synced(client: Res<Client>) -> bool {
+    client.synced
+}
+
+struct Connected {
+    peer: Peer,
+    /// Who they are, once they've said.
+    player: Option<LobbyPlayer>,
+    /// Has the lobby as it is now.
+    briefed: bool,
+    /// Number of journal entries sent so far, nothing until caught up.
+    sent: Option<usize>,
+}
+
+/// A game being hosted for others to join, given with `--host`.
+#[derive(Resource)]
+pub struct Host {
+    listener: TcpListener,
+    port: u16,
+    peers: Vec<Connected>,
+}
+
+impl Host {
+    pub fn listen(port: u16) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+
+        info!(port, "hosting");
+
+        Ok(Self {
+            listener,
+            port,
+            peers: Vec::new(),
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+}
+
+/// A game hosted elsewhere being played here, given with `--join`.
+#[derive(Resource)]
+pub struct Client {
+    /// Where the host is, until one is picked from those found on the local
+    /// network.
+    address: Option<String>,
+    peer: Option<Peer>,
+    /// Has told the host who's playing here since connecting.
+    introduced: bool,
+    /// Caught up with the host, so input is sent along again.
+    synced: bool,
+    retry: Timer,
+    /// Arrived before the game here began, restored once it has.
+    pending: Option<Snapshot>,
+}
+
+impl Client {
+    pub fn new(address: Option<String>) -> Self {
+        Self {
+            address,
+            peer: None,
+            introduced: false,
+            synced: false,
+            // Finished already so the first attempt is made right away.
+            retry: Timer::from_seconds(0.0, TimerMode::Once),
+            pending: None,
+        }
+    }
+
+    pub fn addressed(&self) -> bool {
+        self.address.is_some()
+    }
+
+    pub fn join(&mut self, address: String) {
+        self.address = Some(address);
+    }
+
+    fn disconnect(&mut self) {
+        self.peer = None;
+        self.introduced = false;
+        self.synced = false;
+        self.retry = Timer::from_seconds(RETRY_SECONDS, TimerMode::Once);
+    }
+}
+
+fn open_lobby(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    timers: Res<PhaseTimers>,
+    rules: Res<Rules>,
+) {
+    commands.insert_resource(Lobby::new(&settings, &timers, &rules));
+}
+
+/// Keeps the lobby up to date with whoever is connected and sends it to
+/// everyone whenever it changes.
+fn host_lobby(mut host: ResMut<Host>, mut lobby: ResMut<Lobby>, local: Res<LocalPlayer>) {
+    host.peers
+        .retain_mut(|connected| match connected.peer.receive() {
+            Ok(messages) => {
+                for message in messages {
+                    match message {
+                        Message::Player(player) => connected.player = Some(player),
+                        message => debug!(?message, "ignored"),
+                    }
+                }
+                true
+            }
+            Err(e) => {
+                info!("peer-disconnected: {}", e);
+                false
+            }
+        });
+
+    let players: Vec<_> = std::iter::once(local.0.clone())
+        .chain(host.peers.iter().filter_map(|c| c.player.clone()))
+        .collect();
+    if lobby.players != players {
+        lobby.players = players;
+    }
+
+    let changed = lobby.is_changed();
+    host.peers.retain_mut(|connected| {
+        connected.briefed &= !changed;
+        if connected.briefed {
+            return true;
+        }
+
+        match connected.peer.send(&Message::Lobby(lobby.clone())) {
+            Ok(_) => {
+                connected.briefed = true;
+                true
+            }
+            Err(e) => {
+                info!("peer-disconnected: {}", e);
+                false
+            }
+        }
+    });
+}
+
+/// Begins the match once everyone is ready, set up the way the lobby says.
+fn start_match(
+    lobby: Res<Lobby>,
+    mut local: ResMut<LocalPlayer>,
+    mut settings: ResMut<Settings>,
+    mut timers: ResMut<PhaseTimers>,
+    mut rules: ResMut<Rules>,
+    mut app_state: ResMut<NextState<AppState>>,
+    mut activity: ResMut<NextState<Activity>>,
+) {
+    if !lobby.ready() {
+        return;
+    }
+
+    info!(seed = lobby.seed, size = lobby.size, "match-starting");
+
+    lobby.apply(&mut settings, &mut timers, &mut rules);
+    local.0.ready = false;
+
+    app_state.set(AppState::Generating);
+    activity.set(Activity::Observing);
+}
+
+fn accept_peers(mut host: ResMut<Host>) {
+    loop {
+        match host.listener.accept() {
+            Ok((stream, address)) => match Peer::new(stream) {
+                Ok(peer) => {
+                    info!(%address, "peer-connected");
+                    host.peers.push(Connected {
+                        peer,
+                        player: None,
+                        briefed: false,
+                        sent: None,
+                    });
+                }
+                Err(e) => warn!("error accepting peer: {}", e),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+            Err(e) => {
+                warn!("error accepting peer: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+/// Acts on whatever clients have asked for, once it's been checked as theirs
+/// to ask for and allowed by the rules. Nothing a client sends is applied as
+/// it was sent, and only the client with the seat can ask for anything at all.
+#[allow(clippy::too_many_arguments)]
+fn host_requests(
+    mut host: ResMut<Host>,
+    mut chat: ResMut<Chat>,
+    mut construction: EventWriter<ConstructionEvent>,
+    mut turned: EventWriter<CannonTurned>,
+    mut turns: EventWriter<TurnEnded>,
+    mut picks: EventWriter<TargetPicked>,
+    mut pieces: ResMut<Pieces>,
+    phase: Res<State<Phase>>,
+    terrain: Query<&Terrain>,
+    structures: Option<Res<StructureLayers>>,
+    props: Res<Props>,
+    time: Res<Time>,
+) {
+    let (Ok(terrain), Some(structures)) = (terrain.get_single(), structures) else {
+        return;
+    };
+
+    let host = &mut *host;
+
+    let mut received = Vec::new();
+    host.peers
+        .retain_mut(|connected| match connected.peer.receive() {
+            Ok(messages) => {
+                received.push(messages);
+                true
+            }
+            Err(e) => {
+                info!("peer-disconnected: {}", e);
+                false
+            }
+        });
+
+    // Who they are comes first, so the seat goes to whoever it should before
+    // anything they've asked for is looked at.
+    for (connected, messages) in host.peers.iter_mut().zip(received.iter()) {
+        for message in messages {
+            if let Message::Player(player) = message {
+                connected.player = Some(player.clone());
+            }
+        }
+    }
+
+    let players: Vec<_> = host.peers.iter().map(|c| c.player.as_ref()).collect();
+    let seat = validation::seated(&players);
+    let phase = phase.get();
+
+    for (index, messages) in received.into_iter().enumerate() {
+        for message in messages {
+            let request = match message {
+                Message::Place(position) => Request::Place(position),
+                Message::Arm(position, gun) => Request::Arm(position, gun),
+                Message::Target(target, volley) => Request::Target(target, volley),
+                Message::Discard => Request::Discard,
+                Message::Hold => Request::Hold,
+                Message::Turn(position) => Request::Turn(position),
+                Message::Chat(line) => {
+                    chat.show(line.clone(), time.elapsed_seconds());
+                    chat.send(line);
+                    continue;
+                }
+                Message::Player(_) => continue,
+                message => {
+                    debug!(?message, ?phase, "ignored");
+                    continue;
+                }
+            };
+
+            if seat != Some(index) {
+                warn!(?request, rejection = ?Rejection::Spectating, "rejected");
+                continue;
+            }
+
+            if let Err(rejection) =
+                validation::check_turn(phase, REMOTE, &request, pieces.left() == 0)
+            {
+                warn!(?request, ?rejection, "rejected");
+                continue;
+            }
+
+            match request {
+                Request::Place(_) | Request::Arm(..) => {
+                    let placed = validation::check_piece(&request, pieces.next()).and_then(|_| {
+                        validation::check_placed(terrain, &structures, &props, REMOTE, &request)
+                    });
+                    match placed {
+                        Ok((grid, structure)) => {
+                            let left = pieces.take();
+                            construction.send(ConstructionEvent::new(grid.into(), structure));
+                            if left == Some(0) {
+                                turns.send(TurnEnded::new(REMOTE));
+                            }
+                        }
+                        Err(rejection) => warn!(?request, ?rejection, "rejected"),
+                    }
+                }
+                Request::Target(target, volley) => {
+                    match validation::check_target(terrain, &structures, REMOTE, target) {
+                        Ok(target) => {
+                            picks.send(TargetPicked::new(REMOTE, target, volley));
+                        }
+                        Err(rejection) => warn!(?request, ?rejection, "rejected"),
+                    }
+                }
+                Request::Discard => match pieces.discard() {
+                    Some(left) => {
+                        info!(player = ?REMOTE, left, "discarded");
+                        if left == 0 {
+                            turns.send(TurnEnded::new(REMOTE));
+                        }
+                    }
+                    None => warn!(?request, rejection = ?Rejection::NoDiscards, "rejected"),
+                },
+                Request::Hold => match pieces.hold() {
+                    Some(left) => {
+                        info!(player = ?REMOTE, held = ?pieces.held(), left, "piece-held");
+                        if left == 0 {
+                            turns.send(TurnEnded::new(REMOTE));
+                        }
+                    }
+                    None => warn!(?request, rejection = ?Rejection::AlreadyHeld, "rejected"),
+                },
+                Request::Turn(position) => {
+                    match validation::check_turned(terrain, &structures, REMOTE, position) {
+                        Ok((grid, cannon)) => {
+                            turned.send(CannonTurned::new(grid.into(), cannon));
+                        }
+                        Err(rejection) => warn!(?request, ?rejection, "rejected"),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Sends newly connected clients everything they need to catch up, and
+/// everyone else what's been recorded since last time.
+#[allow(clippy::too_many_arguments)]
+fn catch_up_peers(
+    mut host: ResMut<Host>,
+    snapshots: Res<Snapshots>,
+    journal: Res<EventJournal>,
+    structures: Option<Res<StructureLayers>>,
+    terrain: Query<&Terrain>,
+    director: Res<WaveDirector>,
+    weather: Res<Weather>,
+    rng: Res<GameRng>,
+    settings: Res<Settings>,
+    timers: Res<PhaseTimers>,
+    phase: Res<State<Phase>>,
+    time: Res<Time>,
+) {
+    let (Ok(terrain), Some(structures)) = (terrain.get_single(), structures) else {
+        return;
+    };
+
+    host.peers.retain_mut(|connected| {
+        let sent = match connected.sent {
+            Some(sent) if journal.since(sent).is_empty() => Ok(()),
+            Some(sent) => connected
+                .peer
+                .send(&Message::Entries(journal.since(sent).to_vec())),
+            None => {
+                // Until the first round begins there's no snapshot, so one is
+                // taken for them.
+                let snapshot = snapshots.latest().cloned().unwrap_or_else(|| {
+                    Snapshot::capture(
+                        Capture {
+                            journal: &journal,
+                            structures: &structures,
+                            director: &director,
+                            weather: &weather,
+                            rng: &rng,
+                        },
+                        phase.get().clone(),
+                        time.elapsed_seconds(),
+                    )
+                });
+                let entries = journal.since(snapshot.entry).to_vec();
+
+                info!(
+                    round = snapshot.round,
+                    entries = entries.len(),
+                    "peer-resuming"
+                );
+
+                connected.peer.send(&Message::Resume {
+                    scenario: Scenario {
+                        timers: Some(timers.clone()),
+                        ..Scenario::capture(&settings, terrain, &structures)
+                    },
+                    snapshot,
+                    entries,
+                })
+            }
+        };
+
+        match sent.and_then(|_| connected.peer.flush()) {
+            Ok(_) => {
+                connected.sent = Some(journal.entries().len());
+                true
+            }
+            Err(e) => {
+                info!("peer-disconnected: {}", e);
+                false
+            }
+        }
+    });
+}
+
+fn connect(mut client: ResMut<Client>, time: Res<Time>) {
+    if client.peer.is_some() || !client.retry.tick(time.delta()).finished() {
+        return;
+    }
+
+    let Some(address) = client.address.clone() else {
+        return;
+    };
+
+    let connected = address
+        .to_socket_addrs()
+        .and_then(|mut addresses| {
+            addresses
+                .next()
+                .ok_or_else(|| std::io::ErrorKind::AddrNotAvailable.into())
+        })
+        .and_then(|address| TcpStream::connect_timeout(&address, CONNECT_TIMEOUT))
+        .and_then(Peer::new);
+
+    match connected {
+        Ok(peer) => {
+            info!(%address, "connected");
+            client.peer = Some(peer);
+        }
+        Err(e) => {
+            debug!(%address, "unable to connect: {}", e);
+            client.disconnect();
+        }
+    }
+}
+
+/// Tells the host who's playing here, again whenever that changes.
+fn introduce(mut client: ResMut<Client>, local: Res<LocalPlayer>) {
+    let client = &mut *client;
+    let Some(peer) = client.peer.as_mut() else {
+        return;
+    };
+
+    if client.introduced && !local.is_changed() {
+        return;
+    }
+
+    match peer.send(&Message::Player(local.0.clone())) {
+        Ok(_) => client.introduced = true,
+        Err(e) => warn!("error introducing: {}", e),
+    }
+}
+
+/// Catches up with the host whenever it says to and follows along with
+/// everything it records after.
+#[allow(clippy::too_many_arguments)]
+fn follow_host(
+    mut commands: Commands,
+    mut client: ResMut<Client>,
+    mut restores: EventWriter<RestoreSnapshot>,
+    mut follow: FollowJournal,
+    mut settings: ResMut<Settings>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut local: ResMut<LocalPlayer>,
+    mut chat: ResMut<Chat>,
+    app_state: Res<State<AppState>>,
+    lobby: Option<Res<Lobby>>,
+    time: Res<Time>,
+) {
+    let client = &mut *client;
+    let Some(peer) = client.peer.as_mut() else {
+        return;
+    };
+
+    let messages = match peer.receive() {
+        Ok(messages) => messages,
+        Err(e) => {
+            warn!("disconnected, reconnecting: {}", e);
+            client.disconnect();
+            return;
+        }
+    };
+
+    for message in messages {
+        match message {
+            Message::Resume {
+                scenario,
+                mut snapshot,
+                entries,
+            } => {
+                snapshot.apply(&entries);
+
+                info!(round = snapshot.round, phase = ?snapshot.phase, "resuming");
+
+                if *app_state.get() == AppState::Game {
+                    restores.send(RestoreSnapshot(snapshot));
+                    client.synced = true;
+                } else {
+                    // The terrain only comes from the host, so the game here
+                    // can't begin until it's arrived.
+                    settings.seed = Seed::new(scenario.seed);
+                    settings.size = UVec2::new(scenario.size.0, scenario.size.1);
+                    if let Some(timers) = scenario.timers.clone() {
+                        commands.insert_resource(timers);
+                    }
+                    if let Some(lobby) = &lobby {
+                        commands.insert_resource(lobby.rules.clone());
+                    }
+                    commands.insert_resource(scenario);
+                    local.0.ready = false;
+                    next_state.set(AppState::Generating);
+                    client.pending = Some(snapshot);
+                }
+            }
+            Message::Lobby(lobby) => commands.insert_resource(lobby),
+            Message::Chat(line) => chat.show(line, time.elapsed_seconds()),
+            Message::Entries(entries) => match client.pending.as_mut() {
+                Some(pending) => pending.apply(&entries),
+                None => {
+                    for entry in entries.iter() {
+                        follow.play(entry);
+                    }
+                }
+            },
+            message => debug!(?message, "ignored"),
+        }
+    }
+}
+
+/// Restores whatever the host sent before the game here began, once it has.
+fn resume_game(mut client: ResMut<Client>, mut restores: EventWriter<RestoreSnapshot>) {
+    if let Some(snapshot) = client.pending.take() {
+        restores.send(RestoreSnapshot(snapshot));
+        client.synced = true;
+    }
+}
+
+/// Keeps the pieces dealt here in step with the host's, for knowing which is
+/// next: one taken for every piece the host builds for this player.
+fn follow_pieces(
+    mut construction: EventReader<ConstructionEvent>,
+    mut pieces: ResMut<Pieces>,
+    phase: Res<State<Phase>>,
+) {
+    let building =
+        matches!(phase.get(), Phase::Fortify(player) | Phase::Arm(player) if *player == REMOTE);
+    for event in construction.read() {
+        if building && event.structure().player() == REMOTE {
+            pieces.take();
+        }
+    }
+}
+
+/// Sends clicks, discards, holds and turns along to the host during this
+/// player's turns, rather than acting on them here. Clicks place whichever
+/// piece is next, as far as the pieces followed here go.
+fn forward_input(
+    mut client: ResMut<Client>,
+    mut events: EventReader<Pointer<Click>>,
+    mut pieces: ResMut<Pieces>,
+    placing: Query<(&Transform, &Placing)>,
+    keys: Res<ButtonInput<KeyCode>>,
+    controls: Res<Controls>,
+    phase: Res<State<Phase>>,
+) {
+    let Some(peer) = client.peer.as_mut() else {
+        return;
+    };
+
+    if *phase.get() == Phase::Fortify(REMOTE) && controls.just_pressed(&keys, Action::Discard) {
+        pieces.discard();
+        if let Err(e) = peer.send(&Message::Discard) {
+            warn!("error sending input: {}", e);
+        }
+    }
+
+    let building =
+        matches!(phase.get(), Phase::Fortify(player) | Phase::Arm(player) if *player == REMOTE);
+    if building && controls.just_pressed(&keys, Action::Hold) {
+        pieces.hold();
+        if let Err(e) = peer.send(&Message::Hold) {
+            warn!("error sending input: {}", e);
+        }
+    }
+
+    if *phase.get() == Phase::Arm(REMOTE) && controls.just_pressed(&keys, Action::TurnCannon) {
+        if let Ok((cursor, _)) = placing.get_single() {
+            if let Err(e) = peer.send(&Message::Turn(cursor.translation)) {
+                warn!("error sending input: {}", e);
+            }
+        }
+    }
+
+    for position in events.read().filter_map(|event| event.event.hit.position) {
+        let message = match phase.get() {
+            Phase::Fortify(player) | Phase::Arm(player) if *player == REMOTE => {
+                match pieces.next() {
+                    Some(Piece::Gun) => {
+                        let gun = placing
+                            .get_single()
+                            .map(|(_, p)| p.gun())
+                            .unwrap_or_default();
+                        Message::Arm(position, gun)
+                    }
+                    _ => Message::Place(position),
+                }
+            }
+            Phase::Target(player) if *player == REMOTE => {
+                Message::Target(position, controls.pressed(&keys, Action::Volley))
+            }
+            _ => continue,
+        };
+
+        if let Err(e) = peer.send(&message) {
+            warn!("error sending input: {}", e);
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{controls::Typing, helpers::GamePlayLifetime};
+
+use super::{lobby::LocalPlayer, Client, Host, Message};
+
+/// Most lines shown at once, older ones are dropped.
+const CHAT_LINES: usize = 6;
+
+/// How long a line stays up before it's gone, fading out over the last of it.
+const CHAT_SECONDS: f32 = 8.0;
+const FADE_SECONDS: f32 = 2.0;
+
+/// Longest line that can be typed.
+const LINE_LENGTH: usize = 120;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatLine {
+    pub name: String,
+    pub text: String,
+}
+
+/// Lines said by everyone, as the host passed them along, and whatever is
+/// being typed here.
+#[derive(Debug, Default, Resource)]
+pub struct Chat {
+    lines: VecDeque<(ChatLine, f32)>,
+    /// Being typed, from pressing enter until pressing it again.
+    typing: Option<String>,
+    /// Said here or passed along by the host, waiting to be sent.
+    outgoing: Vec<ChatLine>,
+}
+
+impl Chat {
+    /// Shows a line from `now` on.
+    pub fn show(&mut self, line: ChatLine, now: f32) {
+        self.lines.push_back((line, now));
+        while self.lines.len() > CHAT_LINES {
+            self.lines.pop_front();
+        }
+    }
+
+    /// Queues a line to go out to everyone else.
+    pub fn send(&mut self, line: ChatLine) {
+        self.outgoing.push(line);
+    }
+
+    /// Lines still showing, along with how visible each is. Everything is
+    /// shown in full while typing.
+    pub fn visible(&self, now: f32) -> Vec<(&ChatLine, f32)> {
+        self.lines
+            .iter()
+            .map(|(line, shown)| {
+                let remaining = CHAT_SECONDS - (now - shown);
+                let alpha = match self.typing {
+                    Some(_) => 1.0,
+                    None => (remaining / FADE_SECONDS).clamp(0.0, 1.0),
+                };
+                (line, alpha)
+            })
+            .filter(|(_, alpha)| *alpha > 0.0)
+            .collect()
+    }
+}
+
+#[derive(Component)]
+pub struct ChatText;
+
+pub fn spawn_chat(mut commands: Commands, mut chat: ResMut<Chat>) {
+    *chat = Chat::default();
+
+    commands
+        .spawn((
+            Name::new("Chat"),
+            GamePlayLifetime,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(12.),
+                    left: Val::Px(12.),
+                    max_width: Val::Px(480.),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((ChatText, TextBundle::default()));
+        });
+}
+
+/// Enter begins a line and enter again says it, an empty line says nothing.
+/// Keys only go into the line until then.
+pub fn type_chat(
+    mut chat: ResMut<Chat>,
+    mut keyboard: ResMut<Typing>,
+    mut characters: EventReader<ReceivedCharacter>,
+    keys: Res<ButtonInput<KeyCode>>,
+    local: Res<LocalPlayer>,
+    host: Option<Res<Host>>,
+    time: Res<Time>,
+) {
+    // Something else has the keyboard, or only just let go of it with the
+    // same enter that would start a line.
+    if chat.typing.is_none() && (*keyboard != Typing::Nothing || keyboard.is_changed()) {
+        characters.clear();
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Enter) {
+        *keyboard = Typing::Nothing;
+        match chat.typing.take() {
+            Some(text) if !text.trim().is_empty() => {
+                let line = ChatLine {
+                    name: local.0.name.clone(),
+                    text: text.trim().to_owned(),
+                };
+                // The host shows what it says right away, clients wait to hear
+                // it back along with everyone else.
+                if host.is_some() {
+                    chat.show(line.clone(), time.elapsed_seconds());
+                }
+                chat.send(line);
+            }
+            Some(_) => {}
+            None => {
+                chat.typing = Some(String::new());
+                *keyboard = Typing::Chat;
+            }
+        }
+        characters.clear();
+        return;
+    }
+
+    let Some(typing) = chat.typing.as_mut() else {
+        characters.clear();
+        return;
+    };
+
+    if keys.just_pressed(KeyCode::Backspace) {
+        typing.pop();
+    }
+
+    for event in characters.read() {
+        for character in event.char.chars().filter(|c| !c.is_control()) {
+            if typing.chars().count() < LINE_LENGTH {
+                typing.push(character);
+            }
+        }
+    }
+}
+
+/// Sends whatever is waiting, from the host to everyone connected and from
+/// clients to the host.
+pub fn send_chat(
+    mut chat: ResMut<Chat>,
+    mut host: Option<ResMut<Host>>,
+    mut client: Option<ResMut<Client>>,
+) {
+    if chat.outgoing.is_empty() {
+        return;
+    }
+
+    let messages: Vec<_> = chat.outgoing.drain(..).map(Message::Chat).collect();
+    let peers = host
+        .iter_mut()
+        .flat_map(|host| host.peers.iter_mut().map(|connected| &mut connected.peer))
+        .chain(client.iter_mut().filter_map(|client| client.peer.as_mut()));
+
+    for peer in peers {
+        for message in messages.iter() {
+            if let Err(e) = peer.send(message) {
+                warn!("error sending chat: {}", e);
+            }
+        }
+    }
+}
+
+pub fn update_chat(chat: Res<Chat>, mut texts: Query<&mut Text, With<ChatText>>, time: Res<Time>) {
+    let now = time.elapsed_seconds();
+    let style = |alpha: f32| TextStyle {
+        font_size: 16.,
+        color: Color::WHITE.with_a(alpha),
+        ..default()
+    };
+
+    let mut sections: Vec<TextSection> = chat
+        .visible(now)
+        .into_iter()
+        .map(|(line, alpha)| {
+            TextSection::new(format!("{}: {}\n", line.name, line.text), style(alpha))
+        })
+        .collect();
+    if let Some(typing) = &chat.typing {
+        sections.push(TextSection::new(format!("> {}_", typing), style(1.0)));
+    }
+
+    for mut text in &mut texts {
+        text.sections = sections.clone();
+    }
+}
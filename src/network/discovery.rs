@@ -0,0 +1,195 @@
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{Client, Host};
+
+/// Port hosts announce themselves on, and anyone looking for a game listens
+/// to.
+pub const DISCOVERY_PORT: u16 = 7879;
+
+const ANNOUNCE_SECONDS: f32 = 1.0;
+
+/// Hosts not heard from in this long are dropped from the list.
+const FORGET_SECONDS: f32 = 5.0;
+
+/// Sent ahead of every announcement, anything else arriving on the port is
+/// ignored.
+const MAGIC: &[u8] = b"castle:";
+
+/// What hosts broadcast about themselves.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Announcement {
+    pub name: String,
+    /// Where the game is being hosted, on whichever address it was heard from.
+    pub port: u16,
+}
+
+impl Announcement {
+    pub fn encode(&self) -> Vec<u8> {
+        let text = ron::ser::to_string(self).expect("error encoding announcement");
+
+        [MAGIC, text.as_bytes()].concat()
+    }
+
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let text = std::str::from_utf8(data.strip_prefix(MAGIC)?).ok()?;
+
+        ron::from_str(text).ok()
+    }
+}
+
+/// Broadcasts the hosted game to the local network.
+#[derive(Resource)]
+pub struct Announcer {
+    socket: UdpSocket,
+    announcement: Announcement,
+    timer: Timer,
+}
+
+/// Listens for games being announced.
+#[derive(Resource)]
+pub struct Discovery {
+    socket: UdpSocket,
+}
+
+#[derive(Debug, Clone)]
+pub struct DiscoveredHost {
+    pub name: String,
+    pub address: SocketAddr,
+    seen: f32,
+}
+
+/// Games heard about on the local network, in the order they were found.
+#[derive(Debug, Default, Resource)]
+pub struct DiscoveredHosts {
+    hosts: Vec<DiscoveredHost>,
+}
+
+impl DiscoveredHosts {
+    pub fn hosts(&self) -> &[DiscoveredHost] {
+        &self.hosts
+    }
+
+    /// Keeps track of a host that just announced itself, true when it's new.
+    pub fn heard(&mut self, address: SocketAddr, name: String, now: f32) -> bool {
+        match self.hosts.iter_mut().find(|host| host.address == address) {
+            Some(host) => {
+                host.name = name;
+                host.seen = now;
+                false
+            }
+            None => {
+                self.hosts.push(DiscoveredHost {
+                    name,
+                    address,
+                    seen: now,
+                });
+                true
+            }
+        }
+    }
+
+    /// Drops hosts that have gone quiet, true when there were any.
+    pub fn forget(&mut self, now: f32) -> bool {
+        let before = self.hosts.len();
+        self.hosts.retain(|host| now - host.seen < FORGET_SECONDS);
+
+        self.hosts.len() != before
+    }
+}
+
+/// Whoever is playing here, for telling hosted games apart.
+fn host_name() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .map(|user| format!("{}'s game", user))
+        .unwrap_or_else(|_| "Castle".to_owned())
+}
+
+pub fn start_announcing(mut commands: Commands, host: Res<Host>) {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+        .and_then(|socket| socket.set_broadcast(true).map(|_| socket));
+
+    match socket {
+        Ok(socket) => commands.insert_resource(Announcer {
+            socket,
+            announcement: Announcement {
+                name: host_name(),
+                port: host.port(),
+            },
+            timer: Timer::from_seconds(ANNOUNCE_SECONDS, TimerMode::Repeating),
+        }),
+        Err(e) => warn!("unable to announce game: {}", e),
+    }
+}
+
+pub fn announce(mut announcer: ResMut<Announcer>, time: Res<Time>) {
+    if !announcer.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let data = announcer.announcement.encode();
+    if let Err(e) = announcer
+        .socket
+        .send_to(&data, (Ipv4Addr::BROADCAST, DISCOVERY_PORT))
+    {
+        debug!("error announcing game: {}", e);
+    }
+}
+
+/// Only those joining without saying where go looking.
+pub fn looking(client: Option<Res<Client>>) -> bool {
+    client.is_some_and(|client| !client.addressed())
+}
+
+pub fn start_discovery(mut commands: Commands) {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, DISCOVERY_PORT))
+        .and_then(|socket| socket.set_nonblocking(true).map(|_| socket));
+
+    match socket {
+        Ok(socket) => commands.insert_resource(Discovery { socket }),
+        Err(e) => warn!("unable to look for games: {}", e),
+    }
+}
+
+pub fn discover(
+    discovery: Res<Discovery>,
+    mut discovered: ResMut<DiscoveredHosts>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_seconds();
+    let mut buffer = [0u8; 512];
+
+    loop {
+        match discovery.socket.recv_from(&mut buffer) {
+            Ok((read, from)) => {
+                let Some(announcement) = Announcement::decode(&buffer[..read]) else {
+                    continue;
+                };
+
+                let address = SocketAddr::new(from.ip(), announcement.port);
+
+                // Hosts are heard from every second, the list only needs
+                // rebuilding when one turns up or goes away.
+                if discovered
+                    .bypass_change_detection()
+                    .heard(address, announcement.name, now)
+                {
+                    info!(%address, "discovered");
+                    discovered.set_changed();
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+            Err(e) => {
+                debug!("error looking for games: {}", e);
+                break;
+            }
+        }
+    }
+
+    if discovered.bypass_change_detection().forget(now) {
+        discovered.set_changed();
+    }
+}
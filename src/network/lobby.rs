@@ -0,0 +1,588 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    model::{PhaseTimers, Rules, Seed, Settings},
+    profile::{self, Profile},
+};
+
+use super::{discovery::DiscoveredHosts, Client, Host};
+
+/// Map sizes the host can pick between.
+const SIZES: [u32; 5] = [32, 48, 64, 96, 128];
+
+/// Phase lengths the host can pick between, untimed first.
+const TIMER_CHOICES: [Option<f32>; 4] = [None, Some(15.), Some(30.), Some(60.)];
+
+/// Shots a round, repair costs, sudden death rounds and pieces to fortify with
+/// the host can pick between, none of them first.
+const AMMO_CHOICES: [Option<u32>; 4] = [None, Some(1), Some(2), Some(3)];
+const REPAIR_CHOICES: [Option<u32>; 4] = [None, Some(5), Some(10), Some(25)];
+const SUDDEN_DEATH_CHOICES: [Option<u32>; 4] = [None, Some(3), Some(5), Some(10)];
+const PIECE_CHOICES: [Option<u32>; 4] = [None, Some(5), Some(10), Some(20)];
+
+/// Most bookmarked seeds offered to the host, the latest of them.
+const BOOKMARKS_SHOWN: usize = 6;
+
+/// Longest name a player can give themselves.
+const NAME_LENGTH: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LobbyPlayer {
+    pub name: String,
+    /// Preferred color, as hex.
+    pub color: String,
+    pub ready: bool,
+    /// Watching rather than playing, given with `--spectate`.
+    pub spectating: bool,
+}
+
+/// Whoever is playing here, as everyone else in the lobby sees them.
+#[derive(Debug, Resource)]
+pub struct LocalPlayer(pub LobbyPlayer);
+
+impl Default for LocalPlayer {
+    fn default() -> Self {
+        let name = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "Player".to_owned());
+
+        Self(LobbyPlayer {
+            name: name.chars().take(NAME_LENGTH).collect(),
+            color: profile::COLORS[0].to_owned(),
+            ready: false,
+            spectating: false,
+        })
+    }
+}
+
+impl LocalPlayer {
+    /// Joining only to watch.
+    pub fn spectator() -> Self {
+        let Self(player) = Self::default();
+
+        Self(LobbyPlayer {
+            spectating: true,
+            ..player
+        })
+    }
+}
+
+/// How the next match is played and who's in it. The host decides and sends
+/// it to everyone connected whenever it changes.
+#[derive(Debug, Clone, PartialEq, Resource, Serialize, Deserialize)]
+pub struct Lobby {
+    pub seed: u32,
+    pub size: u32,
+    pub timers: PhaseTimers,
+    pub rules: Rules,
+    /// The host first, then everyone connected.
+    pub players: Vec<LobbyPlayer>,
+}
+
+impl Lobby {
+    pub fn new(settings: &Settings, timers: &PhaseTimers, rules: &Rules) -> Self {
+        Self {
+            seed: settings.seed().into(),
+            size: settings.size().x,
+            timers: timers.clone(),
+            rules: rules.clone(),
+            players: Vec::new(),
+        }
+    }
+
+    /// Everyone playing is ready and there's someone to play against,
+    /// spectators are never waited on.
+    pub fn ready(&self) -> bool {
+        let playing: Vec<_> = self.players.iter().filter(|p| !p.spectating).collect();
+
+        playing.len() > 1 && playing.iter().all(|player| player.ready)
+    }
+
+    /// Sets up the game here the way the lobby says.
+    pub fn apply(&self, settings: &mut Settings, timers: &mut PhaseTimers, rules: &mut Rules) {
+        settings.seed = Seed::new(self.seed);
+        settings.size = UVec2::splat(self.size);
+        *timers = self.timers.clone();
+        *rules = self.rules.clone();
+    }
+
+    pub fn change(&mut self, button: LobbyButton) {
+        match button {
+            LobbyButton::Seed => self.seed = rand::random(),
+            LobbyButton::Bookmark(seed) => self.seed = seed,
+            LobbyButton::Size => self.size = next_choice(&SIZES, self.size),
+            LobbyButton::Fortify => {
+                self.timers.fortify = next_choice(&TIMER_CHOICES, self.timers.fortify)
+            }
+            LobbyButton::Arm => self.timers.arm = next_choice(&TIMER_CHOICES, self.timers.arm),
+            LobbyButton::Target => {
+                self.timers.target = next_choice(&TIMER_CHOICES, self.timers.target)
+            }
+            LobbyButton::FusedShells => self.rules.fused_shells = !self.rules.fused_shells,
+            LobbyButton::LineOfSight => self.rules.line_of_sight = !self.rules.line_of_sight,
+            LobbyButton::FriendlyFire => self.rules.friendly_fire = !self.rules.friendly_fire,
+            LobbyButton::Volleys => self.rules.volleys = !self.rules.volleys,
+            LobbyButton::Ammo => self.rules.ammo = next_choice(&AMMO_CHOICES, self.rules.ammo),
+            LobbyButton::Repairs => {
+                self.rules.repair_cost = next_choice(&REPAIR_CHOICES, self.rules.repair_cost)
+            }
+            LobbyButton::SuddenDeath => {
+                self.rules.sudden_death =
+                    next_choice(&SUDDEN_DEATH_CHOICES, self.rules.sudden_death)
+            }
+            LobbyButton::Coop => self.rules.coop = !self.rules.coop,
+            LobbyButton::Pieces => {
+                self.rules.pieces = next_choice(&PIECE_CHOICES, self.rules.pieces)
+            }
+            LobbyButton::Color | LobbyButton::Ready => {}
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let timer = |seconds: Option<f32>| match seconds {
+            Some(seconds) => format!("{:.0}s", seconds),
+            None => "untimed".to_owned(),
+        };
+        let rule = |enabled: bool| if enabled { "on" } else { "off" };
+        let limit = |limit: Option<u32>, format: fn(u32) -> String| match limit {
+            Some(limit) => format(limit),
+            None => "off".to_owned(),
+        };
+
+        format!(
+            "Seed {}, {}x{}\nFortify {}, arm {}, target {}\nFused shells {}, line of sight {}\n\
+             Friendly fire {}, volleys {}, ammo {}\nRepairs {}, sudden death {}, co-op {}\n\
+             Pieces {} a turn",
+            self.seed,
+            self.size,
+            self.size,
+            timer(self.timers.fortify),
+            timer(self.timers.arm),
+            timer(self.timers.target),
+            rule(self.rules.fused_shells),
+            rule(self.rules.line_of_sight),
+            rule(self.rules.friendly_fire),
+            rule(self.rules.volleys),
+            limit(self.rules.ammo, |ammo| format!("{} a round", ammo)),
+            limit(self.rules.repair_cost, |cost| format!("{} points", cost)),
+            limit(self.rules.sudden_death, |round| format!(
+                "from round {}",
+                round
+            )),
+            rule(self.rules.coop),
+            self.rules.pieces.unwrap_or(1),
+        )
+    }
+}
+
+/// The choice after the current one, going back around to the first.
+fn next_choice<T: PartialEq + Copy>(choices: &[T], current: T) -> T {
+    let next = choices
+        .iter()
+        .position(|choice| *choice == current)
+        .map(|index| index + 1)
+        .unwrap_or_default();
+
+    choices[next % choices.len()]
+}
+
+#[derive(Component)]
+pub struct HostList;
+
+#[derive(Component)]
+pub struct HostEntries;
+
+#[derive(Component)]
+pub struct HostButton(String);
+
+#[derive(Component)]
+pub struct LobbyScreen;
+
+#[derive(Component)]
+pub struct LobbySettingsText;
+
+#[derive(Component)]
+pub struct LobbyPlayersText;
+
+#[derive(Component)]
+pub struct ReadyLabel;
+
+/// Only the host changes anything besides their own color and whether they're
+/// ready.
+#[derive(Component, Clone, Copy, Debug)]
+pub enum LobbyButton {
+    Seed,
+    /// A seed bookmarked here.
+    Bookmark(u32),
+    Size,
+    Fortify,
+    Arm,
+    Target,
+    FusedShells,
+    LineOfSight,
+    FriendlyFire,
+    Volleys,
+    Ammo,
+    Repairs,
+    SuddenDeath,
+    Coop,
+    Pieces,
+    Color,
+    Ready,
+}
+
+const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.2);
+const HOVERED_COLOR: Color = Color::rgb(0.25, 0.25, 0.35);
+
+fn text(value: &str, font_size: f32) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font_size,
+            color: Color::WHITE,
+            ..default()
+        },
+    )
+}
+
+pub fn spawn_host_list(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Lobby:Hosts"),
+            HostList,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(12.),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("Games on the local network", 32.));
+            parent.spawn((
+                HostEntries,
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(12.),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ));
+        });
+}
+
+pub fn despawn_host_list(mut commands: Commands, lists: Query<Entity, With<HostList>>) {
+    for entity in lists.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Rebuilds the list whenever a host turns up or goes away.
+pub fn update_host_list(
+    mut commands: Commands,
+    discovered: Res<DiscoveredHosts>,
+    entries: Query<Entity, With<HostEntries>>,
+) {
+    for entity in entries.iter() {
+        commands
+            .entity(entity)
+            .despawn_descendants()
+            .with_children(|parent| {
+                if discovered.hosts().is_empty() {
+                    parent.spawn(text("Looking for games...", 18.));
+                }
+
+                for host in discovered.hosts() {
+                    let address = host.address.to_string();
+                    parent
+                        .spawn((
+                            HostButton(address.clone()),
+                            ButtonBundle {
+                                style: Style {
+                                    width: Val::Px(480.),
+                                    padding: UiRect::all(Val::Px(8.)),
+                                    flex_direction: FlexDirection::Column,
+                                    ..default()
+                                },
+                                background_color: BUTTON_COLOR.into(),
+                                ..default()
+                            },
+                        ))
+                        .with_children(|button| {
+                            button.spawn(text(&host.name, 22.));
+                            button.spawn(text(&address, 16.));
+                        });
+                }
+            });
+    }
+}
+
+pub fn join_host(
+    mut client: ResMut<Client>,
+    mut buttons: Query<(&Interaction, &HostButton, &mut BackgroundColor), Changed<Interaction>>,
+) {
+    for (interaction, button, mut color) in &mut buttons {
+        match interaction {
+            Interaction::Pressed => {
+                info!(address = %button.0, "joining");
+                client.join(button.0.clone());
+            }
+            Interaction::Hovered => *color = HOVERED_COLOR.into(),
+            Interaction::None => *color = BUTTON_COLOR.into(),
+        }
+    }
+}
+
+fn spawn_button(parent: &mut ChildBuilder, label: &str, kind: LobbyButton) {
+    parent
+        .spawn((
+            kind,
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::axes(Val::Px(12.), Val::Px(6.)),
+                    ..default()
+                },
+                background_color: BUTTON_COLOR.into(),
+                ..default()
+            },
+        ))
+        .with_children(|button| {
+            let mut label = button.spawn(text(label, 18.));
+            if matches!(kind, LobbyButton::Ready) {
+                label.insert(ReadyLabel);
+            }
+        });
+}
+
+/// Shown once there's a lobby, either hosting one or having joined one,
+/// replacing the list of hosts.
+pub fn spawn_lobby(
+    mut commands: Commands,
+    screens: Query<(), With<LobbyScreen>>,
+    lists: Query<Entity, With<HostList>>,
+    mut lobby: ResMut<Lobby>,
+    host: Option<Res<Host>>,
+    profile: Res<Profile>,
+) {
+    if !screens.is_empty() {
+        return;
+    }
+
+    // Filled in once spawned, however long ago the lobby last changed.
+    lobby.set_changed();
+
+    for entity in lists.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    commands
+        .spawn((
+            Name::new("Lobby"),
+            LobbyScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(12.),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("Lobby", 32.));
+            parent.spawn((LobbySettingsText, text("", 18.)));
+
+            if host.is_some() {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            column_gap: Val::Px(6.),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        spawn_button(row, "New seed", LobbyButton::Seed);
+                        spawn_button(row, "Size", LobbyButton::Size);
+                        spawn_button(row, "Fortify", LobbyButton::Fortify);
+                        spawn_button(row, "Arm", LobbyButton::Arm);
+                        spawn_button(row, "Target", LobbyButton::Target);
+                        spawn_button(row, "Fused shells", LobbyButton::FusedShells);
+                        spawn_button(row, "Line of sight", LobbyButton::LineOfSight);
+                    });
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            column_gap: Val::Px(6.),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        spawn_button(row, "Friendly fire", LobbyButton::FriendlyFire);
+                        spawn_button(row, "Volleys", LobbyButton::Volleys);
+                        spawn_button(row, "Ammo", LobbyButton::Ammo);
+                        spawn_button(row, "Repairs", LobbyButton::Repairs);
+                        spawn_button(row, "Sudden death", LobbyButton::SuddenDeath);
+                        spawn_button(row, "Co-op", LobbyButton::Coop);
+                        spawn_button(row, "Pieces", LobbyButton::Pieces);
+                    });
+
+                if !profile.bookmarks.is_empty() {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                column_gap: Val::Px(6.),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|row| {
+                            for bookmark in profile.bookmarks.iter().rev().take(BOOKMARKS_SHOWN) {
+                                let label = match bookmark.note.as_str() {
+                                    "" => bookmark.seed.to_string(),
+                                    note => format!("{} ({})", note, bookmark.seed),
+                                };
+                                spawn_button(row, &label, LobbyButton::Bookmark(bookmark.seed));
+                            }
+                        });
+                }
+            }
+
+            parent.spawn((LobbyPlayersText, text("", 18.)));
+            parent.spawn(text("Type to change your name", 14.));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Val::Px(6.),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|row| {
+                    spawn_button(row, "Color", LobbyButton::Color);
+                    spawn_button(row, "Ready", LobbyButton::Ready);
+                });
+        });
+}
+
+pub fn despawn_lobby(mut commands: Commands, screens: Query<Entity, With<LobbyScreen>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn lobby_input(
+    mut buttons: Query<(&Interaction, &LobbyButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut lobby: ResMut<Lobby>,
+    mut local: ResMut<LocalPlayer>,
+    host: Option<Res<Host>>,
+) {
+    for (interaction, button, mut color) in &mut buttons {
+        match interaction {
+            Interaction::Pressed => match button {
+                LobbyButton::Color => {
+                    local.0.color = profile::next_color(&local.0.color).to_owned()
+                }
+                LobbyButton::Ready => local.0.ready = !local.0.ready,
+                button if host.is_some() => lobby.change(*button),
+                _ => {}
+            },
+            Interaction::Hovered => *color = HOVERED_COLOR.into(),
+            Interaction::None => *color = BUTTON_COLOR.into(),
+        }
+    }
+}
+
+pub fn type_name(
+    mut characters: EventReader<ReceivedCharacter>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut local: ResMut<LocalPlayer>,
+) {
+    if keys.just_pressed(KeyCode::Backspace) {
+        local.0.name.pop();
+    }
+
+    for event in characters.read() {
+        for character in event.char.chars().filter(|c| !c.is_control()) {
+            if local.0.name.chars().count() < NAME_LENGTH {
+                local.0.name.push(character);
+            }
+        }
+    }
+}
+
+/// Plays under the name and color kept in the profile here.
+pub fn load_local_player(profile: Res<Profile>, mut local: ResMut<LocalPlayer>) {
+    local.0.name = profile.name.chars().take(NAME_LENGTH).collect();
+    local.0.color = profile.color.clone();
+}
+
+/// Keeps whatever name and color were picked in the lobby for next time.
+pub fn remember_local_player(local: Res<LocalPlayer>, mut profile: ResMut<Profile>) {
+    if profile.name != local.0.name || profile.color != local.0.color {
+        profile.name = local.0.name.clone();
+        profile.color = local.0.color.clone();
+    }
+}
+
+pub fn update_lobby(
+    lobby: Res<Lobby>,
+    local: Res<LocalPlayer>,
+    mut settings: Query<&mut Text, (With<LobbySettingsText>, Without<LobbyPlayersText>)>,
+    mut players: Query<&mut Text, (With<LobbyPlayersText>, Without<LobbySettingsText>)>,
+    mut ready: Query<
+        &mut Text,
+        (
+            With<ReadyLabel>,
+            Without<LobbySettingsText>,
+            Without<LobbyPlayersText>,
+        ),
+    >,
+) {
+    for mut text in &mut settings {
+        text.sections[0].value = lobby.describe();
+    }
+
+    // Each player's line is shown in their color.
+    let sections: Vec<_> = lobby
+        .players
+        .iter()
+        .map(|player| {
+            let status = match (player.spectating, player.ready) {
+                (true, _) => "spectating",
+                (false, true) => "ready",
+                (false, false) => "not ready",
+            };
+            TextSection::new(
+                format!("{} - {}\n", player.name, status),
+                TextStyle {
+                    font_size: 18.,
+                    color: profile::color(&player.color),
+                    ..default()
+                },
+            )
+        })
+        .collect();
+
+    for mut text in &mut players {
+        text.sections = sections.clone();
+    }
+
+    for mut text in &mut ready {
+        text.sections[0].value = if local.0.ready { "Not ready" } else { "Ready" }.to_owned();
+    }
+}
@@ -0,0 +1,124 @@
+use std::{
+    io::{self, Read, Write},
+    net::TcpStream,
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{building::catalog::Gun, journal::Entry, scenario::Scenario, snapshot::Snapshot};
+
+use super::{
+    chat::ChatLine,
+    lobby::{Lobby, LobbyPlayer},
+};
+
+/// Everything sent between a host and the clients playing along, one to a
+/// line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Message {
+    /// The lobby as the host has it, sent whenever it changes.
+    Lobby(Lobby),
+    /// A client's name and whether they're ready to begin.
+    Player(LobbyPlayer),
+    /// What a client needs to catch up, whether joining for the first time or
+    /// again after losing the connection: the terrain, the latest snapshot and
+    /// everything recorded since.
+    Resume {
+        scenario: Scenario,
+        snapshot: Snapshot,
+        entries: Vec<Entry>,
+    },
+    /// Entries recorded on the host since the last were sent.
+    Entries(Vec<Entry>),
+    /// A client asking to build where they clicked.
+    Place(Vec3),
+    /// A client asking to put a gun of the kind where they clicked.
+    Arm(Vec3, Gun),
+    /// A client picking where to fire, and whether as a volley.
+    Target(Vec3, bool),
+    /// A client throwing away the piece they'd have placed next.
+    Discard,
+    /// A client setting aside the piece they'd have placed next.
+    Hold,
+    /// A client turning their cannon under the cursor, at the position.
+    Turn(Vec3),
+    /// Something said, by a client to the host and passed along by the host
+    /// to everyone.
+    Chat(ChatLine),
+}
+
+/// One end of a connection, never blocking on either reading or writing.
+pub struct Peer {
+    stream: TcpStream,
+    incoming: Vec<u8>,
+    outgoing: Vec<u8>,
+}
+
+impl Peer {
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+
+        Ok(Self {
+            stream,
+            incoming: Vec::new(),
+            outgoing: Vec::new(),
+        })
+    }
+
+    pub fn send(&mut self, message: &Message) -> io::Result<()> {
+        let text = ron::ser::to_string(message)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        self.outgoing.extend_from_slice(text.as_bytes());
+        self.outgoing.push(b'\n');
+
+        self.flush()
+    }
+
+    /// Writes as much of what's waiting as the connection will take right
+    /// now, the rest goes out on a later call.
+    pub fn flush(&mut self) -> io::Result<()> {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(written) => {
+                    self.outgoing.drain(..written);
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Messages that have arrived in full since the last call. Fails once the
+    /// other end has gone away.
+    pub fn receive(&mut self) -> io::Result<Vec<Message>> {
+        let mut buffer = [0u8; 4096];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(read) => self.incoming.extend_from_slice(&buffer[..read]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        let mut messages = Vec::new();
+        while let Some(end) = self.incoming.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.incoming.drain(..=end).collect();
+            let text = std::str::from_utf8(&line[..end])
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let message =
+                ron::from_str(text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            messages.push(message);
+        }
+
+        Ok(messages)
+    }
+}
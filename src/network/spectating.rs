@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+
+use crate::{
+    building::StructureLayers,
+    camera::CameraMode,
+    helpers::PlayerColor,
+    model::{Player, TILE_SIZE},
+    terrain::Terrain,
+};
+
+use super::{lobby::LocalPlayer, Client};
+
+/// Joined only to watch, given with `--spectate`.
+pub fn spectating(client: Option<Res<Client>>, local: Res<LocalPlayer>) -> bool {
+    client.is_some() && local.0.spectating
+}
+
+/// Spectators aren't tied to either castle, so they fly around instead.
+pub fn free_camera(mut camera_mode: ResMut<NextState<CameraMode>>) {
+    camera_mode.set(CameraMode::Free);
+}
+
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct TerritoryGizmos;
+
+/// Shades everything inside each player's walls in their color, so both sides
+/// can be followed at once, by spectators and anyone zoomed out.
+pub fn draw_territories(
+    mut gizmos: Gizmos<TerritoryGizmos>,
+    structures: Option<Res<StructureLayers>>,
+    terrain: Query<&Terrain>,
+) {
+    let (Ok(terrain), Some(structures)) = (terrain.get_single(), structures) else {
+        return;
+    };
+
+    let flat = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
+
+    for player in [Player::One, Player::Two] {
+        let color = player.color().with_a(0.3);
+        let territory = structures.territory(&player);
+
+        for (grid, enclosed) in territory.enumerate_cells() {
+            if !*enclosed {
+                continue;
+            }
+
+            if let Some(survey) = terrain.survey_grid(grid) {
+                gizmos.rect(
+                    survey.world() + Vec3::Y * 0.05,
+                    flat,
+                    Vec2::splat(TILE_SIZE * 0.9),
+                    color,
+                );
+            }
+        }
+    }
+}
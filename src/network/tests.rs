@@ -0,0 +1,388 @@
+use std::{
+    net::{TcpListener, TcpStream},
+    thread,
+    time::Duration,
+};
+
+use bevy::math::Vec3;
+
+use crate::{
+    building::{catalog::Gun, Piece},
+    model::{Phase, PhaseTimers, Player, Rules},
+};
+
+use super::{
+    chat::{Chat, ChatLine},
+    discovery::{Announcement, DiscoveredHosts},
+    lobby::{Lobby, LobbyButton, LobbyPlayer},
+    validation::{check_piece, check_turn, seated, Rejection, Request},
+    Message, Peer,
+};
+
+fn connected() -> (Peer, Peer) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (server, _) = listener.accept().unwrap();
+
+    (Peer::new(client).unwrap(), Peer::new(server).unwrap())
+}
+
+/// Keeps receiving until something arrives, as nothing ever blocks.
+fn receive(peer: &mut Peer) -> Vec<Message> {
+    for _ in 0..100 {
+        let messages = peer.receive().unwrap();
+        if !messages.is_empty() {
+            return messages;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    Vec::new()
+}
+
+#[test]
+fn test_messages_arrive_in_order() {
+    let (mut client, mut server) = connected();
+
+    client.send(&Message::Place(Vec3::new(1., 0., 2.))).unwrap();
+    client
+        .send(&Message::Target(Vec3::new(3., 0., 4.), true))
+        .unwrap();
+
+    let mut messages = receive(&mut server);
+    if messages.len() < 2 {
+        messages.extend(receive(&mut server));
+    }
+
+    assert!(matches!(messages[0], Message::Place(p) if p == Vec3::new(1., 0., 2.)));
+    assert!(matches!(messages[1], Message::Target(p, true) if p == Vec3::new(3., 0., 4.)));
+}
+
+#[test]
+fn test_nothing_waiting_is_not_an_error() {
+    let (_client, mut server) = connected();
+
+    assert!(server.receive().unwrap().is_empty());
+}
+
+#[test]
+fn test_closed_connection_fails() {
+    let (client, mut server) = connected();
+    drop(client);
+
+    let mut closed = false;
+    for _ in 0..100 {
+        if server.receive().is_err() {
+            closed = true;
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    assert!(closed);
+}
+
+#[test]
+fn test_announcements_round_trip() {
+    let announcement = Announcement {
+        name: "Someone's game".to_owned(),
+        port: 7878,
+    };
+
+    assert_eq!(
+        Announcement::decode(&announcement.encode()),
+        Some(announcement)
+    );
+}
+
+#[test]
+fn test_stray_packets_are_not_announcements() {
+    assert_eq!(Announcement::decode(b"(name:\"x\",port:1)"), None);
+    assert_eq!(Announcement::decode(b"castle:garbage"), None);
+}
+
+#[test]
+fn test_hosts_are_forgotten_once_quiet() {
+    let mut hosts = DiscoveredHosts::default();
+    let first = "10.0.0.2:7878".parse().unwrap();
+    let second = "10.0.0.3:7878".parse().unwrap();
+
+    assert!(hosts.heard(first, "First".to_owned(), 0.0));
+    assert!(hosts.heard(second, "Second".to_owned(), 1.0));
+    assert!(!hosts.heard(first, "First".to_owned(), 4.0));
+    assert_eq!(hosts.hosts().len(), 2);
+
+    assert!(hosts.forget(7.0));
+    assert_eq!(hosts.hosts().len(), 1);
+    assert_eq!(hosts.hosts()[0].address, first);
+    assert!(!hosts.forget(7.0));
+}
+
+fn lobby() -> Lobby {
+    Lobby {
+        seed: 1,
+        size: 64,
+        timers: PhaseTimers::default(),
+        rules: Rules::default(),
+        players: vec![LobbyPlayer {
+            name: "Host".to_owned(),
+            color: "3a86ff".to_owned(),
+            ready: true,
+            spectating: false,
+        }],
+    }
+}
+
+#[test]
+fn test_lobby_choices_go_around() {
+    let mut lobby = lobby();
+
+    lobby.change(LobbyButton::Size);
+    assert_eq!(lobby.size, 96);
+    lobby.change(LobbyButton::Size);
+    lobby.change(LobbyButton::Size);
+    assert_eq!(lobby.size, 32);
+
+    lobby.change(LobbyButton::Fortify);
+    assert_eq!(lobby.timers.fortify, Some(15.));
+    lobby.change(LobbyButton::LineOfSight);
+    assert!(lobby.rules.line_of_sight);
+
+    lobby.change(LobbyButton::Bookmark(1234));
+    assert_eq!(lobby.seed, 1234);
+}
+
+#[test]
+fn test_lobby_needs_everyone_ready() {
+    let mut lobby = lobby();
+    assert!(!lobby.ready());
+
+    lobby.players.push(LobbyPlayer {
+        name: "Guest".to_owned(),
+        color: "3a86ff".to_owned(),
+        ready: false,
+        spectating: false,
+    });
+    assert!(!lobby.ready());
+
+    lobby.players[1].ready = true;
+    assert!(lobby.ready());
+}
+
+#[test]
+fn test_lobby_never_waits_on_spectators() {
+    let mut lobby = lobby();
+    lobby.players.push(LobbyPlayer {
+        name: "Watcher".to_owned(),
+        color: "3a86ff".to_owned(),
+        ready: false,
+        spectating: true,
+    });
+    assert!(!lobby.ready());
+
+    lobby.players.push(LobbyPlayer {
+        name: "Guest".to_owned(),
+        color: "3a86ff".to_owned(),
+        ready: true,
+        spectating: false,
+    });
+    assert!(lobby.ready());
+}
+
+fn player(name: &str, spectating: bool) -> LobbyPlayer {
+    LobbyPlayer {
+        name: name.to_owned(),
+        color: "3a86ff".to_owned(),
+        ready: false,
+        spectating,
+    }
+}
+
+#[test]
+fn test_seat_goes_to_the_first_player() {
+    let watcher = player("Watcher", true);
+    let guest = player("Guest", false);
+    let late = player("Late", false);
+
+    assert_eq!(seated(&[]), None);
+    assert_eq!(seated(&[None, Some(&watcher)]), None);
+    assert_eq!(seated(&[Some(&watcher), None, Some(&guest)]), Some(2));
+    assert_eq!(seated(&[Some(&guest), Some(&late)]), Some(0));
+}
+
+fn said(text: &str) -> ChatLine {
+    ChatLine {
+        name: "Someone".to_owned(),
+        text: text.to_owned(),
+    }
+}
+
+#[test]
+fn test_chat_keeps_the_latest_lines() {
+    let mut chat = Chat::default();
+    for number in 0..10 {
+        chat.show(said(&number.to_string()), 0.0);
+    }
+
+    let visible = chat.visible(0.0);
+    assert_eq!(visible.len(), 6);
+    assert_eq!(visible[0].0.text, "4");
+}
+
+#[test]
+fn test_chat_fades_out() {
+    let mut chat = Chat::default();
+    chat.show(said("hello"), 0.0);
+
+    assert_eq!(chat.visible(1.0)[0].1, 1.0);
+    assert_eq!(chat.visible(7.0)[0].1, 0.5);
+    assert!(chat.visible(8.0).is_empty());
+}
+
+#[test]
+fn test_requests_only_on_their_turn() {
+    let place = Request::Place(Vec3::ZERO);
+    let target = Request::Target(Vec3::ZERO, false);
+
+    assert_eq!(
+        check_turn(&Phase::Fortify(Player::One), Player::Two, &place, false),
+        Err(Rejection::NotTheirTurn)
+    );
+    assert_eq!(
+        check_turn(&Phase::Fortify(Player::Two), Player::Two, &place, false),
+        Ok(())
+    );
+    assert_eq!(
+        check_turn(&Phase::Arm(Player::Two), Player::Two, &target, false),
+        Err(Rejection::WrongPhase)
+    );
+    assert_eq!(
+        check_turn(&Phase::Target(Player::Two), Player::Two, &place, false),
+        Err(Rejection::WrongPhase)
+    );
+    assert_eq!(
+        check_turn(&Phase::Target(Player::Two), Player::Two, &target, false),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_one_piece_a_turn() {
+    assert_eq!(
+        check_turn(
+            &Phase::Fortify(Player::Two),
+            Player::Two,
+            &Request::Place(Vec3::ZERO),
+            true
+        ),
+        Err(Rejection::AlreadyPlaced)
+    );
+}
+
+#[test]
+fn test_guns_placed_while_building() {
+    let arm = Request::Arm(Vec3::ZERO, Gun::Mortar);
+
+    assert_eq!(
+        check_turn(&Phase::Arm(Player::Two), Player::Two, &arm, false),
+        Ok(())
+    );
+    assert_eq!(
+        check_turn(&Phase::Arm(Player::Two), Player::Two, &arm, true),
+        Err(Rejection::AlreadyPlaced)
+    );
+    assert_eq!(
+        check_turn(&Phase::Target(Player::Two), Player::Two, &arm, false),
+        Err(Rejection::WrongPhase)
+    );
+}
+
+#[test]
+fn test_only_the_next_piece_is_placed() {
+    let arm = Request::Arm(Vec3::ZERO, Gun::Cannon);
+    let place = Request::Place(Vec3::ZERO);
+
+    assert_eq!(check_piece(&arm, Some(Piece::Gun)), Ok(()));
+    assert_eq!(check_piece(&place, Some(Piece::Wall)), Ok(()));
+    assert_eq!(
+        check_piece(&place, Some(Piece::Gun)),
+        Err(Rejection::WrongPiece)
+    );
+    assert_eq!(
+        check_piece(&arm, Some(Piece::Wall)),
+        Err(Rejection::WrongPiece)
+    );
+    assert_eq!(check_piece(&arm, None), Err(Rejection::AlreadyPlaced));
+}
+
+#[test]
+fn test_discards_only_while_fortifying() {
+    assert_eq!(
+        check_turn(
+            &Phase::Fortify(Player::Two),
+            Player::Two,
+            &Request::Discard,
+            false
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        check_turn(
+            &Phase::Arm(Player::Two),
+            Player::Two,
+            &Request::Discard,
+            false
+        ),
+        Err(Rejection::WrongPhase)
+    );
+    assert_eq!(
+        check_turn(
+            &Phase::Fortify(Player::Two),
+            Player::Two,
+            &Request::Discard,
+            true
+        ),
+        Err(Rejection::AlreadyPlaced)
+    );
+}
+
+#[test]
+fn test_holds_whenever_placing() {
+    for phase in [Phase::Fortify(Player::Two), Phase::Arm(Player::Two)] {
+        assert_eq!(
+            check_turn(&phase, Player::Two, &Request::Hold, false),
+            Ok(())
+        );
+        assert_eq!(
+            check_turn(&phase, Player::Two, &Request::Hold, true),
+            Err(Rejection::AlreadyPlaced)
+        );
+    }
+    assert_eq!(
+        check_turn(
+            &Phase::Target(Player::Two),
+            Player::Two,
+            &Request::Hold,
+            false
+        ),
+        Err(Rejection::WrongPhase)
+    );
+}
+
+#[test]
+fn test_turns_only_while_arming() {
+    let turn = Request::Turn(Vec3::ZERO);
+
+    assert_eq!(
+        check_turn(&Phase::Arm(Player::Two), Player::Two, &turn, true),
+        Ok(())
+    );
+    assert_eq!(
+        check_turn(&Phase::Arm(Player::One), Player::Two, &turn, false),
+        Err(Rejection::NotTheirTurn)
+    );
+    assert_eq!(
+        check_turn(&Phase::Fortify(Player::Two), Player::Two, &turn, false),
+        Err(Rejection::WrongPhase)
+    );
+}
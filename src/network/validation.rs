@@ -0,0 +1,172 @@
+use bevy::prelude::*;
+
+use crate::{
+    building::{self, catalog::Gun, Cannon, Footing, Piece, Structure, StructureLayers},
+    model::{Phase, Player},
+    terrain::{Props, Terrain},
+};
+
+use super::lobby::LobbyPlayer;
+
+/// Something a client asked the host to do.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Request {
+    Place(Vec3),
+    /// Where to put a gun, and which kind.
+    Arm(Vec3, Gun),
+    /// Where to fire, and whether as a volley.
+    Target(Vec3, bool),
+    Discard,
+    Hold,
+    /// Turning the cannon at the position.
+    Turn(Vec3),
+}
+
+/// Why the host refused a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    /// Someone else's turn.
+    NotTheirTurn,
+    /// Their turn, but not for doing that.
+    WrongPhase,
+    /// Every piece dealt for the turn placed, anything more has to wait.
+    AlreadyPlaced,
+    /// Nowhere that can be built on.
+    Unbuildable,
+    /// Guns only go on the ground inside their walls.
+    OutsideWalls,
+    /// Asking to place something other than the piece that's next.
+    WrongPiece,
+    /// Beyond the reach of any of their cannons.
+    OutOfRange,
+    /// Only watching, so never their turn.
+    Spectating,
+    /// Thrown away as many pieces as they're allowed this turn.
+    NoDiscards,
+    /// None of their cannons there to turn.
+    NoCannon,
+    /// Set a piece aside already since they last placed one.
+    AlreadyHeld,
+}
+
+/// Which of those connected has the seat, the first to have said who they are
+/// without asking to spectate. Everyone else only watches, and whoever comes
+/// back after losing their connection gets the seat back once they've said.
+pub fn seated(players: &[Option<&LobbyPlayer>]) -> Option<usize> {
+    players
+        .iter()
+        .position(|player| player.is_some_and(|player| !player.spectating))
+}
+
+/// Checks a request made by whoever is playing `player` is theirs to make
+/// right now, before anything about where. They've `placed` once every piece
+/// dealt for the turn has been.
+pub fn check_turn(
+    phase: &Phase,
+    player: Player,
+    request: &Request,
+    placed: bool,
+) -> Result<(), Rejection> {
+    if phase.player() != player {
+        return Err(Rejection::NotTheirTurn);
+    }
+
+    match (request, phase) {
+        (Request::Place(_) | Request::Arm(..), Phase::Fortify(_) | Phase::Arm(_)) if placed => {
+            Err(Rejection::AlreadyPlaced)
+        }
+        (Request::Place(_) | Request::Arm(..), Phase::Fortify(_) | Phase::Arm(_)) => Ok(()),
+        (Request::Discard, Phase::Fortify(_)) if placed => Err(Rejection::AlreadyPlaced),
+        (Request::Discard, Phase::Fortify(_)) => Ok(()),
+        (Request::Hold, Phase::Fortify(_) | Phase::Arm(_)) if placed => {
+            Err(Rejection::AlreadyPlaced)
+        }
+        (Request::Hold, Phase::Fortify(_) | Phase::Arm(_)) => Ok(()),
+        (Request::Target(..), Phase::Target(_)) => Ok(()),
+        (Request::Turn(_), Phase::Arm(_)) => Ok(()),
+        _ => Err(Rejection::WrongPhase),
+    }
+}
+
+/// Checks what's asked to be placed is the `next` piece, walls placed and guns
+/// armed.
+pub fn check_piece(request: &Request, next: Option<Piece>) -> Result<(), Rejection> {
+    match (request, next) {
+        (Request::Place(_), Some(Piece::Wall)) | (Request::Arm(..), Some(Piece::Gun)) => Ok(()),
+        (Request::Place(_) | Request::Arm(..), Some(_)) => Err(Rejection::WrongPiece),
+        (Request::Place(_) | Request::Arm(..), None) => Err(Rejection::AlreadyPlaced),
+        _ => Ok(()),
+    }
+}
+
+/// Where a piece would go and what it'd stand on, if it can go there at all.
+pub fn check_place(
+    terrain: &Terrain,
+    structures: &StructureLayers,
+    props: &Props,
+    position: Vec3,
+) -> Result<(IVec2, Footing), Rejection> {
+    building::buildable(terrain, structures, props, position).ok_or(Rejection::Unbuildable)
+}
+
+/// Where a gun would go, as long as it's on ground inside the player's walls.
+pub fn check_armed(
+    terrain: &Terrain,
+    structures: &StructureLayers,
+    props: &Props,
+    player: Player,
+    position: Vec3,
+) -> Result<IVec2, Rejection> {
+    check_place(terrain, structures, props, position)?;
+
+    building::armable(terrain, structures, props, &player, position).ok_or(Rejection::OutsideWalls)
+}
+
+/// What a request to place a piece builds and where, walls or chains wherever
+/// they can go and guns inside the player's walls.
+pub fn check_placed(
+    terrain: &Terrain,
+    structures: &StructureLayers,
+    props: &Props,
+    player: Player,
+    request: &Request,
+) -> Result<(IVec2, Structure), Rejection> {
+    match *request {
+        Request::Place(position) => check_place(terrain, structures, props, position)
+            .map(|(grid, footing)| (grid, footing.structure(player))),
+        Request::Arm(position, gun) => check_armed(terrain, structures, props, player, position)
+            .map(|grid| (grid, Structure::Cannon(Cannon::armed(player, gun)))),
+        _ => Err(Rejection::WrongPhase),
+    }
+}
+
+/// The target, as long as one of the player's cannons can reach it.
+pub fn check_target(
+    terrain: &Terrain,
+    structures: &StructureLayers,
+    player: Player,
+    target: Vec3,
+) -> Result<Vec3, Rejection> {
+    let reachable = structures
+        .guns(&player)
+        .into_iter()
+        .filter_map(|(grid, gun)| terrain.survey_grid(grid).map(|survey| (survey, gun)))
+        .any(|(cannon, gun)| gun.entry().ordnance.in_range(cannon.world(), target));
+
+    reachable.then_some(target).ok_or(Rejection::OutOfRange)
+}
+
+/// Where the player's cannon at the position is, along with how it'd face
+/// once turned.
+pub fn check_turned(
+    terrain: &Terrain,
+    structures: &StructureLayers,
+    player: Player,
+    position: Vec3,
+) -> Result<(IVec2, Cannon), Rejection> {
+    terrain
+        .survey(position)
+        .map(|survey| survey.location())
+        .and_then(|grid| Some((grid, structures.cannon(grid, &player)?.turned())))
+        .ok_or(Rejection::NoCannon)
+}
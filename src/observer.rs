@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use bevy_rts_camera::{RtsCamera, RtsCameraControls};
+
+use crate::{
+    camera::{self, CameraMode},
+    controls::{typing, Action, Controls},
+    firing::ShotFired,
+    model::{AppState, Phase, Player, Settings},
+    network::{self, Client, Host, REMOTE},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// How quickly the camera turns toward an incoming shot.
+const FOCUS_SPEED: f32 = 3.0;
+
+/// Hands the camera over to flying around freely while the computer or
+/// someone across the network takes their turn, turning to follow their shots
+/// when asked to, and puts it back as it was once it's this player's turn.
+pub struct ObserverPlugin;
+
+impl Plugin for ObserverPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Observer>()
+            .add_systems(OnExit(AppState::Game), stop_observing)
+            .add_systems(
+                Update,
+                (
+                    watch_turns,
+                    toggle_following.run_if(not(typing)),
+                    camera::fly_camera.run_if(observing).run_if(not(typing)),
+                    focus_shots,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Game))
+                    .run_if(not(network::spectating)),
+            );
+    }
+}
+
+#[derive(Debug, Resource)]
+pub struct Observer {
+    /// Turns to look at wherever the other player fires.
+    pub follow_shots: bool,
+    held: Option<Held>,
+    focus: Option<Vec3>,
+}
+
+impl Default for Observer {
+    fn default() -> Self {
+        Self {
+            follow_shots: true,
+            held: None,
+            focus: None,
+        }
+    }
+}
+
+/// The camera as it was before observing, to go back to.
+#[derive(Debug)]
+struct Held {
+    camera: Entity,
+    transform: Transform,
+    rts: Option<RtsCamera>,
+}
+
+/// Whether the player's turn is taken by someone other than whoever's here,
+/// the computer or a player across the network.
+pub fn opponents_turn(player: &Player, computer: bool, hosting: bool, joined: bool) -> bool {
+    computer || (hosting && *player == REMOTE) || (joined && *player != REMOTE)
+}
+
+pub fn observing(observer: Res<Observer>) -> bool {
+    observer.held.is_some()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn watch_turns(
+    mut commands: Commands,
+    mut observer: ResMut<Observer>,
+    cameras: Query<(Entity, &Transform, Option<&RtsCamera>), With<Camera>>,
+    phase: Res<State<Phase>>,
+    mode: Res<State<CameraMode>>,
+    settings: Res<Settings>,
+    host: Option<Res<Host>>,
+    client: Option<Res<Client>>,
+) {
+    let player = phase.get().player();
+    let watching = *mode.get() == CameraMode::Normal
+        && opponents_turn(
+            &player,
+            settings.computer(&player),
+            host.is_some(),
+            client.is_some(),
+        );
+
+    match (watching, observer.held.is_some()) {
+        (true, false) => {
+            let Ok((camera, transform, rts)) = cameras.get_single() else {
+                return;
+            };
+
+            info!(?player, "observing");
+
+            observer.held = Some(Held {
+                camera,
+                transform: *transform,
+                rts: rts.cloned(),
+            });
+            commands
+                .entity(camera)
+                .remove::<(RtsCamera, RtsCameraControls)>();
+        }
+        (false, true) => {
+            info!(?player, "observing-ended");
+            restore(&mut commands, &mut observer);
+        }
+        _ => {}
+    }
+}
+
+/// Puts the camera back where it was, unless it's been replaced since.
+fn restore(commands: &mut Commands, observer: &mut Observer) {
+    observer.focus = None;
+
+    let Some(held) = observer.held.take() else {
+        return;
+    };
+
+    if let Some(mut camera) = commands.get_entity(held.camera) {
+        camera.insert(held.transform);
+        if let Some(rts) = held.rts {
+            camera.insert((rts, RtsCameraControls::default()));
+        }
+    }
+}
+
+fn stop_observing(mut commands: Commands, mut observer: ResMut<Observer>) {
+    restore(&mut commands, &mut observer);
+}
+
+fn toggle_following(
+    keys: Res<ButtonInput<KeyCode>>,
+    controls: Res<Controls>,
+    mut observer: ResMut<Observer>,
+) {
+    if controls.just_pressed(&keys, Action::FollowShots) {
+        observer.follow_shots = !observer.follow_shots;
+        info!(following = %observer.follow_shots, "follow-shots");
+    }
+}
+
+/// Turns the camera toward the latest shot fired while observing, until
+/// they look around for themselves.
+fn focus_shots(
+    mut observer: ResMut<Observer>,
+    mut shots: EventReader<ShotFired>,
+    mut cameras: Query<&mut Transform, With<Camera>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    time: Res<Time<Real>>,
+) {
+    let latest = shots.read().last().map(|shot| shot.target());
+    if observer.held.is_none() {
+        return;
+    }
+
+    if observer.follow_shots && latest.is_some() {
+        observer.focus = latest;
+    }
+    if !observer.follow_shots || buttons.pressed(MouseButton::Right) {
+        observer.focus = None;
+    }
+
+    let Some(focus) = observer.focus else {
+        return;
+    };
+
+    for mut transform in &mut cameras {
+        let wanted = transform.looking_at(focus, Vec3::Y).rotation;
+        let turned = (FOCUS_SPEED * time.delta_seconds()).min(1.0);
+        transform.rotation = transform.rotation.slerp(wanted, turned);
+    }
+}
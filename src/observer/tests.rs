@@ -0,0 +1,19 @@
+use crate::{model::Player, network::REMOTE};
+
+use super::opponents_turn;
+
+#[test]
+fn test_computer_turns_are_observed_playing_alone() {
+    assert!(opponents_turn(&Player::Two, true, false, false));
+    assert!(!opponents_turn(&Player::One, false, false, false));
+    assert!(!opponents_turn(&Player::Two, false, false, false));
+}
+
+#[test]
+fn test_whoever_is_across_the_network_is_observed() {
+    assert!(opponents_turn(&REMOTE, false, true, false));
+    assert!(!opponents_turn(&REMOTE.next(), false, true, false));
+
+    assert!(opponents_turn(&REMOTE.next(), false, false, true));
+    assert!(!opponents_turn(&REMOTE, false, false, true));
+}
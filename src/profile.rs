@@ -0,0 +1,406 @@
+use std::{io, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    challenge::in_challenge,
+    controls::Typing,
+    helpers::GamePlayLifetime,
+    journal::{Entry, EventJournal, Recorded},
+    leaderboard::{Leaderboard, Outcome},
+    model::{AppState, Phase, Player, Settings},
+    network::{self, Client},
+    replay::in_replay,
+};
+
+#[cfg(test)]
+mod tests;
+
+const PROFILE_PATH: &str = "profile.ron";
+
+/// Longest note that can be kept with a bookmark.
+const NOTE_LENGTH: usize = 40;
+
+/// Colors a player can pick between, the first is the default.
+pub const COLORS: [&str; 6] = ["3a86ff", "ffbe0b", "8ac926", "ff595e", "c77dff", "f4f4f4"];
+
+/// Keeps track of whoever plays here across matches, loaded as the game starts
+/// and saved whenever it changes.
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        let profile = match Profile::load(PROFILE_PATH) {
+            Ok(profile) => profile,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Profile::default(),
+            Err(e) => {
+                warn!("error loading profile: {}", e);
+                Profile::default()
+            }
+        };
+
+        app.insert_resource(profile)
+            .init_resource::<BookmarkNote>()
+            .add_systems(
+                Update,
+                save_profile
+                    .run_if(resource_changed::<Profile>)
+                    .run_if(not(resource_added::<Profile>)),
+            )
+            .add_systems(
+                OnEnter(AppState::Game),
+                spawn_profile_label.run_if(not(in_challenge)),
+            )
+            .add_systems(OnEnter(AppState::Game), spawn_bookmark_label)
+            .add_systems(
+                Update,
+                (
+                    type_bookmark,
+                    update_bookmark_label.run_if(resource_changed::<BookmarkNote>),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                OnExit(AppState::Game),
+                record_match
+                    .run_if(not(in_replay))
+                    .run_if(not(network::spectating)),
+            );
+    }
+}
+
+/// Totals over every match played here.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stats {
+    pub matches: u32,
+    pub won: u32,
+    pub shots: u32,
+    /// Structures of the other player's destroyed.
+    pub hits: u32,
+}
+
+impl Stats {
+    /// How a single match went for `player`, from everything recorded during
+    /// it. Only matches that got as far as firing count, and they're won by
+    /// finishing ahead on points.
+    pub fn tally(entries: &[Entry], player: &Player) -> Self {
+        let mut stats = Self::default();
+        let mut totals = [0, 0];
+
+        for entry in entries {
+            match &entry.recorded {
+                Recorded::Phase(Phase::Target(_)) => stats.matches = 1,
+                Recorded::Shot(shooter, _, _) if shooter == player => stats.shots += 1,
+                Recorded::Destroyed(_, structure) if structure.player() != *player => {
+                    stats.hits += 1
+                }
+                Recorded::Score(scorer, _, total) => {
+                    totals[usize::from(scorer != player)] = *total;
+                }
+                _ => {}
+            }
+        }
+
+        if stats.matches == 0 {
+            return Self::default();
+        }
+
+        stats.won = u32::from(totals[0] > totals[1]);
+
+        stats
+    }
+
+    pub fn add(&mut self, other: &Stats) {
+        self.matches += other.matches;
+        self.won += other.won;
+        self.shots += other.shots;
+        self.hits += other.hits;
+    }
+
+    /// Hits for every shot fired, nothing until there's been a shot. Volleys
+    /// can take out more than one thing a shot, so this is capped at all of
+    /// them.
+    pub fn accuracy(&self) -> Option<f32> {
+        (self.shots > 0).then(|| (self.hits as f32 / self.shots as f32).min(1.0))
+    }
+}
+
+/// How targets are picked while firing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Aiming {
+    /// Clicking on where to fire.
+    #[default]
+    Click,
+    /// Dragging from a cannon for which way and how hard, with the elevation
+    /// set on its own.
+    Manual,
+    /// Looking down the barrel of a cannon and firing wherever it's pointed.
+    FirstPerson,
+}
+
+impl Aiming {
+    /// The next way of aiming, going back around to clicking.
+    pub fn toggled(&self) -> Self {
+        match self {
+            Aiming::Click => Aiming::Manual,
+            Aiming::Manual => Aiming::FirstPerson,
+            Aiming::FirstPerson => Aiming::Click,
+        }
+    }
+}
+
+/// A seed worth playing again, starred during a game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub seed: u32,
+    pub note: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    /// Preferred color, as hex.
+    pub color: String,
+    pub stats: Stats,
+    /// Oldest first.
+    pub bookmarks: Vec<Bookmark>,
+    pub aiming: Aiming,
+    /// How matches against the computer have gone.
+    pub leaderboard: Leaderboard,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .unwrap_or_else(|_| "Player".to_owned()),
+            color: COLORS[0].to_owned(),
+            stats: Stats::default(),
+            bookmarks: Vec::new(),
+            aiming: Aiming::default(),
+            leaderboard: Leaderboard::default(),
+        }
+    }
+}
+
+impl Profile {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+
+        ron::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        std::fs::write(path, text)
+    }
+
+    pub fn color(&self) -> Color {
+        color(&self.color)
+    }
+
+    /// Stars the seed, or changes its note when it already is.
+    pub fn bookmark(&mut self, seed: u32, note: &str) {
+        let note = note.trim().chars().take(NOTE_LENGTH).collect();
+        match self.bookmarks.iter_mut().find(|b| b.seed == seed) {
+            Some(bookmark) => bookmark.note = note,
+            None => self.bookmarks.push(Bookmark { seed, note }),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let accuracy = match self.stats.accuracy() {
+            Some(accuracy) => format!("{:.0}%", accuracy * 100.),
+            None => "-".to_owned(),
+        };
+
+        format!(
+            "{} - won {} of {}, accuracy {}",
+            self.name, self.stats.won, self.stats.matches, accuracy
+        )
+    }
+}
+
+/// Whether the player's aiming by hand rather than clicking on targets.
+pub fn aiming_manually(profile: Res<Profile>) -> bool {
+    profile.aiming == Aiming::Manual
+}
+
+/// A color as picked from `COLORS`, white when it isn't one.
+pub fn color(hex: &str) -> Color {
+    Color::hex(hex).unwrap_or(Color::WHITE)
+}
+
+/// The color after the current one, going back around to the first.
+pub fn next_color(current: &str) -> &'static str {
+    let next = COLORS
+        .iter()
+        .position(|color| *color == current)
+        .map(|index| index + 1)
+        .unwrap_or_default();
+
+    COLORS[next % COLORS.len()]
+}
+
+fn save_profile(profile: Res<Profile>) {
+    match profile.save(PROFILE_PATH) {
+        Ok(_) => info!(path = PROFILE_PATH, "profile-saved"),
+        Err(e) => warn!("error saving profile: {}", e),
+    }
+}
+
+/// Adds how the match just finished went to the lifetime totals, and to the
+/// leaderboard when it was against the computer.
+fn record_match(
+    mut profile: ResMut<Profile>,
+    journal: Res<EventJournal>,
+    settings: Res<Settings>,
+    client: Option<Res<Client>>,
+) {
+    let player = match client {
+        Some(_) => network::REMOTE,
+        None => Player::One,
+    };
+
+    let stats = Stats::tally(journal.entries(), &player);
+    if stats.matches == 0 {
+        return;
+    }
+
+    info!(?stats, "match-recorded");
+
+    profile.stats.add(&stats);
+
+    if let (Some(ai), None) = (&settings.ai, &client) {
+        let outcome = Outcome {
+            difficulty: ai.difficulty,
+            seed: settings.seed().into(),
+            won: stats.won > 0,
+            score: journal.total(&player),
+            seconds: journal.entries().last().map_or(0.0, |entry| entry.at),
+        };
+
+        info!(?outcome, "leaderboard-recorded");
+
+        profile.leaderboard.record(&outcome);
+    }
+}
+
+fn spawn_profile_label(mut commands: Commands, profile: Res<Profile>) {
+    commands.spawn((
+        Name::new("Profile"),
+        GamePlayLifetime,
+        TextBundle::from_section(
+            profile.describe(),
+            TextStyle {
+                font_size: 18.,
+                color: profile.color(),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.),
+            left: Val::Px(12.),
+            ..default()
+        }),
+    ));
+}
+
+/// A note being typed for the seed being played, from pressing K until enter
+/// stars it or backspace on an empty note gives up on it. Escape already
+/// closes the window.
+#[derive(Debug, Default, Resource)]
+struct BookmarkNote(Option<String>);
+
+#[derive(Component)]
+struct BookmarkLabel;
+
+fn type_bookmark(
+    mut note: ResMut<BookmarkNote>,
+    mut keyboard: ResMut<Typing>,
+    mut characters: EventReader<ReceivedCharacter>,
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    mut profile: ResMut<Profile>,
+) {
+    let Some(typing) = note.0.as_mut() else {
+        // Only when nothing else is being typed, K could be part of a chat line.
+        if keys.just_pressed(KeyCode::KeyK) && *keyboard == Typing::Nothing {
+            note.0 = Some(String::new());
+            *keyboard = Typing::Note;
+        }
+        characters.clear();
+        return;
+    };
+
+    if keys.just_pressed(KeyCode::Enter) {
+        let seed: u32 = settings.seed().into();
+        info!(%seed, note = %typing, "seed-bookmarked");
+        profile.bookmark(seed, typing);
+        note.0 = None;
+        *keyboard = Typing::Nothing;
+        characters.clear();
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Backspace) && typing.pop().is_none() {
+        note.0 = None;
+        *keyboard = Typing::Nothing;
+        characters.clear();
+        return;
+    }
+
+    for event in characters.read() {
+        for character in event.char.chars().filter(|c| !c.is_control()) {
+            if typing.chars().count() < NOTE_LENGTH {
+                typing.push(character);
+            }
+        }
+    }
+}
+
+fn spawn_bookmark_label(mut commands: Commands, mut note: ResMut<BookmarkNote>) {
+    note.0 = None;
+
+    commands.spawn((
+        Name::new("Bookmark"),
+        BookmarkLabel,
+        GamePlayLifetime,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(36.),
+            left: Val::Px(12.),
+            ..default()
+        }),
+    ));
+}
+
+fn update_bookmark_label(
+    note: Res<BookmarkNote>,
+    settings: Res<Settings>,
+    mut labels: Query<&mut Text, With<BookmarkLabel>>,
+) {
+    let value = match &note.0 {
+        Some(typing) => format!("Bookmark seed {}: {}_", u32::from(settings.seed()), typing),
+        None => String::new(),
+    };
+
+    for mut text in labels.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
@@ -0,0 +1,167 @@
+use bevy::math::{IVec2, Vec3};
+
+use crate::{
+    building::{Structure, Wall},
+    journal::{Entry, Recorded},
+    leaderboard::Leaderboard,
+    model::{Phase, Player},
+};
+
+use super::{next_color, Aiming, Bookmark, Profile, Stats, COLORS, NOTE_LENGTH};
+
+fn entry(recorded: Recorded) -> Entry {
+    Entry { at: 0.0, recorded }
+}
+
+fn shot(player: Player) -> Entry {
+    entry(Recorded::Shot(player, Vec3::ZERO, Vec3::ONE))
+}
+
+fn destroyed(player: Player) -> Entry {
+    entry(Recorded::Destroyed(
+        IVec2::ZERO,
+        Structure::Wall(Wall::new(player)),
+    ))
+}
+
+#[test]
+fn test_tally_only_counts_matches_that_got_to_firing() {
+    let entries = vec![
+        entry(Recorded::Phase(Phase::Fortify(Player::One))),
+        entry(Recorded::Score(Player::One, 10, 10)),
+    ];
+
+    assert_eq!(Stats::tally(&entries, &Player::One), Stats::default());
+}
+
+#[test]
+fn test_tally_shots_hits_and_winning() {
+    let entries = vec![
+        entry(Recorded::Phase(Phase::Target(Player::One))),
+        shot(Player::One),
+        shot(Player::One),
+        shot(Player::Two),
+        destroyed(Player::Two),
+        destroyed(Player::One),
+        entry(Recorded::Score(Player::One, 5, 5)),
+        entry(Recorded::Score(Player::Two, 3, 3)),
+    ];
+
+    assert_eq!(
+        Stats::tally(&entries, &Player::One),
+        Stats {
+            matches: 1,
+            won: 1,
+            shots: 2,
+            hits: 1,
+        }
+    );
+    assert_eq!(
+        Stats::tally(&entries, &Player::Two),
+        Stats {
+            matches: 1,
+            won: 0,
+            shots: 1,
+            hits: 1,
+        }
+    );
+}
+
+#[test]
+fn test_accuracy() {
+    let mut stats = Stats::default();
+    assert_eq!(stats.accuracy(), None);
+
+    stats.add(&Stats {
+        matches: 1,
+        won: 0,
+        shots: 4,
+        hits: 1,
+    });
+    assert_eq!(stats.accuracy(), Some(0.25));
+
+    stats.hits = 10;
+    assert_eq!(stats.accuracy(), Some(1.0));
+}
+
+#[test]
+fn test_colors_cycle() {
+    assert_eq!(next_color(COLORS[0]), COLORS[1]);
+    assert_eq!(next_color(COLORS[COLORS.len() - 1]), COLORS[0]);
+    assert_eq!(next_color("nonsense"), COLORS[0]);
+}
+
+#[test]
+fn test_bookmarking_again_changes_the_note() {
+    let mut profile = Profile::default();
+
+    profile.bookmark(7, "Narrow strait ");
+    profile.bookmark(9, "");
+    profile.bookmark(7, "Wide strait");
+
+    assert_eq!(
+        profile.bookmarks,
+        vec![
+            Bookmark {
+                seed: 7,
+                note: "Wide strait".to_owned(),
+            },
+            Bookmark {
+                seed: 9,
+                note: String::new(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_bookmark_notes_are_limited() {
+    let mut profile = Profile::default();
+
+    profile.bookmark(1, &"x".repeat(NOTE_LENGTH * 2));
+
+    assert_eq!(profile.bookmarks[0].note.len(), NOTE_LENGTH);
+}
+
+#[test]
+fn test_profile_round_trip() {
+    let path = std::env::temp_dir().join(format!("castle-profile-{}.ron", std::process::id()));
+    let profile = Profile {
+        name: "Someone".to_owned(),
+        color: COLORS[2].to_owned(),
+        stats: Stats {
+            matches: 3,
+            won: 2,
+            shots: 40,
+            hits: 12,
+        },
+        bookmarks: vec![Bookmark {
+            seed: 1234,
+            note: "Two islands".to_owned(),
+        }],
+        aiming: Aiming::Manual,
+        leaderboard: Leaderboard::default(),
+    };
+
+    profile.save(&path).unwrap();
+    assert_eq!(Profile::load(&path).unwrap(), profile);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_aiming_goes_around_every_way_and_back_to_clicking() {
+    let ways = std::iter::successors(Some(Aiming::Click), |aiming| Some(aiming.toggled()))
+        .take(4)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        ways,
+        vec![
+            Aiming::Click,
+            Aiming::Manual,
+            Aiming::FirstPerson,
+            Aiming::Click
+        ]
+    );
+}
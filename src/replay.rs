@@ -0,0 +1,341 @@
+use std::path::Path;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    building::{CannonTurned, ConstructionEvent, DemolitionEvent, StructureLayers},
+    controls::typing,
+    journal::{Entry, EventJournal, Recorded},
+    model::{AppState, GameRng, Phase, PhaseTimers, Player, Settings},
+    network::following,
+    scenario::{self, Scenario, ScenarioError},
+    snapshot::{self, Capture, RestoreSnapshot, Snapshot},
+    terrain::Terrain,
+    units::WaveDirector,
+    weather::Weather,
+};
+
+mod migration;
+#[cfg(test)]
+mod tests;
+mod timeline;
+
+const REPLAY_PATH: &str = "replay.ron";
+
+/// Records every game so it can be watched again with `--replay`, and plays
+/// them back.
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Recording>()
+            .add_systems(
+                OnEnter(AppState::Game),
+                start_recording.run_if(not(following)),
+            )
+            .add_systems(
+                Update,
+                take_snapshots
+                    .after(snapshot::take_snapshot)
+                    .run_if(in_state(AppState::Game))
+                    .run_if(not(following)),
+            )
+            .add_systems(
+                OnEnter(Phase::Fortify(Player::One)),
+                save_replay
+                    .run_if(in_state(AppState::Game))
+                    .run_if(not(following)),
+            )
+            .add_systems(OnExit(AppState::Game), save_replay.run_if(not(following)))
+            .add_systems(
+                OnEnter(AppState::Game),
+                (begin_playback, timeline::spawn_timeline).run_if(in_replay),
+            )
+            .add_systems(
+                Update,
+                (
+                    timeline::timeline_input.run_if(not(typing)),
+                    play,
+                    timeline::update_timeline,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Game))
+                    .run_if(in_replay),
+            );
+    }
+}
+
+pub fn in_replay(playback: Option<Res<Playback>>) -> bool {
+    playback.is_some()
+}
+
+/// A game as it was played, the terrain it was played on along with snapshots
+/// and everything from the journal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    /// Format the replay was written in, see `migration`.
+    #[serde(default)]
+    pub version: u32,
+    pub scenario: Scenario,
+    pub snapshots: Vec<Snapshot>,
+    pub entries: Vec<Entry>,
+}
+
+impl Replay {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ScenarioError> {
+        let text = std::fs::read_to_string(path)?;
+
+        scenario::migration::upgrade(&text, migration::STEPS)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ScenarioError> {
+        let text = ron::ser::to_string(self).map_err(|e| ScenarioError::Format(e.to_string()))?;
+
+        std::fs::write(path, text)?;
+
+        Ok(())
+    }
+
+    /// How long the game went on for, as far as anything recorded.
+    pub fn duration(&self) -> f32 {
+        let entries = self.entries.last().map(|entry| entry.at);
+        let snapshots = self.snapshots.last().map(|snapshot| snapshot.at);
+
+        entries.into_iter().chain(snapshots).fold(0.0, f32::max)
+    }
+
+    /// When each phase began, for jumping between them.
+    pub fn markers(&self) -> Vec<(f32, Phase)> {
+        self.snapshots
+            .iter()
+            .map(|snapshot| (snapshot.at, snapshot.phase.clone()))
+            .collect()
+    }
+
+    /// The game as it was `at` seconds in, along with the first journal entry
+    /// after then. Starts from the latest snapshot before then and goes
+    /// through the journal from there.
+    pub fn state_at(&self, at: f32) -> (Snapshot, usize) {
+        let mut snapshot = self
+            .snapshots
+            .iter()
+            .rev()
+            .find(|snapshot| snapshot.at <= at)
+            .or(self.snapshots.first())
+            .cloned()
+            .unwrap_or_default();
+
+        let from = snapshot.entry.min(self.entries.len());
+        let cursor = from
+            + self.entries[from..]
+                .iter()
+                .take_while(|entry| entry.at <= at)
+                .count();
+        snapshot.apply(&self.entries[from..cursor]);
+
+        snapshot.at = at;
+        snapshot.entry = cursor;
+
+        (snapshot, cursor)
+    }
+}
+
+/// The game being recorded, for saving along with the journal.
+#[derive(Debug, Default, Resource)]
+struct Recording {
+    scenario: Option<Scenario>,
+    snapshots: Vec<Snapshot>,
+}
+
+fn start_recording(mut recording: ResMut<Recording>) {
+    *recording = Recording::default();
+}
+
+/// Snapshots everything as each phase begins, capturing the terrain along with
+/// the first of them.
+#[allow(clippy::too_many_arguments)]
+fn take_snapshots(
+    mut recording: ResMut<Recording>,
+    journal: Res<EventJournal>,
+    phase: Res<State<Phase>>,
+    structures: Option<Res<StructureLayers>>,
+    terrain: Query<&Terrain>,
+    director: Res<WaveDirector>,
+    weather: Res<Weather>,
+    rng: Res<GameRng>,
+    settings: Res<Settings>,
+    timers: Res<PhaseTimers>,
+    time: Res<Time>,
+) {
+    if !phase.is_changed() {
+        return;
+    }
+
+    let (Ok(terrain), Some(structures)) = (terrain.get_single(), structures) else {
+        return;
+    };
+
+    if recording.scenario.is_none() {
+        recording.scenario = Some(Scenario {
+            timers: Some(timers.clone()),
+            ..Scenario::capture(&settings, terrain, &structures)
+        });
+    }
+
+    recording.snapshots.push(Snapshot::capture(
+        Capture {
+            journal: &journal,
+            structures: &structures,
+            director: &director,
+            weather: &weather,
+            rng: &rng,
+        },
+        phase.get().clone(),
+        time.elapsed_seconds(),
+    ));
+}
+
+fn save_replay(recording: Res<Recording>, journal: Res<EventJournal>) {
+    let Some(scenario) = &recording.scenario else {
+        return;
+    };
+
+    let replay = Replay {
+        version: migration::CURRENT_VERSION,
+        scenario: scenario.clone(),
+        snapshots: recording.snapshots.clone(),
+        entries: journal.entries().to_vec(),
+    };
+
+    match replay.save(REPLAY_PATH) {
+        Ok(_) => info!("saved {}", REPLAY_PATH),
+        Err(e) => warn!("error saving replay: {}", e),
+    }
+}
+
+/// Speeds a replay can be watched at.
+const SPEEDS: [f32; 4] = [0.5, 1.0, 2.0, 4.0];
+
+/// A replay being watched, given with `--replay`.
+#[derive(Debug, Resource)]
+pub struct Playback {
+    replay: Replay,
+    time: f32,
+    speed: f32,
+    paused: bool,
+    /// The next journal entry to play.
+    cursor: usize,
+    /// Where to jump to, done on the next update.
+    seeking: Option<f32>,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            time: 0.0,
+            speed: 1.0,
+            paused: false,
+            cursor: 0,
+            seeking: None,
+        }
+    }
+
+    pub fn seek(&mut self, at: f32) {
+        self.seeking = Some(at.clamp(0.0, self.replay.duration()));
+    }
+
+    /// Jumps to the beginning of the phase after, or before, the current one.
+    fn skip(&mut self, forward: bool) {
+        let markers = self.replay.markers().into_iter().map(|(at, _)| at);
+        let at = if forward {
+            markers.filter(|at| *at > self.time).reduce(f32::min)
+        } else {
+            // A moment into a phase goes back to its start rather than the
+            // one before.
+            markers.filter(|at| *at < self.time - 1.0).reduce(f32::max)
+        };
+
+        self.seek(at.unwrap_or(if forward { self.replay.duration() } else { 0.0 }));
+    }
+}
+
+fn begin_playback(mut playback: ResMut<Playback>) {
+    playback.seek(0.0);
+}
+
+/// Plays journal entries recorded elsewhere into this game, through the same
+/// events and states as when they happened.
+#[derive(SystemParam)]
+pub struct FollowJournal<'w> {
+    construction: EventWriter<'w, ConstructionEvent>,
+    turns: EventWriter<'w, CannonTurned>,
+    demolitions: EventWriter<'w, DemolitionEvent>,
+    next_phase: ResMut<'w, NextState<Phase>>,
+    journal: ResMut<'w, EventJournal>,
+    time: Res<'w, Time>,
+}
+
+impl<'w> FollowJournal<'w> {
+    pub fn play(&mut self, entry: &Entry) {
+        match &entry.recorded {
+            Recorded::Constructed(grid, structure) => {
+                self.construction
+                    .send(ConstructionEvent::new((*grid).into(), structure.clone()));
+            }
+            Recorded::Turned(grid, cannon) => {
+                self.turns
+                    .send(CannonTurned::new((*grid).into(), cannon.clone()));
+            }
+            Recorded::Destroyed(grid, _) => {
+                self.demolitions.send(DemolitionEvent::new((*grid).into()));
+            }
+            Recorded::Phase(phase) => self.next_phase.set(phase.clone()),
+            Recorded::Score(player, _, total) => {
+                self.journal
+                    .restore_score(self.time.elapsed_seconds(), *player, *total);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Moves the replay along, putting everything back the way it was when
+/// jumping elsewhere and otherwise playing the journal entries as their time
+/// comes.
+fn play(
+    mut playback: ResMut<Playback>,
+    mut restores: EventWriter<RestoreSnapshot>,
+    mut follow: FollowJournal,
+    time: Res<Time>,
+) {
+    let playback = &mut *playback;
+
+    if let Some(at) = playback.seeking.take() {
+        let (snapshot, cursor) = playback.replay.state_at(at);
+        info!(%at, phase = ?snapshot.phase, "seeking");
+
+        restores.send(RestoreSnapshot(snapshot));
+        playback.time = at;
+        playback.cursor = cursor;
+        return;
+    }
+
+    if playback.paused {
+        return;
+    }
+
+    playback.time =
+        (playback.time + time.delta_seconds() * playback.speed).min(playback.replay.duration());
+
+    while let Some(entry) = playback
+        .replay
+        .entries
+        .get(playback.cursor)
+        .filter(|entry| entry.at <= playback.time)
+    {
+        follow.play(entry);
+        playback.cursor += 1;
+    }
+}
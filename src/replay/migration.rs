@@ -0,0 +1,18 @@
+use crate::scenario::migration::Step;
+
+use super::Replay;
+
+/// Steps for every replay version after the first, in order.
+pub const STEPS: &[Step<Replay>] = &[headed];
+
+/// Written to every replay saved, bumped whenever the format changes in a way
+/// older files need help with.
+pub const CURRENT_VERSION: u32 = STEPS.len() as u32;
+
+/// Nothing but the header was added.
+fn headed(replay: Replay) -> Replay {
+    Replay {
+        version: 1,
+        ..replay
+    }
+}
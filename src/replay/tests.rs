@@ -0,0 +1,147 @@
+use bevy::math::IVec2;
+
+use crate::{
+    building::{Cannon, Structure, Wall},
+    journal::{Entry, Recorded},
+    model::{Phase, Player},
+    scenario::{self, PlacedStructure, Scenario},
+    snapshot::Snapshot,
+};
+
+use super::{migration, Replay};
+
+fn wall(player: Player) -> Structure {
+    Structure::Wall(Wall::new(player))
+}
+
+fn entry(at: f32, recorded: Recorded) -> Entry {
+    Entry { at, recorded }
+}
+
+fn replay() -> Replay {
+    Replay {
+        version: migration::CURRENT_VERSION,
+        scenario: Scenario {
+            version: 1,
+            seed: 1,
+            size: (8, 8),
+            heights: None,
+            castles: Vec::new(),
+            structures: Vec::new(),
+            timers: None,
+            rules: None,
+            challenge: None,
+            script: None,
+            progress: None,
+        },
+        snapshots: vec![
+            Snapshot {
+                at: 0.0,
+                entry: 0,
+                round: 1,
+                phase: Phase::Fortify(Player::One),
+                structures: vec![PlacedStructure::new(IVec2::new(1, 1), wall(Player::One))],
+                ..Default::default()
+            },
+            Snapshot {
+                at: 10.0,
+                entry: 2,
+                round: 1,
+                phase: Phase::Arm(Player::One),
+                structures: vec![
+                    PlacedStructure::new(IVec2::new(1, 1), wall(Player::One)),
+                    PlacedStructure::new(IVec2::new(2, 1), wall(Player::One)),
+                ],
+                ..Default::default()
+            },
+        ],
+        entries: vec![
+            entry(
+                5.0,
+                Recorded::Constructed(IVec2::new(2, 1), wall(Player::One)),
+            ),
+            entry(10.0, Recorded::Phase(Phase::Arm(Player::One))),
+            entry(
+                12.0,
+                Recorded::Constructed(
+                    IVec2::new(3, 3),
+                    Structure::Cannon(Cannon::new(Player::One)),
+                ),
+            ),
+            entry(
+                15.0,
+                Recorded::Destroyed(IVec2::new(1, 1), wall(Player::One)),
+            ),
+        ],
+    }
+}
+
+fn grids(snapshot: &Snapshot) -> Vec<IVec2> {
+    let mut grids: Vec<IVec2> = snapshot
+        .structures
+        .iter()
+        .map(|placed| placed.location())
+        .collect();
+    grids.sort_by_key(|grid| (grid.x, grid.y));
+    grids
+}
+
+#[test]
+fn test_state_from_first_snapshot() {
+    let (snapshot, cursor) = replay().state_at(6.0);
+
+    assert_eq!(grids(&snapshot), vec![IVec2::new(1, 1), IVec2::new(2, 1)]);
+    assert_eq!(snapshot.phase, Phase::Fortify(Player::One));
+    assert_eq!(snapshot.at, 6.0);
+    assert_eq!(cursor, 1);
+}
+
+#[test]
+fn test_state_from_latest_snapshot() {
+    let (snapshot, cursor) = replay().state_at(20.0);
+
+    assert_eq!(grids(&snapshot), vec![IVec2::new(2, 1), IVec2::new(3, 3)]);
+    assert_eq!(snapshot.phase, Phase::Arm(Player::One));
+    assert_eq!(snapshot.scores, vec![(Player::Two, 10)]);
+    assert_eq!(cursor, 5);
+}
+
+#[test]
+fn test_duration_and_markers() {
+    let replay = replay();
+
+    assert_eq!(replay.duration(), 16.0);
+    assert_eq!(
+        replay.markers(),
+        vec![
+            (0.0, Phase::Fortify(Player::One)),
+            (10.0, Phase::Arm(Player::One))
+        ]
+    );
+}
+
+fn fixture(name: &str) -> String {
+    let path = format!(
+        "{}/src/replay/fixtures/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    std::fs::read_to_string(path).unwrap()
+}
+
+fn assert_fixture(replay: &Replay) {
+    assert_eq!(replay.version, migration::CURRENT_VERSION);
+    assert_eq!(replay.scenario.seed, 7);
+    assert_eq!(replay.snapshots.len(), 1);
+    assert_eq!(replay.entries.len(), 3);
+    assert_eq!(replay.duration(), 12.0);
+}
+
+#[test]
+fn test_every_replay_version_upgrades() {
+    for version in 0..=migration::CURRENT_VERSION {
+        let text = fixture(&format!("v{}.ron", version));
+
+        assert_fixture(&scenario::migration::upgrade(&text, migration::STEPS).unwrap());
+    }
+}
@@ -0,0 +1,240 @@
+use bevy::{prelude::*, ui::RelativeCursorPosition};
+
+use crate::{
+    helpers::{GamePlayLifetime, PlayerColor},
+    model::{Phase, Player},
+};
+
+use super::{Playback, SPEEDS};
+
+#[derive(Component, Clone, Copy, Debug)]
+pub enum TimelineButton {
+    Pause,
+    Speed(f32),
+    /// Jumps to when a phase began.
+    Marker(f32),
+}
+
+/// The bar along the timeline, clicked anywhere to jump there.
+#[derive(Component)]
+pub struct TimelineTrack;
+
+#[derive(Component)]
+pub struct TimelineProgress;
+
+#[derive(Component)]
+pub struct TimelineLabel;
+
+#[derive(Component)]
+pub struct PauseLabel;
+
+const IDLE_BUTTON: Color = Color::rgba(0.2, 0.2, 0.2, 0.8);
+const ACTIVE_BUTTON: Color = Color::rgba(0.4, 0.4, 0.4, 0.8);
+
+fn text(value: &str, font_size: f32) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font_size,
+            color: Color::WHITE,
+            ..default()
+        },
+    )
+}
+
+fn button() -> ButtonBundle {
+    ButtonBundle {
+        style: Style {
+            padding: UiRect::axes(Val::Px(8.), Val::Px(2.)),
+            ..default()
+        },
+        background_color: IDLE_BUTTON.into(),
+        ..default()
+    }
+}
+
+pub fn spawn_timeline(mut commands: Commands, playback: Res<Playback>) {
+    let duration = playback.replay.duration().max(f32::EPSILON);
+
+    commands
+        .spawn((
+            Name::new("Replay:Timeline"),
+            GamePlayLifetime,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(12.),
+                    left: Val::Percent(25.),
+                    width: Val::Percent(50.),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.),
+                    padding: UiRect::all(Val::Px(6.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.4).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Val::Px(6.),
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn((button(), TimelineButton::Pause))
+                        .with_children(|button| {
+                            button.spawn((PauseLabel, text("Pause", 16.)));
+                        });
+                    for speed in SPEEDS {
+                        row.spawn((button(), TimelineButton::Speed(speed)))
+                            .with_children(|button| {
+                                button.spawn(text(&format!("{}x", speed), 16.));
+                            });
+                    }
+                    row.spawn((TimelineLabel, text("", 16.)));
+                });
+
+            parent
+                .spawn((
+                    TimelineTrack,
+                    RelativeCursorPosition::default(),
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Percent(100.),
+                            height: Val::Px(16.),
+                            ..default()
+                        },
+                        background_color: IDLE_BUTTON.into(),
+                        ..default()
+                    },
+                ))
+                .with_children(|track| {
+                    track.spawn((
+                        TimelineProgress,
+                        NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                width: Val::Percent(0.),
+                                height: Val::Percent(100.),
+                                ..default()
+                            },
+                            background_color: Color::rgba(1., 1., 1., 0.5).into(),
+                            ..default()
+                        },
+                    ));
+
+                    for (at, phase) in playback.replay.markers() {
+                        // Rounds begin with the first player fortifying, those
+                        // stand out from the rest.
+                        let width = match phase {
+                            Phase::Fortify(Player::One) => 4.,
+                            _ => 2.,
+                        };
+                        track.spawn((
+                            TimelineButton::Marker(at),
+                            ButtonBundle {
+                                style: Style {
+                                    position_type: PositionType::Absolute,
+                                    left: Val::Percent(at / duration * 100.),
+                                    width: Val::Px(width),
+                                    height: Val::Percent(100.),
+                                    ..default()
+                                },
+                                background_color: phase.player().color().into(),
+                                ..default()
+                            },
+                        ));
+                    }
+                });
+        });
+}
+
+/// Space pauses, comma and period jump to the previous and next phases.
+pub fn timeline_input(
+    mut playback: ResMut<Playback>,
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Query<(&Interaction, &TimelineButton), Changed<Interaction>>,
+    track: Query<
+        (&Interaction, &RelativeCursorPosition),
+        (With<TimelineTrack>, Changed<Interaction>),
+    >,
+) {
+    if keys.just_pressed(KeyCode::Space) {
+        playback.paused = !playback.paused;
+    }
+    if keys.just_pressed(KeyCode::Comma) {
+        playback.skip(false);
+    }
+    if keys.just_pressed(KeyCode::Period) {
+        playback.skip(true);
+    }
+
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            TimelineButton::Pause => playback.paused = !playback.paused,
+            TimelineButton::Speed(speed) => playback.speed = *speed,
+            TimelineButton::Marker(at) => playback.seek(*at),
+        }
+    }
+
+    for (interaction, cursor) in &track {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        if let Some(position) = cursor.normalized {
+            let at = position.x * playback.replay.duration();
+            playback.seek(at);
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn update_timeline(
+    playback: Res<Playback>,
+    mut progress: Query<&mut Style, With<TimelineProgress>>,
+    mut labels: Query<&mut Text, (With<TimelineLabel>, Without<PauseLabel>)>,
+    mut pause: Query<&mut Text, (With<PauseLabel>, Without<TimelineLabel>)>,
+    mut buttons: Query<(&TimelineButton, &mut BackgroundColor)>,
+) {
+    if !playback.is_changed() {
+        return;
+    }
+
+    let duration = playback.replay.duration();
+    let fraction = if duration > 0.0 {
+        playback.time / duration
+    } else {
+        0.0
+    };
+
+    for mut style in &mut progress {
+        style.width = Val::Percent(fraction * 100.);
+    }
+
+    for mut text in &mut labels {
+        text.sections[0].value = format!("{:.0}s / {:.0}s", playback.time, duration);
+    }
+
+    for mut text in &mut pause {
+        text.sections[0].value = if playback.paused { "Play" } else { "Pause" }.to_owned();
+    }
+
+    for (button, mut color) in &mut buttons {
+        let active = match button {
+            TimelineButton::Pause => playback.paused,
+            TimelineButton::Speed(speed) => *speed == playback.speed,
+            TimelineButton::Marker(_) => continue,
+        };
+        *color = if active { ACTIVE_BUTTON } else { IDLE_BUTTON }.into();
+    }
+}
@@ -0,0 +1,158 @@
+use std::path::Path;
+
+use bevy::{ecs::system::Resource, math::IVec2};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    building::{Structure, StructureLayers},
+    challenge::Challenge,
+    model::{PhaseTimers, Player, Rules, Settings},
+    terrain::Terrain,
+};
+
+pub mod migration;
+#[cfg(test)]
+mod tests;
+
+/// A predefined game setup, loaded with `--scenario` or written by the editor.
+#[derive(Debug, Clone, Resource, Serialize, Deserialize)]
+pub struct Scenario {
+    /// Format the scenario was written in, see `migration`.
+    #[serde(default)]
+    pub version: u32,
+    pub seed: u32,
+    pub size: (u32, u32),
+    /// Corner heights of every cell, row by row. When missing the terrain is
+    /// generated from the seed.
+    #[serde(default)]
+    pub heights: Option<Vec<[f64; 4]>>,
+    /// Castles to build before the game starts, replacing the defaults.
+    #[serde(default)]
+    pub castles: Vec<Castle>,
+    /// Any other structures, such as starting cannons.
+    #[serde(default)]
+    pub structures: Vec<PlacedStructure>,
+    #[serde(default)]
+    pub timers: Option<PhaseTimers>,
+    /// House rules to play by, instead of those given on the command line.
+    #[serde(default)]
+    pub rules: Option<Rules>,
+    /// Makes the scenario a puzzle, with a goal and limits to meet it within.
+    #[serde(default)]
+    pub challenge: Option<Challenge>,
+    /// Rhai source calling back into the game as things happen, see
+    /// `scripting`.
+    #[serde(default)]
+    pub script: Option<String>,
+    /// How far a game had got, for picking up again from an autosave.
+    #[serde(default)]
+    pub progress: Option<Progress>,
+}
+
+/// The round a saved game was on and what everyone had scored by then.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Progress {
+    pub round: u32,
+    pub scores: Vec<(Player, u32)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Castle {
+    pub center: (i32, i32),
+    pub size: (i32, i32),
+    pub player: Player,
+}
+
+impl Castle {
+    pub fn center(&self) -> IVec2 {
+        IVec2::new(self.center.0, self.center.1)
+    }
+
+    pub fn size(&self) -> IVec2 {
+        IVec2::new(self.size.0, self.size.1)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlacedStructure {
+    pub location: (i32, i32),
+    pub structure: Structure,
+}
+
+impl PlacedStructure {
+    pub fn new(location: IVec2, structure: Structure) -> Self {
+        Self {
+            location: (location.x, location.y),
+            structure,
+        }
+    }
+
+    pub fn location(&self) -> IVec2 {
+        IVec2::new(self.location.0, self.location.1)
+    }
+}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io(std::io::Error),
+    Format(String),
+    /// Written by a newer version of the game than this one.
+    Version(u32),
+}
+
+impl std::fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScenarioError::Io(e) => write!(f, "scenario io: {}", e),
+            ScenarioError::Format(e) => write!(f, "scenario format: {}", e),
+            ScenarioError::Version(v) => write!(f, "scenario version {} unsupported", v),
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+impl From<std::io::Error> for ScenarioError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl Scenario {
+    /// The terrain and structures of the game being played, for picking it
+    /// back up later.
+    pub fn capture(settings: &Settings, terrain: &Terrain, structures: &StructureLayers) -> Self {
+        Self {
+            version: migration::CURRENT_VERSION,
+            seed: settings.seed().into(),
+            size: settings.size().into(),
+            heights: Some(terrain.heights()),
+            castles: Vec::default(),
+            structures: structures
+                .structures()
+                .into_iter()
+                .map(|(grid, structure)| PlacedStructure::new(grid, structure))
+                .collect(),
+            timers: None,
+            rules: None,
+            challenge: None,
+            script: None,
+            progress: None,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ScenarioError> {
+        let text = std::fs::read_to_string(path)?;
+
+        migration::upgrade(&text, migration::STEPS)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ScenarioError> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| ScenarioError::Format(e.to_string()))?;
+
+        std::fs::write(path, text)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use serde::{de::DeserializeOwned, Deserialize};
+
+use super::{Scenario, ScenarioError};
+
+/// Brings something read as one version of its format up to the next.
+pub type Step<T> = fn(T) -> T;
+
+/// Steps for every scenario version after the first, in order.
+pub const STEPS: &[Step<Scenario>] = &[headed];
+
+/// Written to every scenario saved, bumped whenever the format changes in a way
+/// older files need help with.
+pub const CURRENT_VERSION: u32 = STEPS.len() as u32;
+
+/// Nothing but the header was added.
+fn headed(scenario: Scenario) -> Scenario {
+    Scenario {
+        version: 1,
+        ..scenario
+    }
+}
+
+/// Just enough of a saved file to know which version it is, before committing
+/// to a format to read the rest with. Files from before versions were written
+/// are version 0.
+#[derive(Deserialize)]
+struct Header {
+    #[serde(default)]
+    version: u32,
+}
+
+pub fn version(text: &str) -> Result<u32, ScenarioError> {
+    let header: Header = ron::from_str(text).map_err(|e| ScenarioError::Format(e.to_string()))?;
+
+    Ok(header.version)
+}
+
+/// Reads a file of any version, taking it through every step after the one
+/// it was written in. The current version is the number of steps.
+pub fn upgrade<T: DeserializeOwned>(text: &str, steps: &[Step<T>]) -> Result<T, ScenarioError> {
+    let version = version(text)?;
+    if version as usize > steps.len() {
+        return Err(ScenarioError::Version(version));
+    }
+
+    let read = ron::from_str(text).map_err(|e| ScenarioError::Format(e.to_string()))?;
+
+    Ok(steps[version as usize..]
+        .iter()
+        .fold(read, |read, step| step(read)))
+}
@@ -0,0 +1,64 @@
+use crate::model::Player;
+
+use super::{migration, Progress, Scenario, ScenarioError};
+
+fn fixture(name: &str) -> String {
+    let path = format!(
+        "{}/src/scenario/fixtures/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    std::fs::read_to_string(path).unwrap()
+}
+
+fn assert_fixture(scenario: &Scenario) {
+    assert_eq!(scenario.version, migration::CURRENT_VERSION);
+    assert_eq!(scenario.seed, 7);
+    assert_eq!(scenario.size, (16, 16));
+    assert_eq!(scenario.castles.len(), 1);
+    assert_eq!(scenario.structures.len(), 2);
+    assert_eq!(
+        scenario.timers.as_ref().and_then(|timers| timers.fortify),
+        Some(20.0)
+    );
+}
+
+fn upgrade(text: &str) -> Result<Scenario, ScenarioError> {
+    migration::upgrade(text, migration::STEPS)
+}
+
+#[test]
+fn test_every_scenario_version_upgrades() {
+    for version in 0..=migration::CURRENT_VERSION {
+        let text = fixture(&format!("v{}.ron", version));
+
+        assert_eq!(migration::version(&text).unwrap(), version);
+        assert_fixture(&upgrade(&text).unwrap());
+    }
+
+    let text = fixture("v1.ron").replace("version: 1", "version: 99");
+    assert!(matches!(upgrade(&text), Err(ScenarioError::Version(99))));
+}
+
+#[test]
+fn test_saved_scenario_round_trips() {
+    let scenario = upgrade(&fixture("v0.ron")).unwrap();
+    let text = ron::ser::to_string_pretty(&scenario, ron::ser::PrettyConfig::default()).unwrap();
+
+    assert_fixture(&upgrade(&text).unwrap());
+}
+
+#[test]
+fn test_saved_progress_round_trips() {
+    let progress = Progress {
+        round: 4,
+        scores: vec![(Player::One, 120), (Player::Two, 85)],
+    };
+    let scenario = Scenario {
+        progress: Some(progress.clone()),
+        ..upgrade(&fixture("v1.ron")).unwrap()
+    };
+    let text = ron::ser::to_string_pretty(&scenario, ron::ser::PrettyConfig::default()).unwrap();
+
+    assert_eq!(upgrade(&text).unwrap().progress, Some(progress));
+}
@@ -0,0 +1,370 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, FuncArgs, Scope, AST};
+
+use crate::{
+    building::{
+        Cannon, ConstructionEvent, ConstructionSet, DestructionEvent, Structure, StructureLayers,
+        Wall,
+    },
+    model::{AppState, Phase, Player},
+    network::following,
+    scenario::Scenario,
+    terrain::{Sculpt, Terrain, TerrainEdited},
+    units::{Casualty, Raid, WaveDirector},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Most operations a single call into a script may take, so one that never
+/// returns gives up rather than freezing the game.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// Largest radius a script may reshape the terrain within.
+const MAX_SCULPT_RADIUS: i32 = 8;
+
+/// Runs the script that came with the scenario, calling into it as things
+/// happen so it can build, send ships and reshape the terrain in response.
+pub struct ScriptingPlugin;
+
+impl Plugin for ScriptingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Game), load_script)
+            .add_systems(OnExit(AppState::Game), unload_script)
+            .add_systems(
+                Update,
+                run_script
+                    .in_set(ConstructionSet::Input)
+                    .run_if(in_state(AppState::Game))
+                    .run_if(resource_exists::<ScenarioScript>)
+                    .run_if(not(following)),
+            );
+    }
+}
+
+/// Something a script asked for, carried out once it returns.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Wall(Player, IVec2),
+    Cannon(Player, IVec2),
+    Mortar(Player, IVec2),
+    Raid(Player, Vec<String>),
+    Sculpt(IVec2, i32, Sculpt),
+}
+
+impl Command {
+    /// Whether the cell it's for, if any, is on a map of `size`.
+    pub fn on_map(&self, size: UVec2) -> bool {
+        let grid = match self {
+            Command::Wall(_, grid)
+            | Command::Cannon(_, grid)
+            | Command::Mortar(_, grid)
+            | Command::Sculpt(grid, _, _) => *grid,
+            Command::Raid(_, _) => return true,
+        };
+
+        grid.cmpge(IVec2::ZERO).all() && grid.cmplt(size.as_ivec2()).all()
+    }
+}
+
+/// What a script can find out about the game, kept up to date before every
+/// call into it.
+#[derive(Debug, Clone, Default)]
+pub struct Known {
+    pub round: u32,
+    /// Cells inside each player's walls.
+    pub territory: [usize; 2],
+}
+
+#[derive(Default)]
+struct Shared {
+    commands: Vec<Command>,
+    known: Known,
+}
+
+/// A scenario's compiled script, along with whatever it keeps between calls.
+#[derive(Resource)]
+pub struct ScenarioScript {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl ScenarioScript {
+    pub fn compile(source: &str) -> Result<Self, String> {
+        let shared: Arc<Mutex<Shared>> = Arc::default();
+        let engine = engine(&shared);
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            engine,
+            ast,
+            scope: Scope::new(),
+            shared,
+        })
+    }
+
+    /// Calls the function when the script has one by that name, returning
+    /// everything it asked for. Anything outside of functions is never run,
+    /// and calls that fail are logged and otherwise ignored.
+    pub fn call(&mut self, name: &str, args: impl FuncArgs, known: Known) -> Vec<Command> {
+        if !self.ast.iter_functions().any(|f| f.name == name) {
+            return Vec::new();
+        }
+
+        self.shared.lock().expect("script state").known = known;
+
+        let options = CallFnOptions::new().eval_ast(false);
+        if let Err(e) = self.engine.call_fn_with_options::<Dynamic>(
+            options,
+            &mut self.scope,
+            &self.ast,
+            name,
+            args,
+        ) {
+            warn!(%name, "error in script: {}", e);
+        }
+
+        std::mem::take(&mut self.shared.lock().expect("script state").commands)
+    }
+}
+
+/// An engine with everything scripts can call, which queue commands rather
+/// than acting right away.
+fn engine(shared: &Arc<Mutex<Shared>>) -> Engine {
+    let mut engine = Engine::new();
+
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.on_print(|text| info!("script: {}", text));
+
+    let queue = |shared: &Arc<Mutex<Shared>>| {
+        let shared = shared.clone();
+        move |command: Command| shared.lock().expect("script state").commands.push(command)
+    };
+
+    let push = queue(shared);
+    engine.register_fn("wall", move |player: i64, x: i64, y: i64| {
+        if let Some(player) = player_from(player) {
+            push(Command::Wall(player, grid(x, y)));
+        }
+    });
+
+    let push = queue(shared);
+    engine.register_fn("cannon", move |player: i64, x: i64, y: i64| {
+        if let Some(player) = player_from(player) {
+            push(Command::Cannon(player, grid(x, y)));
+        }
+    });
+
+    let push = queue(shared);
+    engine.register_fn("mortar", move |player: i64, x: i64, y: i64| {
+        if let Some(player) = player_from(player) {
+            push(Command::Mortar(player, grid(x, y)));
+        }
+    });
+
+    let push = queue(shared);
+    engine.register_fn("raid", move |player: i64, classes: Array| {
+        if let Some(player) = player_from(player) {
+            let classes = classes
+                .into_iter()
+                .filter_map(|class| class.into_string().ok())
+                .collect();
+            push(Command::Raid(player, classes));
+        }
+    });
+
+    for (name, sculpt) in [
+        ("raise", Sculpt::Raise),
+        ("lower", Sculpt::Lower),
+        ("flatten", Sculpt::Flatten),
+    ] {
+        let push = queue(shared);
+        engine.register_fn(name, move |x: i64, y: i64, radius: i64| {
+            let radius = radius.clamp(0, MAX_SCULPT_RADIUS as i64) as i32;
+            push(Command::Sculpt(grid(x, y), radius, sculpt));
+        });
+    }
+
+    let known = shared.clone();
+    engine.register_fn("round", move || {
+        known.lock().expect("script state").known.round as i64
+    });
+
+    let known = shared.clone();
+    engine.register_fn("territory", move |player: i64| {
+        let territory = known.lock().expect("script state").known.territory;
+        player_from(player)
+            .map(|player| territory[player_index(&player)] as i64)
+            .unwrap_or_default()
+    });
+
+    engine
+}
+
+/// Players are numbered from one in scripts.
+fn player_from(number: i64) -> Option<Player> {
+    match number {
+        1 => Some(Player::One),
+        2 => Some(Player::Two),
+        _ => {
+            warn!(%number, "script: no such player");
+            None
+        }
+    }
+}
+
+fn player_number(player: &Player) -> i64 {
+    match player {
+        Player::One => 1,
+        Player::Two => 2,
+    }
+}
+
+fn player_index(player: &Player) -> usize {
+    player_number(player) as usize - 1
+}
+
+/// Anything too large to be a cell is kept too large, rather than wrapping
+/// around onto one.
+fn grid(x: i64, y: i64) -> IVec2 {
+    let clamp = |value: i64| value.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+    IVec2::new(clamp(x), clamp(y))
+}
+
+fn phase_name(phase: &Phase) -> &'static str {
+    match phase {
+        Phase::Choose(_) => "choose",
+        Phase::Fortify(_) => "fortify",
+        Phase::Arm(_) => "arm",
+        Phase::Target(_) => "target",
+    }
+}
+
+fn load_script(mut commands: Commands, scenario: Option<Res<Scenario>>) {
+    let Some(source) = scenario.as_ref().and_then(|s| s.script.as_ref()) else {
+        return;
+    };
+
+    match ScenarioScript::compile(source) {
+        Ok(script) => {
+            info!("script-loaded");
+            commands.insert_resource(script);
+        }
+        Err(e) => warn!("error compiling script: {}", e),
+    }
+}
+
+fn unload_script(mut commands: Commands) {
+    commands.remove_resource::<ScenarioScript>();
+}
+
+/// Calls `on_start` once, then `on_phase`, `on_destroyed` and `on_killed` as
+/// those happen, carrying out whatever the script asks for after each.
+#[allow(clippy::too_many_arguments)]
+fn run_script(
+    mut script: ResMut<ScenarioScript>,
+    mut started: Local<bool>,
+    mut destroyed: EventReader<DestructionEvent>,
+    mut casualties: EventReader<Casualty>,
+    mut construction: EventWriter<ConstructionEvent>,
+    mut raids: EventWriter<Raid>,
+    mut edited: EventWriter<TerrainEdited>,
+    mut terrain: Query<&mut Terrain>,
+    structures: Res<StructureLayers>,
+    director: Res<WaveDirector>,
+    phase: Res<State<Phase>>,
+) {
+    let Some(size) = terrain.get_single().ok().map(|terrain| terrain.size()) else {
+        return;
+    };
+
+    let count = |player: &Player| {
+        structures
+            .territory(player)
+            .iter()
+            .filter(|cell| **cell)
+            .count()
+    };
+    let known = Known {
+        round: director.round(),
+        territory: [count(&Player::One), count(&Player::Two)],
+    };
+
+    let mut requested = Vec::new();
+
+    if script.is_added() {
+        *started = false;
+    }
+    if !*started {
+        *started = true;
+        requested.extend(script.call("on_start", (), known.clone()));
+    }
+
+    if phase.is_changed() {
+        let args = (
+            phase_name(phase.get()).to_owned(),
+            player_number(&phase.get().player()),
+        );
+        requested.extend(script.call("on_phase", args, known.clone()));
+    }
+
+    for event in destroyed.read() {
+        let structure = event.structure();
+        let grid: IVec2 = (*event.coordinates()).into();
+        let args = (
+            structure.name().to_owned(),
+            player_number(&structure.player()),
+            grid.x as i64,
+            grid.y as i64,
+        );
+        requested.extend(script.call("on_destroyed", args, known.clone()));
+    }
+
+    for casualty in casualties.read() {
+        let args = (casualty.name().to_owned(),);
+        requested.extend(script.call("on_killed", args, known.clone()));
+    }
+
+    for command in requested {
+        if !command.on_map(size) {
+            warn!(?command, "script: off the map");
+            continue;
+        }
+
+        info!(?command, "script-command");
+        match command {
+            Command::Wall(player, grid) => {
+                construction.send(ConstructionEvent::new(
+                    grid.into(),
+                    Structure::Wall(Wall::new(player)),
+                ));
+            }
+            Command::Cannon(player, grid) => {
+                construction.send(ConstructionEvent::new(
+                    grid.into(),
+                    Structure::Cannon(Cannon::new(player)),
+                ));
+            }
+            Command::Mortar(player, grid) => {
+                construction.send(ConstructionEvent::new(
+                    grid.into(),
+                    Structure::Cannon(Cannon::mortar(player)),
+                ));
+            }
+            Command::Raid(player, classes) => {
+                raids.send(Raid::new(player, classes));
+            }
+            Command::Sculpt(center, radius, sculpt) => {
+                let Ok(mut terrain) = terrain.get_single_mut() else {
+                    continue;
+                };
+                let cells = terrain.sculpt(center, radius, sculpt);
+                edited.send(TerrainEdited::new(cells));
+            }
+        }
+    }
+}
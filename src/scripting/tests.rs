@@ -0,0 +1,132 @@
+use bevy::math::{IVec2, UVec2};
+
+use crate::{model::Player, terrain::Sculpt};
+
+use super::{Command, Known, ScenarioScript, MAX_SCULPT_RADIUS};
+
+const BREACHED: &str = r#"
+fn on_destroyed(kind, player, x, y) {
+    if kind == "wall" && x > 10 {
+        raid(player, ["gunboat", "gunboat"]);
+        wall(player, x, y);
+    }
+}
+"#;
+
+#[test]
+fn test_compile_errors_are_reported() {
+    assert!(ScenarioScript::compile("fn on_start( {").is_err());
+}
+
+#[test]
+fn test_callbacks_queue_commands() {
+    let mut script = ScenarioScript::compile(BREACHED).unwrap();
+
+    let commands = script.call(
+        "on_destroyed",
+        ("wall".to_owned(), 1_i64, 12_i64, 4_i64),
+        Known::default(),
+    );
+
+    assert_eq!(
+        commands,
+        vec![
+            Command::Raid(
+                Player::One,
+                vec!["gunboat".to_owned(), "gunboat".to_owned()]
+            ),
+            Command::Wall(Player::One, IVec2::new(12, 4)),
+        ]
+    );
+}
+
+#[test]
+fn test_callbacks_only_queue_what_they_ask_for() {
+    let mut script = ScenarioScript::compile(BREACHED).unwrap();
+
+    let commands = script.call(
+        "on_destroyed",
+        ("wall".to_owned(), 2_i64, 3_i64, 4_i64),
+        Known::default(),
+    );
+
+    assert!(commands.is_empty());
+}
+
+#[test]
+fn test_missing_callbacks_are_skipped() {
+    let mut script = ScenarioScript::compile(BREACHED).unwrap();
+
+    assert!(script.call("on_start", (), Known::default()).is_empty());
+}
+
+#[test]
+fn test_unknown_players_are_ignored() {
+    let mut script = ScenarioScript::compile("fn on_start() { cannon(3, 1, 1); }").unwrap();
+
+    assert!(script.call("on_start", (), Known::default()).is_empty());
+}
+
+#[test]
+fn test_scripts_can_see_what_is_known() {
+    let mut script = ScenarioScript::compile(
+        r#"
+fn on_phase(phase, player) {
+    if phase == "target" && territory(player) < 20 && round() > 1 {
+        flatten(5, 5, 2);
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    let known = Known {
+        round: 2,
+        territory: [30, 10],
+    };
+    let args = || ("target".to_owned(), 2_i64);
+
+    assert_eq!(
+        script.call("on_phase", args(), known.clone()),
+        vec![Command::Sculpt(IVec2::new(5, 5), 2, Sculpt::Flatten)]
+    );
+    assert!(script
+        .call("on_phase", ("target".to_owned(), 1_i64), known)
+        .is_empty());
+}
+
+#[test]
+fn test_scripts_that_never_return_give_up() {
+    let mut script = ScenarioScript::compile("fn on_start() { wall(1, 1, 1); loop {} }").unwrap();
+
+    // Whatever was asked for before it gave up is still carried out.
+    assert_eq!(
+        script.call("on_start", (), Known::default()),
+        vec![Command::Wall(Player::One, IVec2::new(1, 1))]
+    );
+}
+
+#[test]
+fn test_sculpting_radius_is_limited() {
+    let mut script = ScenarioScript::compile("fn on_start() { raise(4, 4, 1000000); }").unwrap();
+
+    assert_eq!(
+        script.call("on_start", (), Known::default()),
+        vec![Command::Sculpt(
+            IVec2::new(4, 4),
+            MAX_SCULPT_RADIUS,
+            Sculpt::Raise
+        )]
+    );
+}
+
+#[test]
+fn test_commands_off_the_map_are_refused() {
+    let size = UVec2::new(32, 32);
+
+    assert!(Command::Wall(Player::One, IVec2::new(31, 0)).on_map(size));
+    assert!(!Command::Wall(Player::One, IVec2::new(32, 0)).on_map(size));
+    assert!(!Command::Cannon(Player::Two, IVec2::new(-1, 4)).on_map(size));
+    assert!(!Command::Sculpt(IVec2::new(999, 999), 1, Sculpt::Flatten).on_map(size));
+    assert!(Command::Raid(Player::One, Vec::new()).on_map(size));
+}
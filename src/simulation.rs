@@ -0,0 +1,426 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    log::LogPlugin,
+    prelude::*,
+    render::{
+        settings::{RenderCreation, WgpuSettings},
+        RenderPlugin,
+    },
+    time::TimeUpdateStrategy,
+    window::ExitCondition,
+    winit::WinitPlugin,
+};
+use bevy_hanabi::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    building::{
+        buildable,
+        sites::{self, CASTLE_SIZE},
+        Cannon, ConstructionEvent, ConstructionSet, Footing, Pieces, Structure, StructureLayers,
+        TurnEnded,
+    },
+    damage::Damaged,
+    display::DisplaySettings,
+    firing::{ShotFired, TargetPicked},
+    model::{
+        Activity, AppState, Difficulty, Health, Phase, Player, Rules, Settings, CANNON_HP, WALL_HP,
+    },
+    scenario::{Castle, Scenario},
+    terrain::{Props, Terrain, TerrainOptions},
+    units::WaveDirector,
+    GamePlugin,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Cells from its center to each castle's walls.
+const CASTLE_RADIUS: i32 = CASTLE_SIZE.x / 2;
+
+/// Most walls scripted for a single fortify turn.
+const WALLS_A_TURN: usize = 4;
+
+/// Seconds spent watching shots land before a target turn's ended.
+const TARGET_SECONDS: f32 = 4.0;
+
+/// Time that passes every update, however long the update really took.
+const FRAME_SECONDS: f32 = 1.0 / 60.0;
+
+/// Longest a simulation is given before it's assumed to be stuck.
+const TIME_LIMIT: Duration = Duration::from_secs(600);
+
+/// One turn of input, for whoever's turn it is.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Input {
+    /// Walls on each of the cells.
+    Fortify(Vec<IVec2>),
+    /// A cannon on the cell.
+    Arm(IVec2),
+    /// Every cannon that can reach fires at the cell.
+    Target(IVec2),
+    /// Nothing at all this turn.
+    Pass,
+}
+
+/// Input waiting to be played, one a turn.
+#[derive(Debug, Default, Resource)]
+pub struct Script(pub VecDeque<Input>);
+
+impl Script {
+    /// Plausible input for `turns` turns between castles at `castles`: walls
+    /// and cannons around their own and shots at the other.
+    pub fn random(seed: u64, castles: [IVec2; 2], turns: usize) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut phase = Phase::Fortify(Player::One);
+        let mut inputs = VecDeque::new();
+
+        for _ in 0..turns {
+            let own = castles[index(&phase.player())];
+            let other = castles[index(&phase.player().next())];
+            let mut near = |center: IVec2, distance: i32| {
+                center
+                    + IVec2::new(
+                        rng.gen_range(-distance..=distance),
+                        rng.gen_range(-distance..=distance),
+                    )
+            };
+            inputs.push_back(match phase {
+                Phase::Fortify(_) => Input::Fortify(
+                    (0..WALLS_A_TURN)
+                        .map(|_| near(own, CASTLE_RADIUS + 1))
+                        .collect(),
+                ),
+                Phase::Arm(_) => Input::Arm(near(own, CASTLE_RADIUS - 1)),
+                Phase::Target(_) => Input::Target(near(other, CASTLE_RADIUS)),
+                Phase::Choose(_) => Input::Pass,
+            });
+            phase = phase.next();
+        }
+
+        Self(inputs)
+    }
+}
+
+/// How the simulation has gone so far.
+#[derive(Debug, Default, Resource)]
+pub struct Outcome {
+    pub turns: u32,
+    pub shots: usize,
+    /// Damage done by one player to the other's structures.
+    pub hits: usize,
+    /// Cells held by each player as of the last check.
+    pub territory: [usize; 2],
+    /// Everything found wrong, along with the turn it was found on.
+    pub violations: Vec<(u32, String)>,
+}
+
+impl Outcome {
+    pub fn describe(&self) -> String {
+        format!(
+            "{} turns, {} shots, {} hits, {} violations",
+            self.turns,
+            self.shots,
+            self.hits,
+            self.violations.len()
+        )
+    }
+}
+
+/// Plays scripted input through the game's own systems as each turn comes
+/// around, checking everything built is as it should be between turns.
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Outcome>()
+            .init_resource::<Script>()
+            .add_systems(OnEnter(AppState::Menu), begin)
+            .add_systems(
+                Update,
+                play.in_set(ConstructionSet::Input)
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                Update,
+                (count_shots, count_hits).run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                Update,
+                check
+                    .after(ConstructionSet::Refresh)
+                    .run_if(in_state(AppState::Game))
+                    .run_if(state_changed::<Phase>),
+            );
+    }
+}
+
+/// The game without a window or anything drawn, on the terrain `settings`
+/// generate with castles wherever the game would choose, and `turns` turns of
+/// scripted input to play.
+pub fn headless(settings: Settings, rules: Rules, turns: usize) -> App {
+    let terrain = Terrain::from(TerrainOptions::from(&settings));
+    let castles = sites::select_castle_sites(&terrain, rules.coop);
+    let centers = [castles[0].1, castles[1].1];
+    let scenario = Scenario {
+        castles: castles
+            .into_iter()
+            .map(|(player, center)| Castle {
+                center: (center.x, center.y),
+                size: (CASTLE_SIZE.x, CASTLE_SIZE.y),
+                player,
+            })
+            .collect(),
+        ..Scenario::capture(&settings, &terrain, &StructureLayers::new(settings.size()))
+    };
+    let script = Script::random(u32::from(settings.seed()) as u64, centers, turns);
+
+    let mut app = App::new();
+    app.add_plugins(
+        DefaultPlugins
+            .set(RenderPlugin {
+                render_creation: RenderCreation::Automatic(WgpuSettings {
+                    backends: None,
+                    ..default()
+                }),
+                ..default()
+            })
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+            })
+            .disable::<WinitPlugin>()
+            .disable::<LogPlugin>(),
+    )
+    // Effects are only ever drawn, there's nothing to draw them with.
+    .init_asset::<EffectAsset>()
+    .add_plugins(GamePlugin)
+    .add_plugins(SimulationPlugin)
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+        FRAME_SECONDS,
+    )))
+    .insert_resource(settings)
+    .insert_resource(rules)
+    .insert_resource(WaveDirector::new(Difficulty::default()))
+    .insert_resource(DisplaySettings::default())
+    .insert_resource(scenario)
+    .insert_resource(script);
+
+    app
+}
+
+/// Updates until `turns` turns have been played, or until so long has gone by
+/// that something must be stuck.
+pub fn run(app: &mut App, turns: u32) {
+    app.finish();
+    app.cleanup();
+
+    let started = Instant::now();
+    while app.world.resource::<Outcome>().turns < turns {
+        if started.elapsed() > TIME_LIMIT {
+            warn!(%turns, "simulation-stuck");
+            break;
+        }
+        app.update();
+    }
+}
+
+/// Straight into the game, there's nobody to choose an opponent.
+fn begin(mut app_state: ResMut<NextState<AppState>>, mut activity: ResMut<NextState<Activity>>) {
+    app_state.set(AppState::Generating);
+    activity.set(Activity::Observing);
+}
+
+/// Whose turn the script's playing, and how far through it it is.
+#[derive(Default)]
+struct Turn {
+    phase: Option<Phase>,
+    /// Where to fire, once cannons can be fired.
+    target: Option<Vec3>,
+    /// Seconds left watching shots land.
+    watching: f32,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn play(
+    mut turn: Local<Turn>,
+    mut script: ResMut<Script>,
+    mut outcome: ResMut<Outcome>,
+    mut pieces: ResMut<Pieces>,
+    mut modified: EventWriter<ConstructionEvent>,
+    mut picks: EventWriter<TargetPicked>,
+    mut turns: EventWriter<TurnEnded>,
+    mut next_activity: ResMut<NextState<Activity>>,
+    activity: Res<State<Activity>>,
+    phase: Res<State<Phase>>,
+    terrain: Query<&Terrain>,
+    structures: Res<StructureLayers>,
+    props: Res<Props>,
+    time: Res<Time>,
+) {
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    let current = phase.get();
+    let player = current.player();
+
+    if turn.phase.as_ref() == Some(current) {
+        // Only target turns last longer than the update they begin in.
+        if turn.watching <= 0.0 || *activity.get() != Activity::Firing {
+            return;
+        }
+        if let Some(target) = turn.target.take() {
+            picks.send(TargetPicked::new(player, target, true));
+        }
+
+        turn.watching -= time.delta_seconds();
+        if turn.watching <= 0.0 {
+            next_activity.set(Activity::Observing);
+            end_turn(&mut turns, &mut outcome, current);
+        }
+        return;
+    }
+
+    turn.phase = Some(current.clone());
+    if matches!(current, Phase::Choose(_)) {
+        return;
+    }
+    let Some(input) = script.0.pop_front() else {
+        return;
+    };
+
+    let world = |grid: IVec2| terrain.survey_grid(grid).map(|survey| survey.world());
+    let site = |grid: IVec2| {
+        world(grid).and_then(|position| buildable(terrain, &structures, &props, position))
+    };
+
+    match (current, input) {
+        (Phase::Fortify(_), Input::Fortify(cells)) => {
+            for (grid, footing) in cells.into_iter().filter_map(site) {
+                if pieces.take().is_none() {
+                    break;
+                }
+                modified.send(ConstructionEvent::new(
+                    grid.into(),
+                    footing.structure(player),
+                ));
+            }
+        }
+        (Phase::Arm(_), Input::Arm(grid)) => {
+            let territory = structures.territory(&player);
+            let placed = site(grid).filter(|(grid, footing)| {
+                *footing == Footing::Ground && territory.get(*grid) == Some(&true)
+            });
+            if let Some((grid, _)) = placed {
+                if pieces.take().is_some() {
+                    modified.send(ConstructionEvent::new(
+                        grid.into(),
+                        Structure::Cannon(Cannon::new(player)),
+                    ));
+                }
+            }
+        }
+        (Phase::Target(_), Input::Target(grid)) => {
+            turn.target = world(grid);
+            turn.watching = TARGET_SECONDS;
+            next_activity.set(Activity::Firing);
+            return;
+        }
+        (phase, input) => debug!(?phase, ?input, "simulation-skipped"),
+    }
+
+    end_turn(&mut turns, &mut outcome, current);
+}
+
+fn end_turn(turns: &mut EventWriter<TurnEnded>, outcome: &mut Outcome, phase: &Phase) {
+    turns.send(TurnEnded::new(phase.player()));
+    outcome.turns += 1;
+    debug!(turn = outcome.turns, ?phase, "simulation-turn");
+}
+
+fn count_shots(mut fired: EventReader<ShotFired>, mut outcome: ResMut<Outcome>) {
+    outcome.shots += fired.read().count();
+}
+
+fn count_hits(mut damaged: EventReader<Damaged>, mut outcome: ResMut<Outcome>) {
+    outcome.hits += damaged
+        .read()
+        .filter(|damaged| damaged.confirmed().is_some())
+        .count();
+}
+
+/// Everything the game says has been built is there, whose it should be and
+/// neither dead nor healthier than new, and territory is inside the walls.
+fn check(
+    mut outcome: ResMut<Outcome>,
+    structures: Res<StructureLayers>,
+    built: Query<(&Player, &Health)>,
+) {
+    let mut violations = Vec::new();
+
+    for player in [Player::One, Player::Two] {
+        let walls = structures
+            .walls(&player)
+            .into_iter()
+            .map(|(grid, entity)| (grid, entity, "wall", WALL_HP));
+        let cannons = structures
+            .cannons(&player)
+            .into_iter()
+            .map(|(grid, entity)| (grid, entity, "cannon", CANNON_HP));
+
+        for (grid, entity, name, full) in walls.chain(cannons) {
+            match built.get(entity) {
+                Err(_) => {
+                    violations.push(format!("{:?}'s {} at {} has no entity", player, name, grid))
+                }
+                Ok((owner, _)) if *owner != player => violations.push(format!(
+                    "{:?}'s {} at {} belongs to {:?}",
+                    player, name, grid, owner
+                )),
+                Ok((_, health)) if health.dead() || health.hp() > full => violations.push(format!(
+                    "{:?}'s {} at {} with {} hp",
+                    player,
+                    name,
+                    grid,
+                    health.hp()
+                )),
+                Ok(_) => {}
+            }
+        }
+
+        let walls = structures.wall_grid(&player);
+        let territory = structures.territory(&player);
+        for (grid, inside) in territory.enumerate_cells() {
+            if *inside && walls[grid] {
+                violations.push(format!(
+                    "{:?} territory includes a wall at {}",
+                    player, grid
+                ));
+            }
+        }
+
+        outcome.territory[index(&player)] = territory
+            .enumerate_cells()
+            .filter(|(_, inside)| **inside)
+            .count();
+    }
+
+    let turn = outcome.turns;
+    for violation in violations {
+        warn!(%turn, %violation, "simulation-violation");
+        outcome.violations.push((turn, violation));
+    }
+}
+
+fn index(player: &Player) -> usize {
+    match player {
+        Player::One => 0,
+        Player::Two => 1,
+    }
+}
@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+
+use super::*;
+use crate::model::Seed;
+
+/// Three rounds, every phase of each.
+const TURNS: u32 = 18;
+
+fn simulate(seed: u32, rules: Rules) -> App {
+    let settings = Settings {
+        seed: Seed::new(seed),
+        size: UVec2::new(32, 32),
+        ..Settings::default()
+    };
+    let mut app = headless(settings, rules, TURNS as usize);
+    run(&mut app, TURNS);
+    app
+}
+
+#[test]
+fn test_scripted_games_keep_invariants() {
+    for seed in 0..3 {
+        let app = simulate(seed, Rules::default());
+        let outcome = app.world.resource::<Outcome>();
+
+        assert_eq!(outcome.turns, TURNS);
+        assert!(outcome.violations.is_empty(), "{:?}", outcome.violations);
+    }
+}
+
+#[test]
+fn test_coop_players_never_hit_each_other() {
+    let rules = Rules {
+        coop: true,
+        ..Rules::default()
+    };
+    let app = simulate(0, rules);
+    let outcome = app.world.resource::<Outcome>();
+
+    assert_eq!(outcome.turns, TURNS);
+    assert!(outcome.shots > 0);
+    assert_eq!(outcome.hits, 0);
+    assert!(outcome.violations.is_empty(), "{:?}", outcome.violations);
+}
@@ -0,0 +1,255 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    building::{self, RestoreEvent, Structure, StructureLayers},
+    journal::{Entry, EventJournal, Recorded},
+    model::{AppState, GameRng, Phase, Player, Settings},
+    network::following,
+    scenario::{PlacedStructure, ScenarioError},
+    units::WaveDirector,
+    weather::Weather,
+};
+
+#[cfg(test)]
+mod tests;
+
+const SNAPSHOT_DIRECTORY: &str = "snapshots";
+
+/// Keeps a snapshot of the game as every round begins, in memory and on disk,
+/// and puts the game back the way it was in one when asked.
+pub struct SnapshotPlugin;
+
+impl Plugin for SnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Snapshots>()
+            .add_event::<RestoreSnapshot>()
+            .add_systems(OnExit(AppState::Generating), begin_snapshots)
+            .add_systems(
+                Update,
+                take_snapshot
+                    .run_if(in_state(AppState::Game))
+                    .run_if(state_changed::<Phase>)
+                    .run_if(not(following)),
+            )
+            .add_systems(
+                Update,
+                restore_snapshot
+                    .before(building::ConstructionSet::Apply)
+                    .run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+/// Everything that decides how the rest of a game plays out, as of some
+/// moment in it. Ships, projectiles and anything else in motion are left
+/// out, they come and go within a phase.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Seconds since the game began.
+    pub at: f32,
+    /// Number of journal entries recorded by then.
+    pub entry: usize,
+    pub round: u32,
+    pub phase: Phase,
+    pub structures: Vec<PlacedStructure>,
+    pub scores: Vec<(Player, u32)>,
+    pub weather: Weather,
+    /// What chance was seeded with as the round began.
+    pub rng: u64,
+}
+
+/// Everything needed to take a snapshot, gathered from around the world.
+pub struct Capture<'a> {
+    pub journal: &'a EventJournal,
+    pub structures: &'a StructureLayers,
+    pub director: &'a WaveDirector,
+    pub weather: &'a Weather,
+    pub rng: &'a GameRng,
+}
+
+impl Snapshot {
+    pub fn capture(capture: Capture, phase: Phase, now: f32) -> Self {
+        Self {
+            at: capture.journal.elapsed(now),
+            entry: capture.journal.entries().len(),
+            round: capture.director.round(),
+            phase,
+            structures: capture
+                .structures
+                .structures()
+                .into_iter()
+                .map(|(grid, structure)| PlacedStructure::new(grid, structure))
+                .collect(),
+            scores: [Player::One, Player::Two]
+                .into_iter()
+                .map(|player| (player, capture.journal.total(&player)))
+                .collect(),
+            weather: capture.weather.clone(),
+            rng: capture.rng.seed(),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ScenarioError> {
+        let text = ron::ser::to_string(self).map_err(|e| ScenarioError::Format(e.to_string()))?;
+
+        std::fs::write(path, text)?;
+
+        Ok(())
+    }
+
+    /// Brings the snapshot up to date with entries recorded after it was
+    /// taken, as far as structures, the phase and scores go.
+    pub fn apply(&mut self, entries: &[Entry]) {
+        let mut structures: HashMap<_, _> = self
+            .structures
+            .drain(..)
+            .map(|placed| (placed.location(), placed.structure))
+            .collect();
+
+        for entry in entries {
+            match &entry.recorded {
+                Recorded::Constructed(grid, structure) => {
+                    structures.insert(*grid, structure.clone());
+                }
+                Recorded::Destroyed(grid, _) => {
+                    structures.remove(grid);
+                }
+                Recorded::Turned(grid, cannon) => {
+                    if let Some(Structure::Cannon(_)) = structures.get(grid) {
+                        structures.insert(*grid, Structure::Cannon(cannon.clone()));
+                    }
+                }
+                Recorded::Phase(phase) => self.phase = phase.clone(),
+                Recorded::Score(player, _, total) => {
+                    self.scores.retain(|(scorer, _)| scorer != player);
+                    self.scores.push((*player, *total));
+                }
+                _ => {}
+            }
+            self.at = self.at.max(entry.at);
+            self.entry += 1;
+        }
+
+        self.structures = structures
+            .into_iter()
+            .map(|(grid, structure)| PlacedStructure::new(grid, structure))
+            .collect();
+    }
+}
+
+/// Snapshots taken so far this game, one for each round.
+#[derive(Debug, Default, Resource)]
+pub struct Snapshots {
+    taken: Vec<Snapshot>,
+}
+
+impl Snapshots {
+    pub fn latest(&self) -> Option<&Snapshot> {
+        self.taken.last()
+    }
+}
+
+/// Puts the game back the way it was in the snapshot.
+#[derive(Clone, Debug)]
+pub struct RestoreSnapshot(pub Snapshot);
+
+impl Event for RestoreSnapshot {}
+
+fn snapshot_path(round: u32) -> PathBuf {
+    Path::new(SNAPSHOT_DIRECTORY).join(format!("round-{}.ron", round))
+}
+
+/// Starts chance over for the new game, so every game on a map starts the
+/// same.
+fn begin_snapshots(
+    mut snapshots: ResMut<Snapshots>,
+    mut rng: ResMut<GameRng>,
+    settings: Res<Settings>,
+) {
+    *snapshots = Snapshots::default();
+    rng.reseed(GameRng::round_seed(settings.seed(), 0));
+}
+
+/// Reseeds chance and takes a snapshot once each round has begun.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn take_snapshot(
+    mut snapshots: ResMut<Snapshots>,
+    mut rng: ResMut<GameRng>,
+    phase: Res<State<Phase>>,
+    journal: Res<EventJournal>,
+    structures: Option<Res<StructureLayers>>,
+    director: Res<WaveDirector>,
+    weather: Res<Weather>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    if *phase.get() != Phase::Fortify(Player::One) {
+        return;
+    }
+
+    let Some(structures) = structures else {
+        return;
+    };
+
+    rng.reseed(GameRng::round_seed(settings.seed(), director.round()));
+
+    let snapshot = Snapshot::capture(
+        Capture {
+            journal: &journal,
+            structures: &structures,
+            director: &director,
+            weather: &weather,
+            rng: &rng,
+        },
+        phase.get().clone(),
+        time.elapsed_seconds(),
+    );
+
+    let path = snapshot_path(snapshot.round);
+    let saved = std::fs::create_dir_all(SNAPSHOT_DIRECTORY)
+        .map_err(ScenarioError::from)
+        .and_then(|_| snapshot.save(&path));
+    match saved {
+        Ok(_) => info!(?path, round = snapshot.round, "snapshot"),
+        Err(e) => warn!("error saving snapshot: {}", e),
+    }
+
+    snapshots.taken.push(snapshot);
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn restore_snapshot(
+    mut restores: EventReader<RestoreSnapshot>,
+    mut structures: EventWriter<RestoreEvent>,
+    mut next_phase: ResMut<NextState<Phase>>,
+    mut journal: ResMut<EventJournal>,
+    mut director: ResMut<WaveDirector>,
+    mut weather: ResMut<Weather>,
+    mut rng: ResMut<GameRng>,
+    time: Res<Time>,
+) {
+    for RestoreSnapshot(snapshot) in restores.read() {
+        info!(at = snapshot.at, round = snapshot.round, phase = ?snapshot.phase, "restoring");
+
+        structures.send(RestoreEvent::new(
+            snapshot
+                .structures
+                .iter()
+                .map(|placed| (placed.location(), placed.structure.clone()))
+                .collect(),
+        ));
+        next_phase.set(snapshot.phase.clone());
+        for (player, total) in snapshot.scores.iter() {
+            journal.restore_score(time.elapsed_seconds(), *player, *total);
+        }
+        director.resume(snapshot.round);
+        *weather = snapshot.weather.clone();
+        rng.reseed(snapshot.rng);
+    }
+}
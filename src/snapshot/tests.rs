@@ -0,0 +1,147 @@
+use bevy::math::{IVec2, UVec2};
+use rand::Rng;
+
+use crate::{
+    building::{Cannon, Structure, StructureLayers, Wall},
+    journal::{Entry, EventJournal, Recorded},
+    model::{GameRng, Phase, Player, Seed},
+    scenario::PlacedStructure,
+    units::WaveDirector,
+    weather::Weather,
+};
+
+use super::{Capture, Snapshot};
+
+#[test]
+fn test_capture_scores_and_chance() {
+    let mut journal = EventJournal::default();
+    journal.open(100.0);
+    journal.score(110.0, Player::Two, 25);
+
+    let snapshot = Snapshot::capture(
+        Capture {
+            journal: &journal,
+            structures: &StructureLayers::new(UVec2::new(4, 4)),
+            director: &WaveDirector::default(),
+            weather: &Weather::default(),
+            rng: &GameRng::new(42),
+        },
+        Phase::Fortify(Player::One),
+        120.0,
+    );
+
+    assert_eq!(snapshot.at, 20.0);
+    assert_eq!(snapshot.entry, 1);
+    assert_eq!(snapshot.scores, vec![(Player::One, 0), (Player::Two, 25)]);
+    assert_eq!(snapshot.rng, 42);
+    assert!(snapshot.structures.is_empty());
+}
+
+#[test]
+fn test_snapshot_round_trips() {
+    let snapshot = Snapshot {
+        round: 3,
+        phase: Phase::Target(Player::Two),
+        structures: vec![PlacedStructure::new(
+            IVec2::new(2, 2),
+            Structure::Wall(Wall::new(Player::One)),
+        )],
+        scores: vec![(Player::One, 5)],
+        rng: GameRng::round_seed(Seed::new(7), 3),
+        ..Default::default()
+    };
+
+    let text = ron::ser::to_string(&snapshot).unwrap();
+    let loaded: Snapshot = ron::from_str(&text).unwrap();
+
+    assert_eq!(loaded.round, 3);
+    assert_eq!(loaded.phase, Phase::Target(Player::Two));
+    assert_eq!(loaded.structures.len(), 1);
+    assert_eq!(loaded.scores, vec![(Player::One, 5)]);
+    assert_eq!(loaded.rng, (7 << 32) | 3);
+}
+
+#[test]
+fn test_reseeding_repeats_chance() {
+    let mut rng = GameRng::new(9);
+    let first: u32 = rng.rng().gen();
+    rng.reseed(9);
+
+    assert_eq!(rng.rng().gen::<u32>(), first);
+}
+
+#[test]
+fn test_apply_catches_up_with_journal() {
+    let mut snapshot = Snapshot {
+        at: 10.0,
+        entry: 4,
+        phase: Phase::Fortify(Player::One),
+        structures: vec![PlacedStructure::new(
+            IVec2::new(2, 2),
+            Structure::Wall(Wall::new(Player::One)),
+        )],
+        scores: vec![(Player::Two, 5)],
+        ..Default::default()
+    };
+
+    snapshot.apply(&[
+        Entry {
+            at: 12.0,
+            recorded: Recorded::Destroyed(
+                IVec2::new(2, 2),
+                Structure::Wall(Wall::new(Player::One)),
+            ),
+        },
+        Entry {
+            at: 13.0,
+            recorded: Recorded::Constructed(
+                IVec2::new(3, 3),
+                Structure::Wall(Wall::new(Player::Two)),
+            ),
+        },
+        Entry {
+            at: 14.0,
+            recorded: Recorded::Phase(Phase::Arm(Player::One)),
+        },
+        Entry {
+            at: 15.0,
+            recorded: Recorded::Score(Player::Two, 10, 15),
+        },
+    ]);
+
+    assert_eq!(snapshot.at, 15.0);
+    assert_eq!(snapshot.entry, 8);
+    assert_eq!(snapshot.phase, Phase::Arm(Player::One));
+    assert_eq!(snapshot.scores, vec![(Player::Two, 15)]);
+    assert_eq!(snapshot.structures.len(), 1);
+    assert_eq!(snapshot.structures[0].location(), IVec2::new(3, 3));
+}
+
+#[test]
+fn test_apply_keeps_cannons_as_turned() {
+    let mut snapshot = Snapshot {
+        structures: vec![PlacedStructure::new(
+            IVec2::new(2, 2),
+            Structure::Cannon(Cannon::new(Player::One)),
+        )],
+        ..Default::default()
+    };
+
+    snapshot.apply(&[
+        Entry {
+            at: 1.0,
+            recorded: Recorded::Turned(IVec2::new(2, 2), Cannon::new(Player::One).turned()),
+        },
+        // Nothing there to turn.
+        Entry {
+            at: 2.0,
+            recorded: Recorded::Turned(IVec2::new(3, 3), Cannon::new(Player::One).turned()),
+        },
+    ]);
+
+    assert_eq!(snapshot.structures.len(), 1);
+    match &snapshot.structures[0].structure {
+        Structure::Cannon(cannon) => assert_eq!(cannon.facing(), 90),
+        structure => panic!("expected a cannon, not {:?}", structure),
+    }
+}
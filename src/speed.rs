@@ -0,0 +1,171 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    controls::{typing, Action, Controls},
+    helpers::GamePlayLifetime,
+    model::AppState,
+    network,
+    replay::in_replay,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Times faster than normal the game can be run, in the order they're cycled
+/// through.
+pub const SPEEDS: [f32; 3] = [1.0, 2.0, 4.0];
+
+/// Longest physics step at normal speed, as rapier defaults to.
+const PHYSICS_STEP: f32 = 1.0 / 60.0;
+
+/// Pauses and speeds up the game when playing alone, for skipping through
+/// the computer's turns or watching a long bombardment. Everything that goes
+/// by the game's clock is affected, so the camera and UI go by real time.
+pub struct SpeedPlugin;
+
+impl Plugin for SpeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameSpeed>()
+            .add_systems(OnEnter(AppState::Game), spawn_indicator)
+            .add_systems(OnExit(AppState::Game), reset_speed)
+            .add_systems(
+                Update,
+                change_speed
+                    .run_if(in_state(AppState::Game))
+                    .run_if(not(network::networked))
+                    .run_if(not(in_replay))
+                    .run_if(not(typing)),
+            )
+            .add_systems(Update, (apply_speed, show_speed).chain());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Resource)]
+pub struct GameSpeed {
+    pub paused: bool,
+    pub speed: f32,
+}
+
+impl Default for GameSpeed {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            speed: SPEEDS[0],
+        }
+    }
+}
+
+impl GameSpeed {
+    /// The next speed up, going back around to normal after the fastest.
+    pub fn faster(&self) -> Self {
+        let index = SPEEDS.iter().position(|s| *s == self.speed).unwrap_or(0);
+        Self {
+            speed: SPEEDS[(index + 1) % SPEEDS.len()],
+            ..*self
+        }
+    }
+
+    /// Shown while not running at normal speed.
+    pub fn label(&self) -> Option<String> {
+        if self.paused {
+            Some("Paused".to_owned())
+        } else if self.speed != SPEEDS[0] {
+            Some(format!("{}x", self.speed))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Component)]
+struct SpeedIndicator;
+
+fn reset_speed(mut speed: ResMut<GameSpeed>) {
+    *speed = GameSpeed::default();
+}
+
+fn change_speed(
+    keys: Res<ButtonInput<KeyCode>>,
+    controls: Res<Controls>,
+    mut speed: ResMut<GameSpeed>,
+) {
+    let changed = if controls.just_pressed(&keys, Action::Pause) {
+        GameSpeed {
+            paused: !speed.paused,
+            ..*speed
+        }
+    } else if controls.just_pressed(&keys, Action::Faster) {
+        speed.faster()
+    } else {
+        return;
+    };
+
+    info!(paused = %changed.paused, speed = %changed.speed, "game-speed");
+    *speed = changed;
+}
+
+/// Runs the game's clock, and physics along with it, at the chosen speed.
+/// Physics takes more steps rather than longer ones, so shots fly the same.
+fn apply_speed(
+    speed: Res<GameSpeed>,
+    mut time: ResMut<Time<Virtual>>,
+    mut physics: ResMut<RapierConfiguration>,
+) {
+    if !speed.is_changed() {
+        return;
+    }
+
+    if speed.paused {
+        time.pause();
+    } else {
+        time.unpause();
+    }
+    time.set_relative_speed(speed.speed);
+
+    physics.physics_pipeline_active = !speed.paused;
+    physics.timestep_mode = TimestepMode::Variable {
+        max_dt: PHYSICS_STEP * speed.speed,
+        time_scale: 1.0,
+        substeps: speed.speed.round().max(1.0) as usize,
+    };
+}
+
+fn spawn_indicator(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Speed"),
+            GamePlayLifetime,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    top: Val::Px(12.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                SpeedIndicator,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 24.,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+            ));
+        });
+}
+
+fn show_speed(speed: Res<GameSpeed>, mut indicators: Query<&mut Text, With<SpeedIndicator>>) {
+    for mut text in &mut indicators {
+        if speed.is_changed() || text.is_added() {
+            text.sections[0].value = speed.label().unwrap_or_default();
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use super::{GameSpeed, SPEEDS};
+
+#[test]
+fn test_speeding_up_goes_back_around_to_normal() {
+    let normal = GameSpeed::default();
+
+    assert_eq!(normal.faster().speed, 2.0);
+    assert_eq!(normal.faster().faster().speed, 4.0);
+    assert_eq!(normal.faster().faster().faster().speed, SPEEDS[0]);
+}
+
+#[test]
+fn test_only_unusual_speeds_are_shown() {
+    let normal = GameSpeed::default();
+    let paused = GameSpeed {
+        paused: true,
+        ..normal.faster()
+    };
+
+    assert_eq!(normal.label(), None);
+    assert_eq!(normal.faster().label(), Some("2x".to_owned()));
+    assert_eq!(paused.label(), Some("Paused".to_owned()));
+}
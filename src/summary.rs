@@ -0,0 +1,235 @@
+use bevy::prelude::*;
+
+use crate::{
+    building::StructureLayers,
+    controls::typing,
+    helpers::{GamePlayLifetime, PlayerColor},
+    journal::{Entry, EventJournal, Recorded},
+    model::{AppState, Phase, Player},
+    replay::in_replay,
+};
+
+#[cfg(test)]
+mod tests;
+
+const DISMISS_KEYS: [KeyCode; 2] = [KeyCode::Enter, KeyCode::Space];
+
+/// Sums up each round as the next one begins, until it's dismissed.
+pub struct SummaryPlugin;
+
+impl Plugin for SummaryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RoundStart>()
+            .add_systems(OnEnter(AppState::Game), reset_rounds)
+            .add_systems(
+                OnEnter(Phase::Fortify(Player::One)),
+                summarize_round
+                    .run_if(in_state(AppState::Game))
+                    .run_if(not(in_replay)),
+            )
+            .add_systems(
+                Update,
+                dismiss_summary
+                    .run_if(in_state(AppState::Game))
+                    .run_if(showing_summary)
+                    .run_if(not(typing)),
+            );
+    }
+}
+
+/// How a single round went for one player.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PlayerRound {
+    /// Cells held as the round began and as it ended.
+    pub territory: (usize, usize),
+    pub shots: u32,
+    /// Structures of the other player's destroyed.
+    pub hits: u32,
+    /// Structures of their own destroyed, by anyone.
+    pub lost: u32,
+}
+
+impl PlayerRound {
+    /// Cells gained, or lost when negative.
+    pub fn gained(&self) -> i64 {
+        self.territory.1 as i64 - self.territory.0 as i64
+    }
+
+    /// Hits for every shot fired, capped at all of them as in the lifetime
+    /// stats, nothing without a shot.
+    pub fn accuracy(&self) -> Option<f32> {
+        (self.shots > 0).then(|| (self.hits as f32 / self.shots as f32).min(1.0))
+    }
+
+    fn describe(&self, player: &Player) -> String {
+        let accuracy = match self.accuracy() {
+            Some(accuracy) => format!("{:.0}%", accuracy * 100.),
+            None => "-".to_owned(),
+        };
+
+        format!(
+            "Player {:?}: territory {} ({:+}), destroyed {}, lost {}, shots {}, accuracy {}",
+            player,
+            self.territory.1,
+            self.gained(),
+            self.hits,
+            self.lost,
+            self.shots,
+            accuracy
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoundSummary {
+    pub players: [PlayerRound; 2],
+}
+
+impl RoundSummary {
+    /// A round from everything recorded during it, along with the territory
+    /// each player held before and after.
+    pub fn tally(entries: &[Entry], before: [usize; 2], after: [usize; 2]) -> Self {
+        let mut players = [PlayerRound::default(); 2];
+        for (index, player) in players.iter_mut().enumerate() {
+            player.territory = (before[index], after[index]);
+        }
+
+        for entry in entries {
+            match &entry.recorded {
+                Recorded::Shot(shooter, _, _) => players[index(shooter)].shots += 1,
+                Recorded::Destroyed(_, structure) => {
+                    let owner = structure.player();
+                    players[index(&owner)].lost += 1;
+                    players[index(&owner.next())].hits += 1;
+                }
+                _ => {}
+            }
+        }
+
+        Self { players }
+    }
+}
+
+fn index(player: &Player) -> usize {
+    match player {
+        Player::One => 0,
+        Player::Two => 1,
+    }
+}
+
+/// Where the round underway began, in the journal, and the territory each
+/// player held then. Nothing before the first round.
+#[derive(Debug, Default, Resource)]
+struct RoundStart(Option<(usize, [usize; 2])>);
+
+#[derive(Component)]
+struct SummaryPanel;
+
+/// Whether the last round's summary is up, holding the clock until it's
+/// dismissed.
+pub fn showing_summary(panels: Query<(), With<SummaryPanel>>) -> bool {
+    !panels.is_empty()
+}
+
+fn reset_rounds(mut start: ResMut<RoundStart>) {
+    start.0 = None;
+}
+
+fn territory(structures: &StructureLayers) -> [usize; 2] {
+    [Player::One, Player::Two].map(|player| {
+        structures
+            .territory(&player)
+            .iter()
+            .filter(|cell| **cell)
+            .count()
+    })
+}
+
+fn summarize_round(
+    mut commands: Commands,
+    mut start: ResMut<RoundStart>,
+    panels: Query<Entity, With<SummaryPanel>>,
+    journal: Res<EventJournal>,
+    structures: Res<StructureLayers>,
+) {
+    let now = territory(&structures);
+    let seen = journal.entries().len();
+
+    let Some((began, before)) = start.0.replace((seen, now)) else {
+        return;
+    };
+
+    let summary = RoundSummary::tally(&journal.entries()[began.min(seen)..], before, now);
+
+    info!(?summary, "round-summary");
+
+    for entity in &panels {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    commands
+        .spawn((
+            Name::new("Summary"),
+            SummaryPanel,
+            GamePlayLifetime,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    top: Val::Percent(30.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(6.),
+                        padding: UiRect::all(Val::Px(12.)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0., 0., 0., 0.7).into(),
+                    ..default()
+                })
+                .with_children(|panel| {
+                    let text = |value: String, color: Color| {
+                        TextBundle::from_section(
+                            value,
+                            TextStyle {
+                                font_size: 20.,
+                                color,
+                                ..default()
+                            },
+                        )
+                    };
+
+                    panel.spawn(text("Round over".to_owned(), Color::WHITE));
+                    for player in [Player::One, Player::Two] {
+                        let round = &summary.players[index(&player)];
+                        panel.spawn(text(round.describe(&player), player.color()));
+                    }
+                    panel.spawn(text(
+                        "Press Enter to continue".to_owned(),
+                        Color::rgb(0.7, 0.7, 0.7),
+                    ));
+                });
+        });
+}
+
+fn dismiss_summary(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    panels: Query<Entity, With<SummaryPanel>>,
+) {
+    if !keys.any_just_pressed(DISMISS_KEYS) {
+        return;
+    }
+
+    for entity in &panels {
+        commands.entity(entity).despawn_recursive();
+    }
+}
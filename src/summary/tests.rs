@@ -0,0 +1,61 @@
+use bevy::math::{IVec2, Vec3};
+
+use crate::{
+    building::{Structure, Wall},
+    journal::{Entry, Recorded},
+    model::Player,
+};
+
+use super::{PlayerRound, RoundSummary};
+
+fn entry(recorded: Recorded) -> Entry {
+    Entry { at: 0.0, recorded }
+}
+
+fn shot(player: Player) -> Entry {
+    entry(Recorded::Shot(player, Vec3::ZERO, Vec3::ONE))
+}
+
+fn destroyed(player: Player) -> Entry {
+    entry(Recorded::Destroyed(
+        IVec2::ZERO,
+        Structure::Wall(Wall::new(player)),
+    ))
+}
+
+#[test]
+fn test_tally_shots_and_structures_destroyed_each_way() {
+    let entries = vec![
+        shot(Player::One),
+        shot(Player::One),
+        shot(Player::Two),
+        destroyed(Player::Two),
+        entry(Recorded::Score(Player::One, 5, 5)),
+    ];
+
+    let summary = RoundSummary::tally(&entries, [20, 30], [24, 25]);
+
+    assert_eq!(
+        summary.players[0],
+        PlayerRound {
+            territory: (20, 24),
+            shots: 2,
+            hits: 1,
+            lost: 0,
+        }
+    );
+    assert_eq!(summary.players[1].lost, 1);
+    assert_eq!(summary.players[1].hits, 0);
+    assert_eq!(summary.players[0].gained(), 4);
+    assert_eq!(summary.players[1].gained(), -5);
+    assert_eq!(summary.players[0].accuracy(), Some(0.5));
+    assert_eq!(summary.players[1].accuracy(), Some(0.0));
+}
+
+#[test]
+fn test_accuracy_needs_a_shot() {
+    let summary = RoundSummary::tally(&[destroyed(Player::One)], [0, 0], [0, 0]);
+
+    assert_eq!(summary.players[1].hits, 1);
+    assert_eq!(summary.players[1].accuracy(), None);
+}
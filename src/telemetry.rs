@@ -0,0 +1,154 @@
+use std::{
+    fs::OpenOptions,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    journal::{Entry, EventJournal},
+    model::{AiSettings, AppState, Difficulty, PhaseTimers, Rules, Settings},
+    replay::in_replay,
+    units::WaveDirector,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Appends a record of every match played to a file, given with
+/// `--telemetry`, for looking over later when balancing.
+pub struct TelemetryPlugin;
+
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::Game),
+            begin_match.run_if(resource_exists::<Telemetry>),
+        )
+        .add_systems(
+            Update,
+            record_frame_time
+                .run_if(in_state(AppState::Game))
+                .run_if(resource_exists::<Telemetry>),
+        )
+        .add_systems(
+            OnExit(AppState::Game),
+            write_match
+                .run_if(resource_exists::<Telemetry>)
+                .run_if(not(in_replay)),
+        );
+    }
+}
+
+/// Where telemetry goes, along with frame times for the match being played.
+#[derive(Debug, Resource)]
+pub struct Telemetry {
+    path: PathBuf,
+    /// Milliseconds.
+    frames: Vec<f32>,
+}
+
+impl Telemetry {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            frames: Vec::new(),
+        }
+    }
+}
+
+/// How long frames took over a match, in milliseconds.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FrameTimes {
+    pub count: usize,
+    pub p50: f32,
+    pub p90: f32,
+    pub p99: f32,
+    pub max: f32,
+}
+
+impl FrameTimes {
+    /// Nothing without any frames.
+    pub fn measure(frames: &[f32]) -> Option<Self> {
+        let mut sorted = frames.to_vec();
+        sorted.sort_by(f32::total_cmp);
+        let max = *sorted.last()?;
+
+        let percentile = |p: f32| {
+            let index = ((sorted.len() - 1) as f32 * p).round() as usize;
+            sorted[index]
+        };
+
+        Some(Self {
+            count: sorted.len(),
+            p50: percentile(0.5),
+            p90: percentile(0.9),
+            p99: percentile(0.99),
+            max,
+        })
+    }
+}
+
+/// One line of telemetry, each match is written as its settings, then every
+/// journal entry, then frame times.
+#[derive(Debug, Serialize)]
+pub enum Line<'a> {
+    Match {
+        seed: u32,
+        size: u32,
+        timers: &'a PhaseTimers,
+        rules: &'a Rules,
+        difficulty: Difficulty,
+        ai: Option<&'a AiSettings>,
+    },
+    Entry(&'a Entry),
+    Frames(FrameTimes),
+}
+
+/// Appends the lines as JSON, one to a line.
+pub fn append(path: &Path, lines: &[Line]) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    for line in lines {
+        let text = serde_json::to_string(line)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        writeln!(file, "{}", text)?;
+    }
+
+    Ok(())
+}
+
+fn begin_match(mut telemetry: ResMut<Telemetry>) {
+    telemetry.frames.clear();
+}
+
+fn record_frame_time(time: Res<Time>, mut telemetry: ResMut<Telemetry>) {
+    telemetry.frames.push(time.delta_seconds() * 1000.);
+}
+
+fn write_match(
+    telemetry: Res<Telemetry>,
+    journal: Res<EventJournal>,
+    settings: Res<Settings>,
+    timers: Res<PhaseTimers>,
+    rules: Res<Rules>,
+    director: Res<WaveDirector>,
+) {
+    let mut lines = vec![Line::Match {
+        seed: settings.seed().into(),
+        size: settings.size().x,
+        timers: &timers,
+        rules: &rules,
+        difficulty: director.difficulty(),
+        ai: settings.ai(),
+    }];
+    lines.extend(journal.entries().iter().map(Line::Entry));
+    lines.extend(FrameTimes::measure(&telemetry.frames).map(Line::Frames));
+
+    match append(&telemetry.path, &lines) {
+        Ok(_) => info!(path = %telemetry.path.display(), lines = lines.len(), "telemetry"),
+        Err(e) => warn!("error writing telemetry: {}", e),
+    }
+}
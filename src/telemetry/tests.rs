@@ -0,0 +1,68 @@
+use bevy::math::IVec2;
+
+use crate::{
+    building::{Structure, Wall},
+    journal::{Entry, Recorded},
+    model::{Difficulty, PhaseTimers, Player, Rules},
+};
+
+use super::{append, FrameTimes, Line};
+
+#[test]
+fn test_frame_times_need_frames() {
+    assert_eq!(FrameTimes::measure(&[]), None);
+}
+
+#[test]
+fn test_frame_time_percentiles() {
+    let frames: Vec<f32> = (1..=100).rev().map(|ms| ms as f32).collect();
+
+    assert_eq!(
+        FrameTimes::measure(&frames),
+        Some(FrameTimes {
+            count: 100,
+            p50: 51.0,
+            p90: 90.0,
+            p99: 99.0,
+            max: 100.0,
+        })
+    );
+}
+
+#[test]
+fn test_lines_are_appended_as_json() {
+    let path = std::env::temp_dir().join(format!("castle-telemetry-{}.jsonl", std::process::id()));
+    let timers = PhaseTimers::default();
+    let rules = Rules::default();
+    let entry = Entry {
+        at: 1.5,
+        recorded: Recorded::Destroyed(IVec2::new(3, 4), Structure::Wall(Wall::new(Player::Two))),
+    };
+    let lines = [
+        Line::Match {
+            seed: 7,
+            size: 32,
+            timers: &timers,
+            rules: &rules,
+            difficulty: Difficulty::Hard,
+            ai: None,
+        },
+        Line::Entry(&entry),
+    ];
+
+    append(&path, &lines).unwrap();
+    append(&path, &lines[1..]).unwrap();
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    let written: Vec<serde_json::Value> = text
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert_eq!(written.len(), 3);
+    assert_eq!(written[0]["Match"]["seed"], 7);
+    assert_eq!(written[0]["Match"]["difficulty"], "Hard");
+    assert_eq!(written[2]["Entry"]["at"], 1.5);
+
+    std::fs::remove_file(&path).unwrap();
+}
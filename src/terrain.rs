@@ -1,24 +1,43 @@
-use bevy::{pbr::wireframe::NoWireframe, prelude::*};
-use bevy_rapier3d::prelude::*;
-use bevy_tweening::{
-    component_animator_system, lens::TransformPositionLens, AnimationSystem, Animator,
-    EaseFunction, RepeatCount, RepeatStrategy, Tween,
+use bevy::tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task};
+use bevy::{
+    pbr::{wireframe::NoWireframe, NotShadowCaster},
+    prelude::*,
 };
+use bevy_rapier3d::prelude::*;
 use noise::{
     utils::{NoiseMap, NoiseMapBuilder, PlaneMapBuilder},
-    Perlin, Terrace,
+    Fbm, MultiFractal, Perlin, Terrace,
+};
+use std::collections::HashSet;
+use std::sync::{
+    atomic::{AtomicU8, Ordering},
+    Arc,
 };
-use std::time::Duration;
 
+mod biomes;
 mod mesh;
+mod props;
+mod shaping;
 #[cfg(test)]
 mod tests;
 mod textures;
+mod water;
 
+use super::collision::Collides;
+use super::display::DisplaySettings;
 use super::helpers::GamePlayLifetime;
-use super::model::{AppState, AroundCenter, Seed, Settings, SquareGrid, TILE_SIZE};
+use super::model::{
+    AppState, Around, AroundCenter, Connectivity, NoiseSettings, Phase, Player, Regions, Seed,
+    Settings, SquareGrid, Symmetry, TerrainStyle, WorldGrid, TILE_SIZE,
+};
+use super::scenario::Scenario;
+use super::ui::LoadingProgress;
 
-use mesh::{HeightOnlyCell, RectangularMapping};
+use biomes::Biomes;
+use mesh::{HeightOnlyCell, RectangularMapping, CORNERS};
+use water::{WaterExtension, WaterMaterial};
+
+pub use props::Props;
 
 #[derive(Clone, Default, Debug)]
 struct TerrainSeed {
@@ -36,28 +55,71 @@ impl TerrainSeed {
 }
 
 #[derive(Debug, Clone)]
-struct TerrainOptions {
+pub struct TerrainOptions {
     seed: TerrainSeed,
     size: UVec2,
+    style: TerrainStyle,
+    symmetry: Symmetry,
+    noise: NoiseSettings,
+}
+
+impl From<&Settings> for TerrainOptions {
+    fn from(value: &Settings) -> Self {
+        Self {
+            seed: TerrainSeed::new(value.seed()),
+            size: value.size(),
+            style: value.style(),
+            symmetry: value.symmetry(),
+            noise: value.noise().clone(),
+        }
+    }
 }
 
 impl TerrainOptions {
-    fn new(seed: TerrainSeed, size: UVec2) -> Self {
-        Self { seed, size }
+    /// The portion of the noise actually sampled by `RectangularMapping`, which
+    /// only needs half the resolution of the grid.
+    fn used(&self) -> UVec2 {
+        self.size / 2 + UVec2::ONE
     }
 
     fn noise(&self) -> NoiseMap {
-        let perlin = Perlin::new(self.seed.clone().into());
-
-        let terraced: Terrace<_, _, 2> = Terrace::new(perlin)
-            .add_control_point(-1.0)
-            .add_control_point(-0.5)
-            .add_control_point(0.1)
-            .add_control_point(1.0)
+        let fbm = Fbm::<Perlin>::new(self.seed.clone().into())
+            .set_octaves(self.noise.octaves)
+            .set_frequency(self.noise.frequency);
+
+        let terraced: Terrace<_, _, 2> = self
+            .noise
+            .terraces()
+            .into_iter()
+            .fold(Terrace::new(fbm), |terrace, point| {
+                terrace.add_control_point(point)
+            })
             .invert_terraces(true);
 
         // Yes, this generates more noise than we'll use.
-        PlaneMapBuilder::new(terraced)
+        let mut map = PlaneMapBuilder::new(terraced)
+            .set_size(self.size.x as usize, self.size.y as usize)
+            .build();
+
+        match self.style {
+            TerrainStyle::Continent => {}
+            TerrainStyle::Islands => {
+                shaping::islands(&mut map, self.used(), self.seed.clone().into())
+            }
+        }
+
+        map
+    }
+
+    /// Second, much smoother noise channel used to pick biomes, so that they
+    /// cover large regions rather than changing from cell to cell.
+    fn climate(&self) -> NoiseMap {
+        let seed: u32 = self.seed.clone().into();
+        let fbm = Fbm::<Perlin>::new(seed.wrapping_add(1))
+            .set_octaves(1)
+            .set_frequency(0.75);
+
+        PlaneMapBuilder::new(fbm)
             .set_size(self.size.x as usize, self.size.y as usize)
             .build()
     }
@@ -66,64 +128,287 @@ impl TerrainOptions {
 #[derive(Component, Debug)]
 struct Water {}
 
+/// Terrain is split into square chunks of this many cells along each side.
+const CHUNK_SIZE: u32 = 16;
+
+/// A child entity of the terrain holding the mesh, texture and collider for a
+/// rectangle of cells, so edits only rebuild the chunks they touch.
+#[derive(Component, Debug, Clone)]
+pub struct TerrainChunk {
+    origin: IVec2,
+    size: UVec2,
+}
+
+impl TerrainChunk {
+    fn contains(&self, cell: IVec2) -> bool {
+        let local = cell - self.origin;
+        local.x >= 0 && local.y >= 0 && local.x < self.size.x as i32 && local.y < self.size.y as i32
+    }
+}
+
+/// Sent after cells of the terrain have had their heights changed, so the
+/// mesh, texture, and collider can catch up.
+#[derive(Clone, Debug)]
+pub struct TerrainEdited(Vec<IVec2>);
+
+impl Event for TerrainEdited {}
+
+impl TerrainEdited {
+    pub fn new(cells: Vec<IVec2>) -> Self {
+        Self(cells)
+    }
+
+    pub fn cells(&self) -> &[IVec2] {
+        &self.0
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sculpt {
+    Raise,
+    Lower,
+    /// Pulls heights towards those of the center of the brush.
+    Flatten,
+}
+
+/// How much a single application of a brush moves the center of the brush,
+/// before scaling by the height scale.
+const SCULPT_STEP: f64 = 0.1;
+
+/// How far the tide rises above and falls below the usual water level, before
+/// scaling by the height scale.
+const TIDE_AMPLITUDE: f64 = 0.15;
+
+/// Number of rounds between one high tide and the next.
+const TIDE_PERIOD: u32 = 4;
+
+/// The water rises and falls between rounds, flooding low lying ground and
+/// opening up new approaches from the sea.
+#[derive(Debug, Default, Resource)]
+pub struct Tide {
+    round: u32,
+}
+
+impl Tide {
+    fn turn(&mut self) {
+        self.round += 1;
+    }
+
+    /// Height of the water relative to its usual level, before scaling by the
+    /// height scale.
+    pub fn level(&self) -> f64 {
+        let angle = std::f64::consts::TAU * self.round as f64 / TIDE_PERIOD as f64;
+        TIDE_AMPLITUDE * angle.sin()
+    }
+}
+
 #[derive(Component)]
 pub struct Terrain {
     options: TerrainOptions,
     grid: SquareGrid<HeightOnlyCell>,
+    climate: SquareGrid<[f64; 4]>,
+    biomes: Biomes,
+    /// Height of the water, which changes with the tide.
+    water_level: f64,
+}
+
+impl WorldGrid for Terrain {
+    fn grid_size(&self) -> UVec2 {
+        self.options.size
+    }
 }
 
 impl Terrain {
-    pub fn world_to_grid(&self, position: Vec3) -> Option<UVec2> {
-        let local = position + self.grid.world_to_local() + (TILE_SIZE / 2.0);
-        let local = local.xz();
-
-        if false {
-            info!(
-                "world-to-local={:?} position={:?} local={:?}",
-                self.grid.world_to_local(),
-                position,
-                local
-            );
-        }
+    pub fn survey(&self, position: Vec3) -> Option<Survey> {
+        self.world_to_grid(position)
+            .and_then(|index| self.survey_grid(index))
+    }
 
-        if local.x > self.options.size.x as f32
-            || local.y > self.options.size.y as f32
-            || local.x < 0.0
-            || local.y < 0.0
-        {
-            None
-        } else {
-            Some(local.as_uvec2())
-        }
+    pub fn survey_grid(&self, index: IVec2) -> Option<Survey> {
+        let around = self.grid.around(index);
+        around.center().clone().map(|v| Survey {
+            world: self.grid.grid_to_world(index) + v.world_y(),
+            location: index,
+            cell: SurveyedCell::classify(
+                v,
+                self.biome(index),
+                self.height_scale(),
+                self.water_level,
+            ),
+        })
     }
 
-    pub fn survey(&self, position: Vec3) -> Option<Survey> {
-        match self.world_to_grid(position) {
-            Some(index) => {
-                let index = index.as_ivec2();
-                let around = self.grid.around(index);
-                around.center().clone().map(|v| Survey {
-                    world: self.grid.grid_to_world(index) + v.world_y(),
-                    location: index,
-                    cell: v.into(),
-                })
-            }
-            None => None,
-        }
+    pub fn corners(&self, grid: IVec2) -> Option<[f64; 4]> {
+        self.grid.get(grid).map(|cell| cell.corners())
     }
 
-    #[allow(dead_code)]
-    fn size(&self) -> UVec2 {
+    pub fn size(&self) -> UVec2 {
         self.options.size
     }
 
-    fn bounds(&self) -> Vec2 {
+    pub fn bounds(&self) -> Vec2 {
         self.options.size.as_vec2() * Vec2::splat(TILE_SIZE)
     }
 
     fn grid(&self) -> &SquareGrid<HeightOnlyCell> {
         &self.grid
     }
+
+    pub fn height_scale(&self) -> f64 {
+        self.options.noise.height_scale
+    }
+
+    /// Land that isn't under water, split into the islands it makes up.
+    pub fn islands(&self) -> Regions {
+        let land = self.grid.apply(|p, _| {
+            self.survey_grid(p.as_ivec2())
+                .is_some_and(|survey| !matches!(survey.cell(), SurveyedCell::Water))
+        });
+
+        land.regions(Connectivity::Edges, |land| *land)
+    }
+
+    /// Water over a cell that's nowhere deeper than `SHALLOW_DEPTH`, shallow
+    /// enough to be chained across.
+    pub fn shallow(&self, grid: IVec2) -> bool {
+        let Some(survey) = self.survey_grid(grid) else {
+            return false;
+        };
+        let Some(corners) = self.corners(grid) else {
+            return false;
+        };
+
+        let deepest = corners.iter().copied().fold(f64::MAX, f64::min);
+        matches!(survey.cell(), SurveyedCell::Water)
+            && deepest >= self.water_level - SHALLOW_DEPTH * self.height_scale()
+    }
+
+    /// Height of whatever something falling would hit first, ground or water.
+    pub fn surface_height(&self, position: Vec3) -> Option<f32> {
+        self.survey(position)
+            .map(|survey| survey.world().y.max(self.water_surface()))
+    }
+
+    /// Height of the top of the water, wherever the tide has it.
+    pub fn water_surface(&self) -> f32 {
+        WATER_LEVEL + self.water_level as f32
+    }
+
+    /// How far under the water the position is, when it's over water at all.
+    pub fn submerged(&self, position: Vec3) -> Option<f32> {
+        let survey = self.survey(position)?;
+        let depth = self.water_surface() - position.y;
+
+        (matches!(survey.cell(), SurveyedCell::Water) && depth > 0.0).then_some(depth)
+    }
+
+    fn set_water_level(&mut self, tide: &Tide) {
+        self.water_level = tide.level() * self.height_scale();
+    }
+
+    /// Applies a brush centered on a cell, returning every cell that changed.
+    /// The brush works on the vertices shared between cells, falling off with
+    /// distance from the center, so neighboring cells always stay connected.
+    pub fn sculpt(&mut self, center: IVec2, radius: i32, sculpt: Sculpt) -> Vec<IVec2> {
+        let Some(target) = self.grid.get(center).map(|c| c.iter().sum::<f64>() / 4.0) else {
+            return Vec::default();
+        };
+
+        let scale = self.height_scale();
+        let middle = center.as_vec2() + Vec2::splat(0.5);
+        let reach = radius as f32 + 1.0;
+        let mut changed = Vec::default();
+
+        // Corners of the cells just outside the radius are still within reach,
+        // and those cells have to move with the ones they share them with.
+        let outer = radius + 1;
+        for y in (center.y - outer)..=(center.y + outer) {
+            for x in (center.x - outer)..=(center.x + outer) {
+                let cell = IVec2::new(x, y);
+                let Some(value) = self.grid.get(cell) else {
+                    continue;
+                };
+
+                let mut corners = value.corners();
+                for (corner, offset) in corners.iter_mut().zip(CORNERS.iter()) {
+                    let distance = (cell + *offset).as_vec2().distance(middle);
+                    let weight = (1.0 - distance / reach).max(0.0) as f64;
+                    let height = match sculpt {
+                        Sculpt::Raise => *corner + SCULPT_STEP * scale * weight,
+                        Sculpt::Lower => *corner - SCULPT_STEP * scale * weight,
+                        Sculpt::Flatten => *corner + (target - *corner) * weight,
+                    };
+                    *corner = height.clamp(-scale, scale);
+                }
+
+                if corners != value.corners() {
+                    self.grid.set(cell, HeightOnlyCell::new(corners));
+                    changed.push(cell);
+                }
+            }
+        }
+
+        changed
+    }
+
+    /// Replaces the generated heights, for instance with those from a
+    /// scenario, as long as there's one for every cell.
+    fn with_heights(self, heights: &[[f64; 4]]) -> Self {
+        let size = self.options.size;
+        if heights.len() != (size.x * size.y) as usize {
+            warn!(cells = heights.len(), %size, "ignoring mismatched heights");
+            return self;
+        }
+
+        let cells = heights.iter().cloned().map(HeightOnlyCell::new).collect();
+
+        Self {
+            grid: SquareGrid::new(size, cells),
+            ..self
+        }
+    }
+
+    pub fn heights(&self) -> Vec<[f64; 4]> {
+        self.grid.iter().map(|cell| cell.corners()).collect()
+    }
+
+    /// Chunks covering the whole terrain, the last along each side being
+    /// smaller when the size isn't a multiple of `CHUNK_SIZE`.
+    fn chunks(&self) -> Vec<TerrainChunk> {
+        let size = self.size();
+        let mut chunks = Vec::default();
+
+        for y in (0..size.y).step_by(CHUNK_SIZE as usize) {
+            for x in (0..size.x).step_by(CHUNK_SIZE as usize) {
+                chunks.push(TerrainChunk {
+                    origin: IVec2::new(x as i32, y as i32),
+                    size: UVec2::new(CHUNK_SIZE.min(size.x - x), CHUNK_SIZE.min(size.y - y)),
+                });
+            }
+        }
+
+        chunks
+    }
+
+    fn chunk_mesh(&self, chunk: &TerrainChunk) -> Mesh {
+        mesh::region(&self.grid, chunk.origin, chunk.size)
+    }
+
+    fn texture(&self) -> textures::TerrainTextureBuilder<'_> {
+        textures::TerrainTextureBuilder::new(&self.grid, UVec2::splat(32))
+            .height_scale(self.height_scale())
+            .climate(&self.climate, &self.biomes)
+    }
+
+    fn biome(&self, grid: IVec2) -> &biomes::Biome {
+        let climate = self
+            .climate
+            .get(grid)
+            .map(|c| c.iter().sum::<f64>() / 4.0)
+            .unwrap_or_default();
+
+        self.biomes.at(climate)
+    }
 }
 
 #[derive(Debug)]
@@ -154,6 +439,8 @@ impl Survey {
 #[derive(Debug)]
 pub enum SurveyedCell {
     Ground(HeightOnlyCell),
+    /// Land in a biome that can't be built on.
+    Rough(HeightOnlyCell),
     Beach,
     Water,
 }
@@ -162,22 +449,29 @@ impl SurveyedCell {
     fn can_build(&self) -> bool {
         match self {
             SurveyedCell::Ground(_) => true,
+            SurveyedCell::Rough(_) => false,
             SurveyedCell::Beach => false,
             SurveyedCell::Water => false,
         }
     }
-}
 
-impl From<HeightOnlyCell> for SurveyedCell {
-    fn from(value: HeightOnlyCell) -> Self {
-        let all_below_0 = value.iter().all(|v| *v < 0.);
-        let any_below_0 = value.iter().any(|v| *v < 0.);
-        if all_below_0 {
+    fn classify(
+        value: HeightOnlyCell,
+        biome: &biomes::Biome,
+        height_scale: f64,
+        water_level: f64,
+    ) -> Self {
+        let beach = biome.beach * height_scale + water_level;
+        let all_below_water = value.iter().all(|v| *v < water_level);
+        let any_below_beach = value.iter().any(|v| *v < beach);
+        if all_below_water {
             SurveyedCell::Water
-        } else if any_below_0 {
+        } else if any_below_beach {
             SurveyedCell::Beach
-        } else {
+        } else if biome.buildable {
             SurveyedCell::Ground(value)
+        } else {
+            SurveyedCell::Rough(value)
         }
     }
 }
@@ -186,40 +480,52 @@ impl From<TerrainOptions> for Terrain {
     fn from(value: TerrainOptions) -> Self {
         let flat: SquareGrid<()> = SquareGrid::new_flat(value.size);
         let mapping = RectangularMapping::new(value.noise());
-        let grid = flat.map(|p, _| {
-            let value = mapping.get(p);
-            HeightOnlyCell::new(value)
-        });
+        let scale = value.noise.height_scale;
+        let corners = flat.map(|p, _| mapping.get(p).map(|v| v * scale));
+        let corners = shaping::symmetric(corners, value.symmetry);
+        let grid = corners.map(|_, value| HeightOnlyCell::new(value));
+
+        let mapping = RectangularMapping::new(value.climate());
+        let climate = SquareGrid::<()>::new_flat(value.size).map(|p, _| mapping.get(p));
+        let climate = shaping::symmetric(climate, value.symmetry);
 
         Self {
             grid,
+            climate,
+            biomes: Biomes::default(),
+            water_level: 0.0,
             options: value,
         }
     }
 }
 
-impl Meshable for Terrain {
-    type Output = Mesh;
-
-    fn mesh(&self) -> Self::Output {
-        self.grid.mesh()
-    }
-}
-
 #[derive(Bundle)]
-struct TerrainMeshBundle {
+struct TerrainChunkBundle {
+    name: Name,
+    chunk: TerrainChunk,
     pbr: PbrBundle,
+    ground: bevy_rts_camera::Ground,
+    collision_groups: CollisionGroups,
+    collider: Collider,
 }
 
-impl TerrainMeshBundle {
+impl TerrainChunkBundle {
     fn new(
-        mesh: Mesh,
-        texture: Image,
+        generated: GeneratedChunk,
         meshes: &mut ResMut<Assets<Mesh>>,
         images: &mut ResMut<Assets<Image>>,
         materials: &mut ResMut<Assets<StandardMaterial>>,
     ) -> Self {
+        let GeneratedChunk {
+            chunk,
+            mesh,
+            texture,
+            collider,
+        } = generated;
+
         Self {
+            name: Name::new(format!("Terrain:Chunk:{}", chunk.origin)),
+            chunk,
             pbr: PbrBundle {
                 mesh: meshes.add(mesh),
                 material: materials.add(StandardMaterial {
@@ -229,6 +535,9 @@ impl TerrainMeshBundle {
                 }),
                 ..default()
             },
+            ground: bevy_rts_camera::Ground,
+            collision_groups: Collides::Terrain.groups(),
+            collider,
         }
     }
 }
@@ -238,78 +547,72 @@ struct TerrainBundle {
     name: Name,
     lifetime: GamePlayLifetime,
     terrain: Terrain,
-    ground: bevy_rts_camera::Ground,
-    collision_groups: CollisionGroups,
-    collider: Collider,
     ivis: InheritedVisibility,
     transform: GlobalTransform,
 }
 
 impl TerrainBundle {
-    fn new(terrain: Terrain, mesh: &Mesh) -> Self {
-        let collider = Collider::from_bevy_mesh(mesh, &ComputedColliderShape::ConvexHull)
-            .expect("terrain collider error");
-
+    fn new(terrain: Terrain) -> Self {
         Self {
             name: Name::new("Terrain"),
             lifetime: GamePlayLifetime,
             terrain,
-            collider,
-            collision_groups: CollisionGroups::new(Group::all(), Group::all()),
-            ground: bevy_rts_camera::Ground,
             ivis: InheritedVisibility::default(),
             transform: GlobalTransform::default(),
         }
     }
 }
 
+/// Height of the surface of the water, anything below is underwater.
+const WATER_LEVEL: f32 = -0.01;
+
+/// Deepest water that's still shallow, as a fraction of the height scale.
+const SHALLOW_DEPTH: f64 = 0.25;
+
 #[derive(Bundle)]
 struct WaterBundle {
     name: Name,
     lifetime: GamePlayLifetime,
     water: Water,
-    pbr: PbrBundle,
-    collider: Collider,
-    collision_groups: CollisionGroups,
+    material: MaterialMeshBundle<WaterMaterial>,
     wireframe: NoWireframe,
-    animator: Animator<Transform>,
+    shadows: NotShadowCaster,
 }
 
 impl WaterBundle {
     fn new(
-        bounds: Vec2,
+        terrain: &Terrain,
+        heights: Image,
         meshes: &mut ResMut<Assets<Mesh>>,
-        materials: &mut ResMut<Assets<StandardMaterial>>,
+        images: &mut ResMut<Assets<Image>>,
+        materials: &mut ResMut<Assets<WaterMaterial>>,
     ) -> Self {
+        let bounds = terrain.bounds();
+        let heights = images.add(heights);
+
         Self {
             name: Name::new("Water"),
             lifetime: GamePlayLifetime,
             water: Water {},
-            pbr: PbrBundle {
+            material: MaterialMeshBundle {
                 mesh: meshes.add(Plane3d::default().mesh().size(bounds.x, bounds.y)),
-                material: materials.add(Color::rgba(0., 0., 1., 0.85)), // TODO WATER_COLOR
-                transform: Transform::from_xyz(0.0, -0.5, 0.0),
+                material: materials.add(WaterMaterial {
+                    base: StandardMaterial {
+                        base_color: Color::rgba(0., 0., 1., 0.85),
+                        alpha_mode: AlphaMode::Blend,
+                        perceptual_roughness: 0.1,
+                        reflectance: 0.6,
+                        ..default()
+                    },
+                    extension: WaterExtension::new(terrain.size(), terrain.height_scale(), heights),
+                }),
+                transform: Transform::from_xyz(0.0, WATER_LEVEL, 0.0),
                 ..Default::default()
             },
-            animator: Animator::new(WaterBundle::animation()),
             wireframe: NoWireframe,
-            collision_groups: CollisionGroups::new(Group::all(), Group::all()),
-            collider: Collider::cuboid(bounds.x, 0.5, bounds.y),
+            shadows: NotShadowCaster,
         }
     }
-
-    fn animation() -> Tween<Transform> {
-        Tween::new(
-            EaseFunction::QuadraticInOut,
-            Duration::from_secs(2),
-            TransformPositionLens {
-                start: Vec3::ZERO,
-                end: Vec3::new(0.0, -0.01, 0.0),
-            },
-        )
-        .with_repeat_count(RepeatCount::Infinite)
-        .with_repeat_strategy(RepeatStrategy::MirroredRepeat)
-    }
 }
 
 #[derive(Bundle)]
@@ -341,50 +644,300 @@ impl SunBundle {
     }
 }
 
-fn generate_terrain(
+/// Everything about the terrain that's expensive to build, done off the main
+/// thread so larger maps don't freeze the window.
+struct GeneratedTerrain {
+    terrain: Terrain,
+    chunks: Vec<GeneratedChunk>,
+    heights: Image,
+    props: Vec<(IVec2, props::Prop)>,
+}
+
+struct GeneratedChunk {
+    chunk: TerrainChunk,
+    mesh: Mesh,
+    texture: Image,
+    collider: Collider,
+}
+
+#[derive(Component)]
+struct GeneratingTerrain {
+    task: Task<GeneratedTerrain>,
+    /// Percentage complete, updated by the task as it goes.
+    progress: Arc<AtomicU8>,
+}
+
+fn start_generating(
+    mut commands: Commands,
     settings: Res<Settings>,
+    scenario: Option<Res<Scenario>>,
+) {
+    info!("generating {:?} {:?}", settings.seed(), settings.style());
+    let options = TerrainOptions::from(&*settings);
+    let heights = scenario.and_then(|s| s.heights.clone());
+    let seed: u32 = settings.seed().into();
+
+    let progress = Arc::new(AtomicU8::new(0));
+    let reporting = progress.clone();
+    let report = move |percentage: u8| reporting.store(percentage, Ordering::Relaxed);
+
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        let terrain: Terrain = options.into();
+        let terrain = match heights {
+            Some(heights) => terrain.with_heights(&heights),
+            None => terrain,
+        };
+        let islands = terrain.islands();
+        let largest = islands
+            .largest()
+            .map(|label| islands.sizes()[label])
+            .unwrap_or_default();
+        info!(count = islands.len(), %largest, "islands");
+        report(30);
+
+        let chunks = terrain.chunks();
+        let total = chunks.len();
+        let chunks = chunks
+            .into_iter()
+            .enumerate()
+            .map(|(index, chunk)| {
+                let mesh = terrain.chunk_mesh(&chunk);
+                let texture = terrain.texture().region(chunk.origin, chunk.size).build();
+                let collider = chunk_collider(&mesh);
+
+                report(30 + (70 * (index + 1) / total) as u8);
+
+                GeneratedChunk {
+                    chunk,
+                    mesh,
+                    texture,
+                    collider,
+                }
+            })
+            .collect();
+        info!(chunks = total, "chunks");
+
+        let heights = water::height_map(terrain.grid(), terrain.height_scale());
+        let props = props::scatter(&terrain, seed);
+
+        GeneratedTerrain {
+            terrain,
+            chunks,
+            heights,
+            props,
+        }
+    });
+
+    commands.spawn((
+        Name::new("Terrain:Generating"),
+        GamePlayLifetime,
+        GeneratingTerrain { task, progress },
+    ));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn finish_generating(
     mut commands: Commands,
+    mut generating: Query<(Entity, &mut GeneratingTerrain)>,
+    mut loading: ResMut<LoadingProgress>,
+    mut app_state: ResMut<NextState<AppState>>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut images: ResMut<Assets<Image>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut water_materials: ResMut<Assets<WaterMaterial>>,
 ) {
-    info!("generating {:?}", settings.seed());
-    let options = TerrainOptions::new(TerrainSeed::new(settings.seed()), settings.size());
-    let terrain: Terrain = options.into();
-    let bounds = terrain.bounds();
-
-    let mesh = terrain.mesh();
-    info!("mesh");
-
-    let texture = textures::TerrainTextureBuilder::new(terrain.grid(), UVec2::splat(32)).build();
-    info!("texture");
-
-    commands
-        .spawn(TerrainBundle::new(terrain, &mesh))
-        .with_children(|p| {
-            p.spawn(TerrainMeshBundle::new(
-                mesh,
-                texture,
-                &mut meshes,
-                &mut images,
-                &mut materials,
-            ));
-        });
-    commands.spawn(WaterBundle::new(bounds, &mut meshes, &mut materials));
-    commands.spawn(SunBundle::new());
-    info!("ready");
+    for (entity, mut generating) in &mut generating {
+        let percentage = generating.progress.load(Ordering::Relaxed);
+        loading.set("Generating terrain", percentage as f32 / 100.);
+
+        let Some(generated) = block_on(future::poll_once(&mut generating.task)) else {
+            continue;
+        };
+
+        commands.entity(entity).despawn_recursive();
+
+        let GeneratedTerrain {
+            terrain,
+            chunks,
+            heights,
+            props,
+        } = generated;
+
+        let props = props::spawn(&mut commands, &terrain, props, &mut meshes, &mut materials);
+        commands.insert_resource(props);
+
+        commands.spawn(WaterBundle::new(
+            &terrain,
+            heights,
+            &mut meshes,
+            &mut images,
+            &mut water_materials,
+        ));
+
+        commands
+            .spawn(TerrainBundle::new(terrain))
+            .with_children(|p| {
+                for chunk in chunks {
+                    p.spawn(TerrainChunkBundle::new(
+                        chunk,
+                        &mut meshes,
+                        &mut images,
+                        &mut materials,
+                    ));
+                }
+            });
+        commands.spawn(SunBundle::new());
+        info!("ready");
+
+        app_state.set(AppState::Game);
+    }
+}
+
+/// Follows the mesh exactly, valleys and craters included, so shots and the
+/// pointer hit the ground where it's drawn.
+fn chunk_collider(mesh: &Mesh) -> Collider {
+    Collider::from_bevy_mesh(mesh, &ComputedColliderShape::TriMesh).expect("terrain collider error")
+}
+
+#[allow(clippy::too_many_arguments)]
+fn refresh_edited(
+    mut commands: Commands,
+    mut edited: EventReader<TerrainEdited>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut images: ResMut<Assets<Image>>,
+    materials: Res<Assets<StandardMaterial>>,
+    water_materials: Res<Assets<WaterMaterial>>,
+    water: Query<&Handle<WaterMaterial>, With<Water>>,
+    terrain: Query<&Terrain>,
+    chunks: Query<(
+        Entity,
+        &TerrainChunk,
+        &Handle<Mesh>,
+        &Handle<StandardMaterial>,
+    )>,
+) {
+    let cells: Vec<IVec2> = edited
+        .read()
+        .flat_map(|e| e.cells().iter().cloned())
+        .collect();
+    if cells.is_empty() {
+        return;
+    }
+
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    // Normals depend on neighboring heights, so cells around those that
+    // changed need their meshes updated too.
+    let affected: Vec<IVec2> = cells
+        .iter()
+        .flat_map(|cell| Around::centered(*cell).to_vec())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+
+    let mut refreshed = 0;
+
+    for (entity, chunk, mesh, material) in chunks.iter() {
+        if !affected.iter().any(|cell| chunk.contains(*cell)) {
+            continue;
+        }
+
+        if let Some(mesh) = meshes.get_mut(mesh) {
+            mesh::update_heights(mesh, terrain.grid(), chunk.origin, chunk.size, &affected);
+
+            commands.entity(entity).insert(chunk_collider(mesh));
+        }
+
+        let texture = materials
+            .get(material)
+            .and_then(|material| material.base_color_texture.as_ref());
+        if let Some(image) = texture.and_then(|texture| images.get_mut(texture)) {
+            terrain
+                .texture()
+                .region(chunk.origin, chunk.size)
+                .update(image, &cells);
+        }
+
+        refreshed += 1;
+    }
+
+    let heights = water
+        .get_single()
+        .ok()
+        .and_then(|handle| water_materials.get(handle))
+        .map(|material| material.extension.heights());
+    if let Some(image) = heights.and_then(|heights| images.get_mut(heights)) {
+        *image = water::height_map(terrain.grid(), terrain.height_scale());
+    }
+
+    debug!(cells = cells.len(), chunks = refreshed, "terrain-refreshed");
+}
+
+fn reset_tide(mut commands: Commands) {
+    commands.insert_resource(Tide::default());
+}
+
+/// Stills the waves for anyone bothered by motion, whenever that changes or
+/// there's new water.
+fn still_water(
+    display: Res<DisplaySettings>,
+    mut water_materials: ResMut<Assets<WaterMaterial>>,
+    water: Query<&Handle<WaterMaterial>, With<Water>>,
+    added: Query<(), Added<Water>>,
+) {
+    if !display.is_changed() && added.is_empty() {
+        return;
+    }
+
+    for handle in &water {
+        if let Some(material) = water_materials.get_mut(handle) {
+            material.extension.set_moving(!display.reduce_motion);
+        }
+    }
+}
+
+/// Moves the water, and with it what's considered water, once every round.
+fn turn_tide(
+    mut tide: ResMut<Tide>,
+    mut terrain: Query<&mut Terrain>,
+    mut water: Query<&mut Transform, With<Water>>,
+) {
+    tide.turn();
+
+    for mut terrain in &mut terrain {
+        terrain.set_water_level(&tide);
+
+        for mut transform in &mut water {
+            transform.translation.y = terrain.water_surface();
+        }
+
+        info!(round = tide.round, level = terrain.water_level, "tide");
+    }
 }
 
 pub struct TerrainPlugin;
 
 impl Plugin for TerrainPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::Game), generate_terrain)
+        app.add_plugins(MaterialPlugin::<WaterMaterial>::default())
+            .init_resource::<Props>()
+            .add_event::<TerrainEdited>()
+            .add_systems(
+                OnEnter(AppState::Generating),
+                (start_generating, reset_tide),
+            )
+            .add_systems(OnExit(Phase::Target(Player::Two)), turn_tide)
+            .add_systems(
+                Update,
+                finish_generating.run_if(in_state(AppState::Generating)),
+            )
+            .add_systems(Update, refresh_edited.run_if(in_state(AppState::Game)))
+            .add_systems(Update, still_water)
             .add_systems(
                 Update,
-                component_animator_system::<Water>
-                    .in_set(AnimationSystem::AnimationUpdate)
-                    .run_if(in_state(AppState::Game)),
+                props::destroy_props.run_if(in_state(AppState::Game)),
             );
     }
 }
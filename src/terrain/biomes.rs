@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+
+/// How far either side of the boundary between two biomes, in units of the
+/// climate channel, their colors are blended.
+const BLEND: f32 = 0.1;
+
+pub struct Layer(pub f32, pub Color);
+
+pub struct Layers(pub Vec<Layer>);
+
+impl Layers {
+    pub fn get(&self, v: f32) -> Color {
+        for layer in self.0.iter() {
+            if v <= layer.0 {
+                return layer.1;
+            }
+        }
+
+        Color::RED
+    }
+}
+
+/// A region of the map with its own look and rules. Which biome applies is
+/// decided by a second, lower frequency noise channel called the climate.
+pub struct Biome {
+    /// Upper bound of the climate values this biome covers.
+    pub climate: f32,
+    /// Color ramp by height, for heights between -1 and 1.
+    pub layers: Layers,
+    /// Land with any corner lower than this is considered beach.
+    pub beach: f64,
+    pub buildable: bool,
+}
+
+impl Biome {
+    /// Low, wet ground where even land a little above sea level is beach.
+    fn marsh() -> Self {
+        Self {
+            climate: -0.45,
+            layers: Layers(vec![
+                // water
+                Layer(-0.50, Color::rgb_u8(48, 92, 150)),
+                // mud
+                Layer(0.12, Color::rgb_u8(120, 110, 72)),
+                // reeds
+                Layer(0.55, Color::rgb_u8(88, 120, 52)),
+                // bog
+                Layer(1.00, Color::rgb_u8(52, 80, 40)),
+            ]),
+            beach: 0.1,
+            buildable: true,
+        }
+    }
+
+    fn temperate() -> Self {
+        Self {
+            climate: 0.35,
+            layers: Layers(vec![
+                // water 2
+                Layer(-0.50, Color::rgb_u8(51, 100, 197)),
+                // water 1
+                Layer(-0.95, Color::rgb_u8(57, 106, 203)),
+                // sand
+                Layer(0.04, Color::rgb_u8(210, 208, 125)),
+                // grass 1
+                Layer(0.55, Color::rgb_u8(86, 152, 23)),
+                // grass 2
+                Layer(0.85, Color::rgb_u8(62, 107, 18)),
+                // grass 3
+                Layer(1.00, Color::rgb_u8(0x1b, 0x37, 0x20)),
+            ]),
+            beach: 0.0,
+            buildable: true,
+        }
+    }
+
+    /// Rocky ground that's too rough to build on.
+    fn highlands() -> Self {
+        Self {
+            climate: 1.0,
+            layers: Layers(vec![
+                // water
+                Layer(-0.50, Color::rgb_u8(45, 88, 170)),
+                // gravel
+                Layer(0.04, Color::rgb_u8(150, 140, 120)),
+                // rock 1
+                Layer(0.7, Color::rgb_u8(90, 69, 60)),
+                // rock 2
+                Layer(0.9, Color::rgb_u8(75, 60, 53)),
+                // snow
+                Layer(1.0, Color::ANTIQUE_WHITE),
+            ]),
+            beach: 0.0,
+            buildable: false,
+        }
+    }
+}
+
+pub struct Biomes(Vec<Biome>);
+
+impl Default for Biomes {
+    fn default() -> Self {
+        Self(vec![Biome::marsh(), Biome::temperate(), Biome::highlands()])
+    }
+}
+
+impl Biomes {
+    fn index(&self, climate: f32) -> usize {
+        self.0
+            .iter()
+            .position(|biome| climate <= biome.climate)
+            .unwrap_or(self.0.len() - 1)
+    }
+
+    pub fn at(&self, climate: f64) -> &Biome {
+        &self.0[self.index(climate as f32)]
+    }
+
+    /// Color of the terrain at the given height and climate, blending between
+    /// neighboring biomes near their boundary so there are no hard seams.
+    pub fn color(&self, height: f32, climate: f32) -> Color {
+        let index = self.index(climate);
+        let biome = &self.0[index];
+        let color = biome.layers.get(height);
+
+        let (other, distance) = match (
+            index.checked_sub(1).map(|i| &self.0[i]),
+            self.0.get(index + 1),
+        ) {
+            (Some(before), _) if climate - before.climate < BLEND => {
+                (before, climate - before.climate)
+            }
+            (_, Some(after)) if biome.climate - climate < BLEND => (after, biome.climate - climate),
+            _ => return color,
+        };
+
+        // Halfway at the boundary, fully this biome at BLEND away from it.
+        let t = 0.5 - (distance / BLEND) * 0.5;
+
+        mix(color, other.layers.get(height), t)
+    }
+}
+
+fn mix(a: Color, b: Color, t: f32) -> Color {
+    let a = a.as_rgba_f32();
+    let b = b.as_rgba_f32();
+    let channel = |i: usize| a[i] + (b[i] - a[i]) * t;
+    Color::rgba(channel(0), channel(1), channel(2), channel(3))
+}
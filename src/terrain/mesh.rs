@@ -1,4 +1,4 @@
-use std::ops::Index;
+use std::{collections::HashSet, ops::Index};
 
 use bevy::{
     prelude::*,
@@ -9,7 +9,19 @@ use bevy::{
 };
 use noise::utils::NoiseMap;
 
-use crate::{model::SquareGrid, model::HEIGHT_SCALE, model::TILE_SIZE};
+use crate::{model::SquareGrid, model::WorldGrid, model::TILE_SIZE};
+
+/// Offsets of the vertices for each of the values in a cell, relative to the
+/// cell's own coordinates. Neighboring cells share vertices.
+pub const CORNERS: [IVec2; 4] = [
+    IVec2::new(0, 0),
+    IVec2::new(1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(1, 1),
+];
+
+/// Order the corners of a cell appear in as vertices of its mesh.
+const VERTEX_ORDER: [usize; 4] = [1, 0, 2, 3];
 
 #[derive(Debug, Clone)]
 pub struct HeightOnlyCell([f64; 4]);
@@ -19,6 +31,15 @@ impl HeightOnlyCell {
         Self(value)
     }
 
+    pub fn corners(&self) -> [f64; 4] {
+        self.0
+    }
+
+    /// Heights of the four vertices, in the order they appear in the mesh.
+    fn vertex_heights(&self) -> [f32; 4] {
+        VERTEX_ORDER.map(|corner| self.0[corner] as f32)
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &f64> {
         self.0.iter()
     }
@@ -36,14 +57,21 @@ impl HeightOnlyCell {
     }
 
     pub fn interpolate(&self, idx: UVec2, size: UVec2) -> f64 {
-        let r1 = (size.x - idx.x) as f64 / (size.x as f64) * self.0[0]
-            + (idx.x as f64 / size.x as f64) * self.0[1];
+        interpolate(&self.0, idx, size)
+    }
+}
 
-        let r2 = (size.x - idx.x) as f64 / (size.x as f64) * self.0[2]
-            + (idx.x as f64 / size.x as f64) * self.0[3];
+/// Bilinear interpolation between the four corner values of a cell, in the
+/// same order as `HeightOnlyCell`, where `idx` is a position within a cell
+/// subdivided into `size` steps.
+pub fn interpolate(corners: &[f64; 4], idx: UVec2, size: UVec2) -> f64 {
+    let r1 = (size.x - idx.x) as f64 / (size.x as f64) * corners[0]
+        + (idx.x as f64 / size.x as f64) * corners[1];
 
-        ((size.y - idx.y) as f64 / size.y as f64) * r1 + (idx.y as f64 / size.y as f64) * r2
-    }
+    let r2 = (size.x - idx.x) as f64 / (size.x as f64) * corners[2]
+        + (idx.x as f64 / size.x as f64) * corners[3];
+
+    ((size.y - idx.y) as f64 / size.y as f64) * r1 + (idx.y as f64 / size.y as f64) * r2
 }
 
 impl Index<usize> for HeightOnlyCell {
@@ -60,11 +88,12 @@ impl Meshable for HeightOnlyCell {
     fn mesh(&self) -> Self::Output {
         let half_size = Vec2::splat(TILE_SIZE) / 2.0;
         let rotation = Quat::from_rotation_arc(Vec3::Y, Vec3::Y);
+        let heights = self.vertex_heights();
         let positions = vec![
-            rotation * Vec3::new(half_size.x, self.0[1] as f32 * HEIGHT_SCALE, -half_size.y),
-            rotation * Vec3::new(-half_size.x, self.0[0] as f32 * HEIGHT_SCALE, -half_size.y),
-            rotation * Vec3::new(-half_size.x, self.0[2] as f32 * HEIGHT_SCALE, half_size.y),
-            rotation * Vec3::new(half_size.x, self.0[3] as f32 * HEIGHT_SCALE, half_size.y),
+            rotation * Vec3::new(half_size.x, heights[0], -half_size.y),
+            rotation * Vec3::new(-half_size.x, heights[1], -half_size.y),
+            rotation * Vec3::new(-half_size.x, heights[2], half_size.y),
+            rotation * Vec3::new(half_size.x, heights[3], half_size.y),
         ];
 
         let normals = vec![Vec3::Y.to_array(); 4];
@@ -162,40 +191,154 @@ where
     type Output = Mesh;
 
     fn mesh(&self) -> Self::Output {
-        let all = self.local_to_world();
-
         let size = self.size().as_vec2();
         let uv_scale = 1. / size;
 
         let meshes = self
             .apply(|p, cell| {
                 MeshModifier::new(cell.mesh())
-                    .translated_by(
-                        Vec3::new(p.x as f32, 0.0, p.y as f32) * Vec3::splat(TILE_SIZE) + all,
-                    )
+                    .translated_by(self.grid_to_world(p.as_ivec2()))
                     .uvs_scaled_by(uv_scale)
                     .uvs_translated_by(Vec2::new(p.x as f32, p.y as f32) / size)
                     .into_inner()
             })
             .into_cells();
 
-        let empty = Mesh::new(
-            PrimitiveTopology::TriangleList,
-            RenderAssetUsages::default(),
-        )
-        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, Vec::<Vec3>::default())
-        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, Vec::<Vec3>::default())
-        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, Vec::<Vec2>::default())
-        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, Vec::<Vec4>::default())
-        .with_inserted_indices(Indices::U32(Default::default()));
-
-        meshes.into_iter().fold(empty, |mut all, m| {
+        meshes.into_iter().fold(empty(), |mut all, m| {
             all.merge(m);
             all
         })
     }
 }
 
+fn empty() -> Mesh {
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, Vec::<Vec3>::default())
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, Vec::<Vec3>::default())
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, Vec::<Vec2>::default())
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, Vec::<Vec4>::default())
+    .with_inserted_indices(Indices::U32(Default::default()))
+}
+
+/// Meshes a rectangle of cells from the grid, positioned where they'd be in a
+/// mesh of the whole grid, with UVs spanning only the rectangle. Unlike merging
+/// the mesh of each cell, neighboring cells share vertices, so there's one
+/// vertex per grid corner.
+pub fn region(grid: &SquareGrid<HeightOnlyCell>, origin: IVec2, size: UVec2) -> Mesh {
+    let columns = size.x + 1;
+    let rows = size.y + 1;
+
+    let capacity = (columns * rows) as usize;
+    let mut positions = Vec::with_capacity(capacity);
+    let mut normals = Vec::with_capacity(capacity);
+    let mut uvs = Vec::with_capacity(capacity);
+
+    for y in 0..rows {
+        for x in 0..columns {
+            let local = IVec2::new(x as i32, y as i32);
+            let vertex = origin + local;
+            let height = vertex_height(grid, vertex).expect("region outside of grid");
+
+            positions.push(grid.corner_to_world(vertex) + Vec3::Y * height as f32);
+            normals.push(vertex_normal(grid, vertex));
+            uvs.push((local.as_vec2() / size.as_vec2()).to_array());
+        }
+    }
+
+    let mut indices = Vec::with_capacity((size.x * size.y * 6) as usize);
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let c0 = y * columns + x;
+            let c1 = c0 + 1;
+            let c2 = c0 + columns;
+            let c3 = c2 + 1;
+            indices.extend_from_slice(&[c1, c0, c2, c1, c2, c3]);
+        }
+    }
+
+    let colors = vec![Color::WHITE.as_rgba_f32(); capacity];
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Height of the vertex shared by the cells around it, if any of them exist.
+pub fn vertex_height(grid: &SquareGrid<HeightOnlyCell>, vertex: IVec2) -> Option<f64> {
+    CORNERS
+        .iter()
+        .enumerate()
+        .find_map(|(corner, offset)| grid.get(vertex - *offset).map(|cell| cell[corner]))
+}
+
+/// Normal of a vertex from the slope between the vertices on either side of
+/// it, so hills shade smoothly.
+fn vertex_normal(grid: &SquareGrid<HeightOnlyCell>, vertex: IVec2) -> [f32; 3] {
+    let center = vertex_height(grid, vertex).unwrap_or_default();
+    let height = |v: IVec2| vertex_height(grid, v).unwrap_or(center);
+
+    let dx = height(vertex - IVec2::X) - height(vertex + IVec2::X);
+    let dz = height(vertex - IVec2::Y) - height(vertex + IVec2::Y);
+
+    Vec3::new(dx as f32, 2.0 * TILE_SIZE, dz as f32)
+        .normalize()
+        .to_array()
+}
+
+/// Updates the heights and normals of the vertices of the given cells in a
+/// mesh previously produced by `region`, without regenerating it. Cells outside
+/// of the region are ignored. As normals depend on neighboring heights, callers
+/// should include the cells around those that changed.
+pub fn update_heights(
+    mesh: &mut Mesh,
+    grid: &SquareGrid<HeightOnlyCell>,
+    origin: IVec2,
+    size: UVec2,
+    cells: &[IVec2],
+) {
+    let columns = size.x as usize + 1;
+
+    let vertices: HashSet<(IVec2, usize)> = cells
+        .iter()
+        .filter(|cell| {
+            let local = **cell - origin;
+            local.x >= 0 && local.y >= 0 && local.x < size.x as i32 && local.y < size.y as i32
+        })
+        .flat_map(|cell| CORNERS.map(|offset| *cell + offset))
+        .map(|vertex| {
+            let local = vertex - origin;
+            (vertex, local.y as usize * columns + local.x as usize)
+        })
+        .collect();
+
+    if let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION)
+    {
+        for (vertex, index) in vertices.iter() {
+            if let Some(height) = vertex_height(grid, *vertex) {
+                positions[*index][1] = height as f32;
+            }
+        }
+    }
+
+    if let Some(VertexAttributeValues::Float32x3(normals)) =
+        mesh.attribute_mut(Mesh::ATTRIBUTE_NORMAL)
+    {
+        for (vertex, index) in vertices.iter() {
+            normals[*index] = vertex_normal(grid, *vertex);
+        }
+    }
+}
+
 /// Maps values from 2-dimensional structures to 4 array values based on the
 /// surrounding values of the coordinate. Specifically such that odd coordinates
 /// include adjacent values from the original, and even coordinates include the
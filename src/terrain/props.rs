@@ -0,0 +1,179 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    collision::Collides,
+    firing::ExplosionEvent,
+    helpers::GamePlayLifetime,
+    model::{Around, SquareGrid, WorldGrid, TILE_SIZE},
+};
+
+use super::{SurveyedCell, Terrain};
+
+/// Chance of any given buildable cell getting a tree.
+const TREE_CHANCE: f64 = 0.08;
+
+/// Chance of any given rough cell getting a rock.
+const ROCK_CHANCE: f64 = 0.15;
+
+/// Props closer than this to an explosion are destroyed.
+const DESTRUCTION_RADIUS: f32 = TILE_SIZE;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub enum Prop {
+    Tree,
+    Rock,
+}
+
+/// Trees and rocks standing on the terrain, which have to be shot away before
+/// anything can be built where they are.
+#[derive(Default, Resource)]
+pub struct Props {
+    entities: SquareGrid<Option<Entity>>,
+}
+
+impl Props {
+    pub fn blocked(&self, grid: IVec2) -> bool {
+        self.entities
+            .get(grid)
+            .map(|e| e.is_some())
+            .unwrap_or_default()
+    }
+
+    pub fn clear(&mut self, commands: &mut Commands, grid: IVec2) {
+        if let Some(Some(entity)) = self.entities.get(grid).cloned() {
+            commands.entity(entity).despawn_recursive();
+            self.entities.set(grid, None);
+        }
+    }
+}
+
+/// Picks where props go, seeded so the same terrain always gets the same ones.
+pub fn scatter(terrain: &Terrain, seed: u32) -> Vec<(IVec2, Prop)> {
+    let mut rng = StdRng::seed_from_u64(seed.wrapping_add(2) as u64);
+    let size = terrain.size();
+
+    let mut props = Vec::default();
+
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let grid = IVec2::new(x as i32, y as i32);
+            let roll: f64 = rng.gen();
+            let Some(survey) = terrain.survey_grid(grid) else {
+                continue;
+            };
+
+            match survey.cell() {
+                SurveyedCell::Ground(_) if roll < TREE_CHANCE => props.push((grid, Prop::Tree)),
+                SurveyedCell::Rough(_) if roll < ROCK_CHANCE => props.push((grid, Prop::Rock)),
+                _ => {}
+            }
+        }
+    }
+
+    props
+}
+
+pub fn spawn(
+    commands: &mut Commands,
+    terrain: &Terrain,
+    placed: Vec<(IVec2, Prop)>,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+) -> Props {
+    let trunk = meshes.add(Cylinder::new(0.06, 0.3));
+    let canopy = meshes.add(Sphere::new(0.22));
+    let rock = meshes.add(Sphere::new(0.2));
+    let bark = materials.add(Color::hex("6b4f2a").unwrap());
+    let leaves = materials.add(Color::hex("2d6a4f").unwrap());
+    let stone = materials.add(Color::hex("8d8d8d").unwrap());
+
+    let mut entities: SquareGrid<Option<Entity>> = SquareGrid::new_flat(terrain.size());
+
+    for (grid, prop) in placed {
+        let Some(survey) = terrain.survey_grid(grid) else {
+            continue;
+        };
+
+        let collider = match prop {
+            Prop::Tree => Collider::cuboid(0.15, 0.35, 0.15),
+            Prop::Rock => Collider::cuboid(0.2, 0.12, 0.2),
+        };
+
+        let entity = commands
+            .spawn((
+                Name::new(format!("{:?}-{:?}", prop, grid)),
+                GamePlayLifetime,
+                prop,
+                SpatialBundle {
+                    transform: Transform::from_translation(survey.world()),
+                    ..default()
+                },
+                collider,
+                Collides::Props.groups(),
+            ))
+            .with_children(|parent| match prop {
+                Prop::Tree => {
+                    parent.spawn(PbrBundle {
+                        mesh: trunk.clone(),
+                        material: bark.clone(),
+                        transform: Transform::from_xyz(0., 0.15, 0.),
+                        ..default()
+                    });
+                    parent.spawn(PbrBundle {
+                        mesh: canopy.clone(),
+                        material: leaves.clone(),
+                        transform: Transform::from_xyz(0., 0.45, 0.),
+                        ..default()
+                    });
+                }
+                Prop::Rock => {
+                    parent.spawn(PbrBundle {
+                        mesh: rock.clone(),
+                        material: stone.clone(),
+                        transform: Transform::from_scale(Vec3::new(1., 0.6, 1.)),
+                        ..default()
+                    });
+                }
+            })
+            .id();
+
+        entities.set(grid, Some(entity));
+    }
+
+    Props { entities }
+}
+
+pub fn destroy_props(
+    mut commands: Commands,
+    mut explosions: EventReader<ExplosionEvent>,
+    mut props: ResMut<Props>,
+    terrain: Query<&Terrain>,
+) {
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    for explosion in explosions.read() {
+        let world = explosion.world();
+        let Some(center) = terrain.world_to_grid(world) else {
+            continue;
+        };
+
+        for grid in Around::centered(center).to_vec() {
+            if !props.blocked(grid) {
+                continue;
+            }
+
+            let Some(survey) = terrain.survey_grid(grid) else {
+                continue;
+            };
+
+            if survey.world().xz().distance(world.xz()) <= DESTRUCTION_RADIUS {
+                info!(%grid, "prop-destroyed");
+                props.clear(&mut commands, grid);
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use bevy::{
+    log::warn,
+    math::{IVec2, UVec2, Vec2},
+};
+use noise::utils::NoiseMap;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::model::{SquareGrid, Symmetry};
+
+use super::mesh::CORNERS;
+
+/// Centers of the islands, as a fraction of the used portion of the noise. The
+/// first two are the player islands and so are kept in opposing corners.
+const ISLAND_CENTERS: [Vec2; 4] = [
+    Vec2::new(0.25, 0.25),
+    Vec2::new(0.75, 0.75),
+    Vec2::new(0.75, 0.25),
+    Vec2::new(0.25, 0.75),
+];
+
+const ISLAND_RADIUS: f32 = 0.3;
+const ISLAND_JITTER: f32 = 0.05;
+
+/// Sinks everything far away from a handful of centers below sea level, which
+/// leaves several separate landmasses. Each cell is averaged with a cone that
+/// is 1.0 at an island's center and -1.0 beyond its radius, so the middle of an
+/// island is always land and the space between islands is always water.
+pub fn islands(map: &mut NoiseMap, used: UVec2, seed: u32) {
+    let mut rng = StdRng::seed_from_u64(seed as u64);
+
+    let centers: Vec<(Vec2, f32)> = ISLAND_CENTERS
+        .iter()
+        .map(|center| {
+            let jitter = Vec2::new(
+                rng.gen_range(-ISLAND_JITTER..ISLAND_JITTER),
+                rng.gen_range(-ISLAND_JITTER..ISLAND_JITTER),
+            );
+            let radius = ISLAND_RADIUS * rng.gen_range(0.8..1.0);
+            (*center + jitter, radius)
+        })
+        .collect();
+
+    let (width, height) = map.size();
+    let used = used.as_vec2();
+
+    for y in 0..height {
+        for x in 0..width {
+            let p = Vec2::new(x as f32, y as f32) / used;
+            let falloff = centers
+                .iter()
+                .map(|(center, radius)| (1.0 - p.distance(*center) / radius).clamp(0.0, 1.0))
+                .fold(0.0, f32::max);
+            let cone = (falloff * 2.0 - 1.0) as f64;
+            let value = (map.get_value(x, y) + cone) / 2.0;
+            map.set_value(x, y, value.clamp(-1.0, 1.0));
+        }
+    }
+}
+
+/// Makes the corner heights of each cell symmetric so both players get the
+/// same terrain. This works on the vertices shared between cells rather than
+/// the cells themselves, keeping neighboring corners identical so the mesh
+/// stays watertight: every vertex on the first "half" of the map is kept and
+/// the rest are copied from their partner.
+pub fn symmetric(grid: SquareGrid<[f64; 4]>, symmetry: Symmetry) -> SquareGrid<[f64; 4]> {
+    let size = grid.size();
+    let symmetry = match symmetry {
+        Symmetry::Mirror if size.x != size.y => {
+            warn!("mirror symmetry requires a square map, rotating instead");
+            Symmetry::Rotational
+        }
+        symmetry => symmetry,
+    };
+
+    let last = size.as_ivec2();
+    let partner = |p: IVec2| -> IVec2 {
+        match symmetry {
+            Symmetry::None => p,
+            Symmetry::Mirror => IVec2::new(last.x - p.y, last.y - p.x),
+            Symmetry::Rotational => last - p,
+        }
+    };
+    let keep = |p: IVec2, q: IVec2| -> bool {
+        match symmetry {
+            Symmetry::None => true,
+            Symmetry::Mirror => p.x + p.y <= last.x,
+            Symmetry::Rotational => (p.y, p.x) <= (q.y, q.x),
+        }
+    };
+
+    let mut vertices: SquareGrid<f64> = SquareGrid::new_flat(size + UVec2::ONE);
+    for (p, corners) in grid.enumerate_cells() {
+        for (offset, value) in CORNERS.iter().zip(corners.iter()) {
+            vertices[p + *offset] = *value;
+        }
+    }
+
+    let vertices = vertices.apply(|p, value| {
+        let p = p.as_ivec2();
+        let q = partner(p);
+        if keep(p, q) {
+            *value
+        } else {
+            *vertices.get(q).expect("symmetric vertex")
+        }
+    });
+
+    grid.map(|p, _| CORNERS.map(|offset| *vertices.get(p.as_ivec2() + offset).unwrap()))
+}
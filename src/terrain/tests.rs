@@ -1,3 +1,5 @@
+use bevy::render::mesh::VertexAttributeValues;
+
 use super::*;
 
 #[test]
@@ -80,3 +82,83 @@ fn test_rectangular_mapping_map_vec_vec() {
     assert_eq!(map.get(UVec2::new(0, 5)), [12, 12, 18, 18]);
     assert_eq!(map.get(UVec2::new(5, 5)), [14, 15, 20, 21]);
 }
+
+#[test]
+fn test_symmetric_rotational_and_mirror() {
+    let size = UVec2::new(4, 4);
+    let grid: SquareGrid<[f64; 4]> = SquareGrid::new_flat(size).map(|p, _: ()| {
+        let corner = |x: u32, y: u32| (y * (size.x + 1) + x) as f64;
+        [
+            corner(p.x, p.y),
+            corner(p.x + 1, p.y),
+            corner(p.x, p.y + 1),
+            corner(p.x + 1, p.y + 1),
+        ]
+    });
+
+    let rotated = shaping::symmetric(grid.clone(), Symmetry::Rotational);
+    for (p, _, cell) in rotated.layout() {
+        let other = rotated.get(IVec2::new(3, 3) - p).unwrap();
+        assert_eq!(*cell, [other[3], other[2], other[1], other[0]]);
+    }
+
+    let mirrored = shaping::symmetric(grid.clone(), Symmetry::Mirror);
+    for (p, _, cell) in mirrored.layout() {
+        let other = mirrored.get(IVec2::new(3 - p.y, 3 - p.x)).unwrap();
+        assert_eq!(*cell, [other[3], other[1], other[2], other[0]]);
+    }
+
+    let unchanged = shaping::symmetric(grid.clone(), Symmetry::None);
+    assert_eq!(unchanged.into_cells(), grid.into_cells());
+}
+
+#[test]
+fn test_region_welds_shared_vertices() {
+    let size = UVec2::new(4, 4);
+    let grid: SquareGrid<HeightOnlyCell> = SquareGrid::new_flat(size).map(|p, _: ()| {
+        let corner = |x: u32, y: u32| (y * (size.x + 1) + x) as f64;
+        HeightOnlyCell::new([
+            corner(p.x, p.y),
+            corner(p.x + 1, p.y),
+            corner(p.x, p.y + 1),
+            corner(p.x + 1, p.y + 1),
+        ])
+    });
+
+    let mesh = mesh::region(&grid, IVec2::new(1, 1), UVec2::new(2, 3));
+    assert_eq!(mesh.count_vertices(), 3 * 4);
+    assert_eq!(mesh.indices().map(|i| i.len()), Some(2 * 3 * 6));
+
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        panic!("missing positions");
+    };
+    // First vertex is the top left corner of cell (1, 1), the last is the
+    // bottom right corner of cell (2, 3).
+    assert_eq!(positions[0][1], 6.0);
+    assert_eq!(positions[11][1], 23.0);
+}
+
+#[test]
+fn test_sculpt_keeps_shared_corners_together() {
+    let settings = Settings::default();
+    let mut terrain = Terrain::from(TerrainOptions::from(&settings));
+    let center = (terrain.size() / 2).as_ivec2();
+
+    for sculpt in [Sculpt::Raise, Sculpt::Lower, Sculpt::Flatten] {
+        terrain.sculpt(center, 2, sculpt);
+
+        for (p, _, cell) in terrain.grid.layout() {
+            let corners = cell.corners();
+            if let Some(right) = terrain.grid.get(p + IVec2::X) {
+                let right = right.corners();
+                assert_eq!((corners[1], corners[3]), (right[0], right[2]), "{}", p);
+            }
+            if let Some(below) = terrain.grid.get(p + IVec2::Y) {
+                let below = below.corners();
+                assert_eq!((corners[2], corners[3]), (below[0], below[1]), "{}", p);
+            }
+        }
+    }
+}
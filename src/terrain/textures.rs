@@ -3,12 +3,14 @@ use bevy::{
     render::{
         render_asset::RenderAssetUsages,
         render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::{ImageSampler, ImageSamplerDescriptor},
     },
 };
 
 use crate::model::SquareGrid;
 
-use super::mesh::HeightOnlyCell;
+use super::biomes::Biomes;
+use super::mesh::{self, HeightOnlyCell};
 
 #[allow(dead_code)]
 pub fn square() -> Image {
@@ -46,76 +48,59 @@ pub fn square() -> Image {
 pub struct TerrainTextureBuilder<'g> {
     grid: &'g SquareGrid<HeightOnlyCell>,
     tile_size: UVec2,
+    origin: IVec2,
+    size: UVec2,
+    height_scale: f64,
+    climate: Option<(&'g SquareGrid<[f64; 4]>, &'g Biomes)>,
+    fallback: Biomes,
 }
 
-struct Layer(f32, Color);
-
-struct Layers(Vec<Layer>);
-
-impl Layers {
-    fn get(&self, v: f32) -> Color {
-        for layer in self.0.iter() {
-            if v <= layer.0 {
-                return layer.1;
-            }
+impl<'g> TerrainTextureBuilder<'g> {
+    pub fn new(grid: &'g SquareGrid<HeightOnlyCell>, tile_size: UVec2) -> Self {
+        Self {
+            grid,
+            tile_size,
+            origin: IVec2::ZERO,
+            size: grid.size(),
+            height_scale: 1.0,
+            climate: None,
+            fallback: Biomes::default(),
         }
+    }
 
-        Color::RED
+    /// Layers are defined for heights between -1 and 1, so scaled terrain is
+    /// normalized back into that range before picking colors.
+    pub fn height_scale(self, height_scale: f64) -> Self {
+        Self {
+            height_scale,
+            ..self
+        }
     }
-}
 
-impl Default for Layers {
-    fn default() -> Self {
-        Self(vec![
-            // water 2
-            Layer(-0.50, Color::rgb_u8(51, 100, 197)),
-            // water 1
-            Layer(-0.95, Color::rgb_u8(57, 106, 203)),
-            // sand
-            Layer(0.04, Color::rgb_u8(210, 208, 125)),
-            // grass 1
-            Layer(0.55, Color::rgb_u8(86, 152, 23)),
-            // grass 2
-            Layer(0.85, Color::rgb_u8(62, 107, 18)),
-            // grass 3
-            Layer(1.00, Color::rgb_u8(0x1b, 0x37, 0x20)),
-            // rock 1
-            // Layer(0.7, Color::rgb_u8(90, 69, 60)),
-            // rock 2
-            // Layer(0.9, Color::rgb_u8(75, 60, 53)),
-            // snow
-            // Layer(1.0, Color::ANTIQUE_WHITE),
-        ])
+    /// Colors by biome, rather than using the default biome everywhere.
+    pub fn climate(self, climate: &'g SquareGrid<[f64; 4]>, biomes: &'g Biomes) -> Self {
+        Self {
+            climate: Some((climate, biomes)),
+            ..self
+        }
     }
-}
 
-impl<'g> TerrainTextureBuilder<'g> {
-    pub fn new(grid: &'g SquareGrid<HeightOnlyCell>, tile_size: UVec2) -> Self {
-        Self { grid, tile_size }
+    /// Only paints a rectangle of cells, rather than the whole grid.
+    pub fn region(self, origin: IVec2, size: UVec2) -> Self {
+        Self {
+            origin,
+            size,
+            ..self
+        }
     }
 
     pub fn build(self) -> Image {
-        let layers = Layers::default();
-        let image_size = self.grid.size() * self.tile_size;
+        let image_size = self.size * self.tile_size;
         let mut data = vec![0; (image_size.x * image_size.y * 4) as usize];
 
-        for y in 0..self.grid.size().y {
-            for x in 0..self.grid.size().x {
-                let cell = self.grid.get(IVec2::new(x as i32, y as i32)).unwrap();
-
-                for ty in 0..self.tile_size.y {
-                    for tx in 0..self.tile_size.x {
-                        let p = cell.interpolate(UVec2::new(tx, ty), self.tile_size);
-
-                        let color = layers.get(p as f32);
-                        let color = color.as_rgba_u8();
-
-                        let iy = (y * self.tile_size.y) + ty;
-                        let ix = (x * self.tile_size.x) + tx;
-                        let pixel = ((iy * image_size.x * 4) + ix * 4) as usize;
-                        data[pixel..pixel + 4].copy_from_slice(&color);
-                    }
-                }
+        for y in 0..self.size.y {
+            for x in 0..self.size.x {
+                self.paint(&mut data, self.origin + IVec2::new(x as i32, y as i32));
             }
         }
 
@@ -131,7 +116,9 @@ impl<'g> TerrainTextureBuilder<'g> {
             .unwrap();
         }
 
-        Image::new(
+        let (mips, levels) = mipmaps(&data, image_size);
+
+        let mut image = Image::new(
             Extent3d {
                 width: image_size.x,
                 height: image_size.y,
@@ -141,6 +128,111 @@ impl<'g> TerrainTextureBuilder<'g> {
             data,
             TextureFormat::Rgba8UnormSrgb,
             RenderAssetUsages::RENDER_WORLD,
-        )
+        );
+
+        // Without these the terrain shimmers when the camera is far away.
+        image.data.extend(mips);
+        image.texture_descriptor.mip_level_count = levels;
+        image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+            anisotropy_clamp: 16,
+            ..ImageSamplerDescriptor::linear()
+        });
+
+        image
     }
+
+    /// Repaints only the given cells of an image previously built for the same
+    /// grid and region, for instance after they've been edited.
+    pub fn update(self, image: &mut Image, cells: &[IVec2]) {
+        for cell in cells {
+            self.paint(&mut image.data, *cell);
+        }
+
+        let image_size = self.size * self.tile_size;
+        let base = (image_size.x * image_size.y * 4) as usize;
+        image.data.truncate(base);
+
+        let (mips, levels) = mipmaps(&image.data, image_size);
+        image.data.extend(mips);
+        image.texture_descriptor.mip_level_count = levels;
+    }
+
+    fn paint(&self, data: &mut [u8], cell: IVec2) {
+        let image_size = self.size * self.tile_size;
+        let local = cell - self.origin;
+        if local.x < 0
+            || local.y < 0
+            || local.x >= self.size.x as i32
+            || local.y >= self.size.y as i32
+        {
+            return;
+        }
+        let Some(value) = self.grid.get(cell) else {
+            return;
+        };
+        let (climate, biomes) = match self.climate {
+            Some((climate, biomes)) => (climate.get(cell).cloned(), biomes),
+            None => (None, &self.fallback),
+        };
+        let (x, y) = (local.x as u32, local.y as u32);
+
+        for ty in 0..self.tile_size.y {
+            for tx in 0..self.tile_size.x {
+                let idx = UVec2::new(tx, ty);
+                let p = value.interpolate(idx, self.tile_size) / self.height_scale;
+                let c = climate
+                    .map(|c| mesh::interpolate(&c, idx, self.tile_size))
+                    .unwrap_or_default();
+
+                let color = biomes.color(p as f32, c as f32);
+                let color = color.as_rgba_u8();
+
+                let iy = (y * self.tile_size.y) + ty;
+                let ix = (x * self.tile_size.x) + tx;
+                let pixel = ((iy * image_size.x * 4) + ix * 4) as usize;
+                data[pixel..pixel + 4].copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+/// Every mip level below the base image, one after the other, along with the
+/// total number of levels including the base. Each level is half the size of
+/// the one above, with every pixel averaging the four beneath it.
+fn mipmaps(base: &[u8], size: UVec2) -> (Vec<u8>, u32) {
+    let mut levels = 1;
+    let mut mips = Vec::default();
+    let mut previous = base.to_vec();
+    let mut previous_size = size;
+
+    while previous_size.x > 1 || previous_size.y > 1 {
+        let next_size = (previous_size / 2).max(UVec2::ONE);
+        let mut next = vec![0; (next_size.x * next_size.y * 4) as usize];
+
+        for y in 0..next_size.y {
+            for x in 0..next_size.x {
+                let mut sum = [0u32; 4];
+                for (sx, sy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let px = (x * 2 + sx).min(previous_size.x - 1);
+                    let py = (y * 2 + sy).min(previous_size.y - 1);
+                    let pixel = ((py * previous_size.x + px) * 4) as usize;
+                    for (channel, value) in sum.iter_mut().enumerate() {
+                        *value += previous[pixel + channel] as u32;
+                    }
+                }
+
+                let pixel = ((y * next_size.x + x) * 4) as usize;
+                for (channel, value) in sum.iter().enumerate() {
+                    next[pixel + channel] = (value / 4) as u8;
+                }
+            }
+        }
+
+        mips.extend_from_slice(&next);
+        previous = next;
+        previous_size = next_size;
+        levels += 1;
+    }
+
+    (mips, levels)
 }
@@ -0,0 +1,100 @@
+use bevy::{
+    pbr::{ExtendedMaterial, MaterialExtension},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{
+            AsBindGroup, Extent3d, ShaderRef, ShaderType, TextureDimension, TextureFormat,
+        },
+    },
+};
+
+use crate::model::{SquareGrid, TILE_SIZE};
+
+use super::mesh::{self, HeightOnlyCell};
+
+pub type WaterMaterial = ExtendedMaterial<StandardMaterial, WaterExtension>;
+
+/// Colors the water by how deep it is over the terrain, adds foam where it
+/// meets the shore and ripples the surface with a couple of scrolling layers of
+/// waves. See `assets/shaders/water.wgsl`.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+pub struct WaterExtension {
+    #[uniform(100)]
+    settings: WaterSettings,
+    #[texture(101)]
+    #[sampler(102)]
+    heights: Handle<Image>,
+}
+
+#[derive(ShaderType, Reflect, Debug, Clone)]
+struct WaterSettings {
+    shallow: Color,
+    deep: Color,
+    foam: Color,
+    /// Depth at which the water is fully deep, how far foam reaches from the
+    /// shore, the terrain's height scale and how quickly the waves move. The
+    /// water level itself is wherever the surface is, which moves with the
+    /// tide.
+    levels: Vec4,
+    /// Size of the grid in cells and the size of each cell.
+    grid: Vec4,
+}
+
+impl MaterialExtension for WaterExtension {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/water.wgsl".into()
+    }
+}
+
+impl WaterExtension {
+    pub fn new(size: UVec2, height_scale: f64, heights: Handle<Image>) -> Self {
+        Self {
+            settings: WaterSettings {
+                shallow: Color::hex("4fb3bf").unwrap(),
+                deep: Color::hex("0d3b66").unwrap(),
+                foam: Color::rgba(1., 1., 1., 0.9),
+                levels: Vec4::new(0.75, 0.08, height_scale as f32, 1.),
+                grid: Vec4::new(size.x as f32, size.y as f32, TILE_SIZE, 0.),
+            },
+            heights,
+        }
+    }
+
+    pub fn heights(&self) -> &Handle<Image> {
+        &self.heights
+    }
+
+    /// Stills the waves, or sets them going again.
+    pub fn set_moving(&mut self, moving: bool) {
+        self.settings.levels.w = if moving { 1. } else { 0. };
+    }
+}
+
+/// Heights of every vertex of the terrain, normalized into a single channel
+/// so the water can tell how deep it is anywhere.
+pub fn height_map(grid: &SquareGrid<HeightOnlyCell>, height_scale: f64) -> Image {
+    let size = grid.size() + UVec2::ONE;
+    let mut data = Vec::with_capacity((size.x * size.y) as usize);
+
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let height =
+                mesh::vertex_height(grid, IVec2::new(x as i32, y as i32)).unwrap_or_default();
+            let normalized = (height / height_scale + 1.0) / 2.0;
+            data.push((normalized.clamp(0.0, 1.0) * 255.0) as u8);
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::R8Unorm,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
@@ -0,0 +1,46 @@
+use bevy::math::IVec2;
+
+use crate::model::{Connectivity, SquareGrid};
+
+#[cfg(test)]
+mod tests;
+
+/// Cells walled off from the edges of the map. Open cells only connect north,
+/// south, east and west, so walls touching diagonally are enough to close a
+/// gap. Open regions that never reach an edge are enclosed.
+pub fn enclosed(walls: &SquareGrid<bool>) -> SquareGrid<bool> {
+    let open = walls.regions(Connectivity::Edges, |wall| !wall);
+    let outside = open.touching_edges();
+
+    walls.apply(|p, _| {
+        open.label(p.as_ivec2())
+            .is_some_and(|label| !outside[label])
+    })
+}
+
+fn area(walls: &SquareGrid<bool>) -> usize {
+    enclosed(walls).iter().filter(|inside| **inside).count()
+}
+
+/// How many enclosed cells would be lost if each wall came down, leaving out
+/// the walls whose loss changes nothing.
+pub fn breaches(walls: &SquareGrid<bool>) -> Vec<(IVec2, usize)> {
+    let before = area(walls);
+    let standing: Vec<IVec2> = walls
+        .enumerate_cells()
+        .filter(|(_, wall)| **wall)
+        .map(|(p, _)| p)
+        .collect();
+
+    let mut walls = walls.clone();
+
+    standing
+        .into_iter()
+        .filter_map(|p| {
+            walls.set(p, false);
+            let lost = before - area(&walls);
+            walls.set(p, true);
+            (lost > 0).then_some((p, lost))
+        })
+        .collect()
+}
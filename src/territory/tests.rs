@@ -0,0 +1,42 @@
+use bevy::math::{IVec2, UVec2};
+
+use crate::model::SquareGrid;
+
+use super::*;
+
+fn walls(size: UVec2, outline: (IVec2, IVec2)) -> SquareGrid<bool> {
+    let mut walls = SquareGrid::new_flat(size);
+    walls.outline(outline.0, outline.1, true);
+    walls
+}
+
+#[test]
+fn test_enclosed_inside_outline() {
+    let walls = walls(UVec2::new(8, 8), (IVec2::new(1, 1), IVec2::new(5, 5)));
+    let enclosed = enclosed(&walls);
+
+    assert_eq!(enclosed.get(IVec2::new(3, 3)), Some(&true));
+    assert_eq!(enclosed.get(IVec2::new(2, 4)), Some(&true));
+    assert_eq!(enclosed.get(IVec2::new(1, 1)), Some(&false));
+    assert_eq!(enclosed.get(IVec2::new(0, 0)), Some(&false));
+    assert_eq!(enclosed.get(IVec2::new(6, 6)), Some(&false));
+}
+
+#[test]
+fn test_breached_outline_encloses_nothing() {
+    let mut walls = walls(UVec2::new(8, 8), (IVec2::new(1, 1), IVec2::new(5, 5)));
+    walls.set(IVec2::new(3, 1), false);
+    let enclosed = enclosed(&walls);
+
+    assert!(enclosed.into_cells().iter().all(|cell| !cell));
+}
+
+#[test]
+fn test_breaches_skip_corners() {
+    let walls = walls(UVec2::new(8, 8), (IVec2::new(1, 1), IVec2::new(5, 5)));
+    let breaches = breaches(&walls);
+
+    assert!(breaches.contains(&(IVec2::new(3, 1), 9)));
+    assert!(!breaches.iter().any(|(p, _)| *p == IVec2::new(1, 1)));
+    assert_eq!(breaches.len(), 12);
+}
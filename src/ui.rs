@@ -0,0 +1,354 @@
+use bevy::prelude::*;
+
+use std::collections::VecDeque;
+
+use crate::{
+    building::Pieces,
+    helpers::GamePlayLifetime,
+    journal::{EventJournal, Recorded},
+    model::{AppState, Phase, PhaseTimers},
+    weather::Weather,
+};
+
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadingProgress>()
+            .add_systems(OnEnter(AppState::Loading), spawn_loading_screen)
+            .add_systems(OnExit(AppState::Loading), despawn_loading_screen)
+            .add_systems(OnEnter(AppState::Generating), spawn_loading_screen)
+            .add_systems(OnExit(AppState::Generating), despawn_loading_screen)
+            .add_systems(
+                Update,
+                update_loading_screen
+                    .run_if(in_state(AppState::Loading).or_else(in_state(AppState::Generating))),
+            )
+            .init_resource::<CombatLog>()
+            .add_systems(OnEnter(AppState::Game), spawn_wind_indicator)
+            .add_systems(OnEnter(AppState::Game), spawn_piece_tray)
+            .add_systems(OnEnter(AppState::Game), spawn_combat_log)
+            .add_systems(
+                Update,
+                update_combat_log
+                    .run_if(in_state(AppState::Game))
+                    .run_if(resource_changed::<EventJournal>),
+            )
+            .add_systems(
+                Update,
+                update_wind_indicator
+                    .run_if(in_state(AppState::Game))
+                    .run_if(resource_changed::<Weather>),
+            )
+            .add_systems(
+                Update,
+                update_piece_tray
+                    .run_if(in_state(AppState::Game))
+                    .run_if(resource_changed::<Pieces>),
+            );
+    }
+}
+
+/// Whatever is currently being waited on, shown on the loading screen.
+#[derive(Debug, Default, Resource)]
+pub struct LoadingProgress {
+    label: String,
+    fraction: f32,
+}
+
+impl LoadingProgress {
+    pub fn set(&mut self, label: &str, fraction: f32) {
+        if self.label != label {
+            self.label = label.to_owned();
+        }
+        self.fraction = fraction.clamp(0.0, 1.0);
+    }
+}
+
+#[derive(Component)]
+struct LoadingScreen;
+
+#[derive(Component)]
+struct LoadingLabel;
+
+#[derive(Component)]
+struct LoadingBar;
+
+fn spawn_loading_screen(mut commands: Commands, mut progress: ResMut<LoadingProgress>) {
+    progress.set("", 0.0);
+
+    commands
+        .spawn((
+            Name::new("Loading"),
+            LoadingScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(12.),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                LoadingLabel,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 24.,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(400.),
+                        height: Val::Px(16.),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.2, 0.2, 0.2).into(),
+                    ..default()
+                })
+                .with_children(|bar| {
+                    bar.spawn((
+                        LoadingBar,
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(0.),
+                                height: Val::Percent(100.),
+                                ..default()
+                            },
+                            background_color: Color::WHITE.into(),
+                            ..default()
+                        },
+                    ));
+                });
+        });
+}
+
+fn update_loading_screen(
+    progress: Res<LoadingProgress>,
+    mut labels: Query<&mut Text, With<LoadingLabel>>,
+    mut bars: Query<&mut Style, With<LoadingBar>>,
+) {
+    if !progress.is_changed() {
+        return;
+    }
+
+    for mut text in &mut labels {
+        text.sections[0].value = format!("{} {:.0}%", progress.label, progress.fraction * 100.);
+    }
+
+    for mut style in &mut bars {
+        style.width = Val::Percent(progress.fraction * 100.);
+    }
+}
+
+fn despawn_loading_screen(mut commands: Commands, screens: Query<Entity, With<LoadingScreen>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[derive(Component)]
+struct WindIndicator;
+
+fn spawn_wind_indicator(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Wind"),
+        GamePlayLifetime,
+        WindIndicator,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.),
+            right: Val::Px(12.),
+            ..default()
+        }),
+    ));
+}
+
+fn update_wind_indicator(weather: Res<Weather>, mut labels: Query<&mut Text, With<WindIndicator>>) {
+    for mut text in &mut labels {
+        text.sections[0].value = format!(
+            "{:?} / Wind {:.1} {}",
+            weather.sky(),
+            weather.wind().length(),
+            weather.heading()
+        );
+    }
+}
+
+/// Number of upcoming pieces named in the tray, the rest are only counted.
+const PIECE_TRAY_SHOWN: usize = 5;
+
+#[derive(Component)]
+struct PieceTray;
+
+fn spawn_piece_tray(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Pieces"),
+        GamePlayLifetime,
+        PieceTray,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(40.),
+            right: Val::Px(12.),
+            ..default()
+        }),
+    ));
+}
+
+/// What's coming next and what's held this turn.
+fn update_piece_tray(pieces: Res<Pieces>, mut labels: Query<&mut Text, With<PieceTray>>) {
+    let mut upcoming: Vec<_> = pieces
+        .upcoming()
+        .take(PIECE_TRAY_SHOWN)
+        .map(|piece| piece.name().to_owned())
+        .collect();
+    let rest = pieces.left() as usize - upcoming.len();
+    if rest > 0 {
+        upcoming.push(format!("+{}", rest));
+    }
+    let held = pieces.held().map(|piece| piece.name()).unwrap_or("-");
+
+    for mut text in &mut labels {
+        text.sections[0].value = if upcoming.is_empty() {
+            format!("Held: {}", held)
+        } else {
+            format!("Next: {}\nHeld: {}", upcoming.join(", "), held)
+        };
+    }
+}
+
+/// Number of lines kept in the combat log, older ones scroll off the top.
+const COMBAT_LOG_LINES: usize = 8;
+
+/// The most recent notable events from the journal, as shown in the combat
+/// log.
+#[derive(Debug, Default, Resource)]
+struct CombatLog {
+    seen: usize,
+    lines: VecDeque<String>,
+}
+
+impl CombatLog {
+    fn push(&mut self, line: String) {
+        self.lines.push_back(line);
+        while self.lines.len() > COMBAT_LOG_LINES {
+            self.lines.pop_front();
+        }
+    }
+}
+
+#[derive(Component)]
+struct CombatLogText;
+
+fn spawn_combat_log(mut commands: Commands, mut log: ResMut<CombatLog>) {
+    *log = CombatLog::default();
+
+    commands
+        .spawn((
+            Name::new("Combat:Log"),
+            GamePlayLifetime,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(12.),
+                    right: Val::Px(12.),
+                    padding: UiRect::all(Val::Px(6.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.4).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                CombatLogText,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 16.,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+            ));
+        });
+}
+
+fn update_combat_log(
+    journal: Res<EventJournal>,
+    timers: Res<PhaseTimers>,
+    mut log: ResMut<CombatLog>,
+    mut texts: Query<&mut Text, With<CombatLogText>>,
+) {
+    let entries = journal.since(log.seen);
+    let seen = log.seen + entries.len();
+    for entry in entries {
+        if let Some(line) = describe(&entry.recorded, &timers) {
+            log.push(format!("{:>4.0}s {}", entry.at, line));
+        }
+    }
+    log.seen = seen;
+
+    for mut text in &mut texts {
+        text.sections[0].value = log.lines.iter().cloned().collect::<Vec<_>>().join("\n");
+    }
+}
+
+/// How an entry reads in the combat log, or None for those happening too
+/// often to be worth mentioning, like each shot.
+fn describe(recorded: &Recorded, timers: &PhaseTimers) -> Option<String> {
+    match recorded {
+        Recorded::Phase(phase) => {
+            let name = match phase {
+                Phase::Choose(_) => "Choose",
+                Phase::Fortify(_) => "Fortify",
+                Phase::Arm(_) => "Arm",
+                Phase::Target(_) => "Target",
+            };
+            let line = format!("Player {:?}: {} phase", phase.player(), name);
+            Some(match timers.duration(phase) {
+                Some(seconds) => format!("{}: {:.0}s remaining", line, seconds),
+                None => line,
+            })
+        }
+        Recorded::Destroyed(_, structure) => Some(format!(
+            "Player {:?}'s {} destroyed",
+            structure.player(),
+            structure.name()
+        )),
+        Recorded::Score(player, points, total) => {
+            Some(format!("Player {:?} +{} ({})", player, points, total))
+        }
+        Recorded::Constructed(_, _)
+        | Recorded::Turned(_, _)
+        | Recorded::Shot(_, _, _)
+        | Recorded::Explosion(_, _)
+        | Recorded::Killed(_) => None,
+    }
+}
@@ -0,0 +1,196 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    challenge::in_challenge,
+    damage::Damaged,
+    firing::ExplosionEvent,
+    helpers,
+    model::{AppState, Health, Phase, Player},
+    network::following,
+};
+
+mod fleet;
+mod pathfinding;
+mod ships;
+mod soldiers;
+#[cfg(test)]
+mod tests;
+mod waves;
+
+pub use ships::{Raid, Ship};
+pub use waves::{next_round, WaveDirector};
+
+pub struct UnitsPlugin;
+
+impl Plugin for UnitsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<fleet::Fleet>()
+            .register_type::<Unit>()
+            .register_type::<Ship>()
+            .register_type::<soldiers::Soldier>()
+            .add_systems(Startup, setup)
+            .add_systems(OnEnter(AppState::Game), waves::restart)
+            .add_event::<Raid>()
+            .add_event::<Casualty>()
+            .add_systems(
+                OnEnter(Phase::Fortify(Player::One)),
+                waves::next_round
+                    .run_if(not(in_challenge))
+                    .run_if(not(following)),
+            )
+            .add_systems(
+                OnEnter(Phase::Target(Player::One)),
+                ships::launch_wave
+                    .run_if(not(in_challenge))
+                    .run_if(not(following)),
+            )
+            .add_systems(
+                Update,
+                (
+                    ships::raid,
+                    ships::sail,
+                    ships::bombard,
+                    soldiers::march,
+                    soldiers::attack,
+                    explosion_damage,
+                    casualties,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+/// Anything that moves around on its own and can be killed by explosions.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Unit;
+
+#[derive(Resource)]
+struct UnitResources {
+    hull_mesh: Handle<Mesh>,
+    hull_material: Handle<StandardMaterial>,
+    soldier_mesh: Handle<Mesh>,
+    soldier_material: Handle<StandardMaterial>,
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(UnitResources {
+        // Scaled to each ship's hull.
+        hull_mesh: meshes.add(Cuboid::new(1.0, 1.0, 1.0)),
+        hull_material: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.4, 0.25, 0.1),
+            perceptual_roughness: 0.9,
+            ..default()
+        }),
+        soldier_mesh: meshes.add(Capsule3d::new(0.08, 0.2)),
+        soldier_material: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.6, 0.1, 0.1),
+            ..default()
+        }),
+    });
+}
+
+/// Moves towards a position, returning true once it's there.
+fn advance(transform: &mut Transform, to: Vec3, distance: f32) -> bool {
+    let offset = to - transform.translation;
+    if offset.length() <= distance {
+        transform.translation = to;
+        return true;
+    }
+
+    let direction = offset.normalize();
+    transform.translation += direction * distance;
+
+    let heading = direction * Vec3::new(1., 0., 1.);
+    if heading.length_squared() > 0. {
+        transform.look_to(heading, Vec3::Y);
+    }
+
+    false
+}
+
+/// Damage done right at the center of an explosion, falling off to nothing at
+/// the edge of the blast.
+const EXPLOSION_DAMAGE: f32 = 60.0;
+
+fn explosion_damage(
+    mut explosions: EventReader<ExplosionEvent>,
+    mut damaged: EventWriter<Damaged>,
+    mut units: Query<(&Transform, &mut Health), With<Unit>>,
+) {
+    for explosion in explosions.read() {
+        for (transform, mut health) in &mut units {
+            let distance = transform.translation.distance(explosion.world());
+            if distance < explosion.radius() {
+                let damage = EXPLOSION_DAMAGE * (1.0 - distance / explosion.radius());
+                health.damage(damage);
+                damaged.send(Damaged::new(
+                    transform.translation,
+                    damage,
+                    explosion.player().copied(),
+                ));
+            }
+        }
+    }
+}
+
+/// A unit killed, with the points earned by the player it was sent against.
+#[derive(Clone, Debug)]
+pub struct Casualty {
+    name: String,
+    award: Option<(Player, u32)>,
+}
+
+impl Event for Casualty {}
+
+impl Casualty {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn award(&self) -> Option<(Player, u32)> {
+        self.award
+    }
+}
+
+/// Units that have been killed fall over, or sink, and are cleared away after
+/// a moment.
+fn casualties(
+    mut commands: Commands,
+    mut killed: EventWriter<Casualty>,
+    mut units: Query<
+        (Entity, &Health, &mut Transform, &Name, Option<&ships::Ship>),
+        (With<Unit>, Changed<Health>),
+    >,
+) {
+    for (entity, health, mut transform, name, ship) in &mut units {
+        if !health.dead() {
+            continue;
+        }
+
+        let points = ship.map(|ship| ship.points()).unwrap_or_default();
+
+        info!(%name, %points, "killed");
+
+        killed.send(Casualty {
+            name: name.to_string(),
+            award: ship
+                .filter(|_| points > 0)
+                .map(|ship| (ship.target(), points)),
+        });
+
+        transform.rotate_local_x(std::f32::consts::FRAC_PI_2);
+        transform.translation.y -= 0.1;
+
+        commands
+            .entity(entity)
+            .remove::<Unit>()
+            .insert((ColliderDisabled, helpers::Expires::after(1.5)));
+    }
+}
@@ -0,0 +1,85 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// How a kind of attacking ship is built, loaded from `assets/ships.ron`.
+#[derive(Debug, Clone, Deserialize, Reflect)]
+pub struct ShipClass {
+    pub name: String,
+    pub hp: f32,
+    /// Tiles per second.
+    pub speed: f32,
+    /// Shots fired at the nearest wall each time the ship reloads.
+    pub cannons: usize,
+    /// Soldiers put ashore once it reaches the beach.
+    pub troops: usize,
+    /// Cost to the wave director, and what it's worth when sunk.
+    pub points: u32,
+    /// Width, height and length of the hull.
+    pub hull: (f32, f32, f32),
+    /// Kept out of the early rounds.
+    pub first_round: u32,
+}
+
+impl ShipClass {
+    /// The same class with its hit points scaled.
+    pub fn strengthened(self, factor: f32) -> Self {
+        Self {
+            hp: self.hp * factor,
+            ..self
+        }
+    }
+
+    pub fn hull(&self) -> Vec3 {
+        Vec3::new(self.hull.0, self.hull.1, self.hull.2)
+    }
+}
+
+/// Every class of ship that may attack.
+#[derive(Debug, Resource)]
+pub struct Fleet {
+    classes: Vec<ShipClass>,
+}
+
+impl Default for Fleet {
+    fn default() -> Self {
+        Self {
+            classes: ron::from_str(include_str!("../../assets/ships.ron"))
+                .expect("error loading ship classes"),
+        }
+    }
+}
+
+impl Fleet {
+    pub fn class(&self, name: &str) -> Option<&ShipClass> {
+        self.classes.iter().find(|class| class.name == name)
+    }
+
+    /// Ships for the given round, spending as many of the points as possible.
+    /// Unlocked classes take turns, most expensive first, so each round has a
+    /// mix rather than being all one kind.
+    pub fn muster(&self, round: u32, points: u32) -> Vec<ShipClass> {
+        let mut available: Vec<&ShipClass> = self
+            .classes
+            .iter()
+            .filter(|class| class.first_round <= round)
+            .collect();
+        available.sort_by(|a, b| b.points.cmp(&a.points));
+
+        let mut remaining = points;
+        let mut ships = Vec::new();
+        loop {
+            let before = ships.len();
+            for class in available.iter() {
+                if class.points > 0 && class.points <= remaining {
+                    remaining -= class.points;
+                    ships.push((*class).clone());
+                }
+            }
+            if ships.len() == before {
+                break;
+            }
+        }
+
+        ships
+    }
+}
@@ -0,0 +1,111 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+};
+
+use bevy::math::{IVec2, UVec2};
+
+pub const NEIGHBORS: [IVec2; 4] = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+
+/// Breadth first search from any of the starting cells to the closest cell
+/// satisfying `goal`, moving north, south, east and west through passable
+/// cells only. The path includes both the start and the goal.
+pub fn find(
+    size: UVec2,
+    starts: impl IntoIterator<Item = IVec2>,
+    passable: impl Fn(IVec2) -> bool,
+    goal: impl Fn(IVec2) -> bool,
+) -> Option<Vec<IVec2>> {
+    let size = size.as_ivec2();
+    let inside = |p: IVec2| p.x >= 0 && p.y >= 0 && p.x < size.x && p.y < size.y;
+
+    let mut came_from: HashMap<IVec2, Option<IVec2>> = HashMap::new();
+    let mut queue: VecDeque<IVec2> = VecDeque::new();
+
+    for start in starts {
+        if inside(start) && passable(start) && !came_from.contains_key(&start) {
+            came_from.insert(start, None);
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(p) = queue.pop_front() {
+        if goal(p) {
+            let mut path = vec![p];
+            let mut previous = came_from[&p];
+            while let Some(cell) = previous {
+                path.push(cell);
+                previous = came_from[&cell];
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        for neighbor in NEIGHBORS.iter().map(|n| p + *n) {
+            if inside(neighbor) && !came_from.contains_key(&neighbor) && passable(neighbor) {
+                came_from.insert(neighbor, Some(p));
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    None
+}
+
+/// Like `find`, but to the goal that's cheapest to reach rather than closest,
+/// with `cost` giving what it costs to move into each cell and None for those
+/// that can't be entered at all.
+pub fn cheapest(
+    size: UVec2,
+    starts: impl IntoIterator<Item = IVec2>,
+    cost: impl Fn(IVec2) -> Option<u32>,
+    goal: impl Fn(IVec2) -> bool,
+) -> Option<Vec<IVec2>> {
+    let size = size.as_ivec2();
+    let inside = |p: IVec2| p.x >= 0 && p.y >= 0 && p.x < size.x && p.y < size.y;
+
+    let mut came_from: HashMap<IVec2, (u32, Option<IVec2>)> = HashMap::new();
+    let mut queue: BinaryHeap<Reverse<(u32, [i32; 2])>> = BinaryHeap::new();
+
+    for start in starts {
+        if inside(start) && cost(start).is_some() && !came_from.contains_key(&start) {
+            came_from.insert(start, (0, None));
+            queue.push(Reverse((0, start.to_array())));
+        }
+    }
+
+    while let Some(Reverse((spent, p))) = queue.pop() {
+        let p = IVec2::from_array(p);
+        if came_from[&p].0 < spent {
+            continue;
+        }
+
+        if goal(p) {
+            let mut path = vec![p];
+            let mut previous = came_from[&p].1;
+            while let Some(cell) = previous {
+                path.push(cell);
+                previous = came_from[&cell].1;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        for neighbor in NEIGHBORS.iter().map(|n| p + *n).filter(|n| inside(*n)) {
+            let Some(step) = cost(neighbor) else {
+                continue;
+            };
+            let total = spent + step;
+            if came_from
+                .get(&neighbor)
+                .is_some_and(|(known, _)| *known <= total)
+            {
+                continue;
+            }
+            came_from.insert(neighbor, (total, Some(p)));
+            queue.push(Reverse((total, neighbor.to_array())));
+        }
+    }
+
+    None
+}
@@ -0,0 +1,392 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    announcer::Announcement,
+    building::StructureLayers,
+    collision::Collides,
+    firing::Bombardment,
+    helpers::{self, GamePlayLifetime},
+    model::{Health, Player},
+    terrain::{SurveyedCell, Terrain},
+};
+
+use super::{
+    advance,
+    fleet::{Fleet, ShipClass},
+    pathfinding,
+    soldiers::SoldierBundle,
+    waves::WaveDirector,
+    Unit, UnitResources,
+};
+
+/// Seconds between broadsides.
+const SHIP_RELOAD_SECONDS: f32 = 4.0;
+
+/// Times as costly to sail through shallows as open water, so ships keep to
+/// deeper channels when they can.
+const SHALLOWS_COST: u32 = 3;
+
+/// Sails in from the edge of the map to a beach near a castle, puts any
+/// soldiers it carries ashore and then bombards the castle with its cannons.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Ship {
+    class: ShipClass,
+    path: VecDeque<IVec2>,
+    landing: IVec2,
+    target: Player,
+    landed: bool,
+    reload: Timer,
+}
+
+impl Ship {
+    pub fn points(&self) -> u32 {
+        self.class.points
+    }
+
+    /// The player whose castle this was sent against.
+    pub fn target(&self) -> Player {
+        self.target
+    }
+}
+
+#[derive(Bundle)]
+struct ShipBundle {
+    name: Name,
+    lifetime: GamePlayLifetime,
+    unit: Unit,
+    ship: Ship,
+    health: Health,
+    pbr: PbrBundle,
+    body: RigidBody,
+    collider: Collider,
+    collision_groups: CollisionGroups,
+}
+
+impl ShipBundle {
+    fn new(position: Vec3, ship: Ship, resources: &UnitResources) -> Self {
+        let hull = ship.class.hull();
+        Self {
+            name: Name::new(format!("Ship:{}:{:?}", ship.class.name, ship.target)),
+            lifetime: GamePlayLifetime,
+            unit: Unit,
+            health: Health::new(ship.class.hp),
+            ship,
+            pbr: PbrBundle {
+                mesh: resources.hull_mesh.clone(),
+                material: resources.hull_material.clone(),
+                transform: Transform::from_translation(position).with_scale(hull),
+                ..default()
+            },
+            body: RigidBody::KinematicPositionBased,
+            collider: Collider::cuboid(0.5, 0.5, 0.5),
+            collision_groups: Collides::Ships.groups(),
+        }
+    }
+}
+
+fn is_water(terrain: &Terrain, p: IVec2) -> bool {
+    terrain
+        .survey_grid(p)
+        .is_some_and(|survey| matches!(survey.cell(), SurveyedCell::Water))
+}
+
+/// What sailing into the cell costs, nothing when it's land or chained across.
+fn sailing_cost(terrain: &Terrain, structures: &StructureLayers, p: IVec2) -> Option<u32> {
+    if !is_water(terrain, p) || structures.chained(p) {
+        None
+    } else if terrain.shallow(p) {
+        Some(SHALLOWS_COST)
+    } else {
+        Some(1)
+    }
+}
+
+/// Where a ship sits on the water over a cell.
+fn afloat(terrain: &Terrain, p: IVec2) -> Option<Vec3> {
+    let world = terrain.survey_grid(p)?.world();
+    terrain
+        .surface_height(world)
+        .map(|y| Vec3::new(world.x, y, world.z))
+}
+
+/// Each castle is sent the round's wave.
+pub fn launch_wave(
+    mut commands: Commands,
+    mut announcements: EventWriter<Announcement>,
+    director: Res<WaveDirector>,
+    fleet: Res<Fleet>,
+    structures: Res<StructureLayers>,
+    terrain: Query<&Terrain>,
+    resources: Res<UnitResources>,
+) {
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    let wave = director.wave(&fleet);
+
+    info!(round = director.round(), ships = wave.len(), "wave");
+
+    if !wave.is_empty() {
+        announcements.send(Announcement::Wave(director.round()));
+    }
+
+    for target in [Player::One, Player::Two] {
+        dispatch(
+            &mut commands,
+            terrain,
+            &structures,
+            &resources,
+            target,
+            &wave,
+        );
+    }
+}
+
+/// Ships sent against a castle outside of the usual waves.
+#[derive(Clone, Debug)]
+pub struct Raid {
+    target: Player,
+    classes: Vec<String>,
+}
+
+impl Event for Raid {}
+
+impl Raid {
+    pub fn new(target: Player, classes: Vec<String>) -> Self {
+        Self { target, classes }
+    }
+}
+
+pub fn raid(
+    mut commands: Commands,
+    mut raids: EventReader<Raid>,
+    fleet: Res<Fleet>,
+    structures: Res<StructureLayers>,
+    terrain: Query<&Terrain>,
+    resources: Res<UnitResources>,
+) {
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    for raid in raids.read() {
+        let ships: Vec<ShipClass> = raid
+            .classes
+            .iter()
+            .filter_map(|name| {
+                let class = fleet.class(name);
+                if class.is_none() {
+                    warn!(%name, "unknown ship class");
+                }
+                class.cloned()
+            })
+            .collect();
+
+        dispatch(
+            &mut commands,
+            terrain,
+            &structures,
+            &resources,
+            raid.target,
+            &ships,
+        );
+    }
+}
+
+/// Sends the ships against the player's castle, every ship heading for a
+/// different beach, the closest ones first.
+fn dispatch(
+    commands: &mut Commands,
+    terrain: &Terrain,
+    structures: &StructureLayers,
+    resources: &UnitResources,
+    target: Player,
+    ships: &[ShipClass],
+) {
+    let size = terrain.size().as_ivec2();
+
+    let walls = structures.walls(&target);
+    if walls.is_empty() {
+        return;
+    }
+
+    let castle = walls.iter().map(|(grid, _)| grid.as_vec2()).sum::<Vec2>() / walls.len() as f32;
+
+    // Dry land along the shore, closest to the castle.
+    let mut beaches: Vec<IVec2> = (0..size.x)
+        .flat_map(|x| (0..size.y).map(move |y| IVec2::new(x, y)))
+        .filter(|p| terrain.survey_grid(*p).is_some() && !is_water(terrain, *p))
+        .filter(|p| !structures.occupied(*p))
+        .filter(|p| {
+            pathfinding::NEIGHBORS
+                .iter()
+                .any(|n| is_water(terrain, *p + *n))
+        })
+        .collect();
+    beaches.sort_by(|a, b| {
+        let a = a.as_vec2().distance_squared(castle);
+        let b = b.as_vec2().distance_squared(castle);
+        a.total_cmp(&b)
+    });
+
+    if beaches.is_empty() {
+        info!(?target, "no-beaches");
+        return;
+    }
+
+    for (class, landing) in ships.iter().zip(beaches.iter().cycle()) {
+        let landing = *landing;
+
+        // In from whichever edge of the map is closest by water.
+        let edges = (0..size.x)
+            .flat_map(|x| [IVec2::new(x, 0), IVec2::new(x, size.y - 1)])
+            .chain((0..size.y).flat_map(|y| [IVec2::new(0, y), IVec2::new(size.x - 1, y)]));
+        let Some(path) = pathfinding::cheapest(
+            terrain.size(),
+            edges,
+            |p| sailing_cost(terrain, structures, p),
+            |p| (p - landing).abs().element_sum() == 1,
+        ) else {
+            info!(?target, %landing, "no-passage");
+            continue;
+        };
+
+        let Some(position) = afloat(terrain, path[0]) else {
+            continue;
+        };
+
+        info!(?target, %landing, class = %class.name, length = path.len(), "ship");
+
+        commands.spawn(ShipBundle::new(
+            position,
+            Ship {
+                class: class.clone(),
+                path: path.into(),
+                landing,
+                target,
+                landed: false,
+                reload: Timer::from_seconds(SHIP_RELOAD_SECONDS, TimerMode::Repeating),
+            },
+            resources,
+        ));
+    }
+}
+
+pub fn sail(
+    mut commands: Commands,
+    mut ships: Query<(Entity, &mut Transform, &mut Ship), With<Unit>>,
+    terrain: Query<&Terrain>,
+    structures: Res<StructureLayers>,
+    resources: Res<UnitResources>,
+    timer: Res<Time>,
+) {
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    for (entity, mut transform, mut ship) in &mut ships {
+        if let Some(next) = ship.path.front().copied() {
+            // Chained across since it set out, it waits for the chain to be
+            // shot through.
+            if structures.chained(next) {
+                continue;
+            }
+
+            let distance = ship.class.speed * timer.delta_seconds();
+            match afloat(terrain, next) {
+                Some(position) => {
+                    if advance(&mut transform, position, distance) {
+                        ship.path.pop_front();
+                    }
+                }
+                // The tide went out from under it, carry on to the next.
+                None => {
+                    ship.path.pop_front();
+                }
+            }
+            continue;
+        }
+
+        if ship.landed {
+            continue;
+        }
+
+        let Some(beach) = terrain.survey_grid(ship.landing) else {
+            continue;
+        };
+
+        info!(landing = %ship.landing, target = ?ship.target, class = %ship.class.name, "landed");
+
+        ship.landed = true;
+
+        for index in 0..ship.class.troops {
+            let angle = index as f32 / ship.class.troops as f32 * std::f32::consts::TAU;
+            let offset = Vec3::new(angle.cos(), 0., angle.sin()) * 0.2;
+            commands.spawn(SoldierBundle::new(
+                beach.world() + offset,
+                ship.target,
+                &resources,
+            ));
+        }
+
+        // Nothing left to do, it heads back out and is gone.
+        if ship.class.cannons == 0 {
+            commands
+                .entity(entity)
+                .remove::<(Ship, Unit)>()
+                .insert(helpers::Expires::after(2.0));
+        }
+    }
+}
+
+/// Ships that have arrived fire every cannon they have at the closest wall.
+pub fn bombard(
+    mut ships: Query<(&Transform, &mut Ship), With<Unit>>,
+    mut bombardments: EventWriter<Bombardment>,
+    structures: Res<StructureLayers>,
+    terrain: Query<&Terrain>,
+    timer: Res<Time>,
+) {
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    for (transform, mut ship) in &mut ships {
+        if !ship.landed || ship.class.cannons == 0 {
+            continue;
+        }
+
+        if !ship.reload.tick(timer.delta()).just_finished() {
+            continue;
+        }
+
+        let Some(wall) = structures
+            .walls(&ship.target)
+            .into_iter()
+            .filter_map(|(grid, _)| terrain.survey_grid(grid))
+            .map(|survey| survey.world())
+            .min_by(|a, b| {
+                let a = a.distance_squared(transform.translation);
+                let b = b.distance_squared(transform.translation);
+                a.total_cmp(&b)
+            })
+        else {
+            continue;
+        };
+
+        // Fired from above the deck so shots clear the hull, spaced along its
+        // length so they don't collide with each other.
+        let deck = transform.translation + Vec3::Y * (transform.scale.y / 2.0 + 0.2);
+        let cannons = ship.class.cannons;
+        for index in 0..cannons {
+            let along = (index as f32 + 0.5) / cannons as f32 - 0.5;
+            let from = deck + transform.forward() * along * transform.scale.z;
+            bombardments.send(Bombardment::new(from, wall, ship.target.next()));
+        }
+    }
+}
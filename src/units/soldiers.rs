@@ -0,0 +1,171 @@
+use std::collections::{HashMap, VecDeque};
+
+use bevy::prelude::*;
+
+use crate::{
+    building::StructureLayers,
+    damage::Damaged,
+    helpers::GamePlayLifetime,
+    model::{Health, Player, WorldGrid},
+    terrain::{SurveyedCell, Terrain},
+};
+
+use super::{advance, pathfinding, Unit, UnitResources};
+
+const SOLDIER_SPEED: f32 = 0.8;
+const SOLDIER_HP: f32 = 10.0;
+
+/// Damage done to a wall with each blow.
+const SOLDIER_DAMAGE: f32 = 5.0;
+
+/// Seconds between blows.
+const SOLDIER_ATTACK_INTERVAL: f32 = 1.0;
+
+/// Marches on the nearest of the target player's walls and hacks away at it.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Soldier {
+    target: Player,
+    path: VecDeque<IVec2>,
+    attacking: Option<Entity>,
+    cooldown: Timer,
+    /// No way to any wall was found, so none is looked for again until the
+    /// structures change.
+    stranded: bool,
+}
+
+#[derive(Bundle)]
+pub struct SoldierBundle {
+    name: Name,
+    lifetime: GamePlayLifetime,
+    unit: Unit,
+    soldier: Soldier,
+    health: Health,
+    pbr: PbrBundle,
+}
+
+impl SoldierBundle {
+    pub fn new(position: Vec3, target: Player, resources: &UnitResources) -> Self {
+        Self {
+            name: Name::new(format!("Soldier:{:?}", target)),
+            lifetime: GamePlayLifetime,
+            unit: Unit,
+            soldier: Soldier {
+                target,
+                path: VecDeque::new(),
+                attacking: None,
+                cooldown: Timer::from_seconds(SOLDIER_ATTACK_INTERVAL, TimerMode::Repeating),
+                stranded: false,
+            },
+            health: Health::new(SOLDIER_HP),
+            pbr: PbrBundle {
+                mesh: resources.soldier_mesh.clone(),
+                material: resources.soldier_material.clone(),
+                transform: Transform::from_translation(standing(position)),
+                ..default()
+            },
+        }
+    }
+}
+
+/// Raised so their feet are on the ground rather than their middle.
+fn standing(ground: Vec3) -> Vec3 {
+    ground + Vec3::Y * 0.18
+}
+
+pub fn march(
+    mut soldiers: Query<(&mut Transform, &mut Soldier), With<Unit>>,
+    structures: Res<StructureLayers>,
+    terrain: Query<&Terrain>,
+    timer: Res<Time>,
+) {
+    let Ok(terrain) = terrain.get_single() else {
+        return;
+    };
+
+    let passable = |p: IVec2| {
+        !structures.occupied(p)
+            && terrain
+                .survey_grid(p)
+                .is_some_and(|survey| !matches!(survey.cell(), SurveyedCell::Water))
+    };
+
+    for (mut transform, mut soldier) in &mut soldiers {
+        if soldier.attacking.is_some() {
+            continue;
+        }
+
+        if let Some(next) = soldier.path.front().copied() {
+            let Some(survey) = terrain.survey_grid(next) else {
+                soldier.path.clear();
+                continue;
+            };
+            let distance = SOLDIER_SPEED * timer.delta_seconds();
+            if advance(&mut transform, standing(survey.world()), distance) {
+                soldier.path.pop_front();
+            }
+            continue;
+        }
+
+        if soldier.stranded && !structures.is_changed() {
+            continue;
+        }
+
+        let walls: HashMap<IVec2, Entity> = structures.walls(&soldier.target).into_iter().collect();
+        let Some(here) = terrain.world_to_grid(transform.translation) else {
+            continue;
+        };
+
+        // Already next to a wall, time to get to work.
+        if let Some(wall) = pathfinding::NEIGHBORS
+            .iter()
+            .find_map(|n| walls.get(&(here + *n)))
+        {
+            debug!(%here, "attacking");
+            soldier.attacking = Some(*wall);
+            continue;
+        }
+
+        let next_to_wall = |p: IVec2| {
+            pathfinding::NEIGHBORS
+                .iter()
+                .any(|n| walls.contains_key(&(p + *n)))
+        };
+        match pathfinding::find(terrain.size(), [here], passable, next_to_wall) {
+            Some(path) => {
+                soldier.path = path.into_iter().skip(1).collect();
+                soldier.stranded = false;
+            }
+            None => {
+                debug!(%here, "stranded");
+                soldier.stranded = true;
+            }
+        }
+    }
+}
+
+pub fn attack(
+    mut soldiers: Query<&mut Soldier, With<Unit>>,
+    mut walls: Query<(&Transform, &mut Health), Without<Soldier>>,
+    mut damaged: EventWriter<Damaged>,
+    timer: Res<Time>,
+) {
+    for mut soldier in &mut soldiers {
+        let Some(wall) = soldier.attacking else {
+            continue;
+        };
+
+        if !soldier.cooldown.tick(timer.delta()).just_finished() {
+            continue;
+        }
+
+        match walls.get_mut(wall) {
+            Ok((transform, mut health)) if !health.dead() => {
+                health.damage(SOLDIER_DAMAGE);
+                damaged.send(Damaged::new(transform.translation, SOLDIER_DAMAGE, None));
+            }
+            // Gone or rebuilt, find something else to hit.
+            _ => soldier.attacking = None,
+        }
+    }
+}
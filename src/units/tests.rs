@@ -0,0 +1,72 @@
+use bevy::math::{IVec2, UVec2};
+
+use crate::model::Difficulty;
+
+use super::{fleet::Fleet, pathfinding, waves::WaveDirector};
+
+#[test]
+fn test_muster_spends_points_on_unlocked_classes() {
+    let fleet = Fleet::default();
+
+    let first = fleet.muster(1, 150);
+    assert!(first.iter().all(|class| class.first_round <= 1));
+    assert_eq!(first.iter().map(|class| class.points).sum::<u32>(), 150);
+
+    let later = fleet.muster(3, 350);
+    assert!(later.iter().any(|class| class.name == "Galleon"));
+}
+
+#[test]
+fn test_muster_with_nothing_affordable() {
+    assert!(Fleet::default().muster(1, 10).is_empty());
+}
+
+#[test]
+fn test_harder_waves_are_bigger_with_less_time_to_build() {
+    let fleet = Fleet::default();
+    let mut easy = WaveDirector::new(Difficulty::Easy);
+    let mut hard = WaveDirector::new(Difficulty::Hard);
+    easy.next_round();
+    hard.next_round();
+
+    let points = |director: &WaveDirector| {
+        director
+            .wave(&fleet)
+            .iter()
+            .map(|class| class.points)
+            .sum::<u32>()
+    };
+    assert!(points(&easy) < points(&hard));
+    assert!(easy.build_seconds() > hard.build_seconds());
+
+    let first = hard.build_seconds();
+    for _ in 0..50 {
+        hard.next_round();
+    }
+    assert!(hard.build_seconds() < first);
+    assert!(hard.build_seconds() > 0.0);
+}
+
+#[test]
+fn test_cheapest_goes_around_costly_and_blocked_cells() {
+    // A channel straight across, blocked at one end of the wall between and
+    // costly at the other.
+    let cost = |p: IVec2| match (p.x, p.y) {
+        (2, 0) => None,
+        (2, 1) | (2, 2) => Some(10),
+        _ => Some(1),
+    };
+
+    let path = pathfinding::cheapest(UVec2::new(5, 4), [IVec2::new(0, 0)], cost, |p| {
+        p == IVec2::new(4, 0)
+    })
+    .unwrap();
+
+    assert_eq!(path.first(), Some(&IVec2::new(0, 0)));
+    assert_eq!(path.last(), Some(&IVec2::new(4, 0)));
+    assert!(path.contains(&IVec2::new(2, 3)));
+    assert!(!path.contains(&IVec2::new(2, 0)));
+
+    let walled = |p: IVec2| (p.x != 2).then_some(1);
+    assert!(pathfinding::cheapest(UVec2::new(5, 4), [IVec2::ZERO], walled, |p| p.x == 4).is_none());
+}
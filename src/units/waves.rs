@@ -0,0 +1,161 @@
+use bevy::prelude::*;
+
+use crate::{
+    model::{Difficulty, PhaseTimers},
+    scenario::Scenario,
+};
+
+use super::fleet::{Fleet, ShipClass};
+
+/// Points each castle's attackers are given in the first round.
+const FIRST_WAVE_POINTS: u32 = 150;
+
+/// Points added for every round after the first.
+const WAVE_POINTS_PER_ROUND: u32 = 100;
+
+/// Ships get this much tougher every round, compounding.
+const HP_PER_ROUND: f32 = 0.05;
+
+/// Seconds each player is given to fortify in the first round.
+const FIRST_BUILD_SECONDS: f32 = 30.0;
+
+/// Seconds of building time lost every round after the first.
+const BUILD_SECONDS_PER_ROUND: f32 = 2.0;
+
+/// Building time never drops below this fraction of the first round's.
+const MINIMUM_BUILD_FRACTION: f32 = 0.5;
+
+/// How each difficulty scales the director's numbers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Preset {
+    points: f32,
+    hp: f32,
+    build_time: f32,
+}
+
+impl From<Difficulty> for Preset {
+    fn from(value: Difficulty) -> Self {
+        match value {
+            Difficulty::Easy => Self {
+                points: 0.6,
+                hp: 0.75,
+                build_time: 1.5,
+            },
+            Difficulty::Normal => Self {
+                points: 1.0,
+                hp: 1.0,
+                build_time: 1.0,
+            },
+            Difficulty::Hard => Self {
+                points: 1.5,
+                hp: 1.25,
+                build_time: 0.75,
+            },
+        }
+    }
+}
+
+/// Decides what attacks each round and how long players have to prepare,
+/// sending more and stronger ships with less time between them as the game
+/// goes on.
+#[derive(Debug, Default, Resource)]
+pub struct WaveDirector {
+    difficulty: Difficulty,
+    round: u32,
+}
+
+impl WaveDirector {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self {
+            difficulty,
+            round: 0,
+        }
+    }
+
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    fn preset(&self) -> Preset {
+        self.difficulty.into()
+    }
+
+    fn rounds_played(&self) -> u32 {
+        self.round.saturating_sub(1)
+    }
+
+    fn points(&self) -> u32 {
+        let points = FIRST_WAVE_POINTS + WAVE_POINTS_PER_ROUND * self.rounds_played();
+        (points as f32 * self.preset().points).round() as u32
+    }
+
+    fn hp(&self) -> f32 {
+        self.preset().hp * (1.0 + HP_PER_ROUND).powi(self.rounds_played() as i32)
+    }
+
+    /// Seconds each player has to fortify this round.
+    pub fn build_seconds(&self) -> f32 {
+        let first = FIRST_BUILD_SECONDS * self.preset().build_time;
+        let seconds = first - BUILD_SECONDS_PER_ROUND * self.rounds_played() as f32;
+        seconds.max(first * MINIMUM_BUILD_FRACTION)
+    }
+
+    pub fn restart(&mut self) {
+        self.round = 0;
+    }
+
+    pub fn next_round(&mut self) {
+        self.round += 1;
+    }
+
+    /// Picks up from a round, as when restoring a snapshot.
+    pub fn resume(&mut self, round: u32) {
+        self.round = round;
+    }
+
+    /// The ships sent against each castle this round.
+    pub fn wave(&self, fleet: &Fleet) -> Vec<ShipClass> {
+        let hp = self.hp();
+
+        fleet
+            .muster(self.round, self.points())
+            .into_iter()
+            .map(|class| class.strengthened(hp))
+            .collect()
+    }
+}
+
+/// Starts from the first round, or from the round a saved game was on, as
+/// that round begins again.
+pub fn restart(mut director: ResMut<WaveDirector>, scenario: Option<Res<Scenario>>) {
+    match scenario.and_then(|scenario| scenario.progress.clone()) {
+        Some(progress) => director.resume(progress.round.saturating_sub(1)),
+        None => director.restart(),
+    }
+}
+
+/// Rounds begin with the first player fortifying, which is given less time
+/// as the game goes on. Scenarios with their own timers keep them.
+pub fn next_round(
+    mut director: ResMut<WaveDirector>,
+    mut timers: ResMut<PhaseTimers>,
+    scenario: Option<Res<Scenario>>,
+) {
+    director.next_round();
+
+    let fixed = scenario.is_some_and(|scenario| scenario.timers.is_some());
+    if !fixed {
+        timers.fortify = Some(director.build_seconds());
+    }
+
+    info!(
+        round = director.round(),
+        difficulty = ?director.difficulty,
+        build = ?timers.fortify,
+        "round"
+    );
+}
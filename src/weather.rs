@@ -0,0 +1,196 @@
+use bevy::prelude::*;
+use bevy_hanabi::prelude::*;
+use bevy_hanabi::{EffectAsset, Gradient};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helpers::GamePlayLifetime,
+    model::{AppState, GameRng, Phase, Player},
+};
+
+/// Strongest wind, in world units per second, for each kind of weather.
+const CALM: f32 = 1.0;
+const BREEZY: f32 = 2.5;
+const STORMY: f32 = 5.0;
+
+/// Fraction of the wind's speed that's added to a projectile's velocity.
+const WIND_INFLUENCE: f32 = 0.35;
+
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Weather>()
+            .add_systems(Startup, setup)
+            .add_systems(OnEnter(AppState::Game), clear_skies)
+            .add_systems(OnExit(Phase::Target(Player::Two)), change_weather)
+            .add_systems(
+                Update,
+                apply_weather
+                    .run_if(in_state(AppState::Game))
+                    .run_if(resource_changed::<Weather>),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Sky {
+    #[default]
+    Clear,
+    Rain,
+    Storm,
+}
+
+impl Sky {
+    fn illuminance(&self) -> f32 {
+        match self {
+            Sky::Clear => 5000.,
+            Sky::Rain => 2500.,
+            Sky::Storm => 1200.,
+        }
+    }
+
+    fn maximum_wind(&self) -> f32 {
+        match self {
+            Sky::Clear => CALM,
+            Sky::Rain => BREEZY,
+            Sky::Storm => STORMY,
+        }
+    }
+}
+
+/// The current weather, which changes between rounds. Wind pushes projectiles
+/// around, so it's worth keeping an eye on.
+#[derive(Debug, Clone, Default, Resource, Serialize, Deserialize)]
+pub struct Weather {
+    sky: Sky,
+    wind: Vec2,
+}
+
+impl Weather {
+    fn random(sky: Sky, rng: &mut impl Rng) -> Self {
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let speed = rng.gen_range(0.0..sky.maximum_wind());
+
+        Self {
+            sky,
+            wind: Vec2::from_angle(angle) * speed,
+        }
+    }
+
+    pub fn sky(&self) -> Sky {
+        self.sky
+    }
+
+    /// Wind along the ground, x and z in world space.
+    pub fn wind(&self) -> Vec2 {
+        self.wind
+    }
+
+    /// Compass direction the wind is blowing towards, with north being -z.
+    pub fn heading(&self) -> &'static str {
+        const HEADINGS: [&str; 8] = ["E", "NE", "N", "NW", "W", "SW", "S", "SE"];
+        let angle = (-self.wind.y).atan2(self.wind.x);
+        let octant = (angle / std::f32::consts::FRAC_PI_4).round() as i32;
+        HEADINGS[octant.rem_euclid(8) as usize]
+    }
+
+    /// Extra velocity the wind gives anything flying through the air.
+    pub fn drift(&self) -> Vec3 {
+        Vec3::new(self.wind.x, 0., self.wind.y) * WIND_INFLUENCE
+    }
+}
+
+#[derive(Resource)]
+struct WeatherResources {
+    rain: Handle<EffectAsset>,
+    storm: Handle<EffectAsset>,
+}
+
+#[derive(Component)]
+struct Precipitation;
+
+fn setup(mut commands: Commands, mut effects: ResMut<Assets<EffectAsset>>) {
+    commands.insert_resource(WeatherResources {
+        rain: effects.add(precipitation(800.0)),
+        storm: effects.add(precipitation(3000.0)),
+    });
+}
+
+fn precipitation(rate: f32) -> EffectAsset {
+    let mut colors = Gradient::new();
+    colors.add_key(0.0, Vec4::new(0.7, 0.8, 1.0, 0.6));
+    colors.add_key(1.0, Vec4::new(0.7, 0.8, 1.0, 0.6));
+
+    let mut module = Module::default();
+    let init_position = SetPositionSphereModifier {
+        dimension: ShapeDimension::Volume,
+        center: module.lit(Vec3::new(0., 20., 0.)),
+        radius: module.lit(40.),
+    };
+    let init_velocity =
+        SetAttributeModifier::new(Attribute::VELOCITY, module.lit(Vec3::new(0., -12., 0.)));
+    let init_lifetime = SetAttributeModifier::new(Attribute::LIFETIME, module.lit(2.0));
+
+    EffectAsset::new(8192, Spawner::rate(rate.into()), module)
+        .init(init_position)
+        .init(init_velocity)
+        .init(init_lifetime)
+        .render(ColorOverLifetimeModifier { gradient: colors })
+        .render(SetSizeModifier {
+            size: Vec2::new(0.02, 0.3).into(),
+            screen_space_size: false,
+        })
+}
+
+fn clear_skies(mut commands: Commands, mut rng: ResMut<GameRng>) {
+    commands.insert_resource(Weather::random(Sky::Clear, rng.rng()));
+}
+
+fn change_weather(mut weather: ResMut<Weather>, mut rng: ResMut<GameRng>) {
+    let rng = rng.rng();
+    let sky = match rng.gen_range(0..6) {
+        0..=2 => Sky::Clear,
+        3..=4 => Sky::Rain,
+        _ => Sky::Storm,
+    };
+
+    *weather = Weather::random(sky, rng);
+
+    info!(?weather, "weather");
+}
+
+fn apply_weather(
+    mut commands: Commands,
+    mut suns: Query<&mut DirectionalLight>,
+    precipitation: Query<Entity, With<Precipitation>>,
+    weather: Res<Weather>,
+    resources: Res<WeatherResources>,
+) {
+    for mut sun in &mut suns {
+        sun.illuminance = weather.sky.illuminance();
+    }
+
+    for entity in precipitation.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let effect = match weather.sky {
+        Sky::Clear => None,
+        Sky::Rain => Some(resources.rain.clone()),
+        Sky::Storm => Some(resources.storm.clone()),
+    };
+
+    if let Some(effect) = effect {
+        commands.spawn((
+            Name::new("Precipitation"),
+            GamePlayLifetime,
+            Precipitation,
+            ParticleEffectBundle {
+                effect: ParticleEffect::new(effect),
+                ..default()
+            },
+        ));
+    }
+}